@@ -33,11 +33,18 @@ __description__ = "AI agent API with dawei as top-level package"
 
 
 def get_dawei_home() -> Path:
-    """从环境变量获取DAWEI_HOME，默认值为 ~/.dawei
+    """从环境变量获取DAWEI_HOME，其次读取桌面端迁移数据目录后写入的 ~/.dawei-home，默认值为 ~/.dawei
     Returns:
         str: 工作区根目录的绝对路径
     """
-    dawei_home = os.environ.get("DAWEI_HOME", "~/.dawei")
+    dawei_home = os.environ.get("DAWEI_HOME")
+    if not dawei_home:
+        pointer = Path("~/.dawei-home").expanduser()
+        try:
+            relocated = pointer.read_text(encoding="utf-8").strip()
+        except OSError:
+            relocated = ""
+        dawei_home = relocated if relocated and Path(relocated).is_dir() else "~/.dawei"
     return Path(dawei_home).expanduser().resolve()
 
 
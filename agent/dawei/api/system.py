@@ -5,6 +5,8 @@
 
 from fastapi import APIRouter
 
+from dawei import __version__
+
 router = APIRouter(prefix="/api", tags=["system"])
 
 # 注意:websocket_server 需要在实际使用时定义或导入
@@ -22,12 +24,26 @@ async def health_check():
         "status": "healthy",
         "service": "Dawei Agent API - Orchestrator Mode",
         "version": "2.0.0",
+        "dawei_version": __version__,
         "architecture": "Multi-Agent Orchestrator",
         "message": "健康检查端点已迁移,等待实现",
         "websocket": {"status": "running", "connections": 0, "sessions": 0},
     }
 
 
+@router.get("/tasks/running")
+async def get_running_tasks():
+    """正在执行的 Agent 任务（桌面端退出前确认用）"""
+    from dawei.websocket.handlers import chat
+
+    handler = chat.chat_handler_instance
+    if handler is None:
+        return {"running": 0, "tasks": []}
+
+    tasks = [{"task_id": task_id, "session_id": handler._task_to_session_map.get(task_id)} for task_id in list(handler._active_agents)]
+    return {"running": len(tasks), "tasks": tasks}
+
+
 @router.get("/ws/status")
 async def get_websocket_status():
     """获取WebSocket服务器状态"""
@@ -77,6 +77,14 @@ class UTF8StreamHandler(logging.StreamHandler):
             self.handleError(record)
 
 
+def _log_format() -> str:
+    """日志格式；由桌面端启动时在每行日志中带上会话 ID，便于和崩溃报告对应"""
+    session_id = os.environ.get("DAWEI_SESSION_ID")
+    if session_id:
+        return f"%(asctime)s - [{session_id}] - %(name)s - %(levelname)s - %(message)s"
+    return "%(asctime)s - %(name)s - %(levelname)s - %(message)s"
+
+
 def _get_utf8_stream():
     """获取支持 UTF-8 的输出流（保持向后兼容）
 
@@ -101,7 +109,7 @@ class AgenticLogger:
         # 控制台处理器（使用 UTF-8 流）
         console_handler = UTF8StreamHandler()
         console_handler.setFormatter(
-            logging.Formatter("%(asctime)s - %(name)s - %(levelname)s - %(message)s"),
+            logging.Formatter(_log_format()),
         )
         self.logger.addHandler(console_handler)
 
@@ -122,7 +130,7 @@ class AgenticLogger:
                 encoding="utf-8",  # 明确指定 UTF-8 编码
             )
             file_handler.setFormatter(
-                logging.Formatter("%(asctime)s - %(name)s - %(levelname)s - %(message)s"),
+                logging.Formatter(_log_format()),
             )
             self.logger.addHandler(file_handler)
         except Exception as e:
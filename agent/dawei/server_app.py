@@ -132,6 +132,11 @@ def record_server_start(host: str, port: int) -> None:
         "websocket": f"ws://{accessible_host}:{port}/ws",
     }
 
+    # 桌面端启动时传入的会话 ID，用于关联桌面端崩溃报告和日志
+    session_id = os.environ.get("DAWEI_SESSION_ID")
+    if session_id:
+        start_data["session_id"] = session_id
+
     with server_start_file.open("w", encoding="utf-8") as f:
         json.dump(start_data, f, indent=2, ensure_ascii=False)
 
@@ -60,6 +60,10 @@ find . -type d -name "test" -exec rm -rf {} + 2>/dev/null || true
 
 cd - > /dev/null
 
+# 生成完整性清单（应用启动时校验）
+echo "生成资源完整性清单..."
+python3 scripts/generate-resource-manifest.py "$(dirname "$TAURI_RESOURCES")"
+
 # 计算大小
 original_size=$(du -sh "$BACKEND_VENV" 2>/dev/null | cut -f1)
 optimized_size=$(du -sh "$TAURI_RESOURCES" 2>/dev/null | cut -f1)
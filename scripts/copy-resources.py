@@ -7,6 +7,7 @@ Ensures Python environment and scripts are available for bundling
 
 import os
 import shutil
+import subprocess
 import sys
 from pathlib import Path
 
@@ -70,6 +71,9 @@ def copy_resources():
         size_mb = size / (1024 * 1024)
 
         print(f"  [OK] Copied {size_mb:.1f} MB")
+
+        # Integrity manifest verified by the app on startup
+        subprocess.run([sys.executable, str(Path(__file__).parent / "generate-resource-manifest.py"), str(bundle_resources)], check=True)
     else:
         print(f"  [WARN] Source not found")
 
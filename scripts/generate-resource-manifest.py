@@ -0,0 +1,58 @@
+#!/usr/bin/env python3
+# -*- coding: utf-8 -*-
+"""
+Generate the integrity manifest for bundled resources
+
+Writes webui/src-tauri/resources/integrity.json with the SHA-256 of the files the
+desktop app cannot run without (uv binary, Python interpreter, venv config). The app
+verifies them on startup and offers to repair the installation when they don't match.
+Files that backend upgrades rewrite (site-packages, entry points) are not included.
+"""
+
+import hashlib
+import json
+import sys
+from pathlib import Path
+
+# Relative to the resources directory; missing entries are skipped (platform specific)
+CANDIDATES = [
+    "python-env/bin/uv",
+    "python-env/Scripts/uv.exe",
+    "python-env/bin/python",
+    "python-env/bin/python3",
+    "python-env/Scripts/python.exe",
+    "python-env/pyvenv.cfg",
+]
+
+
+def sha256(path: Path) -> str:
+    digest = hashlib.sha256()
+    with path.open("rb") as f:
+        for chunk in iter(lambda: f.read(1024 * 1024), b""):
+            digest.update(chunk)
+    return digest.hexdigest()
+
+
+def main() -> int:
+    default = Path(__file__).resolve().parent.parent / "webui" / "src-tauri" / "resources"
+    resources = Path(sys.argv[1]) if len(sys.argv) > 1 else default
+
+    files = []
+    for relative in CANDIDATES:
+        path = resources / relative
+        if path.is_file():
+            files.append({"path": relative, "sha256": sha256(path), "size": path.stat().st_size})
+            print(f"  [OK] {relative}")
+
+    if not files:
+        print(f"[WARN] No bundled resources found in {resources}, manifest not written")
+        return 0
+
+    manifest = resources / "integrity.json"
+    manifest.write_text(json.dumps({"version": 1, "files": files}, indent=2), encoding="utf-8")
+    print(f"[OK] Wrote {manifest} ({len(files)} files)")
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -0,0 +1,98 @@
+//! 关于/诊断信息
+//!
+//! 汇总各组件的版本（应用、Tauri、WebView、操作系统、后端、uv、Python）和 DAWEI_HOME，
+//! 供关于对话框和问题反馈使用。锁定模式下不返回本机路径。
+
+use serde::Serialize;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+use sysinfo::System;
+
+/// 查询后端版本的超时时间（后端未启动时不应拖慢关于对话框）
+const BACKEND_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 组件版本信息
+#[derive(Debug, Clone, Serialize)]
+pub struct AboutInfo {
+    pub app_version: String,
+    pub tauri_version: String,
+    /// WebView 版本（WebView2 / WebKitGTK / WKWebView）
+    pub webview_version: Option<String>,
+    /// 操作系统，例如 `Windows 11 (26100)`、`Linux (Ubuntu 24.04)`
+    pub os: String,
+    pub kernel_version: Option<String>,
+    pub arch: String,
+    /// 后端版本，后端未运行时为 None
+    pub backend_version: Option<String>,
+    pub uv_version: Option<String>,
+    pub python_version: Option<String>,
+    /// 锁定模式下为 None
+    pub dawei_home: Option<String>,
+    pub session_id: String,
+}
+
+/// 运行 `<program> --version` 并返回第一行输出
+fn command_version(program: &Path) -> Option<String> {
+    let output = Command::new(program).arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // Python 旧版本把版本号输出到 stderr
+    let text = if output.stdout.is_empty() { output.stderr } else { output.stdout };
+    String::from_utf8_lossy(&text).lines().next().map(|line| line.trim().to_string()).filter(|line| !line.is_empty())
+}
+
+/// 从健康检查响应中读取后端版本（优先使用包版本，旧版本后端只有 API 版本）
+fn backend_version_from(health: &serde_json::Value) -> Option<String> {
+    ["dawei_version", "version"]
+        .iter()
+        .find_map(|key| health.get(*key).and_then(serde_json::Value::as_str))
+        .map(str::to_string)
+}
+
+/// 运行中的后端报告的版本
+pub async fn backend_version() -> Option<String> {
+    let client = reqwest::Client::builder().timeout(BACKEND_TIMEOUT).build().ok()?;
+    let url = format!("http://127.0.0.1:{}/api/health", crate::backend_profile::port());
+    let health: serde_json::Value = client.get(url).send().await.ok()?.json().await.ok()?;
+    backend_version_from(&health)
+}
+
+/// 收集组件版本信息
+pub async fn collect() -> AboutInfo {
+    let tools = tauri::async_runtime::spawn_blocking(|| {
+        let uv_path = crate::get_uv_path();
+        let python_version = crate::find_python(&uv_path).and_then(|python| command_version(&python));
+        (command_version(&uv_path), python_version)
+    });
+    let backend_version = backend_version().await;
+    let (uv_version, python_version) = tools.await.unwrap_or_default();
+
+    AboutInfo {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        tauri_version: tauri::VERSION.to_string(),
+        webview_version: tauri::webview_version().ok(),
+        os: System::long_os_version().unwrap_or_else(|| std::env::consts::OS.to_string()),
+        kernel_version: System::kernel_version(),
+        arch: std::env::consts::ARCH.to_string(),
+        backend_version,
+        uv_version,
+        python_version,
+        dawei_home: (!crate::policy::is_locked_down()).then(|| crate::get_dawei_home().display().to_string()),
+        session_id: crate::session::session_id().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_backend_version_from_health() {
+        assert_eq!(backend_version_from(&json!({ "dawei_version": "1.0.0", "version": "2.0.0" })), Some("1.0.0".to_string()));
+        assert_eq!(backend_version_from(&json!({ "version": "2.0.0" })), Some("2.0.0".to_string()));
+        assert_eq!(backend_version_from(&json!({ "status": "healthy" })), None);
+    }
+}
@@ -0,0 +1,38 @@
+//! 主窗口置顶
+//!
+//! 置顶状态保存在设置中，启动时应用到主窗口；设置变化时（命令、托盘菜单、
+//! 手动编辑设置文件）重新应用，托盘菜单的勾选状态也随之更新。
+
+use crate::settings::{self, SETTINGS_CHANGED_EVENT};
+use tauri::{AppHandle, Listener, Manager};
+
+fn apply(app: &AppHandle, enabled: bool) {
+    if let Some(window) = app.get_webview_window("main") {
+        if let Err(e) = window.set_always_on_top(enabled) {
+            tracing::warn!("Failed to set always on top: {}", e);
+        }
+    }
+    crate::tray::set_always_on_top_checked(enabled);
+}
+
+/// 启动时应用设置，并在设置变化时重新应用
+pub fn init(app: &AppHandle) {
+    apply(app, settings::current().always_on_top);
+
+    let handle = app.clone();
+    app.listen_any(SETTINGS_CHANGED_EVENT, move |event| {
+        let changed = serde_json::from_str::<serde_json::Value>(event.payload())
+            .ok()
+            .and_then(|change| change["changed"]["always_on_top"]["new"].as_bool());
+        if let Some(enabled) = changed {
+            apply(&handle, enabled);
+        }
+    });
+}
+
+/// 设置主窗口是否置顶
+pub fn set(app: &AppHandle, enabled: bool) -> Result<bool, String> {
+    // 设置变更事件会触发 apply
+    settings::update(app, serde_json::json!({ "always_on_top": enabled }))?;
+    Ok(enabled)
+}
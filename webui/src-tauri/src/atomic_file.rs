@@ -0,0 +1,63 @@
+//! 原子写入文件
+//!
+//! 先写入同目录下的临时文件（`<文件名>.tmp`）并刷到磁盘，再重命名覆盖目标文件。
+//! 写入中途崩溃或断电时目标文件保持原样，不会留下写了一半的文件。
+
+use serde::Serialize;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".tmp");
+    PathBuf::from(name)
+}
+
+/// 原子写入内容，父目录不存在时自动创建
+pub fn write_atomic(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let tmp = tmp_path(path);
+    let written = fs::File::create(&tmp).and_then(|mut file| {
+        file.write_all(content)?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|_| fs::rename(&tmp, path)) {
+        let _ = fs::remove_file(&tmp);
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// 以格式化的 JSON 原子写入
+pub fn write_json_atomic<T: Serialize + ?Sized>(path: &Path, value: &T) -> std::io::Result<()> {
+    let mut json = serde_json::to_string_pretty(value).map_err(std::io::Error::other)?;
+    json.push('\n');
+    write_atomic(path, json.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_json_atomic_replaces_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("state.json");
+
+        write_json_atomic(&path, &serde_json::json!({ "a": 1 })).unwrap();
+        write_json_atomic(&path, &serde_json::json!({ "a": 2 })).unwrap();
+        let saved: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["a"], 2);
+        assert!(!tmp_path(&path).exists());
+
+        // 目标是目录时重命名失败，不留下临时文件
+        let target = dir.path().join("dir.json");
+        fs::create_dir_all(target.join("child")).unwrap();
+        assert!(write_json_atomic(&target, &1).is_err());
+        assert!(!tmp_path(&target).exists());
+    }
+}
@@ -0,0 +1,209 @@
+//! 附件暂存区
+//!
+//! 用户选择要发给后端的文件时，由应用把文件复制到 `DAWEI_HOME/attachments/<会话>/<附件 ID>/<文件名>`，
+//! 前端只拿到附件句柄（ID 和暂存路径）转交给后端，文件内容不经过 IPC。
+//! 复制前检查：单个文件不超过 50 MB、每个会话合计不超过 200 MB，拒绝可执行文件
+//! （按扩展名和文件头判断）。每个文件单独处理并返回结果，部分失败不影响其他文件。
+//! 删除会话时前端调用 `clear_attachments` 删除该会话的暂存文件。
+
+use serde::Serialize;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// 单个附件的大小上限
+const MAX_FILE_SIZE: u64 = 50 * 1024 * 1024;
+
+/// 每个会话的附件合计大小上限
+const MAX_SESSION_SIZE: u64 = 200 * 1024 * 1024;
+
+/// 不允许作为附件的扩展名（小写）
+const BLOCKED_EXTENSIONS: [&str; 16] =
+    ["exe", "dll", "msi", "bat", "cmd", "com", "scr", "ps1", "vbs", "sh", "app", "dylib", "so", "jar", "lnk", "deb"];
+
+/// 可执行文件的文件头：PE、ELF、Mach-O（32/64 位及通用二进制）
+const EXECUTABLE_MAGIC: [&[u8]; 6] =
+    [b"MZ", b"\x7fELF", b"\xfe\xed\xfa\xce", b"\xfe\xed\xfa\xcf", b"\xcf\xfa\xed\xfe", b"\xca\xfe\xba\xbe"];
+
+/// 暂存的附件
+#[derive(Debug, Clone, Serialize)]
+pub struct Attachment {
+    pub id: String,
+    pub name: String,
+    pub size: u64,
+    pub mime_type: &'static str,
+    /// 暂存后的绝对路径，交给后端读取
+    pub path: PathBuf,
+}
+
+/// 单个文件的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct StageResult {
+    pub source: PathBuf,
+    pub attachment: Option<Attachment>,
+    /// 失败原因
+    pub error: Option<String>,
+}
+
+fn attachments_root() -> PathBuf {
+    crate::get_dawei_home().join("attachments")
+}
+
+/// 会话的暂存目录，会话 ID 只能包含字母、数字、`-` 和 `_`
+fn session_dir(root: &Path, session: &str) -> Result<PathBuf, String> {
+    let valid = !session.is_empty() && session.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(format!("无效的会话 ID: {}", session));
+    }
+    Ok(root.join(session))
+}
+
+/// 按扩展名判断 MIME 类型，未知类型为 `application/octet-stream`
+fn mime_type(name: &str) -> &'static str {
+    let ext = Path::new(name).extension().map(|ext| ext.to_string_lossy().to_lowercase()).unwrap_or_default();
+    match ext.as_str() {
+        "txt" | "log" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "yaml" | "yml" => "application/yaml",
+        "pdf" => "application/pdf",
+        "doc" => "application/msword",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "zip" => "application/zip",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "py" | "rs" | "js" | "ts" | "java" | "c" | "cpp" | "h" | "go" | "toml" => "text/plain",
+        _ => "application/octet-stream",
+    }
+}
+
+/// 检查文件类型，不允许时返回原因
+fn check_type(path: &Path) -> Result<(), String> {
+    let ext = path.extension().map(|ext| ext.to_string_lossy().to_lowercase()).unwrap_or_default();
+    if BLOCKED_EXTENSIONS.contains(&ext.as_str()) {
+        return Err(format!("不支持的文件类型: .{}", ext));
+    }
+    let mut header = [0u8; 4];
+    let len = fs::File::open(path).and_then(|mut file| file.read(&mut header)).map_err(|e| format!("无法读取文件: {}", e))?;
+    if EXECUTABLE_MAGIC.iter().any(|magic| header[..len].starts_with(magic)) {
+        return Err("不支持可执行文件".to_string());
+    }
+    Ok(())
+}
+
+/// 目录中所有文件的大小之和
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| match entry.file_type() {
+            Ok(kind) if kind.is_dir() => dir_size(&entry.path()),
+            _ => entry.metadata().map(|m| m.len()).unwrap_or_default(),
+        })
+        .sum()
+}
+
+/// 复制单个文件到会话目录，`used` 为会话已用的大小
+fn stage_one(source: &Path, dir: &Path, used: &mut u64) -> Result<Attachment, String> {
+    let metadata = fs::metadata(source).map_err(|e| format!("文件不存在: {}", e))?;
+    if !metadata.is_file() {
+        return Err("不是普通文件".to_string());
+    }
+    if metadata.len() > MAX_FILE_SIZE {
+        return Err(format!("文件超过 {} MB", MAX_FILE_SIZE / 1024 / 1024));
+    }
+    if *used + metadata.len() > MAX_SESSION_SIZE {
+        return Err(format!("会话的附件合计超过 {} MB", MAX_SESSION_SIZE / 1024 / 1024));
+    }
+    check_type(source)?;
+
+    let name = source.file_name().map(|n| n.to_string_lossy().to_string()).ok_or_else(|| "无效的文件名".to_string())?;
+    let id = uuid::Uuid::new_v4().to_string();
+    let target_dir = dir.join(&id);
+    let target = target_dir.join(&name);
+    fs::create_dir_all(&target_dir).map_err(|e| format!("无法创建暂存目录: {}", e))?;
+    if let Err(e) = fs::copy(source, &target) {
+        let _ = fs::remove_dir_all(&target_dir);
+        return Err(format!("复制失败: {}", e));
+    }
+    *used += metadata.len();
+    Ok(Attachment { id, mime_type: mime_type(&name), name, size: metadata.len(), path: target })
+}
+
+fn stage_in(root: &Path, session: &str, paths: &[PathBuf]) -> Result<Vec<StageResult>, String> {
+    let dir = session_dir(root, session)?;
+    let mut used = dir_size(&dir);
+    Ok(paths
+        .iter()
+        .map(|source| {
+            let result = stage_one(source, &dir, &mut used);
+            if let Err(e) = &result {
+                tracing::warn!("Rejected attachment {}: {}", source.display(), e);
+            }
+            StageResult { source: source.clone(), error: result.as_ref().err().cloned(), attachment: result.ok() }
+        })
+        .collect())
+}
+
+/// 把文件复制到会话的暂存区（阻塞调用）
+pub fn stage(session: &str, paths: &[PathBuf]) -> Result<Vec<StageResult>, String> {
+    stage_in(&attachments_root(), session, paths)
+}
+
+/// 删除一个附件，不存在时返回 false
+pub fn remove(session: &str, id: &str) -> Result<bool, String> {
+    let id = uuid::Uuid::parse_str(id).map_err(|_| format!("无效的附件 ID: {}", id))?;
+    let dir = session_dir(&attachments_root(), session)?.join(id.to_string());
+    if !dir.exists() {
+        return Ok(false);
+    }
+    fs::remove_dir_all(&dir).map_err(|e| format!("无法删除附件: {}", e))?;
+    Ok(true)
+}
+
+/// 删除会话的所有附件
+pub fn clear(session: &str) -> Result<(), String> {
+    let dir = session_dir(&attachments_root(), session)?;
+    match fs::remove_dir_all(&dir) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("无法删除附件: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stage_checks() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("attachments");
+        let notes = dir.path().join("notes.md");
+        fs::write(&notes, "# notes").unwrap();
+        let renamed_binary = dir.path().join("tool.txt");
+        fs::write(&renamed_binary, b"\x7fELF\x02\x01").unwrap();
+        let script = dir.path().join("run.sh");
+        fs::write(&script, "echo hi").unwrap();
+
+        let results = stage_in(&root, "chat-1", &[notes.clone(), renamed_binary, script, dir.path().join("missing")]).unwrap();
+        let attachment = results[0].attachment.as_ref().unwrap();
+        assert_eq!((attachment.name.as_str(), attachment.mime_type, attachment.size), ("notes.md", "text/markdown", 7));
+        assert_eq!(fs::read_to_string(&attachment.path).unwrap(), "# notes");
+        assert!(attachment.path.starts_with(root.join("chat-1")));
+        assert!(results[1..].iter().all(|result| result.attachment.is_none() && result.error.is_some()));
+
+        assert!(stage_in(&root, "../escape", &[notes]).is_err());
+        assert_eq!(dir_size(&root.join("chat-1")), 7);
+    }
+}
@@ -0,0 +1,291 @@
+//! 后端异常采集模块
+//!
+//! Python 后端会把 traceback 写到 stderr 以及 DAWEI_HOME/logs/agentic/agentic.log。
+//! 这里从这两处提取 traceback 并转换为 `source: "backend"` 的崩溃报告，
+//! 让前后端的所有故障都能在同一个崩溃报告列表中查看。
+
+use crate::crash_handler::CrashReport;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::process::Child;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// traceback 起始行
+const TRACEBACK_HEADER: &str = "Traceback (most recent call last):";
+
+/// 异常链中连接两个 traceback 的说明行
+const CHAIN_MARKERS: [&str; 2] = [
+    "During handling of the above exception, another exception occurred:",
+    "The above exception was the direct cause of the following exception:",
+];
+
+/// 日志文件轮询间隔
+const LOG_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 用于去重的最近 traceback 数量（stderr 和日志文件可能输出同一个异常）
+const RECENT_CAPACITY: usize = 32;
+
+/// 最近写入的 traceback 指纹
+static RECENT: Mutex<VecDeque<u64>> = Mutex::new(VecDeque::new());
+
+/// 提取出的 Python traceback
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Traceback {
+    /// 最终的异常行，例如 `ValueError: bad value`
+    pub exception: String,
+    /// 完整 traceback 文本（包含前一行日志上下文）
+    pub text: String,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+enum State {
+    #[default]
+    Idle,
+    /// 正在读取堆栈帧
+    Frames,
+    /// 已读到异常行，等待判断是否有异常链
+    AfterException,
+}
+
+/// 按行解析 traceback 的状态机
+#[derive(Debug, Default)]
+pub struct TracebackParser {
+    state: State,
+    /// 最近一行普通输出，通常是 logger.exception 的消息
+    context: Option<String>,
+    lines: Vec<String>,
+    exception: String,
+}
+
+impl TracebackParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 输入一行，若一个完整的 traceback 结束则返回它
+    pub fn feed(&mut self, line: &str) -> Option<Traceback> {
+        let line = line.trim_end_matches(['\r', '\n']);
+
+        match self.state {
+            State::Idle => {
+                if line.trim_start() == TRACEBACK_HEADER {
+                    self.lines.extend(self.context.take());
+                    self.lines.push(line.to_string());
+                    self.state = State::Frames;
+                } else if !line.trim().is_empty() {
+                    self.context = Some(line.to_string());
+                }
+                None
+            }
+            State::Frames => {
+                self.lines.push(line.to_string());
+                if !line.is_empty() && !line.starts_with(char::is_whitespace) {
+                    self.exception = line.to_string();
+                    self.state = State::AfterException;
+                }
+                None
+            }
+            State::AfterException => {
+                let trimmed = line.trim();
+                if trimmed.is_empty() || CHAIN_MARKERS.contains(&trimmed) {
+                    self.lines.push(line.to_string());
+                    None
+                } else if trimmed == TRACEBACK_HEADER {
+                    self.lines.push(line.to_string());
+                    self.state = State::Frames;
+                    None
+                } else {
+                    let traceback = self.finish();
+                    // 当前行不属于 traceback，重新按普通行处理
+                    self.feed(line);
+                    traceback
+                }
+            }
+        }
+    }
+
+    /// 输入结束（进程退出或一批日志读取完毕），输出已完整的 traceback
+    pub fn finish(&mut self) -> Option<Traceback> {
+        if self.state != State::AfterException {
+            return None;
+        }
+
+        while self.lines.last().is_some_and(|l| l.trim().is_empty()) {
+            self.lines.pop();
+        }
+        let traceback = Traceback {
+            exception: std::mem::take(&mut self.exception),
+            text: std::mem::take(&mut self.lines).join("\n"),
+        };
+        self.state = State::Idle;
+        Some(traceback)
+    }
+}
+
+/// 将 traceback 保存为后端崩溃报告（重复的 traceback 会被跳过）
+pub fn ingest(traceback: Traceback) {
+    let mut hasher = DefaultHasher::new();
+    traceback.text.hash(&mut hasher);
+    let fingerprint = hasher.finish();
+
+    if let Ok(mut recent) = RECENT.lock() {
+        if recent.contains(&fingerprint) {
+            return;
+        }
+        if recent.len() == RECENT_CAPACITY {
+            recent.pop_front();
+        }
+        recent.push_back(fingerprint);
+    }
+
+    crate::breadcrumbs::record("backend", format!("Backend exception: {}", traceback.exception));
+    let report = CrashReport::backend(traceback.exception, traceback.text);
+    if let Err(e) = report.save() {
+        tracing::error!("Failed to save backend crash report: {}", e);
+    }
+}
+
+/// 接管后端子进程的 stderr：原样转发并从中提取 traceback
+pub fn capture_backend_stderr(child: &mut Child) {
+    let Some(stderr) = child.stderr.take() else {
+        return;
+    };
+
+    std::thread::spawn(move || {
+        let mut parser = TracebackParser::new();
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+            tracing::info!(target: crate::log_timeline::BACKEND_TARGET, "{}", line);
+            if let Some(traceback) = parser.feed(&line) {
+                ingest(traceback);
+            }
+        }
+        if let Some(traceback) = parser.finish() {
+            ingest(traceback);
+        }
+    });
+}
+
+/// 后端主日志文件
+pub fn backend_log_file() -> PathBuf {
+    crate::get_dawei_home().join("logs").join("agentic").join("agentic.log")
+}
+
+/// 后端日志的最后 `max_lines` 行，没有日志时返回空字符串
+pub fn backend_log_tail(max_lines: usize) -> String {
+    crate::logging::tail_lines(&backend_log_file(), max_lines).unwrap_or_default()
+}
+
+/// 在后台线程中跟踪后端日志文件的新增内容并提取 traceback
+///
+/// 只处理启动之后追加的内容，历史日志不会被重复导入。
+pub fn watch_backend_log() {
+    let path = backend_log_file();
+
+    std::thread::spawn(move || {
+        let mut offset = path.metadata().map(|m| m.len()).unwrap_or(0);
+        let mut pending = String::new();
+        let mut parser = TracebackParser::new();
+        let mut stream = crate::log_timeline::BackendStream::default();
+
+        loop {
+            std::thread::sleep(LOG_POLL_INTERVAL);
+
+            let Ok(len) = path.metadata().map(|m| m.len()) else {
+                continue;
+            };
+            if len < offset {
+                // 日志已轮转，从新文件开头读取
+                offset = 0;
+                pending.clear();
+            }
+            if len == offset {
+                continue;
+            }
+
+            let mut chunk = Vec::new();
+            let read = File::open(&path).and_then(|mut file| {
+                file.seek(SeekFrom::Start(offset))?;
+                file.take(len - offset).read_to_end(&mut chunk)
+            });
+            if read.is_err() {
+                continue;
+            }
+            offset = len;
+
+            pending.push_str(&String::from_utf8_lossy(&chunk));
+            // 最后一行可能还没写完，留到下次
+            let complete = pending.rfind('\n').map(|i| i + 1).unwrap_or(0);
+            let lines: String = pending.drain(..complete).collect();
+
+            for line in lines.lines() {
+                stream.feed(line);
+                if let Some(traceback) = parser.feed(line) {
+                    ingest(traceback);
+                }
+            }
+            if let Some(traceback) = parser.finish() {
+                ingest(traceback);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_all(input: &str) -> Vec<Traceback> {
+        let mut parser = TracebackParser::new();
+        let mut result: Vec<Traceback> = input.lines().filter_map(|l| parser.feed(l)).collect();
+        result.extend(parser.finish());
+        result
+    }
+
+    #[test]
+    fn test_parse_logged_traceback_with_context() {
+        let input = "\
+2026-01-01 10:00:00 - dawei.server - INFO - started
+2026-01-01 10:00:01 - dawei.agentic - ERROR - Task failed
+Traceback (most recent call last):
+  File \"/app/dawei/agentic/agent.py\", line 10, in run
+    result = step()
+             ^^^^^^
+ValueError: bad value
+2026-01-01 10:00:02 - dawei.server - INFO - next";
+
+        let tracebacks = parse_all(input);
+        assert_eq!(tracebacks.len(), 1);
+        assert_eq!(tracebacks[0].exception, "ValueError: bad value");
+        assert!(tracebacks[0].text.starts_with("2026-01-01 10:00:01 - dawei.agentic - ERROR - Task failed"));
+        assert!(!tracebacks[0].text.contains("next"));
+    }
+
+    #[test]
+    fn test_parse_chained_traceback_as_single_report() {
+        let input = "\
+Traceback (most recent call last):
+  File \"a.py\", line 1, in <module>
+KeyError: 'x'
+
+During handling of the above exception, another exception occurred:
+
+Traceback (most recent call last):
+  File \"a.py\", line 3, in <module>
+RuntimeError: wrapped";
+
+        let tracebacks = parse_all(input);
+        assert_eq!(tracebacks.len(), 1);
+        assert_eq!(tracebacks[0].exception, "RuntimeError: wrapped");
+        assert!(tracebacks[0].text.contains("KeyError: 'x'"));
+    }
+
+    #[test]
+    fn test_plain_output_is_ignored() {
+        assert!(parse_all("INFO: Uvicorn running\nWARNING: slow request").is_empty());
+    }
+}
@@ -0,0 +1,139 @@
+//! 后端进程管理
+//!
+//! 记录由桌面端启动的后端进程，后台线程等待进程退出并回收，供菜单和命令停止或重启后端。
+//! 不是由桌面端启动的后端（例如命令行手动启动）不在这里管理。
+
+use std::process::Child;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 正在运行的后端进程 PID
+static BACKEND_PID: Mutex<Option<u32>> = Mutex::new(None);
+
+/// 停止后端时等待进程退出的时间，超时后强制结束
+const STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 后端健康检查
+pub async fn healthy(client: &reqwest::Client) -> bool {
+    let url = format!("http://127.0.0.1:{}/api/health", crate::backend_profile::port());
+    client.get(url).send().await.is_ok_and(|response| response.status().is_success())
+}
+
+/// 等待后端可以访问，进程提前退出或超时返回 false
+pub async fn wait_until_healthy(timeout: Duration) -> bool {
+    let client = reqwest::Client::builder().timeout(Duration::from_secs(1)).build().unwrap_or_default();
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if healthy(&client).await {
+            return true;
+        }
+        if pid().is_none() {
+            return false;
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    false
+}
+
+/// 当前由桌面端启动且仍在运行的后端进程 PID
+pub fn pid() -> Option<u32> {
+    *BACKEND_PID.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 记录新启动的后端进程，进程退出后自动清除
+pub fn track(mut child: Child) {
+    let pid = child.id();
+    *BACKEND_PID.lock().unwrap_or_else(|e| e.into_inner()) = Some(pid);
+
+    let result = std::thread::Builder::new().name("backend-reaper".to_string()).spawn(move || {
+        let status = child.wait();
+        {
+            let mut current = BACKEND_PID.lock().unwrap_or_else(|e| e.into_inner());
+            if *current == Some(pid) {
+                *current = None;
+            }
+        }
+        match status {
+            Ok(status) => crate::breadcrumbs::record("backend", format!("Backend exited (PID: {}): {}", pid, status)),
+            Err(e) => tracing::warn!("Failed to wait for backend process {}: {}", pid, e),
+        }
+    });
+    if let Err(e) = result {
+        tracing::warn!("Failed to spawn backend reaper thread: {}", e);
+    }
+}
+
+/// 结束进程，`force` 为 false 时先请求进程自行退出
+#[cfg(unix)]
+fn terminate(pid: u32, force: bool) -> std::io::Result<()> {
+    let signal = if force { libc::SIGKILL } else { libc::SIGTERM };
+    // SAFETY: 只向记录的后端进程发送信号
+    if unsafe { libc::kill(pid as libc::pid_t, signal) } == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+/// 结束进程及其子进程（Windows 没有 SIGTERM，直接结束进程树）
+#[cfg(windows)]
+fn terminate(pid: u32, _force: bool) -> std::io::Result<()> {
+    let status = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T", "/F"])
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!("taskkill exited with {}", status)))
+    }
+}
+
+/// 等待进程退出（回收线程清除 PID），超时返回 false
+fn wait_exit(pid: u32, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if self::pid() != Some(pid) {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    self::pid() != Some(pid)
+}
+
+/// 停止后端并等待其退出，返回是否有正在运行的后端（阻塞调用）
+pub fn stop() -> Result<bool, String> {
+    let Some(pid) = pid() else {
+        return Ok(false);
+    };
+
+    crate::breadcrumbs::record("backend", format!("Stopping backend (PID: {})", pid));
+    terminate(pid, false).map_err(|e| format!("无法停止后端进程 {}: {}", pid, e))?;
+    if wait_exit(pid, STOP_TIMEOUT) {
+        return Ok(true);
+    }
+
+    tracing::warn!("Backend did not exit within {}s, killing it", STOP_TIMEOUT.as_secs());
+    terminate(pid, true).map_err(|e| format!("无法停止后端进程 {}: {}", pid, e))?;
+    if wait_exit(pid, Duration::from_secs(2)) {
+        Ok(true)
+    } else {
+        Err(format!("后端进程 {} 未能退出", pid))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_track_and_stop() {
+        let child = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = child.id();
+        track(child);
+        assert_eq!(super::pid(), Some(pid));
+
+        assert_eq!(stop(), Ok(true));
+        assert_eq!(super::pid(), None);
+        assert_eq!(stop(), Ok(false));
+    }
+}
@@ -0,0 +1,115 @@
+//! 后端启动配置
+//!
+//! 设置中可以保存多个命名配置（例如 dev / staging / prod），每个配置包含
+//! 后端源码目录、环境变量、端口和日志级别；`active_profile` 决定 start_backend
+//! 使用哪一个，便于在 git 源码和安装包中的后端之间切换对比。
+//! 没有选择配置时保持原有行为：debug 构建从源码启动，发布版使用安装包中的后端。
+//! 锁定模式下忽略源码目录和环境变量。
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+/// 后端默认端口
+pub const DEFAULT_PORT: u16 = 8465;
+
+/// 后端支持的日志级别（与 `dawei server start --log-level` 一致）
+const LOG_LEVELS: [&str; 5] = ["critical", "error", "warning", "info", "debug"];
+
+/// 后端启动配置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackendProfile {
+    /// agent 源码目录，设置后通过 `uv run --directory` 启动
+    pub agent_dir: Option<PathBuf>,
+    /// 额外的环境变量
+    pub env: BTreeMap<String, String>,
+    /// 服务端口（默认 8465）
+    pub port: Option<u16>,
+    /// 日志级别
+    pub log_level: Option<String>,
+}
+
+impl BackendProfile {
+    /// 追加到 `server start` 之后的参数
+    pub fn server_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(port) = self.port {
+            args.extend(["--port".to_string(), port.to_string()]);
+        }
+        if let Some(level) = &self.log_level {
+            args.extend(["--log-level".to_string(), level.clone()]);
+        }
+        args
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(level) = &self.log_level {
+            if !LOG_LEVELS.contains(&level.as_str()) {
+                return Err(format!("日志级别无效: {}（可选 {}）", level, LOG_LEVELS.join(" / ")));
+            }
+        }
+        if let Some(dir) = &self.agent_dir {
+            if !dir.is_dir() {
+                return Err(format!("后端源码目录不存在: {}", dir.display()));
+            }
+        }
+        if self.port == Some(0) {
+            return Err("端口不能为 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 当前使用的配置名称和内容（没有选择配置或配置已被删除时为 None）
+pub fn active() -> Option<(String, BackendProfile)> {
+    let settings = crate::settings::current();
+    let name = settings.active_profile?;
+    match settings.backend_profiles.get(&name) {
+        // 锁定模式下不允许以源码或自定义环境变量启动后端
+        Some(profile) if crate::policy::is_locked_down() => {
+            Some((name, BackendProfile { agent_dir: None, env: BTreeMap::new(), ..profile.clone() }))
+        }
+        Some(profile) => Some((name, profile.clone())),
+        None => {
+            tracing::warn!("Backend profile {:?} not found, using defaults", name);
+            None
+        }
+    }
+}
+
+/// 当前配置的后端端口
+pub fn port() -> u16 {
+    active().and_then(|(_, profile)| profile.port).unwrap_or(DEFAULT_PORT)
+}
+
+/// 切换后端配置，`None` 恢复默认行为（下次启动后端时生效）
+pub fn set_active(app: &AppHandle, name: Option<String>) -> Result<Option<String>, String> {
+    if let Some(name) = &name {
+        let profiles = crate::settings::current().backend_profiles;
+        let profile = profiles.get(name).ok_or_else(|| format!("后端配置不存在: {}", name))?;
+        profile.validate()?;
+    }
+    crate::settings::update(app, serde_json::json!({ "active_profile": name }))?;
+    crate::breadcrumbs::record("backend", format!("Active backend profile: {}", name.as_deref().unwrap_or("default")));
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_server_args_and_validation() {
+        let profile = BackendProfile { port: Some(9000), log_level: Some("debug".to_string()), ..Default::default() };
+        assert_eq!(profile.server_args(), ["--port", "9000", "--log-level", "debug"]);
+        assert!(profile.validate().is_ok());
+        assert!(BackendProfile::default().server_args().is_empty());
+
+        let invalid = BackendProfile { log_level: Some("verbose".to_string()), ..Default::default() };
+        assert!(invalid.validate().is_err());
+        let missing_dir = BackendProfile { agent_dir: Some(PathBuf::from("/nonexistent/agent")), ..Default::default() };
+        assert!(missing_dir.validate().is_err());
+    }
+}
@@ -0,0 +1,198 @@
+//! 后端环境快照和回滚
+//!
+//! 后端启动并通过健康检查后，记录当前环境为"最后可用"的快照，保存在 DAWEI_HOME/backend-snapshot/：
+//! - `requirements.txt`：`uv pip freeze` 得到的精确版本；
+//! - `uv.lock`：源码目录模式下项目的锁文件；
+//! - `snapshot.json`：快照信息（[`BackendSnapshot`]）。
+//!
+//! 环境没有变化时不重写快照。升级后的后端起不来时快照仍是升级前的环境，`rollback_backend`
+//! 停止后端、按快照重新安装（内置环境用 `uv pip sync`，源码目录恢复锁文件后 `uv sync --frozen`），
+//! 再启动后端。设置了 `backend_version_pin` 时只能回滚到固定版本的快照。
+
+use crate::backend_update::BackendEnv;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// 快照目录名
+const SNAPSHOT_DIR: &str = "backend-snapshot";
+
+const REQUIREMENTS_FILE: &str = "requirements.txt";
+const LOCK_FILE: &str = "uv.lock";
+const INFO_FILE: &str = "snapshot.json";
+
+/// 任务栏进度中的任务名
+const ROLLBACK_TASK: &str = "backend-rollback";
+
+/// 快照信息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackendSnapshot {
+    /// 快照中的后端版本
+    pub backend_version: Option<String>,
+    /// 记录快照时的应用版本
+    pub app_version: String,
+    /// 已安装的包数量
+    pub packages: usize,
+    /// 是否包含源码目录的锁文件
+    pub has_lock: bool,
+    pub created_at: String,
+}
+
+fn snapshot_dir() -> PathBuf {
+    crate::get_dawei_home().join(SNAPSHOT_DIR)
+}
+
+/// freeze 输出中某个包的版本（包名不区分大小写，`-` 和 `_` 等价）
+fn package_version(freeze: &str, package: &str) -> Option<String> {
+    let normalize = |name: &str| name.trim().to_lowercase().replace('_', "-");
+    freeze.lines().find_map(|line| {
+        let (name, version) = line.split_once("==")?;
+        (normalize(name) == normalize(package)).then(|| version.trim().to_string())
+    })
+}
+
+fn write_file(path: &Path, content: &[u8]) -> Result<(), String> {
+    crate::atomic_file::write_atomic(path, content).map_err(|e| format!("保存快照失败: {}", e))
+}
+
+/// 保存快照，内容与现有快照相同时不重写并返回 None
+fn save(dir: &Path, freeze: &str, lock: Option<&[u8]>) -> Result<Option<BackendSnapshot>, String> {
+    let unchanged = fs::read_to_string(dir.join(REQUIREMENTS_FILE)).is_ok_and(|existing| existing == freeze)
+        && fs::read(dir.join(LOCK_FILE)).ok().as_deref() == lock
+        && dir.join(INFO_FILE).exists();
+    if unchanged {
+        return Ok(None);
+    }
+
+    fs::create_dir_all(dir).map_err(|e| format!("无法创建快照目录: {}", e))?;
+    write_file(&dir.join(REQUIREMENTS_FILE), freeze.as_bytes())?;
+    match lock {
+        Some(lock) => write_file(&dir.join(LOCK_FILE), lock)?,
+        None => {
+            let _ = fs::remove_file(dir.join(LOCK_FILE));
+        }
+    }
+    let snapshot = BackendSnapshot {
+        backend_version: package_version(freeze, crate::backend_update::PACKAGE),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        packages: freeze.lines().filter(|line| !line.trim().is_empty() && !line.starts_with('#')).count(),
+        has_lock: lock.is_some(),
+        created_at: chrono::Local::now().to_rfc3339(),
+    };
+    let json = serde_json::to_string_pretty(&snapshot).map_err(|e| e.to_string())?;
+    write_file(&dir.join(INFO_FILE), json.as_bytes())?;
+    Ok(Some(snapshot))
+}
+
+fn read(dir: &Path) -> Option<BackendSnapshot> {
+    let content = fs::read_to_string(dir.join(INFO_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// 当前的快照
+pub fn current() -> Option<BackendSnapshot> {
+    read(&snapshot_dir())
+}
+
+/// 后端通过健康检查后记录当前环境为最后可用的快照（阻塞调用）
+pub fn mark_good() {
+    let result = crate::backend_update::backend_env().and_then(|env| {
+        let mut command = Command::new(crate::get_uv_path());
+        command.args(["pip", "freeze"]);
+        let lock = match &env {
+            BackendEnv::Source(agent_dir) => {
+                command.current_dir(agent_dir);
+                fs::read(agent_dir.join(LOCK_FILE)).ok()
+            }
+            BackendEnv::Bundled { python, .. } => {
+                command.arg("--python").arg(python);
+                None
+            }
+        };
+        let freeze = crate::backend_update::run_uv(&mut command, "读取后端环境")?;
+        save(&snapshot_dir(), &freeze, lock.as_deref())
+    });
+    match result {
+        Ok(Some(snapshot)) => tracing::info!("Recorded backend snapshot ({:?}, {} packages)", snapshot.backend_version, snapshot.packages),
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Failed to record backend snapshot: {}", e),
+    }
+}
+
+/// 按快照重新安装后端环境（阻塞调用）
+fn restore(dir: &Path) -> Result<(), String> {
+    let requirements = dir.join(REQUIREMENTS_FILE);
+    let mut command = Command::new(crate::get_uv_path());
+    match crate::backend_update::backend_env()? {
+        BackendEnv::Source(agent_dir) => {
+            let lock = dir.join(LOCK_FILE);
+            if !lock.exists() {
+                return Err("快照不是在源码目录模式下记录的，无法回滚".to_string());
+            }
+            fs::copy(&lock, agent_dir.join(LOCK_FILE)).map_err(|e| format!("无法恢复锁文件: {}", e))?;
+            command.args(["sync", "--frozen", "--directory"]).arg(&agent_dir).current_dir(&agent_dir);
+            command.args(crate::offline_wheels::index_args(None));
+        }
+        BackendEnv::Bundled { python, .. } => {
+            command
+                .args(["pip", "sync", "--python"])
+                .arg(python)
+                .args(crate::offline_wheels::index_args(Some(crate::connectivity::pypi_index())))
+                .arg(requirements);
+        }
+    }
+    crate::backend_update::run_uv(&mut command, "回滚后端").map(|_| ())
+}
+
+/// 回滚到最后可用的快照并重新启动后端
+pub async fn rollback(app: &tauri::AppHandle) -> Result<BackendSnapshot, String> {
+    let dir = snapshot_dir();
+    let snapshot = read(&dir).ok_or_else(|| "没有可回滚的后端快照".to_string())?;
+    if let Some(pin) = crate::backend_update::version_pin()? {
+        if snapshot.backend_version.as_deref() != Some(pin.as_str()) {
+            return Err(format!("快照中的后端版本 {:?} 与固定版本 {} 不同，请先清除固定版本", snapshot.backend_version, pin));
+        }
+    }
+    crate::breadcrumbs::record("backend", format!("Rolling back backend to {:?}", snapshot.backend_version));
+    crate::task_progress::update(app, ROLLBACK_TASK, crate::task_progress::TaskState::Running(None));
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        crate::backend_process::stop()?;
+        restore(&dir)
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|result| result);
+    if let Err(e) = result {
+        crate::task_progress::update(app, ROLLBACK_TASK, crate::task_progress::TaskState::Failed);
+        return Err(e);
+    }
+    crate::task_progress::update(app, ROLLBACK_TASK, crate::task_progress::TaskState::Done);
+    tracing::info!("Backend rolled back to {:?}", snapshot.backend_version);
+
+    crate::start_backend(app.clone()).await?;
+    Ok(snapshot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let freeze = "anyio==4.4.0\nDavyBot==0.5.1\n-e file:///src/agent\n";
+        assert_eq!(package_version(freeze, "davybot").as_deref(), Some("0.5.1"));
+
+        let snapshot = save(dir.path(), freeze, Some(b"version = 1")).unwrap().unwrap();
+        assert_eq!((snapshot.backend_version.as_deref(), snapshot.packages, snapshot.has_lock), (Some("0.5.1"), 3, true));
+        assert_eq!(read(dir.path()), Some(snapshot));
+
+        // 环境没有变化时不重写
+        assert_eq!(save(dir.path(), freeze, Some(b"version = 1")).unwrap(), None);
+        let upgraded = save(dir.path(), "DavyBot==0.6.0\n", None).unwrap().unwrap();
+        assert_eq!(upgraded.backend_version.as_deref(), Some("0.6.0"));
+        assert!(!dir.path().join(LOCK_FILE).exists());
+    }
+}
@@ -0,0 +1,216 @@
+//! 后端响应统计
+//!
+//! 桌面端不代理前端的请求，只能通过健康检查观察后端：后台任务定期请求 `/api/health`，
+//! 在最近 100 次结果上计算延迟的 p50/p95 和错误率，供 `get_backend_stats` 查询，
+//! 用于排查"智能体很慢"一类的反馈（区分后端本身卡顿和模型接口慢）。
+//! 后端未启动（连接被拒绝）时不计入统计；p95 或错误率超过阈值时发送 `backend-stats-alert`
+//! 事件，恢复正常时再发送一次。
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// 延迟告警事件，内容为 [`BackendStats`]
+pub const BACKEND_STATS_ALERT_EVENT: &str = "backend-stats-alert";
+
+/// 采样间隔
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 单次健康检查的超时（超时计为错误）
+const SAMPLE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 统计窗口的采样数
+const WINDOW_SIZE: usize = 100;
+
+/// 至少有这么多采样才判断是否变慢
+const MIN_SAMPLES: usize = 10;
+
+/// p95 超过该值视为变慢
+const P95_ALERT_MS: u64 = 2000;
+
+/// 错误率超过该值视为变慢
+const ERROR_RATE_ALERT: f64 = 0.2;
+
+static STATE: Mutex<State> = Mutex::new(State { samples: VecDeque::new(), online: false, degraded: false, last_error: None });
+
+/// 统计结果
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct BackendStats {
+    /// 统计窗口内的采样数
+    pub samples: usize,
+    pub p50_ms: Option<u64>,
+    pub p95_ms: Option<u64>,
+    /// 失败（超时、非 2xx）的比例
+    pub error_rate: f64,
+    /// 最近一次检查时后端可以访问
+    pub online: bool,
+    /// 延迟或错误率超过阈值
+    pub degraded: bool,
+    pub last_error: Option<String>,
+}
+
+struct State {
+    /// 成功时为延迟（毫秒），失败时为 None
+    samples: VecDeque<Option<u64>>,
+    online: bool,
+    degraded: bool,
+    last_error: Option<String>,
+}
+
+/// 单次检查结果
+enum Probe {
+    Ok(u64),
+    Error(String),
+    /// 后端未运行（无法连接）
+    Offline,
+}
+
+/// 最近邻秩法的百分位数，`sorted` 已从小到大排列
+fn percentile(sorted: &[u64], p: f64) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+impl State {
+    /// 记录一次检查结果，变慢或恢复时返回 true
+    fn record(&mut self, probe: Probe) -> bool {
+        match probe {
+            Probe::Ok(latency) => {
+                self.online = true;
+                self.push(Some(latency));
+            }
+            Probe::Error(error) => {
+                self.push(None);
+                self.last_error = Some(error);
+            }
+            // 刚才还可以访问时计为错误，一直无法连接说明后端没有运行
+            Probe::Offline => {
+                if self.online {
+                    self.push(None);
+                    self.last_error = Some("无法连接后端".to_string());
+                }
+                self.online = false;
+            }
+        }
+        let degraded = self.is_degraded();
+        std::mem::replace(&mut self.degraded, degraded) != degraded
+    }
+
+    fn push(&mut self, sample: Option<u64>) {
+        self.samples.push_back(sample);
+        if self.samples.len() > WINDOW_SIZE {
+            self.samples.pop_front();
+        }
+    }
+
+    fn is_degraded(&self) -> bool {
+        let stats = self.stats();
+        stats.samples >= MIN_SAMPLES
+            && (stats.p95_ms.is_some_and(|p95| p95 > P95_ALERT_MS) || stats.error_rate > ERROR_RATE_ALERT)
+    }
+
+    fn stats(&self) -> BackendStats {
+        let mut latencies: Vec<u64> = self.samples.iter().flatten().copied().collect();
+        latencies.sort_unstable();
+        let errors = self.samples.len() - latencies.len();
+        BackendStats {
+            samples: self.samples.len(),
+            p50_ms: percentile(&latencies, 0.5),
+            p95_ms: percentile(&latencies, 0.95),
+            error_rate: if self.samples.is_empty() { 0.0 } else { errors as f64 / self.samples.len() as f64 },
+            online: self.online,
+            degraded: self.degraded,
+            last_error: self.last_error.clone(),
+        }
+    }
+}
+
+async fn probe(client: &reqwest::Client) -> Probe {
+    let url = format!("http://127.0.0.1:{}/api/health", crate::backend_profile::port());
+    let start = Instant::now();
+    match client.get(url).send().await {
+        Ok(response) if response.status().is_success() => Probe::Ok(start.elapsed().as_millis() as u64),
+        Ok(response) => Probe::Error(format!("HTTP {}", response.status())),
+        Err(e) if e.is_connect() => Probe::Offline,
+        Err(e) if e.is_timeout() => Probe::Error(format!("超过 {} 秒没有响应", SAMPLE_TIMEOUT.as_secs())),
+        Err(e) => Probe::Error(e.to_string()),
+    }
+}
+
+/// 当前统计
+pub fn current() -> BackendStats {
+    STATE.lock().unwrap_or_else(|e| e.into_inner()).stats()
+}
+
+/// 启动后台采样任务
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let client = reqwest::Client::builder().timeout(SAMPLE_TIMEOUT).build().unwrap_or_default();
+        loop {
+            tokio::time::sleep(SAMPLE_INTERVAL).await;
+            let result = probe(&client).await;
+            let (changed, stats) = {
+                let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+                (state.record(result), state.stats())
+            };
+            if !changed {
+                continue;
+            }
+            if stats.degraded {
+                tracing::warn!(
+                    "Backend degraded: p95 {:?} ms, error rate {:.0}%",
+                    stats.p95_ms,
+                    stats.error_rate * 100.0
+                );
+                crate::breadcrumbs::record("backend", "Backend latency degraded");
+            } else {
+                tracing::info!("Backend latency back to normal");
+                crate::breadcrumbs::record("backend", "Backend latency back to normal");
+            }
+            if let Err(e) = app.emit(BACKEND_STATS_ALERT_EVENT, &stats) {
+                tracing::warn!("Failed to emit backend stats alert: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_and_alerts() {
+        let mut state = State { samples: VecDeque::new(), online: false, degraded: false, last_error: None };
+        // 后端未启动时不计入
+        assert!(!state.record(Probe::Offline));
+        assert_eq!(state.stats().samples, 0);
+
+        for latency in 1..=10 {
+            assert!(!state.record(Probe::Ok(latency * 10)));
+        }
+        let stats = state.stats();
+        assert_eq!((stats.p50_ms, stats.p95_ms), (Some(50), Some(100)));
+        assert!(stats.online && !stats.degraded);
+
+        // 3/13 的错误率超过 20%
+        state.record(Probe::Error("HTTP 500".to_string()));
+        state.record(Probe::Offline);
+        assert!(!state.online);
+        assert!(state.record(Probe::Error("timeout".to_string())));
+        let stats = state.stats();
+        assert_eq!(stats.samples, 13);
+        assert!(stats.degraded);
+        assert_eq!(stats.last_error.as_deref(), Some("timeout"));
+
+        // 恢复正常时再通知一次
+        let recovered = (0..5).map(|_| state.record(Probe::Ok(20))).filter(|changed| *changed).count();
+        assert_eq!(recovered, 1);
+        assert!(!state.stats().degraded);
+    }
+}
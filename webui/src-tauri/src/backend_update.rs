@@ -0,0 +1,265 @@
+//! 后端更新检查
+//!
+//! 后端（PyPI 包 `davybot`）可以独立于应用升级。`check_backend_updates` 比较已安装的版本和
+//! 配置的 PyPI 索引（与安装依赖时相同，见 [`crate::connectivity::pypi_index`]）上的最新版本：
+//! 先请求索引站点的 JSON API（`<站点>/pypi/davybot/json`，pypi.org 和常见镜像都支持），
+//! 不支持时改用 simple 索引的 JSON 格式（PEP 691）列出版本。最新版本不含预发布版本。
+//!
+//! 已安装的版本优先从内置的 python-env 读取包元数据，开发模式下没有内置环境时询问运行中的后端。
+//!
+//! 设置了 `backend_version_pin` 时后端固定在该版本：安装和升级只装 `davybot==<版本>`，
+//! 不满足新版本应用最低要求的升级被拒绝，需先清除或修改固定版本（见 [`requirement`]）。
+
+use serde::Serialize;
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::Duration;
+
+/// 后端的 PyPI 包名
+pub const PACKAGE: &str = "davybot";
+
+/// 请求索引的超时时间
+const INDEX_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 没有更新说明链接时使用的 Release 页面
+const RELEASES_URL: &str = "https://github.com/geluzhiwei1/davybot/releases";
+
+/// 后端版本信息
+#[derive(Debug, Clone, Serialize)]
+pub struct BackendUpdateInfo {
+    /// 已安装的版本，无法确定时为 None
+    pub current: Option<String>,
+    pub latest: String,
+    pub update_available: bool,
+    /// 最新版本的更新说明
+    pub changelog_url: String,
+    /// 查询的索引
+    pub index: String,
+    /// 设置中固定的版本，固定时只有已安装的版本与之不同才算有更新
+    pub pin: Option<String>,
+}
+
+/// 内置的 Python 环境（应用目录下的 `resources/python-env`），开发模式下不存在
+pub fn python_env_dir() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    let dir = exe.parent()?.join("resources/python-env");
+    dir.is_dir().then_some(dir)
+}
+
+/// 内置 Python 环境中的解释器
+pub fn python_env_python() -> Option<PathBuf> {
+    let dir = python_env_dir()?;
+    #[cfg(windows)]
+    let candidates = [dir.join("Scripts/python.exe")];
+    #[cfg(unix)]
+    let candidates = [dir.join("bin/python"), dir.join("bin/python3")];
+    candidates.into_iter().find(|path| path.exists())
+}
+
+/// 后端所在的环境
+#[derive(Debug, Clone, PartialEq)]
+pub enum BackendEnv {
+    /// 后端配置指定的源码目录（由 uv 管理的项目）
+    Source(PathBuf),
+    /// 内置的 Python 环境
+    Bundled { dir: PathBuf, python: PathBuf },
+}
+
+/// 当前后端配置使用的环境：配置了源码目录时为源码目录（锁定模式下不使用），否则为内置环境
+pub fn backend_env() -> Result<BackendEnv, String> {
+    let agent_dir = crate::backend_profile::active().and_then(|(_, profile)| profile.agent_dir);
+    if let Some(agent_dir) = agent_dir.filter(|_| !crate::policy::is_locked_down()) {
+        return Ok(BackendEnv::Source(agent_dir));
+    }
+    let dir = python_env_dir().ok_or_else(|| "找不到内置的 Python 环境".to_string())?;
+    let python = python_env_python().ok_or_else(|| "找不到内置环境中的 Python".to_string())?;
+    Ok(BackendEnv::Bundled { dir, python })
+}
+
+/// 运行配置好的 uv 命令（带上后端配置中的环境变量），失败时返回 stderr 的最后几行
+pub fn run_uv(command: &mut Command, purpose: &str) -> Result<String, String> {
+    let env = crate::backend_profile::active().map(|(_, profile)| profile.env).unwrap_or_default();
+    tracing::info!("{}: {:?}", purpose, command);
+    let output = command.envs(&env).output().map_err(|e| format!("无法运行 uv: {}", e))?;
+    if output.status.success() {
+        return Ok(String::from_utf8_lossy(&output.stdout).to_string());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let tail: Vec<&str> = stderr.lines().rev().take(5).collect();
+    Err(format!("{}失败: {}", purpose, tail.into_iter().rev().collect::<Vec<_>>().join("\n")))
+}
+
+/// 内置 Python 环境中已安装的后端版本（阻塞调用）
+pub fn installed_version() -> Option<String> {
+    let python = python_env_python()?;
+    let script = format!("import importlib.metadata as m; print(m.version('{}'))", PACKAGE);
+    let output = Command::new(python).args(["-c", &script]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!version.is_empty()).then_some(version)
+}
+
+/// 解析 PEP 440 版本：发布号各段和是否为预发布版本（a/b/rc/dev，不含 post）
+fn parse_version(version: &str) -> (Vec<u64>, bool) {
+    let version = version.trim().trim_start_matches('v');
+    let version = version.split('+').next().unwrap_or_default();
+    let release: String = version.chars().take_while(|c| c.is_ascii_digit() || *c == '.').collect();
+    let numbers = release.split('.').filter(|part| !part.is_empty()).filter_map(|part| part.parse().ok()).collect();
+    let suffix = version[release.trim_end_matches('.').len()..].trim_start_matches(['.', '-', '_']);
+    (numbers, !suffix.is_empty() && !suffix.starts_with("post"))
+}
+
+/// 固定版本只能是单个确切的版本号（例如 `0.3.2`），不能带比较符
+fn check_pin(pin: &str) -> Result<(), String> {
+    let valid = pin.starts_with(|c: char| c.is_ascii_digit())
+        && pin.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '+' | '!' | '-' | '_'));
+    if valid { Ok(()) } else { Err(format!("无效的后端固定版本: {}，应为确切的版本号，例如 0.3.2", pin)) }
+}
+
+/// 设置中固定的后端版本（`backend_version_pin`），未设置时为 None
+pub fn version_pin() -> Result<Option<String>, String> {
+    let pin = crate::settings::current().backend_version_pin;
+    let pin = pin.map(|pin| pin.trim().to_string()).filter(|pin| !pin.is_empty());
+    pin.map(|pin| check_pin(&pin).map(|_| pin)).transpose()
+}
+
+/// 安装后端时的包要求：固定版本时为 `davybot==<版本>`（低于 min_version 时拒绝），否则为 `davybot>=min_version`
+pub fn requirement(pin: Option<&str>, min_version: Option<&str>) -> Result<String, String> {
+    match (pin, min_version) {
+        (Some(pin), Some(min)) if compare_versions(pin, min).is_lt() => Err(format!(
+            "新版本要求后端 {} 或更高，但设置中固定为 {}，请先清除或修改固定版本（backend_version_pin）",
+            min, pin
+        )),
+        (Some(pin), _) => Ok(format!("{}=={}", PACKAGE, pin)),
+        (None, Some(min)) => Ok(format!("{}>={}", PACKAGE, min)),
+        (None, None) => Ok(PACKAGE.to_string()),
+    }
+}
+
+/// 比较两个版本：按数字段比较发布号，发布号相同时预发布版本较旧
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let ((mut a_numbers, a_pre), (mut b_numbers, b_pre)) = (parse_version(a), parse_version(b));
+    let len = a_numbers.len().max(b_numbers.len());
+    a_numbers.resize(len, 0);
+    b_numbers.resize(len, 0);
+    a_numbers.cmp(&b_numbers).then(b_pre.cmp(&a_pre))
+}
+
+fn is_prerelease(version: &str) -> bool {
+    parse_version(version).1
+}
+
+/// 索引站点的 JSON API 地址：`https://host/simple/` → `https://host/pypi/davybot/json`
+fn json_api_url(index: &str) -> String {
+    let base = index.trim().trim_end_matches('/');
+    let base = base.strip_suffix("/simple").unwrap_or(base);
+    format!("{}/pypi/{}/json", base, PACKAGE)
+}
+
+/// JSON API 响应中的最新版本和更新说明链接
+fn parse_json_api(body: &Value) -> Option<(String, Option<String>)> {
+    let info = &body["info"];
+    let latest = info["version"].as_str()?.to_string();
+    let changelog = info["project_urls"].as_object().and_then(|urls| {
+        urls.iter()
+            .find(|(name, _)| matches!(name.to_lowercase().as_str(), "changelog" | "changes" | "release notes"))
+            .and_then(|(_, url)| url.as_str())
+            .map(str::to_string)
+    });
+    Some((latest, changelog))
+}
+
+/// simple 索引 JSON 响应（PEP 691/700）中的最新正式版本
+fn parse_simple_json(body: &Value) -> Option<String> {
+    body["versions"]
+        .as_array()?
+        .iter()
+        .filter_map(Value::as_str)
+        .filter(|version| !is_prerelease(version))
+        .max_by(|a, b| compare_versions(a, b))
+        .map(str::to_string)
+}
+
+/// 查询索引上的最新版本
+async fn latest_version(index: &str) -> Result<(String, Option<String>), String> {
+    let client = reqwest::Client::builder().timeout(INDEX_TIMEOUT).build().map_err(|e| e.to_string())?;
+
+    let json_api = async {
+        let response = client.get(json_api_url(index)).send().await.ok()?.error_for_status().ok()?;
+        parse_json_api(&response.json().await.ok()?)
+    };
+    if let Some(latest) = json_api.await {
+        return Ok(latest);
+    }
+
+    let url = format!("{}/{}/", index.trim().trim_end_matches('/'), PACKAGE);
+    let response = client
+        .get(&url)
+        .header(reqwest::header::ACCEPT, "application/vnd.pypi.simple.v1+json")
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("无法查询索引 {}: {}", index, e))?;
+    let body: Value = response.json().await.map_err(|_| format!("索引 {} 不支持 JSON 格式查询", index))?;
+    let latest = parse_simple_json(&body).ok_or_else(|| format!("索引 {} 上没有 {} 的正式版本", index, PACKAGE))?;
+    Ok((latest, None))
+}
+
+/// 检查后端更新
+pub async fn check() -> Result<BackendUpdateInfo, String> {
+    let index = crate::connectivity::pypi_index();
+    let installed = tauri::async_runtime::spawn_blocking(installed_version);
+    let (latest, changelog_url) = latest_version(&index).await?;
+    let current = match installed.await.ok().flatten() {
+        Some(version) => Some(version),
+        None => crate::about::backend_version().await,
+    };
+    let pin = version_pin()?;
+    let update_available = match (&pin, current.as_deref()) {
+        (Some(pin), Some(current)) => compare_versions(pin, current) != Ordering::Equal,
+        (None, Some(current)) => compare_versions(&latest, current) == Ordering::Greater,
+        (_, None) => false,
+    };
+    tracing::info!("Backend version {:?}, latest {} on {}", current, latest, index);
+    Ok(BackendUpdateInfo {
+        current,
+        changelog_url: changelog_url.unwrap_or_else(|| format!("{}/tag/v{}", RELEASES_URL, latest)),
+        latest,
+        update_available,
+        index,
+        pin,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_versions_and_index() {
+        assert_eq!(compare_versions("0.10.0", "0.9.3"), Ordering::Greater);
+        assert_eq!(compare_versions("1.2", "1.2.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1.2.0rc1", "1.2.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.2.1.dev3", "1.2.0"), Ordering::Greater);
+        assert!(is_prerelease("0.5.0b2") && !is_prerelease("0.5.0") && !is_prerelease("0.5.0.post1"));
+
+        assert_eq!(json_api_url("https://pypi.org/simple/"), "https://pypi.org/pypi/davybot/json");
+        assert_eq!(json_api_url("https://pypi.tuna.tsinghua.edu.cn/simple"), "https://pypi.tuna.tsinghua.edu.cn/pypi/davybot/json");
+
+        let body = json!({ "info": { "version": "0.4.0", "project_urls": { "Changelog": "https://example.com/changes" } } });
+        assert_eq!(parse_json_api(&body), Some(("0.4.0".to_string(), Some("https://example.com/changes".to_string()))));
+        assert_eq!(parse_simple_json(&json!({ "versions": ["0.3.0", "0.10.0", "0.11.0a1"] })).as_deref(), Some("0.10.0"));
+
+        assert_eq!(requirement(None, None).unwrap(), "davybot");
+        assert_eq!(requirement(None, Some("0.3.0")).unwrap(), "davybot>=0.3.0");
+        assert_eq!(requirement(Some("0.3.2"), Some("0.3.0")).unwrap(), "davybot==0.3.2");
+        assert!(requirement(Some("0.2.9"), Some("0.3.0")).is_err());
+        assert!(check_pin("0.3.2").is_ok() && check_pin("1.0.0rc1").is_ok());
+        assert!(check_pin(">=0.3").is_err() && check_pin("0.3; rm").is_err() && check_pin("latest").is_err());
+    }
+}
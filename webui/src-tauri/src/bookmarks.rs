@@ -0,0 +1,93 @@
+//! 收藏的目录
+//!
+//! 用户收藏常用目录，保存在设置的 `bookmarks` 字段中（同步设置文件时一起同步）。
+//! 文件和目录选择对话框可以指定从某个收藏的目录打开（见 [`crate::file_dialog`]），
+//! 前端据此在对话框旁提供快捷入口。同一路径只收藏一次，再次收藏时更新名称。
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+/// 收藏的目录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    /// 显示名称，默认为目录名
+    pub name: String,
+    pub path: PathBuf,
+    /// 收藏时间（RFC 3339）
+    pub added_at: String,
+}
+
+/// 加入或更新收藏（按路径去重，保持原有顺序）
+fn upsert(bookmarks: &mut Vec<Bookmark>, bookmark: Bookmark) {
+    match bookmarks.iter_mut().find(|existing| existing.path == bookmark.path) {
+        Some(existing) => existing.name = bookmark.name,
+        None => bookmarks.push(bookmark),
+    }
+}
+
+/// 所有收藏的目录
+pub fn list() -> Vec<Bookmark> {
+    crate::settings::current().bookmarks
+}
+
+/// 收藏目录，`name` 为空时使用目录名
+pub fn add(app: &AppHandle, path: &Path, name: Option<&str>) -> Result<Bookmark, String> {
+    let path = crate::symlink_policy::canonicalize(path).map_err(|e| format!("目录不存在: {}", e))?;
+    if !path.is_dir() {
+        return Err(format!("不是目录: {}", path.display()));
+    }
+    let name = match name.map(str::trim).filter(|name| !name.is_empty()) {
+        Some(name) => name.to_string(),
+        None => path.file_name().unwrap_or(path.as_os_str()).to_string_lossy().to_string(),
+    };
+    let bookmark = Bookmark { name, path, added_at: chrono::Local::now().to_rfc3339() };
+    let mut bookmarks = list();
+    upsert(&mut bookmarks, bookmark.clone());
+    crate::settings::update(app, serde_json::json!({ "bookmarks": bookmarks }))?;
+    tracing::info!("Bookmarked {}", bookmark.path.display());
+    Ok(bookmark)
+}
+
+/// 取消收藏，不在收藏中时返回 false
+pub fn remove(app: &AppHandle, path: &Path) -> Result<bool, String> {
+    let mut bookmarks = list();
+    let before = bookmarks.len();
+    bookmarks.retain(|bookmark| bookmark.path != path);
+    if bookmarks.len() == before {
+        return Ok(false);
+    }
+    crate::settings::update(app, serde_json::json!({ "bookmarks": bookmarks }))?;
+    Ok(true)
+}
+
+/// 对话框的初始目录：`path` 必须是收藏的目录且仍然存在
+pub fn start_dir(path: &str) -> Result<PathBuf, String> {
+    let bookmark = list()
+        .into_iter()
+        .find(|bookmark| bookmark.path == Path::new(path))
+        .ok_or_else(|| format!("不是收藏的目录: {}", path))?;
+    if !bookmark.path.is_dir() {
+        return Err(format!("收藏的目录已不存在: {}", bookmark.path.display()));
+    }
+    Ok(bookmark.path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upsert() {
+        let bookmark = |name: &str, path: &str| Bookmark {
+            name: name.to_string(),
+            path: PathBuf::from(path),
+            added_at: String::new(),
+        };
+        let mut bookmarks = vec![bookmark("a", "/a"), bookmark("b", "/b")];
+        upsert(&mut bookmarks, bookmark("c", "/c"));
+        upsert(&mut bookmarks, bookmark("renamed", "/a"));
+        let names: Vec<&str> = bookmarks.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(names, ["renamed", "b", "c"]);
+    }
+}
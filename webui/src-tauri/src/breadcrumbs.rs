@@ -0,0 +1,88 @@
+//! 面包屑模块
+//!
+//! 在内存中保留最近的关键事件（调用的命令、后端状态变化、窗口事件），
+//! 崩溃时随报告一起保存，用于还原崩溃前用户正在做什么。
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// 保留的面包屑数量
+const CAPACITY: usize = 200;
+
+static BREADCRUMBS: Ring = Ring::new(CAPACITY);
+
+/// 单条面包屑
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Breadcrumb {
+    /// ISO 8601 格式的时间
+    pub timestamp_iso: String,
+    /// 分类：command / backend / window / page
+    pub category: String,
+    /// 事件描述
+    pub message: String,
+}
+
+/// 固定容量的面包屑环形缓冲区
+struct Ring {
+    capacity: usize,
+    crumbs: Mutex<VecDeque<Breadcrumb>>,
+}
+
+impl Ring {
+    const fn new(capacity: usize) -> Ring {
+        Ring { capacity, crumbs: Mutex::new(VecDeque::new()) }
+    }
+
+    /// 追加一条面包屑，超出容量时丢弃最旧的记录
+    fn push(&self, crumb: Breadcrumb) {
+        let mut crumbs = self.crumbs.lock().unwrap_or_else(|e| e.into_inner());
+        if crumbs.len() == self.capacity {
+            crumbs.pop_front();
+        }
+        crumbs.push_back(crumb);
+    }
+
+    /// 所有面包屑（从旧到新），锁被占用时返回空列表
+    fn snapshot(&self) -> Vec<Breadcrumb> {
+        match self.crumbs.try_lock() {
+            Ok(crumbs) => crumbs.iter().cloned().collect(),
+            Err(std::sync::TryLockError::Poisoned(e)) => e.into_inner().iter().cloned().collect(),
+            Err(std::sync::TryLockError::WouldBlock) => Vec::new(),
+        }
+    }
+}
+
+/// 记录一条面包屑，超出容量时丢弃最旧的记录
+pub fn record(category: &str, message: impl Into<String>) {
+    BREADCRUMBS.push(Breadcrumb {
+        timestamp_iso: chrono::Local::now().to_rfc3339(),
+        category: category.to_string(),
+        message: message.into(),
+    });
+}
+
+/// 获取当前所有面包屑（从旧到新）
+///
+/// 在 panic hook 中调用时锁可能正被崩溃线程持有，此时返回空列表而不是死锁。
+pub fn snapshot() -> Vec<Breadcrumb> {
+    BREADCRUMBS.snapshot()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_buffer_keeps_latest() {
+        let ring = Ring::new(10);
+        for i in 0..15 {
+            ring.push(Breadcrumb { timestamp_iso: String::new(), category: "test".to_string(), message: format!("event {}", i) });
+        }
+
+        let crumbs = ring.snapshot();
+        assert_eq!(crumbs.len(), 10);
+        assert_eq!(crumbs.first().unwrap().message, "event 5");
+        assert_eq!(crumbs.last().unwrap().message, "event 14");
+    }
+}
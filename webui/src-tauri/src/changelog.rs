@@ -0,0 +1,206 @@
+//! 更新日志
+//!
+//! 更新后"新功能"页面调用 `get_changelog(since_version)`，返回比 `since_version` 新、
+//! 不超过当前版本的各版本说明。说明来自两处：
+//! - 随应用打包的 CHANGELOG.md（离线可用）；
+//! - GitHub Release 的发布说明（同一版本以远程为准，测试版通道才包含预发布版本）。
+//!
+//! 两种来源都按 Markdown 解析为结构化的条目：版本标题（`## [0.2.0] - 2026-01-01`）下按小标题
+//! 分组，列表项为各条说明。获取远程说明失败时只返回打包的内容并标记为离线。
+
+use crate::backend_update::compare_versions;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// 打包的更新日志
+const BUNDLED_CHANGELOG: &str = include_str!("../CHANGELOG.md");
+
+/// GitHub Release 列表
+const RELEASES_API: &str = "https://api.github.com/repos/geluzhiwei1/davybot/releases?per_page=30";
+
+/// 请求远程说明的超时时间
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 没有小标题的说明归入的分组
+const DEFAULT_SECTION: &str = "更新内容";
+
+/// 同一小标题下的说明
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChangelogSection {
+    pub title: String,
+    pub items: Vec<String>,
+}
+
+/// 一个版本的说明
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChangelogEntry {
+    pub version: String,
+    /// 发布日期（YYYY-MM-DD）
+    pub date: Option<String>,
+    pub sections: Vec<ChangelogSection>,
+    /// 发布页面
+    pub url: Option<String>,
+}
+
+/// 更新日志（新版本在前）
+#[derive(Debug, Clone, Serialize)]
+pub struct Changelog {
+    pub entries: Vec<ChangelogEntry>,
+    /// 是否未能获取远程说明
+    pub offline: bool,
+}
+
+/// GitHub Release 列表中用到的字段
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    published_at: Option<String>,
+    body: Option<String>,
+    html_url: String,
+    prerelease: bool,
+    draft: bool,
+}
+
+/// 解析说明正文：标题作为分组，列表项（`-`、`*`）作为说明，其他非空行也作为一条说明
+fn parse_sections(text: &str) -> Vec<ChangelogSection> {
+    let mut sections: Vec<ChangelogSection> = Vec::new();
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if let Some(title) = line.strip_prefix('#') {
+            sections.push(ChangelogSection { title: title.trim_start_matches('#').trim().to_string(), items: Vec::new() });
+            continue;
+        }
+        let item = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")).unwrap_or(line).trim();
+        if sections.is_empty() {
+            sections.push(ChangelogSection { title: DEFAULT_SECTION.to_string(), items: Vec::new() });
+        }
+        if let Some(section) = sections.last_mut() {
+            section.items.push(item.to_string());
+        }
+    }
+    sections.retain(|section| !section.items.is_empty());
+    sections
+}
+
+/// 解析版本标题 `[0.2.0] - 2026-01-01`、`v0.2.0 (2026-01-01)` 或 `0.2.0`
+fn parse_heading(heading: &str) -> Option<(String, Option<String>)> {
+    let mut words = heading.split(|c: char| c.is_whitespace() || matches!(c, '[' | ']' | '(' | ')')).filter(|w| !w.is_empty() && *w != "-");
+    let version = words.next()?.trim_start_matches('v');
+    if !version.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let date = words.find(|word| word.len() == 10 && word.as_bytes()[4] == b'-').map(str::to_string);
+    Some((version.to_string(), date))
+}
+
+/// 解析 CHANGELOG.md：每个 `## 版本` 标题开始一个版本，其余二级标题（如"未发布"）及其内容忽略
+fn parse_changelog(markdown: &str) -> Vec<ChangelogEntry> {
+    let mut entries = Vec::new();
+    let mut current: Option<((String, Option<String>), String)> = None;
+    for line in markdown.lines() {
+        if let Some(heading) = line.strip_prefix("## ") {
+            if let Some(((version, date), body)) = current.take() {
+                entries.push(ChangelogEntry { version, date, sections: parse_sections(&body), url: None });
+            }
+            current = parse_heading(heading).map(|heading| (heading, String::new()));
+        } else if let Some((_, body)) = current.as_mut() {
+            body.push_str(line);
+            body.push('\n');
+        }
+    }
+    if let Some(((version, date), body)) = current {
+        entries.push(ChangelogEntry { version, date, sections: parse_sections(&body), url: None });
+    }
+    entries
+}
+
+fn release_entry(release: Release) -> ChangelogEntry {
+    ChangelogEntry {
+        version: release.tag_name.trim_start_matches('v').to_string(),
+        date: release.published_at.map(|date| date.chars().take(10).collect()),
+        sections: parse_sections(release.body.as_deref().unwrap_or_default()),
+        url: Some(release.html_url),
+    }
+}
+
+/// 获取 GitHub Release 的发布说明
+async fn fetch_releases(include_prerelease: bool) -> Result<Vec<ChangelogEntry>, String> {
+    let client = reqwest::Client::builder().timeout(FETCH_TIMEOUT).build().map_err(|e| e.to_string())?;
+    let releases: Vec<Release> = client
+        .get(RELEASES_API)
+        .header(reqwest::header::USER_AGENT, concat!("dawei-gui/", env!("CARGO_PKG_VERSION")))
+        .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("无法获取发布说明: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("无法解析发布说明: {}", e))?;
+    Ok(releases
+        .into_iter()
+        .filter(|release| !release.draft && (include_prerelease || !release.prerelease))
+        // 固定标签的测试版更新清单不是版本
+        .filter(|release| parse_heading(&release.tag_name).is_some())
+        .map(release_entry)
+        .collect())
+}
+
+/// 合并两种来源（同一版本以远程为准），只保留 (since, current] 之间的版本，新版本在前
+fn merge(bundled: Vec<ChangelogEntry>, remote: Vec<ChangelogEntry>, since: Option<&str>, current: &str) -> Vec<ChangelogEntry> {
+    let mut entries = remote;
+    for entry in bundled {
+        if !entries.iter().any(|existing| compare_versions(&existing.version, &entry.version).is_eq()) {
+            entries.push(entry);
+        }
+    }
+    entries.retain(|entry| {
+        compare_versions(&entry.version, current).is_le()
+            && since.is_none_or(|since| compare_versions(&entry.version, since).is_gt())
+    });
+    entries.sort_by(|a, b| compare_versions(&b.version, &a.version));
+    entries
+}
+
+/// 获取比 `since` 新的版本说明（未指定时返回全部）
+pub async fn get(since: Option<&str>) -> Changelog {
+    let include_prerelease = crate::settings::current().update_channel == crate::updater::UpdateChannel::Beta;
+    let (remote, offline) = match fetch_releases(include_prerelease).await {
+        Ok(remote) => (remote, false),
+        Err(e) => {
+            tracing::warn!("{}", e);
+            (Vec::new(), true)
+        }
+    };
+    let entries = merge(parse_changelog(BUNDLED_CHANGELOG), remote, since, env!("CARGO_PKG_VERSION"));
+    Changelog { entries, offline }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_merge() {
+        let markdown = "# 更新日志\n\n## [未发布]\n- wip\n\n## [0.2.0] - 2026-02-01\n### 新增\n- 搜索\n- 书签\n### 修复\n* 崩溃\n\n## 0.1.0\n首个版本\n";
+        let bundled = parse_changelog(markdown);
+        assert_eq!(bundled.len(), 2);
+        assert_eq!((bundled[0].version.as_str(), bundled[0].date.as_deref()), ("0.2.0", Some("2026-02-01")));
+        assert_eq!(bundled[0].sections[0], ChangelogSection { title: "新增".to_string(), items: vec!["搜索".to_string(), "书签".to_string()] });
+        assert_eq!(bundled[0].sections[1].items, ["崩溃"]);
+        assert_eq!(bundled[1].sections[0].title, DEFAULT_SECTION);
+
+        let release = Release {
+            tag_name: "v0.2.0".to_string(),
+            published_at: Some("2026-02-02T08:00:00Z".to_string()),
+            body: Some("## What's Changed\n- Search".to_string()),
+            html_url: "https://example.com/v0.2.0".to_string(),
+            prerelease: false,
+            draft: false,
+        };
+        let remote = vec![release_entry(release), ChangelogEntry { version: "0.3.0".to_string(), date: None, sections: Vec::new(), url: None }];
+        let merged = merge(bundled.clone(), remote, Some("0.1.0"), "0.2.0");
+        assert_eq!(merged.len(), 1);
+        assert_eq!((merged[0].date.as_deref(), merged[0].sections[0].items[0].as_str()), (Some("2026-02-02"), "Search"));
+        assert_eq!(merge(bundled, Vec::new(), None, "0.2.0").iter().map(|e| e.version.as_str()).collect::<Vec<_>>(), ["0.2.0", "0.1.0"]);
+    }
+}
@@ -0,0 +1,254 @@
+//! 卸载前清理应用数据
+//!
+//! 卸载程序只删除安装时写入的文件，内置 Python 环境运行时生成的文件（字节码、新装的包）、
+//! WebView 缓存、日志和 DAWEI_HOME 都会留下，可能有数 GB。`cleanup_app_data` 按选项删除：
+//! - `python_env`：安装目录下的 resources/python-env；
+//! - `caches`：WebView 数据和缓存（便携模式下为 data/cache、data/webview）；
+//! - `logs`：DAWEI_HOME/logs；
+//! - `dawei_home`：整个 DAWEI_HOME（包括工作区索引、设置和日志）以及迁移指针文件。
+//!   DAWEI_HOME 可以被用户改到任意位置，删除前确认它确实是数据目录（见 [`check_dawei_home`]），
+//!   否则拒绝整个清理。
+//!
+//! 应用内调用前须先用 [`request_token`] 获取确认令牌（与恢复出厂设置相同）。Windows 卸载程序
+//! 通过 `--cleanup-app-data[=python-env,caches,logs,dawei-home]` 调用（见 installer-hooks.nsh），
+//! 此时不启动界面，结果输出到标准输出。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 命令行参数
+pub const CLEANUP_ARG: &str = "--cleanup-app-data";
+
+/// 应用标识（安装版和独立版），WebView 数据保存在以标识命名的目录中
+const APP_IDENTIFIERS: [&str; 2] = ["com.dawei.davybot", "com.dawei.davybot.standalone"];
+
+/// DAWEI_HOME 中至少存在其一才视为数据目录
+const HOME_MARKERS: [&str; 2] = ["settings.json", "workspaces.json"];
+
+/// 确认令牌有效期
+const TOKEN_TTL: Duration = Duration::from_secs(60);
+
+/// 尚未使用的确认令牌
+static PENDING: Mutex<Option<(String, CleanupOptions, Instant)>> = Mutex::new(None);
+
+/// 清理选项
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CleanupOptions {
+    pub python_env: bool,
+    pub caches: bool,
+    pub logs: bool,
+    pub dawei_home: bool,
+}
+
+/// 清理结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CleanupReport {
+    /// 已删除的路径
+    pub removed: Vec<PathBuf>,
+    /// 释放的空间（字节）
+    pub freed_bytes: u64,
+    /// 未能删除的文件
+    pub errors: Vec<String>,
+}
+
+impl CleanupOptions {
+    /// 解析命令行参数值，例如 `python-env,caches`；未指定时清理除 DAWEI_HOME 以外的全部
+    fn parse(value: Option<&str>) -> Result<CleanupOptions, String> {
+        let Some(value) = value.filter(|value| !value.trim().is_empty()) else {
+            return Ok(CleanupOptions { python_env: true, caches: true, logs: true, dawei_home: false });
+        };
+        let mut options = CleanupOptions::default();
+        for item in value.split(',').map(str::trim) {
+            match item {
+                "python-env" => options.python_env = true,
+                "caches" => options.caches = true,
+                "logs" => options.logs = true,
+                "dawei-home" => options.dawei_home = true,
+                _ => return Err(format!("未知的清理项: {}", item)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// 生成确认令牌，前端向用户确认后连同选项一起传给 [`cleanup`]
+pub fn request_token(options: CleanupOptions) -> String {
+    let token = uuid::Uuid::new_v4().to_string();
+    *PENDING.lock().unwrap_or_else(|e| e.into_inner()) = Some((token.clone(), options, Instant::now()));
+    token
+}
+
+/// 校验并消耗确认令牌
+fn take_token(options: CleanupOptions, token: &str) -> Result<(), String> {
+    let pending = PENDING.lock().unwrap_or_else(|e| e.into_inner()).take();
+    match pending {
+        Some((expected, expected_options, issued))
+            if expected == token && expected_options == options && issued.elapsed() < TOKEN_TTL =>
+        {
+            Ok(())
+        }
+        _ => Err("确认令牌无效或已过期，请重新确认".to_string()),
+    }
+}
+
+/// 路径占用的空间
+fn size_of(path: &Path) -> u64 {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    fs::read_dir(path).map(|entries| entries.flatten().map(|entry| size_of(&entry.path())).sum()).unwrap_or(0)
+}
+
+/// 确认 DAWEI_HOME 可以整个删除：不是根目录、不是用户主目录或其上级目录，并且包含应用的数据文件
+fn check_dawei_home(home: &Path, user_home: Option<&Path>) -> Result<(), String> {
+    let refuse = |reason: &str| Err(format!("拒绝删除数据目录 {}: {}", home.display(), reason));
+    let Ok(home) = home.canonicalize() else {
+        // 不存在时无需删除
+        return Ok(());
+    };
+    if home.parent().is_none() {
+        return refuse("是磁盘根目录");
+    }
+    if let Some(user_home) = user_home.and_then(|dir| dir.canonicalize().ok()) {
+        if user_home.starts_with(&home) {
+            return refuse("是用户主目录或包含用户主目录");
+        }
+    }
+    if !HOME_MARKERS.iter().any(|marker| home.join(marker).is_file()) {
+        return refuse("不包含大微的设置或工作区索引");
+    }
+    Ok(())
+}
+
+/// 要删除的路径
+fn targets(options: CleanupOptions) -> Result<Vec<PathBuf>, String> {
+    let home = crate::get_dawei_home();
+    let mut targets = Vec::new();
+    if options.python_env {
+        targets.extend(crate::backend_update::python_env_dir());
+    }
+    if options.caches {
+        match crate::portable::data_dir() {
+            Some(data) => targets.extend([data.join("cache"), data.join("webview")]),
+            None => {
+                let bases = [dirs::cache_dir(), dirs::data_local_dir()];
+                for base in bases.into_iter().flatten() {
+                    targets.extend(APP_IDENTIFIERS.iter().map(|identifier| base.join(identifier)));
+                }
+            }
+        }
+    }
+    if options.dawei_home {
+        check_dawei_home(&home, dirs::home_dir().as_deref())?;
+        targets.push(home);
+        if !crate::portable::is_portable() {
+            targets.push(crate::dawei_home::pointer_file());
+        }
+    } else if options.logs {
+        targets.push(home.join("logs"));
+    }
+    Ok(targets)
+}
+
+/// 删除路径并累计释放的空间
+fn remove_paths(paths: Vec<PathBuf>) -> CleanupReport {
+    let mut report = CleanupReport::default();
+    for path in paths.into_iter().filter(|path| path.exists()) {
+        let before = size_of(&path);
+        let result = if path.is_dir() { fs::remove_dir_all(&path) } else { fs::remove_file(&path) };
+        report.freed_bytes += before.saturating_sub(size_of(&path));
+        match result {
+            Ok(()) => report.removed.push(path),
+            Err(e) => report.errors.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+    report
+}
+
+/// 按选项清理（阻塞调用）
+pub fn cleanup(options: CleanupOptions, token: &str) -> Result<CleanupReport, String> {
+    take_token(options, token)?;
+    let targets = targets(options)?;
+    // 后端运行时会占用内置环境和 DAWEI_HOME 中的文件
+    if options.python_env || options.dawei_home {
+        crate::backend_process::stop()?;
+    }
+    let report = remove_paths(targets);
+    crate::breadcrumbs::record("settings", format!("App data cleaned up: {:?}, {} bytes freed", options, report.freed_bytes));
+    tracing::info!("Cleaned up {:?}: {} bytes freed, {} errors", options, report.freed_bytes, report.errors.len());
+    Ok(report)
+}
+
+/// 处理卸载程序传入的 `--cleanup-app-data[=...]`，返回退出码
+pub fn run_cli(value: Option<&str>) -> i32 {
+    let options = match CleanupOptions::parse(value) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 2;
+        }
+    };
+    let report = match targets(options) {
+        Ok(targets) => remove_paths(targets),
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+    for path in &report.removed {
+        println!("Removed {}", path.display());
+    }
+    for error in &report.errors {
+        eprintln!("Failed to remove {}", error);
+    }
+    println!("Freed {:.1} MB", report.freed_bytes as f64 / 1024.0 / 1024.0);
+    if report.errors.is_empty() { 0 } else { 1 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_remove() {
+        assert_eq!(CleanupOptions::parse(None).unwrap(), CleanupOptions { python_env: true, caches: true, logs: true, dawei_home: false });
+        assert_eq!(CleanupOptions::parse(Some("logs,dawei-home")).unwrap(), CleanupOptions { logs: true, dawei_home: true, ..Default::default() });
+        assert!(CleanupOptions::parse(Some("everything")).is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let env = dir.path().join("python-env");
+        fs::create_dir_all(env.join("lib")).unwrap();
+        fs::write(env.join("lib/module.pyc"), vec![0u8; 1000]).unwrap();
+        fs::write(dir.path().join("pointer"), "abc").unwrap();
+        let report = remove_paths(vec![env.clone(), dir.path().join("pointer"), dir.path().join("missing")]);
+        assert_eq!((report.freed_bytes, report.removed.len()), (1003, 2));
+        assert!(!env.exists() && report.errors.is_empty());
+    }
+
+    #[test]
+    fn test_check_dawei_home() {
+        let dir = tempfile::tempdir().unwrap();
+        let user_home = dir.path().join("user");
+        let home = user_home.join(".dawei");
+        fs::create_dir_all(&home).unwrap();
+
+        // 没有数据文件（例如被改到了文档目录）
+        assert!(check_dawei_home(&home, Some(&user_home)).is_err());
+        fs::write(home.join("workspaces.json"), "{}").unwrap();
+        check_dawei_home(&home, Some(&user_home)).unwrap();
+
+        // 用户主目录本身及其上级目录
+        fs::write(user_home.join("settings.json"), "{}").unwrap();
+        assert!(check_dawei_home(&user_home, Some(&user_home)).is_err());
+        assert!(check_dawei_home(dir.path(), Some(&user_home)).is_err());
+        assert!(check_dawei_home(Path::new("/"), None).is_err());
+        check_dawei_home(&dir.path().join("missing"), Some(&user_home)).unwrap();
+    }
+}
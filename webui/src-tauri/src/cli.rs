@@ -0,0 +1,53 @@
+//! 命令行参数处理
+//!
+//! 这些参数在启动崩溃监控和 Tauri 之前处理，不需要创建窗口，
+//! 显卡驱动或 Wayland 环境有问题、界面无法启动时也能获取诊断信息。
+
+use crate::{cleanup, crash_handler};
+
+/// 打印最新的崩溃报告并退出
+const PRINT_LAST_CRASH: &str = "--print-last-crash";
+
+/// 处理无界面的命令行参数
+///
+/// 返回 `Some(code)` 表示参数已处理，调用方应以该退出码退出；`None` 表示正常启动应用。
+pub fn handle_args() -> Option<i32> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if args.iter().any(|arg| arg == PRINT_LAST_CRASH) {
+        attach_console();
+        return Some(print_last_crash());
+    }
+    // 卸载程序调用：`--cleanup-app-data` 或 `--cleanup-app-data=python-env,caches`
+    if let Some(arg) = args.iter().find(|arg| arg.split('=').next() == Some(cleanup::CLEANUP_ARG)) {
+        attach_console();
+        return Some(cleanup::run_cli(arg.split_once('=').map(|(_, value)| value)));
+    }
+    None
+}
+
+fn print_last_crash() -> i32 {
+    match crash_handler::latest_crash_report() {
+        Some(report) => {
+            println!("{}", report.format_display());
+            println!("\nReport file: {}", crash_handler::get_crashes_dir().join(&report.filename).display());
+            0
+        }
+        None => {
+            eprintln!("No crash reports found in {}", crash_handler::get_crashes_dir().display());
+            1
+        }
+    }
+}
+
+/// Windows 发布版是 GUI 子系统程序，没有控制台；从终端启动时附加到父进程的控制台以便输出
+#[cfg(windows)]
+fn attach_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
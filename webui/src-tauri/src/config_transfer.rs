@@ -0,0 +1,405 @@
+//! 配置导入导出
+//!
+//! 把设置（settings.json）、环境变量配置（DAWEI_HOME/.env）和工作区索引
+//! （workspaces.json）打包为一个 JSON 文件，便于迁移到新机器。
+//! 导入时逐项合并：新增的项直接导入，与本机不同的项按 `overwrite` 决定
+//! 保留本机值还是使用导入值，所有冲突都记录在导入报告中。
+//!
+//! 默认不导出密钥：.env 中名称像密钥的变量（见 [`env_file::is_secret`]）、LLM 配置
+//! （providerProfiles）中的 API Key 和后端启动配置（backend_profiles）环境变量中的密钥
+//! 都会被去掉，导入时保留本机已有的密钥。
+//! 只有显式指定 `include_secrets` 才会包含密钥，此时导出文件应妥善保管。
+//! 导出文件只允许当前用户读写。
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use crate::env_file::{self, EnvTarget};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+/// 导出文件格式标识
+const FORMAT: &str = "dawei-config";
+
+/// 导出文件格式版本
+const FORMAT_VERSION: u32 = 1;
+
+/// 导出文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigBundle {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: String,
+    pub app_version: String,
+    /// settings.json 的完整内容（包括后端保存的 LLM 配置）
+    #[serde(default)]
+    pub settings: Map<String, Value>,
+    /// DAWEI_HOME/.env 中的变量（按文件顺序）
+    #[serde(default)]
+    pub env: Vec<(String, String)>,
+    /// workspaces.json 中的工作区
+    #[serde(default)]
+    pub workspaces: Vec<Value>,
+    /// 是否包含密钥（旧版本导出的文件总是包含）
+    #[serde(default = "default_secrets_included")]
+    pub secrets_included: bool,
+}
+
+fn default_secrets_included() -> bool {
+    true
+}
+
+/// 可能包含密钥的设置项：LLM 配置（由后端写入）和后端启动配置的环境变量
+const SECRET_SETTINGS: [&str; 2] = ["providerProfiles", "backend_profiles"];
+
+/// 去掉 JSON 中名称像密钥的字符串字段（数值字段如 maxTokens 保留）
+fn strip_secrets(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|key, v| !(v.is_string() && env_file::is_secret(key)));
+            map.values_mut().for_each(strip_secrets);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_secrets),
+        _ => {}
+    }
+}
+
+/// 把本机已有、导入值中缺少的密钥字段补回导入值，避免覆盖导入时丢失本机的 API Key
+fn keep_local_secrets(incoming: &mut Value, existing: &Value) {
+    match (incoming, existing) {
+        (Value::Object(incoming), Value::Object(existing)) => {
+            for (key, local) in existing {
+                match incoming.get_mut(key) {
+                    Some(value) => keep_local_secrets(value, local),
+                    None if local.is_string() && env_file::is_secret(key) => {
+                        incoming.insert(key.clone(), local.clone());
+                    }
+                    None => {}
+                }
+            }
+        }
+        (Value::Array(incoming), Value::Array(existing)) => {
+            for (value, local) in incoming.iter_mut().zip(existing) {
+                keep_local_secrets(value, local);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 只允许当前用户读写的方式写入文件
+fn write_private(path: &Path, content: &str) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // 文件已存在时 mode 不生效，单独收紧权限
+        if path.exists() {
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    options.open(path)?.write_all(content.as_bytes())
+}
+
+/// 一项冲突
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Conflict {
+    /// 冲突项，例如 `settings.crash_upload`、`env.OPENAI_API_KEY`、`workspaces.<id>`
+    pub item: String,
+    /// 是否使用了导入的值
+    pub overwritten: bool,
+}
+
+/// 导入报告
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportReport {
+    /// 新导入的项
+    pub imported: Vec<String>,
+    /// 本机已有且值不同的项
+    pub conflicts: Vec<Conflict>,
+    /// 需要用户注意的问题（例如工作区目录在本机不存在）
+    pub warnings: Vec<String>,
+}
+
+fn workspaces_file() -> PathBuf {
+    crate::get_dawei_home().join("workspaces.json")
+}
+
+fn read_json_object(path: &Path) -> Map<String, Value> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// 导出配置到文件，`include_secrets` 为 false 时不导出密钥
+pub fn export_to(path: &Path, include_secrets: bool) -> Result<(), String> {
+    let workspaces = read_json_object(&workspaces_file())
+        .remove("workspaces")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+
+    let mut settings = read_json_object(&crate::settings::settings_file());
+    let mut env = env_file::read_vars(EnvTarget::Home);
+    if !include_secrets {
+        for key in SECRET_SETTINGS {
+            if let Some(value) = settings.get_mut(key) {
+                strip_secrets(value);
+            }
+        }
+        env.retain(|(key, _)| !env_file::is_secret(key));
+    }
+
+    let bundle = ConfigBundle {
+        format: FORMAT.to_string(),
+        version: FORMAT_VERSION,
+        exported_at: chrono::Local::now().to_rfc3339(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        settings,
+        env,
+        workspaces,
+        secrets_included: include_secrets,
+    };
+
+    let json = serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())?;
+    write_private(path, &json).map_err(|e| format!("导出配置失败: {}", e))
+}
+
+/// 读取并校验导出文件
+fn read_bundle(path: &Path) -> Result<ConfigBundle, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("无法读取配置文件: {}", e))?;
+    let bundle: ConfigBundle = serde_json::from_str(&content).map_err(|e| format!("配置文件格式错误: {}", e))?;
+
+    if bundle.format != FORMAT {
+        return Err("不是大微导出的配置文件".to_string());
+    }
+    if bundle.version > FORMAT_VERSION {
+        return Err(format!("配置文件版本 {} 过新，请先升级应用", bundle.version));
+    }
+    if let Some((key, _)) = bundle.env.iter().find(|(key, _)| key.is_empty() || key.contains(char::is_whitespace)) {
+        return Err(format!("环境变量名无效: {:?}", key));
+    }
+    Ok(bundle)
+}
+
+/// 合并设置，返回需要写入的补丁
+fn merge_settings(
+    existing: &Map<String, Value>,
+    incoming: Map<String, Value>,
+    overwrite: bool,
+    report: &mut ImportReport,
+) -> Map<String, Value> {
+    let mut patch = Map::new();
+    for (key, value) in incoming {
+        // 版本号由本机的设置模块管理
+        if key == "schema_version" {
+            continue;
+        }
+        let item = format!("settings.{}", key);
+        match existing.get(&key) {
+            None => {
+                report.imported.push(item);
+                patch.insert(key, value);
+            }
+            Some(current) if *current == value => {}
+            Some(_) => {
+                report.conflicts.push(Conflict { item, overwritten: overwrite });
+                if overwrite {
+                    patch.insert(key, value);
+                }
+            }
+        }
+    }
+    patch
+}
+
+/// 合并环境变量，返回合并后的变量列表（保持本机原有顺序，新变量追加在后）
+fn merge_env(
+    existing: Vec<(String, String)>,
+    incoming: Vec<(String, String)>,
+    overwrite: bool,
+    report: &mut ImportReport,
+) -> Vec<(String, String)> {
+    let mut merged = existing;
+    for (key, value) in incoming {
+        let item = format!("env.{}", key);
+        match merged.iter_mut().find(|(k, _)| *k == key) {
+            None => {
+                report.imported.push(item);
+                merged.push((key, value));
+            }
+            Some((_, current)) if *current == value => {}
+            Some((_, current)) => {
+                report.conflicts.push(Conflict { item, overwritten: overwrite });
+                if overwrite {
+                    *current = value;
+                }
+            }
+        }
+    }
+    merged
+}
+
+/// 合并工作区索引（按 id 匹配）
+fn merge_workspaces(existing: Vec<Value>, incoming: Vec<Value>, overwrite: bool, report: &mut ImportReport) -> Vec<Value> {
+    let id_of = |ws: &Value| ws.get("id").and_then(Value::as_str).map(str::to_string);
+
+    let mut merged = existing;
+    for workspace in incoming {
+        let Some(id) = id_of(&workspace) else {
+            report.warnings.push("跳过没有 id 的工作区".to_string());
+            continue;
+        };
+        if let Some(path) = workspace.get("path").and_then(Value::as_str) {
+            if !Path::new(path).exists() {
+                report.warnings.push(format!("工作区 {} 的目录在本机不存在: {}", id, path));
+            }
+        }
+
+        let item = format!("workspaces.{}", id);
+        match merged.iter_mut().find(|ws| id_of(ws).as_deref() == Some(id.as_str())) {
+            None => {
+                report.imported.push(item);
+                merged.push(workspace);
+            }
+            Some(current) if *current == workspace => {}
+            Some(current) => {
+                report.conflicts.push(Conflict { item, overwritten: overwrite });
+                if overwrite {
+                    *current = workspace;
+                }
+            }
+        }
+    }
+    merged
+}
+
+/// 从文件导入配置
+///
+/// `overwrite` 为 true 时冲突项使用导入的值，否则保留本机的值。
+pub fn import_from(app: &AppHandle, path: &Path, overwrite: bool) -> Result<ImportReport, String> {
+    let bundle = read_bundle(path)?;
+    let mut report = ImportReport::default();
+
+    // 设置
+    let existing = read_json_object(&crate::settings::settings_file());
+    let mut incoming = bundle.settings;
+    if !bundle.secrets_included {
+        for key in SECRET_SETTINGS {
+            if let (Some(value), Some(local)) = (incoming.get_mut(key), existing.get(key)) {
+                keep_local_secrets(value, local);
+            }
+        }
+    }
+    let patch = merge_settings(&existing, incoming, overwrite, &mut report);
+    if !patch.is_empty() {
+        crate::settings::update(app, Value::Object(patch))?;
+    }
+
+    // 环境变量
+    let existing_env = env_file::read_vars(EnvTarget::Home);
+    let merged_env = merge_env(existing_env.clone(), bundle.env, overwrite, &mut report);
+    if merged_env != existing_env {
+        env_file::replace_all(EnvTarget::Home, &merged_env)?;
+    }
+
+    // 工作区索引
+    let ws_path = workspaces_file();
+    let mut index = read_json_object(&ws_path);
+    let existing_ws: Vec<Value> = index
+        .get("workspaces")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+    let merged_ws = merge_workspaces(existing_ws.clone(), bundle.workspaces, overwrite, &mut report);
+    if merged_ws != existing_ws {
+        index.insert("workspaces".to_string(), Value::Array(merged_ws));
+        crate::atomic_file::write_json_atomic(&ws_path, &index).map_err(|e| format!("写入工作区索引失败: {}", e))?;
+    }
+
+    crate::breadcrumbs::record(
+        "settings",
+        format!("Configuration imported: {} new, {} conflicts", report.imported.len(), report.conflicts.len()),
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_merge_reports_conflicts() {
+        let mut report = ImportReport::default();
+        let existing = json!({ "crash_upload": false, "hang_timeout_secs": 10 });
+        let incoming = json!({ "schema_version": 1, "crash_upload": true, "hang_timeout_secs": 10, "open_devtools": true });
+
+        let patch = merge_settings(
+            existing.as_object().unwrap(),
+            incoming.as_object().unwrap().clone(),
+            false,
+            &mut report,
+        );
+        assert_eq!(Value::Object(patch), json!({ "open_devtools": true }));
+        assert_eq!(report.conflicts, [Conflict { item: "settings.crash_upload".to_string(), overwritten: false }]);
+
+        let env = merge_env(
+            env_file::parse("# comment\nA=1\nexport B=2\n"),
+            vec![("B".to_string(), "3".to_string()), ("C".to_string(), "4".to_string())],
+            true,
+            &mut report,
+        );
+        assert_eq!(env, [("A", "1"), ("B", "3"), ("C", "4")].map(|(k, v)| (k.to_string(), v.to_string())));
+        assert_eq!(report.imported, ["settings.open_devtools", "env.C"]);
+        assert!(report.conflicts[1].overwritten);
+    }
+
+    #[test]
+    fn test_secrets_stripped_and_kept_on_import() {
+        let local = json!({ "apiConfigs": { "glm": { "apiKey": "sk-local", "modelMaxTokens": 4096, "openAiBaseUrl": "https://a" } } });
+        let mut exported = local.clone();
+        strip_secrets(&mut exported);
+        assert_eq!(exported, json!({ "apiConfigs": { "glm": { "modelMaxTokens": 4096, "openAiBaseUrl": "https://a" } } }));
+
+        exported["apiConfigs"]["glm"]["openAiBaseUrl"] = json!("https://b");
+        keep_local_secrets(&mut exported, &local);
+        assert_eq!(exported["apiConfigs"]["glm"]["apiKey"], "sk-local");
+        assert_eq!(exported["apiConfigs"]["glm"]["openAiBaseUrl"], "https://b");
+
+        // 后端启动配置中的环境变量
+        let local = json!({ "dev": { "env": { "OPENAI_API_KEY": "sk-local", "DAWEI_DEBUG": "1" }, "port": 9000 } });
+        let mut exported = local.clone();
+        strip_secrets(&mut exported);
+        assert_eq!(exported, json!({ "dev": { "env": { "DAWEI_DEBUG": "1" }, "port": 9000 } }));
+        keep_local_secrets(&mut exported, &local);
+        assert_eq!(exported, local);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        write_private(&path, "{}").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_read_bundle_validation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+
+        fs::write(&path, json!({ "format": "other", "version": 1, "exported_at": "", "app_version": "" }).to_string()).unwrap();
+        assert!(read_bundle(&path).is_err());
+
+        fs::write(&path, json!({ "format": FORMAT, "version": FORMAT_VERSION + 1, "exported_at": "", "app_version": "" }).to_string()).unwrap();
+        assert!(read_bundle(&path).is_err());
+
+        fs::write(&path, json!({ "format": FORMAT, "version": FORMAT_VERSION, "exported_at": "", "app_version": "", "env": [["A", "1"]] }).to_string()).unwrap();
+        assert_eq!(read_bundle(&path).unwrap().env.len(), 1);
+    }
+}
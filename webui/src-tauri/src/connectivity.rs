@@ -0,0 +1,203 @@
+//! 网络连通性检查
+//!
+//! 供"连接诊断"面板使用：并行检查 DNS 解析、外网访问、PyPI 镜像和设置中配置的
+//! LLM API 地址，每项都有超时，返回各自的耗时和状态。HTTP 检查只要收到响应就算可达
+//! （LLM 接口未带密钥时返回 401 也说明网络是通的），并沿用系统代理环境变量。
+
+use crate::env_file::{self, EnvTarget};
+use serde::Serialize;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+/// 单项检查的超时
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// DNS 检查解析的域名
+const DNS_HOST: &str = "pypi.org";
+
+/// 外网检查地址（国内外都可以访问）
+const INTERNET_URL: &str = "http://www.msftconnecttest.com/connecttest.txt";
+
+/// 默认的 PyPI 索引
+const DEFAULT_PYPI_INDEX: &str = "https://pypi.org/simple/";
+
+/// 指定 PyPI 镜像的环境变量（按优先级）
+const PYPI_INDEX_VARS: [&str; 3] = ["UV_DEFAULT_INDEX", "UV_INDEX_URL", "PIP_INDEX_URL"];
+
+/// 检查方式
+#[derive(Debug, Clone, PartialEq)]
+enum Probe {
+    Dns(String),
+    Http(String),
+}
+
+/// 检查目标
+#[derive(Debug, Clone, PartialEq)]
+struct Target {
+    /// `dns`、`internet`、`pypi` 或 `llm:<配置名>`
+    id: String,
+    probe: Probe,
+}
+
+/// 单项检查结果
+#[derive(Debug, Clone, Serialize)]
+pub struct TargetStatus {
+    pub id: String,
+    /// 检查的域名或 URL
+    pub address: String,
+    pub reachable: bool,
+    /// HTTP 状态码（DNS 检查为 None）
+    pub status: Option<u16>,
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// 设置中的 LLM API 地址（`providerProfiles.apiConfigs.<name>.openAiBaseUrl`）
+fn llm_endpoints(settings: &Value) -> Vec<(String, String)> {
+    let Some(configs) = settings["providerProfiles"]["apiConfigs"].as_object() else {
+        return Vec::new();
+    };
+    configs
+        .iter()
+        .filter_map(|(name, config)| {
+            let url = config["openAiBaseUrl"].as_str()?.trim();
+            (!url.is_empty()).then(|| (name.clone(), url.to_string()))
+        })
+        .collect()
+}
+
+/// 后端安装依赖使用的 PyPI 索引：.env 或进程环境变量中的镜像，未设置时为 pypi.org
+pub fn pypi_index() -> String {
+    let env_vars: Vec<(String, String)> =
+        [EnvTarget::App, EnvTarget::Home].into_iter().flat_map(env_file::read_vars).collect();
+    PYPI_INDEX_VARS
+        .iter()
+        .find_map(|name| {
+            env_vars
+                .iter()
+                .rev()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.clone())
+                .or_else(|| std::env::var(name).ok())
+                .filter(|value| !value.trim().is_empty())
+        })
+        .unwrap_or_else(|| DEFAULT_PYPI_INDEX.to_string())
+}
+
+/// 所有检查目标
+fn all_targets(settings: &Value) -> Vec<Target> {
+    let mut targets = vec![
+        Target { id: "dns".to_string(), probe: Probe::Dns(DNS_HOST.to_string()) },
+        Target { id: "internet".to_string(), probe: Probe::Http(INTERNET_URL.to_string()) },
+        Target { id: "pypi".to_string(), probe: Probe::Http(pypi_index()) },
+    ];
+    targets.extend(
+        llm_endpoints(settings)
+            .into_iter()
+            .map(|(name, url)| Target { id: format!("llm:{}", name), probe: Probe::Http(url) }),
+    );
+    targets
+}
+
+/// 按 ID 选择目标，`llm` 表示所有 LLM 地址；未指定时检查全部
+fn select(targets: Vec<Target>, ids: Option<&[String]>) -> Vec<Target> {
+    let Some(ids) = ids.filter(|ids| !ids.is_empty()) else {
+        return targets;
+    };
+    targets
+        .into_iter()
+        .filter(|target| ids.iter().any(|id| *id == target.id || id == "llm" && target.id.starts_with("llm:")))
+        .collect()
+}
+
+async fn probe(client: reqwest::Client, target: Target) -> TargetStatus {
+    let start = Instant::now();
+    let (address, result) = match target.probe {
+        Probe::Dns(host) => {
+            let lookup = tokio::time::timeout(CHECK_TIMEOUT, tokio::net::lookup_host((host.clone(), 443))).await;
+            let result = match lookup {
+                Ok(Ok(mut addrs)) => addrs.next().map(|_| None).ok_or_else(|| "没有解析结果".to_string()),
+                Ok(Err(e)) => Err(e.to_string()),
+                Err(_) => Err("超时".to_string()),
+            };
+            (host, result)
+        }
+        Probe::Http(url) => {
+            let result = match client.head(&url).send().await {
+                Ok(response) => Ok(Some(response.status().as_u16())),
+                Err(e) if e.is_timeout() => Err("超时".to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            (url, result)
+        }
+    };
+    let latency_ms = Some(start.elapsed().as_millis() as u64);
+    match result {
+        Ok(status) => TargetStatus { id: target.id, address, reachable: true, status, latency_ms, error: None },
+        Err(error) => TargetStatus { id: target.id, address, reachable: false, status: None, latency_ms: None, error: Some(error) },
+    }
+}
+
+/// 并行检查目标（ID 见 [`Target`]），结果顺序与检查目标顺序一致
+pub async fn check(ids: Option<Vec<String>>) -> Vec<TargetStatus> {
+    let settings = serde_json::to_value(crate::settings::current()).unwrap_or_default();
+    let targets = select(all_targets(&settings), ids.as_deref());
+    let client = reqwest::Client::builder().timeout(CHECK_TIMEOUT).build().unwrap_or_default();
+
+    let handles: Vec<_> = targets
+        .into_iter()
+        .map(|target| {
+            let id = target.id.clone();
+            (id, tokio::spawn(probe(client.clone(), target)))
+        })
+        .collect();
+    let mut results = Vec::with_capacity(handles.len());
+    for (id, handle) in handles {
+        results.push(handle.await.unwrap_or_else(|e| TargetStatus {
+            id,
+            address: String::new(),
+            reachable: false,
+            status: None,
+            latency_ms: None,
+            error: Some(e.to_string()),
+        }));
+    }
+    crate::breadcrumbs::record(
+        "network",
+        format!("Connectivity check: {}/{} reachable", results.iter().filter(|r| r.reachable).count(), results.len()),
+    );
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_llm_targets() {
+        let settings = json!({
+            "providerProfiles": {
+                "currentApiConfigName": "glm",
+                "apiConfigs": {
+                    "glm": { "openAiBaseUrl": "https://open.bigmodel.cn/api/paas/v4" },
+                    "local": { "openAiBaseUrl": " " },
+                    "deepseek": { "openAiBaseUrl": "https://api.deepseek.com" },
+                },
+            },
+        });
+        assert_eq!(
+            llm_endpoints(&settings),
+            [
+                ("deepseek".to_string(), "https://api.deepseek.com".to_string()),
+                ("glm".to_string(), "https://open.bigmodel.cn/api/paas/v4".to_string()),
+            ]
+        );
+
+        let ids = |targets: Vec<Target>| targets.into_iter().map(|t| t.id).collect::<Vec<_>>();
+        assert_eq!(ids(select(all_targets(&settings), None)), ["dns", "internet", "pypi", "llm:deepseek", "llm:glm"]);
+        let selected = select(all_targets(&settings), Some(&["pypi".to_string(), "llm".to_string()]));
+        assert_eq!(ids(selected), ["pypi", "llm:deepseek", "llm:glm"]);
+        assert!(select(all_targets(&json!({})), Some(&["llm".to_string()])).is_empty());
+    }
+}
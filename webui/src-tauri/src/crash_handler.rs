@@ -2,34 +2,156 @@
 //!
 //! 提供 panic hook 和崩溃报告功能
 
+use crate::breadcrumbs::{self, Breadcrumb};
+use crate::i18n::{Locale, Text};
 use serde::{Deserialize, Serialize};
 use std::fs::{self, File};
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
 use std::time::SystemTime;
 
+/// 记录最近一次查看崩溃报告的时间戳的文件名
+const LAST_VIEWED_FILE: &str = ".last_viewed";
+
+/// 计算崩溃指纹时使用的堆栈帧数量
+const FINGERPRINT_FRAMES: usize = 5;
+
+/// 崩溃报告中附带的日志行数
+const LOG_TAIL_LINES: usize = 500;
+
+/// 崩溃报告保存后的监听器类型
+type CrashListener = Box<dyn Fn(&CrashReport) + Send + Sync>;
+
+/// 崩溃报告保存后的监听器（例如打开崩溃查看窗口）
+static CRASH_LISTENER: OnceLock<CrashListener> = OnceLock::new();
+
+/// panic hook 是否正在处理崩溃（防止多个线程同时 panic 时重入）
+static HOOK_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// 崩溃类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CrashKind {
+    /// Rust panic
+    #[default]
+    Panic,
+    /// 原生崩溃（段错误、访问违规等），由进程外监控记录
+    Native,
+    /// Python 后端未处理的异常
+    Exception,
+    /// 主线程无响应（卡死）
+    Hang,
+}
+
+/// 崩溃来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CrashSource {
+    /// 桌面端（Tauri/Rust）
+    #[default]
+    App,
+    /// Python 后端
+    Backend,
+}
+
+/// 报告文件名前缀
+fn file_prefix(kind: CrashKind, source: CrashSource) -> &'static str {
+    match (source, kind) {
+        (CrashSource::Backend, _) => "backend",
+        (CrashSource::App, CrashKind::Native) => "native",
+        (CrashSource::App, CrashKind::Hang) => "hang",
+        (CrashSource::App, _) => "crash",
+    }
+}
+
+/// 结构化的堆栈帧
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BacktraceFrame {
+    /// 函数（符号）名
+    pub function: String,
+    /// 源文件
+    pub file: Option<String>,
+    /// 行号
+    pub line: Option<u32>,
+}
+
 /// 崩溃报告结构
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CrashReport {
+    /// 崩溃类型（旧报告没有该字段，默认为 panic）
+    #[serde(default)]
+    pub kind: CrashKind,
+    /// 崩溃来源（旧报告没有该字段，默认为 app）
+    #[serde(default)]
+    pub source: CrashSource,
     /// 崩溃时间戳
     pub timestamp: u64,
     /// ISO 8601 格式的时间
     pub timestamp_iso: String,
     /// 错误消息
     pub error_message: String,
-    /// 堆栈跟踪
+    /// 堆栈跟踪（原始文本）
     pub backtrace: String,
+    /// 解析后的堆栈帧：Rust 堆栈从崩溃点向外，Python traceback 按调用顺序（最后一帧为异常点）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub frames: Vec<BacktraceFrame>,
     /// 平台
     pub platform: String,
     /// 应用版本
     pub app_version: String,
     /// 文件名
     pub filename: String,
+    /// 关联的 minidump 文件路径
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minidump: Option<String>,
+    /// 崩溃前的关键事件（从旧到新）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub breadcrumbs: Vec<Breadcrumb>,
+    /// 崩溃指纹（错误消息 + 顶部堆栈帧），用于去重
+    #[serde(default)]
+    pub fingerprint: String,
+    /// 相同崩溃出现的次数
+    #[serde(default = "default_occurrences")]
+    pub occurrences: u32,
+    /// 最近一次出现的时间戳
+    #[serde(default)]
+    pub last_seen: u64,
+    /// 崩溃时的日志尾部（桌面端报告为应用日志，后端报告为后端日志）
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub log_tail: String,
+    /// 崩溃所在的会话 ID，用于和日志对应
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub session_id: String,
+}
+
+fn default_occurrences() -> u32 {
+    1
 }
 
 impl CrashReport {
     /// 创建新的崩溃报告
     pub fn new(error: String, backtrace: String) -> Self {
+        Self::with_kind(CrashKind::Panic, error, backtrace)
+    }
+
+    /// 创建原生崩溃报告，`details` 为崩溃前子进程的 stderr 输出
+    pub fn native(error: String, details: String) -> Self {
+        Self::with_kind(CrashKind::Native, error, details)
+    }
+
+    /// 创建后端异常报告，`traceback` 为 Python traceback 原文
+    pub fn backend(error: String, traceback: String) -> Self {
+        Self::build(CrashKind::Exception, CrashSource::Backend, error, traceback)
+    }
+
+    /// 创建指定类型的崩溃报告
+    pub fn with_kind(kind: CrashKind, error: String, backtrace: String) -> Self {
+        Self::build(kind, CrashSource::App, error, backtrace)
+    }
+
+    fn build(kind: CrashKind, source: CrashSource, error: String, backtrace: String) -> Self {
         let now = SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)
             .unwrap_or_default()
@@ -38,145 +160,632 @@ impl CrashReport {
         let chrono_now = chrono::Local::now();
         let timestamp_iso = chrono_now.to_rfc3339();
 
-        // 生成文件名
-        let filename = format!("crash_{}.json", chrono_now.format("%Y%m%d_%H%M%S"));
+        // 生成文件名（带毫秒，避免同一秒内的多个报告互相覆盖）
+        let filename = format!("{}_{}.json", file_prefix(kind, source), chrono_now.format("%Y%m%d_%H%M%S_%3f"));
+
+        // 原生崩溃和卡死报告的详情不是堆栈，不解析
+        let frames = match (source, kind) {
+            (CrashSource::Backend, _) => parse_python_traceback(&backtrace),
+            (CrashSource::App, CrashKind::Panic) => parse_rust_backtrace(&backtrace),
+            _ => Vec::new(),
+        };
 
         Self {
+            kind,
+            source,
             timestamp: now,
             timestamp_iso,
+            fingerprint: compute_fingerprint(source, &error, &frames),
+            frames,
             error_message: error,
             backtrace,
             platform: std::env::consts::OS.to_string(),
             app_version: env!("CARGO_PKG_VERSION").to_string(),
             filename,
+            minidump: None,
+            breadcrumbs: breadcrumbs::snapshot(),
+            occurrences: 1,
+            last_seen: now,
+            log_tail: match source {
+                CrashSource::App => crate::logging::app_log_tail(LOG_TAIL_LINES),
+                CrashSource::Backend => crate::backend_crash::backend_log_tail(LOG_TAIL_LINES),
+            },
+            session_id: crate::session::session_id().to_string(),
         }
     }
 
+    /// 最近一次出现的时间戳（旧报告没有 last_seen，使用首次出现时间）
+    pub fn last_seen_timestamp(&self) -> u64 {
+        self.last_seen.max(self.timestamp)
+    }
+
     /// 转换为 JSON 字符串
     pub fn to_json(&self) -> String {
         serde_json::to_string_pretty(self).unwrap_or_else(|_| "{}".to_string())
     }
 
-    /// 保存崩溃报告到文件
+    /// 保存崩溃报告到文件，开启上传时同时上传
+    ///
+    /// 如果已存在相同指纹的报告，只增加其出现次数并更新最近出现时间；
+    /// 新的 minidump 替换旧的（旧文件删除），报告总是指向最近一次的 minidump。
     pub fn save(&self) -> std::io::Result<PathBuf> {
-        // 获取可执行文件所在目录
-        let exe_path = std::env::current_exe()?;
-        let mut crash_dir = exe_path.parent().unwrap_or(Path::new(".")).to_path_buf();
-        crash_dir.push("crashes");
+        let path = self.save_to(&get_crashes_dir())?;
+        crate::crash_upload::submit(self);
+        crate::telemetry::record_crash(self.kind);
+        Ok(path)
+    }
 
+    /// 保存到指定目录（不上传）
+    pub fn save_to(&self, crash_dir: &Path) -> std::io::Result<PathBuf> {
         // 创建崩溃报告目录
-        fs::create_dir_all(&crash_dir)?;
+        fs::create_dir_all(crash_dir)?;
+
+        if let Some((path, mut existing)) = find_by_fingerprint(crash_dir, &self.fingerprint) {
+            existing.occurrences = existing.occurrences.saturating_add(1);
+            existing.last_seen = self.last_seen;
+            existing.breadcrumbs = self.breadcrumbs.clone();
+            existing.log_tail = self.log_tail.clone();
+            existing.session_id = self.session_id.clone();
+            if let Some(minidump) = &self.minidump {
+                if let Some(old) = existing.minidump.replace(minidump.clone()).filter(|old| old != minidump) {
+                    let _ = fs::remove_file(old);
+                }
+            }
+            write_report(&path, &existing)?;
+
+            tracing::info!("Crash report updated ({} occurrences): {:?}", existing.occurrences, path);
+            return Ok(path);
+        }
 
         // 保存崩溃报告
         let crash_file_path = crash_dir.join(&self.filename);
-        let mut file = File::create(&crash_file_path)?;
-        file.write_all(self.to_json().as_bytes())?;
-        file.write_all(b"\n")?;
+        write_report(&crash_file_path, self)?;
 
-        eprintln!("✅ Crash report saved to: {:?}", crash_file_path);
+        tracing::info!("Crash report saved to: {:?}", crash_file_path);
         Ok(crash_file_path)
     }
 
-    /// 格式化错误信息用于显示
+    /// 格式化错误信息用于显示（使用当前语言）
     pub fn format_display(&self) -> String {
-        format!(
-            "Error: {}\nPlatform: {}\nVersion: {}\nTime: {}\n\nBacktrace:\n{}",
+        self.format_display_in(Locale::current())
+    }
+
+    /// 按指定语言格式化错误信息
+    pub fn format_display_in(&self, locale: Locale) -> String {
+        let t = |text: Text| text.in_locale(locale);
+        let mut display = format!(
+            "{}: {}\n{}: {}\n{}: {}\n{}: {}\n\n{}:\n{}",
+            t(Text::Error),
             self.error_message,
+            t(Text::Platform),
             self.platform,
+            t(Text::Version),
             self.app_version,
+            t(Text::Time),
             self.timestamp_iso,
+            t(Text::Backtrace),
             self.backtrace
-        )
+        );
+
+        if !self.session_id.is_empty() {
+            display.push_str(&format!("\n\n{}: {}", t(Text::Session), self.session_id));
+        }
+
+        if self.occurrences > 1 {
+            let last_seen = chrono::DateTime::from_timestamp(self.last_seen_timestamp() as i64, 0)
+                .map(|t| t.with_timezone(&chrono::Local).to_rfc3339())
+                .unwrap_or_default();
+            display.push_str(&format!(
+                "\n\n{}: {} ({}: {})",
+                t(Text::Occurrences),
+                self.occurrences,
+                t(Text::LastSeen),
+                last_seen
+            ));
+        }
+
+        if !self.breadcrumbs.is_empty() {
+            display.push_str(&format!("\n\n{}:", t(Text::Breadcrumbs)));
+            for crumb in &self.breadcrumbs {
+                display.push_str(&format!("\n[{}] {}: {}", crumb.timestamp_iso, crumb.category, crumb.message));
+            }
+        }
+
+        if !self.log_tail.is_empty() {
+            display.push_str(&format!("\n\n{}:\n", t(Text::LogTail)));
+            display.push_str(&self.log_tail);
+        }
+        display
     }
+
+    /// 格式化为可直接粘贴到 GitHub issue 的 Markdown（使用当前语言）
+    ///
+    /// `max_backtrace_lines` 用于限制堆栈长度（例如放进 URL 时），`None` 表示完整输出；
+    /// 受限时同时省略面包屑。
+    pub fn format_markdown(&self, max_backtrace_lines: Option<usize>) -> String {
+        self.format_markdown_in(max_backtrace_lines, Locale::current())
+    }
+
+    /// 按指定语言导出 Markdown：标签保留英文原文，非英文时附带本地化文字
+    pub fn format_markdown_in(&self, max_backtrace_lines: Option<usize>, locale: Locale) -> String {
+        let label = |text: Text| text.export_label(locale);
+        let last_seen = chrono::DateTime::from_timestamp(self.last_seen_timestamp() as i64, 0)
+            .map(|t| t.with_timezone(&chrono::Local).to_rfc3339())
+            .unwrap_or_default();
+
+        let mut md = String::new();
+        md.push_str(&format!("## {}: {}\n\n", label(Text::CrashReport), self.error_message.lines().next().unwrap_or_default()));
+        md.push_str(&format!("```\n{}\n```\n\n", self.error_message));
+
+        md.push_str("| Field | Value |\n|---|---|\n");
+        for (field, value) in [
+            (Text::Kind, format!("{:?}", self.kind).to_lowercase()),
+            (Text::Source, format!("{:?}", self.source).to_lowercase()),
+            (Text::Version, self.app_version.clone()),
+            (Text::Platform, self.platform.clone()),
+            (Text::FirstSeen, self.timestamp_iso.clone()),
+            (Text::LastSeen, last_seen),
+            (Text::Occurrences, self.occurrences.to_string()),
+            (Text::Session, self.session_id.clone()),
+            (Text::ReportFile, format!("`{}`", self.filename)),
+        ] {
+            md.push_str(&format!("| {} | {} |\n", label(field), value.replace('|', "\\|")));
+        }
+
+        let mut backtrace: Vec<&str> = self.backtrace.lines().collect();
+        let truncated = max_backtrace_lines.is_some_and(|max| backtrace.len() > max);
+        if let Some(max) = max_backtrace_lines {
+            backtrace.truncate(max);
+        }
+        md.push_str(&format!("\n<details>\n<summary>{}</summary>\n\n```\n", label(Text::Backtrace)));
+        md.push_str(&backtrace.join("\n"));
+        if truncated {
+            md.push_str("\n... (truncated)");
+        }
+        md.push_str("\n```\n\n</details>\n");
+
+        if max_backtrace_lines.is_none() && !self.breadcrumbs.is_empty() {
+            md.push_str(&format!(
+                "\n<details>\n<summary>{} ({})</summary>\n\n```\n",
+                label(Text::Breadcrumbs),
+                self.breadcrumbs.len()
+            ));
+            for crumb in &self.breadcrumbs {
+                md.push_str(&format!("[{}] {}: {}\n", crumb.timestamp_iso, crumb.category, crumb.message));
+            }
+            md.push_str("```\n\n</details>\n");
+        }
+
+        if max_backtrace_lines.is_none() && !self.log_tail.is_empty() {
+            md.push_str(&format!("\n<details>\n<summary>{}</summary>\n\n```\n", label(Text::LogTail)));
+            md.push_str(&self.log_tail);
+            md.push_str("\n```\n\n</details>\n");
+        }
+        md
+    }
+}
+
+/// 写入报告文件
+fn write_report(path: &Path, report: &CrashReport) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(report.to_json().as_bytes())?;
+    file.write_all(b"\n")
 }
 
-/// 获取崩溃报告目录
-pub fn get_crashes_dir() -> Option<PathBuf> {
+/// 在崩溃目录中查找指纹相同的报告
+fn find_by_fingerprint(crash_dir: &Path, fingerprint: &str) -> Option<(PathBuf, CrashReport)> {
+    if fingerprint.is_empty() {
+        return None;
+    }
+
+    fs::read_dir(crash_dir).ok()?.flatten().find_map(|entry| {
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            return None;
+        }
+        let report: CrashReport = serde_json::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+        (report.fingerprint == fingerprint).then_some((path, report))
+    })
+}
+
+/// 计算崩溃指纹
+///
+/// 使用归一化后的错误消息（去掉内存地址）和最靠近崩溃点的若干堆栈帧的符号名。
+/// 使用 FNV-1a 而不是 `DefaultHasher`，保证指纹在不同 Rust 版本间稳定。
+fn compute_fingerprint(source: CrashSource, error: &str, frames: &[BacktraceFrame]) -> String {
+    let frames = match source {
+        CrashSource::App => rust_top_frames(frames),
+        CrashSource::Backend => python_top_frames(frames),
+    };
+
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for part in std::iter::once(normalize_message(error)).chain(frames) {
+        for byte in part.bytes().chain(std::iter::once(0)) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+/// 去掉消息中的十六进制地址（每次运行都不同）
+fn normalize_message(message: &str) -> String {
+    let mut result = String::with_capacity(message.len());
+    let mut rest = message;
+    while let Some(pos) = rest.find("0x") {
+        result.push_str(&rest[..pos + 2]);
+        rest = rest[pos + 2..].trim_start_matches(|c: char| c.is_ascii_hexdigit());
+        result.push('?');
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Rust 堆栈中最靠近崩溃点的业务帧（跳过 panic 机制本身的帧）
+fn rust_top_frames(frames: &[BacktraceFrame]) -> Vec<String> {
+    const RUNTIME_PREFIXES: [&str; 6] = ["std::", "core::", "alloc::", "rust_begin_unwind", "__rust", "dawei_gui::crash_handler::"];
+
+    frames
+        .iter()
+        .map(|frame| frame.function.clone())
+        .filter(|function| !RUNTIME_PREFIXES.iter().any(|p| function.starts_with(p)))
+        .take(FINGERPRINT_FRAMES)
+        .collect()
+}
+
+/// Python traceback 中最近调用的若干帧（traceback 按调用顺序排列，最后的最靠近异常点）
+///
+/// 只使用文件和函数名，不使用行号，避免无关改动导致同一个异常无法合并。
+fn python_top_frames(frames: &[BacktraceFrame]) -> Vec<String> {
+    frames[frames.len().saturating_sub(FINGERPRINT_FRAMES)..]
+        .iter()
+        .map(|frame| format!("{}:{}", frame.file.as_deref().unwrap_or_default(), frame.function))
+        .collect()
+}
+
+/// 解析 `std::backtrace::Backtrace` 的文本输出
+///
+/// ```text
+///    3: dawei_gui::start_backend
+///              at ./src/main.rs:10:5
+/// ```
+pub fn parse_rust_backtrace(backtrace: &str) -> Vec<BacktraceFrame> {
+    let mut frames: Vec<BacktraceFrame> = Vec::new();
+
+    for line in backtrace.lines() {
+        let trimmed = line.trim();
+
+        if let Some(location) = trimmed.strip_prefix("at ") {
+            // 位置行属于上一帧；Windows 路径含有盘符冒号，从右侧拆分 行:列
+            if let Some(frame) = frames.last_mut().filter(|f| f.file.is_none()) {
+                let mut parts = location.rsplitn(3, ':');
+                let _column = parts.next();
+                let line_no = parts.next().and_then(|l| l.parse().ok());
+                match (line_no, parts.next()) {
+                    (Some(line_no), Some(file)) => {
+                        frame.file = Some(file.to_string());
+                        frame.line = Some(line_no);
+                    }
+                    _ => frame.file = Some(location.to_string()),
+                }
+            }
+            continue;
+        }
+
+        if let Some((index, function)) = trimmed.split_once(": ") {
+            if index.parse::<usize>().is_ok() {
+                frames.push(BacktraceFrame {
+                    function: function.trim().to_string(),
+                    file: None,
+                    line: None,
+                });
+            }
+        }
+    }
+    frames
+}
+
+/// 解析 Python traceback 中的 `File "...", line N, in func` 行
+pub fn parse_python_traceback(traceback: &str) -> Vec<BacktraceFrame> {
+    traceback
+        .lines()
+        .filter_map(|line| {
+            let rest = line.trim().strip_prefix("File \"")?;
+            let (file, rest) = rest.split_once('"')?;
+            let rest = rest.trim_start_matches(',').trim();
+            let (line_part, function) = match rest.split_once(", in ") {
+                Some((line_part, function)) => (line_part, function.trim().to_string()),
+                None => (rest, String::new()),
+            };
+            Some(BacktraceFrame {
+                function,
+                file: Some(file.to_string()),
+                line: line_part.strip_prefix("line ").and_then(|n| n.trim().parse().ok()),
+            })
+        })
+        .collect()
+}
+
+/// 获取崩溃报告目录（DAWEI_HOME/crashes）
+///
+/// 安装版的可执行文件目录通常只读（Program Files、/usr/lib、.app 包），
+/// 因此崩溃报告统一保存在 DAWEI_HOME 下。
+pub fn get_crashes_dir() -> PathBuf {
+    crate::get_dawei_home().join("crashes")
+}
+
+/// 旧版本保存在可执行文件目录下的崩溃报告目录
+fn legacy_crashes_dir() -> Option<PathBuf> {
     let exe_path = std::env::current_exe().ok()?;
-    let mut crash_dir = exe_path.parent()?.to_path_buf();
-    crash_dir.push("crashes");
-    Some(crash_dir)
+    Some(exe_path.parent()?.join("crashes"))
+}
+
+/// DAWEI_HOME 不可写时 panic hook 使用的备用目录
+fn fallback_crashes_dir() -> PathBuf {
+    std::env::temp_dir().join("dawei-crashes")
+}
+
+/// 将临时备用目录下的崩溃报告移到 DAWEI_HOME/crashes（每次启动时调用）
+pub fn collect_fallback_crash_reports() {
+    let (source_dir, crash_dir) = (fallback_crashes_dir(), get_crashes_dir());
+    if source_dir == crash_dir || !source_dir.is_dir() {
+        return;
+    }
+    match migrate_crash_reports(&source_dir, &crash_dir) {
+        Ok(0) => {}
+        Ok(count) => tracing::info!("Collected {} crash report(s) into {:?}", count, crash_dir),
+        Err(e) => tracing::warn!("Failed to collect crash reports from {:?}: {}", source_dir, e),
+    }
+}
+
+/// 将旧版本保存在可执行文件目录下的崩溃报告迁移到 DAWEI_HOME/crashes，返回迁移的文件数
+pub fn migrate_legacy_crash_reports() -> Result<usize, String> {
+    let crash_dir = get_crashes_dir();
+    match legacy_crashes_dir() {
+        Some(source_dir) if source_dir != crash_dir && source_dir.is_dir() => migrate_crash_reports(&source_dir, &crash_dir)
+            .map_err(|e| format!("无法迁移 {} 中的崩溃报告: {}", source_dir.display(), e)),
+        _ => Ok(0),
+    }
+}
+
+/// 将 `from` 中的崩溃报告移动到 `to`，返回迁移的文件数
+///
+/// 目标中已存在的同名文件会被跳过；源目录只读时退化为复制。
+fn migrate_crash_reports(from: &Path, to: &Path) -> std::io::Result<usize> {
+    fs::create_dir_all(to)?;
+
+    let mut migrated = 0;
+    for entry in fs::read_dir(from)?.flatten() {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        let target = to.join(entry.file_name());
+        if target.exists() {
+            continue;
+        }
+        if fs::rename(&path, &target).is_err() {
+            // 跨分区或源目录只读时无法重命名
+            fs::copy(&path, &target)?;
+            let _ = fs::remove_file(&path);
+        }
+        migrated += 1;
+    }
+
+    // 源目录为空时顺便清理
+    let _ = fs::remove_dir(from);
+    Ok(migrated)
 }
 
 /// 获取所有崩溃报告
 pub fn get_all_crash_reports() -> Vec<CrashReport> {
     let mut reports = Vec::new();
+    let crash_dir = get_crashes_dir();
 
-    if let Some(crash_dir) = get_crashes_dir() {
-        if let Ok(entries) = fs::read_dir(&crash_dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                    if let Ok(content) = fs::read_to_string(&path) {
-                        if let Ok(report) = serde_json::from_str::<CrashReport>(&content) {
-                            reports.push(report);
-                        }
+    if let Ok(entries) = fs::read_dir(&crash_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) == Some("json") {
+                if let Ok(content) = fs::read_to_string(&path) {
+                    if let Ok(report) = serde_json::from_str::<CrashReport>(&content) {
+                        reports.push(report);
                     }
                 }
             }
         }
     }
 
-    // 按时间倒序排序（最新的在前）
-    reports.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    // 没有对应 JSON 报告的 minidump 也作为崩溃展示
+    let orphans = orphan_minidump_reports(&crash_dir, &reports);
+    reports.extend(orphans);
+
+    // 按最近出现时间倒序排序（最新的在前）
+    reports.sort_by_key(|r| std::cmp::Reverse(r.last_seen_timestamp()));
     reports
 }
 
+/// 按文件名查找崩溃报告
+pub fn find_crash_report(filename: &str) -> Option<CrashReport> {
+    get_all_crash_reports().into_iter().find(|r| r.filename == filename)
+}
+
+/// 获取最新的崩溃报告
+pub fn latest_crash_report() -> Option<CrashReport> {
+    get_all_crash_reports().into_iter().next()
+}
+
+/// 获取尚未查看过的最新崩溃报告
+pub fn latest_unviewed_crash_report() -> Option<CrashReport> {
+    let last_viewed = fs::read_to_string(get_crashes_dir().join(LAST_VIEWED_FILE))
+        .ok()
+        .and_then(|content| content.trim().parse::<u64>().ok())
+        .unwrap_or(0);
+
+    latest_crash_report().filter(|r| r.last_seen_timestamp() > last_viewed)
+}
+
+/// 将当前所有崩溃报告标记为已查看
+pub fn mark_crash_reports_viewed() -> std::io::Result<()> {
+    let Some(report) = latest_crash_report() else {
+        return Ok(());
+    };
+    fs::write(get_crashes_dir().join(LAST_VIEWED_FILE), report.last_seen_timestamp().to_string())
+}
+
+/// 为没有对应 JSON 报告的 minidump 生成报告
+///
+/// 同一类型（原生崩溃 / 卡死）的孤立 minidump 合并为一条，出现次数为文件数，指向最新的文件；
+/// 时间取自文件修改时间，不附带读取时本次会话的面包屑和日志。
+fn orphan_minidump_reports(crash_dir: &Path, reports: &[CrashReport]) -> Vec<CrashReport> {
+    let Ok(entries) = fs::read_dir(crash_dir) else {
+        return Vec::new();
+    };
+    let mut merged: Vec<CrashReport> = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("dmp") {
+            continue;
+        }
+        let path_str = path.display().to_string();
+        if reports.iter().any(|r| r.minidump.as_deref() == Some(path_str.as_str())) {
+            continue;
+        }
+        let report = minidump_report(&path);
+        match merged.iter_mut().find(|r| r.kind == report.kind) {
+            Some(existing) => {
+                existing.occurrences += 1;
+                existing.timestamp = existing.timestamp.min(report.timestamp);
+                if report.last_seen > existing.last_seen {
+                    existing.last_seen = report.last_seen;
+                    existing.filename = report.filename;
+                    existing.minidump = report.minidump;
+                }
+            }
+            None => merged.push(report),
+        }
+    }
+    merged
+}
+
+/// 为单个孤立的 minidump 文件生成报告（`hang_*.dmp` 为卡死，其他为原生崩溃）
+fn minidump_report(path: &Path) -> CrashReport {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let mut report = if name.starts_with("hang_") {
+        CrashReport::with_kind(CrashKind::Hang, "Application not responding (minidump)".to_string(), String::new())
+    } else {
+        CrashReport::native("Native crash (minidump)".to_string(), String::new())
+    };
+
+    if let Ok(modified) = path.metadata().and_then(|m| m.modified()) {
+        let modified: chrono::DateTime<chrono::Local> = modified.into();
+        report.timestamp = modified.timestamp().max(0) as u64;
+        report.timestamp_iso = modified.to_rfc3339();
+        report.last_seen = report.timestamp;
+    }
+    report.breadcrumbs.clear();
+    report.log_tail.clear();
+    report.session_id.clear();
+    report.filename = name;
+    report.minidump = Some(path.display().to_string());
+    report
+}
+
 /// 清除所有崩溃报告
 pub fn clear_all_crash_reports() -> std::io::Result<()> {
-    if let Some(crash_dir) = get_crashes_dir() {
-        if crash_dir.exists() {
-            fs::remove_dir_all(&crash_dir)?;
-            println!("✅ All crash reports cleared");
-        }
+    let crash_dir = get_crashes_dir();
+    if crash_dir.exists() {
+        fs::remove_dir_all(&crash_dir)?;
+        tracing::info!("All crash reports cleared");
     }
     Ok(())
 }
 
+/// 注册崩溃报告保存后的监听器，只能注册一次
+pub fn set_crash_listener(listener: impl Fn(&CrashReport) + Send + Sync + 'static) {
+    if CRASH_LISTENER.set(Box::new(listener)).is_err() {
+        tracing::warn!("Crash listener already registered");
+    }
+}
+
+/// 依次尝试在候选目录中保存报告，全部失败时返回 `None`
+fn save_with_fallback(report: &CrashReport, dirs: &[PathBuf]) -> Option<PathBuf> {
+    for dir in dirs {
+        match report.save_to(dir) {
+            Ok(path) => return Some(path),
+            Err(e) => tracing::error!("Failed to save crash report to {:?}: {}", dir, e),
+        }
+    }
+    None
+}
+
 /// 设置 panic hook
+///
+/// hook 本身必须尽量不出错：同一时间只处理一个崩溃，保存失败时依次退化为
+/// 临时目录和仅输出到 stderr。hook 内部再次 panic 会被 Rust 运行时直接 abort，
+/// 因此这里只做不会 panic 的操作。
 pub fn setup_panic_hook() {
     std::panic::set_hook(Box::new(|panic_info| {
-        // 获取错误信息
-        let error_msg = if let Some(s) = panic_info.payload().downcast_ref::<&str>() {
-            s.to_string()
-        } else if let Some(s) = panic_info.payload().downcast_ref::<String>() {
-            s.clone()
-        } else {
-            "Unknown panic".to_string()
-        };
+        if HOOK_ACTIVE.swap(true, Ordering::SeqCst) {
+            // 另一个线程正在处理崩溃，只输出到 stderr，避免并发写同一个报告
+            eprintln!("🚨 Panic while another panic is being handled: {}", panic_info);
+            return;
+        }
 
-        // 获取位置信息
-        let location = panic_info.location().map(|l| {
-            format!("{}:{}:{}", l.file(), l.line(), l.column())
-        });
+        handle_panic(panic_info);
+        HOOK_ACTIVE.store(false, Ordering::SeqCst);
+    }));
 
-        // 构建完整的错误消息
-        let full_error = if let Some(loc) = location {
-            format!("Panic at {}: {}", loc, error_msg)
-        } else {
-            error_msg
-        };
+    tracing::info!("Panic hook installed");
+}
 
-        // 获取堆栈跟踪
-        let backtrace = std::backtrace::Backtrace::capture().to_string();
+/// 生成、保存并输出一次 panic 的崩溃报告
+fn handle_panic(panic_info: &std::panic::PanicHookInfo<'_>) {
+    // 获取错误信息
+    let error_msg = if let Some(s) = panic_info.payload().downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = panic_info.payload().downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "Unknown panic".to_string()
+    };
 
-        // 创建并保存崩溃报告
-        let report = CrashReport::new(full_error, backtrace);
+    // 获取位置信息
+    let location = panic_info.location().map(|l| {
+        format!("{}:{}:{}", l.file(), l.line(), l.column())
+    });
 
-        // 尝试保存崩溃报告
-        if let Err(e) = report.save() {
-            eprintln!("❌ Failed to save crash report: {}", e);
-        }
+    // 构建完整的错误消息
+    let full_error = if let Some(loc) = location {
+        format!("Panic at {}: {}", loc, error_msg)
+    } else {
+        error_msg
+    };
 
-        // 打印到 stderr
-        eprintln!("\n{}", "=".repeat(60));
-        eprintln!("🚨 APPLICATION PANIC");
-        eprintln!("{}", "=".repeat(60));
-        eprintln!("{}", report.format_display());
-        eprintln!("{}\n", "=".repeat(60));
-    }));
+    // 获取堆栈跟踪
+    let backtrace = std::backtrace::Backtrace::capture().to_string();
+
+    // 创建并保存崩溃报告
+    let report = CrashReport::new(full_error, backtrace);
+
+    // 尝试保存崩溃报告：DAWEI_HOME → 临时目录 → 仅 stderr
+    let saved = save_with_fallback(&report, &[get_crashes_dir(), fallback_crashes_dir()]);
+    if saved.is_none() {
+        eprintln!("❌ Crash report could not be written to disk, raw report follows:");
+        eprintln!("{}", report.to_json());
+    }
+    crate::crash_upload::submit(&report);
+    crate::telemetry::record_crash(report.kind);
 
-    println!("✅ Panic hook installed");
+    // 打印到 stderr
+    eprintln!("\n{}", "=".repeat(60));
+    eprintln!("🚨 APPLICATION PANIC");
+    eprintln!("{}", "=".repeat(60));
+    eprintln!("{}", report.format_display());
+    eprintln!("{}\n", "=".repeat(60));
+
+    if let Some(listener) = CRASH_LISTENER.get() {
+        listener(&report);
+    }
 }
 
 #[cfg(test)]
@@ -204,5 +813,188 @@ mod tests {
         let json = report.to_json();
         assert!(json.contains("Test error"));
         assert!(json.contains("Test backtrace"));
+        assert!(json.contains("\"kind\": \"panic\""));
+    }
+
+    #[test]
+    fn test_native_report_and_legacy_kind_default() {
+        let report = CrashReport::native("Native crash".to_string(), "stderr".to_string());
+        assert_eq!(report.kind, CrashKind::Native);
+        assert!(report.filename.starts_with("native_"));
+
+        // 旧版本写入的报告没有 kind 字段
+        let legacy = r#"{"timestamp":1,"timestamp_iso":"","error_message":"e","backtrace":"b","platform":"linux","app_version":"0.1.0","filename":"crash_1.json"}"#;
+        let report: CrashReport = serde_json::from_str(legacy).unwrap();
+        assert_eq!(report.kind, CrashKind::Panic);
+        assert!(report.minidump.is_none());
+    }
+
+    #[test]
+    fn test_duplicate_crash_increments_occurrences() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let first = CrashReport::new("Panic at 0x7ffd1234: boom".to_string(), String::new());
+        let path = first.save_to(dir.path()).unwrap();
+
+        let mut second = CrashReport::new("Panic at 0x7ffd9999: boom".to_string(), String::new());
+        second.filename = "crash_other.json".to_string();
+        assert_eq!(second.save_to(dir.path()).unwrap(), path);
+
+        let mut other = CrashReport::new("different".to_string(), String::new());
+        other.filename = "crash_different.json".to_string();
+        assert_ne!(other.save_to(dir.path()).unwrap(), path);
+
+        let saved: CrashReport = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.occurrences, 2);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_duplicate_crash_keeps_latest_minidump() {
+        let dir = tempfile::tempdir().unwrap();
+        let dump = |name: &str| {
+            let path = dir.path().join(name);
+            fs::write(&path, "MDMP").unwrap();
+            Some(path.display().to_string())
+        };
+
+        let mut first = CrashReport::native("Native crash: SIGSEGV".to_string(), String::new());
+        first.minidump = dump("native_1.dmp");
+        let path = first.save_to(dir.path()).unwrap();
+
+        let mut second = CrashReport::native("Native crash: SIGSEGV".to_string(), String::new());
+        second.filename = "native_other.json".to_string();
+        second.minidump = dump("native_2.dmp");
+        assert_eq!(second.save_to(dir.path()).unwrap(), path);
+
+        let saved: CrashReport = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.occurrences, 2);
+        assert_eq!(saved.minidump, second.minidump);
+        assert!(!dir.path().join("native_1.dmp").exists());
+        // 没有 minidump 的重复崩溃保留原来的
+        let mut third = CrashReport::native("Native crash: SIGSEGV".to_string(), String::new());
+        third.filename = "native_third.json".to_string();
+        third.save_to(dir.path()).unwrap();
+        let saved: CrashReport = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.minidump, second.minidump);
+    }
+
+    #[test]
+    fn test_orphan_minidumps_are_grouped_by_kind() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["native_1.dmp", "native_2.dmp", "hang_1.dmp", "native_3.dmp"] {
+            fs::write(dir.path().join(name), "MDMP").unwrap();
+        }
+        let mut referenced = CrashReport::native("Native crash: SIGSEGV".to_string(), String::new());
+        referenced.minidump = Some(dir.path().join("native_3.dmp").display().to_string());
+
+        let mut orphans = orphan_minidump_reports(dir.path(), &[referenced]);
+        orphans.sort_by_key(|r| r.kind == CrashKind::Hang);
+        assert_eq!(orphans.len(), 2);
+        assert_eq!((orphans[0].kind, orphans[0].occurrences), (CrashKind::Native, 2));
+        assert_eq!((orphans[1].kind, orphans[1].occurrences), (CrashKind::Hang, 1));
+
+        let modified = fs::metadata(dir.path().join("hang_1.dmp")).unwrap().modified().unwrap();
+        let modified = modified.duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
+        assert_eq!(orphans[1].last_seen_timestamp(), modified);
+        assert!(orphans[1].breadcrumbs.is_empty() && orphans[1].log_tail.is_empty() && orphans[1].session_id.is_empty());
+    }
+
+    #[test]
+    fn test_fingerprint_uses_top_frames() {
+        let backtrace = "   0: std::backtrace::Backtrace::capture\n   1: dawei_gui::crash_handler::setup_panic_hook::{{closure}}\n   2: dawei_gui::start_backend\n             at ./src/main.rs:10:5";
+        let other = "   0: std::backtrace::Backtrace::capture\n   1: dawei_gui::get_python_info";
+
+        let frames = parse_rust_backtrace(backtrace);
+        assert_eq!(rust_top_frames(&frames), vec!["dawei_gui::start_backend".to_string()]);
+        assert_ne!(
+            compute_fingerprint(CrashSource::App, "boom", &frames),
+            compute_fingerprint(CrashSource::App, "boom", &parse_rust_backtrace(other))
+        );
+    }
+
+    #[test]
+    fn test_parse_rust_backtrace_frames() {
+        let backtrace = "   0: dawei_gui::start_backend\n             at ./src/main.rs:10:5\n   1: tokio::runtime::park\n   2: app::win\n             at C:\\src\\main.rs:7:1";
+        let frames = parse_rust_backtrace(backtrace);
+
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0], BacktraceFrame { function: "dawei_gui::start_backend".to_string(), file: Some("./src/main.rs".to_string()), line: Some(10) });
+        assert_eq!(frames[1].file, None);
+        assert_eq!(frames[2].file.as_deref(), Some("C:\\src\\main.rs"));
+        assert_eq!(frames[2].line, Some(7));
+    }
+
+    #[test]
+    fn test_parse_python_traceback_frames() {
+        let traceback = "Traceback (most recent call last):\n  File \"/app/dawei/agent.py\", line 42, in run\n    step()\nValueError: bad";
+        let report = CrashReport::backend("ValueError: bad".to_string(), traceback.to_string());
+
+        assert_eq!(report.frames, vec![BacktraceFrame { function: "run".to_string(), file: Some("/app/dawei/agent.py".to_string()), line: Some(42) }]);
+    }
+
+    #[test]
+    fn test_format_markdown() {
+        let backtrace = (0..100).map(|i| format!("frame {}", i)).collect::<Vec<_>>().join("\n");
+        let report = CrashReport::new("Panic at src/main.rs:1:1: boom".to_string(), backtrace);
+
+        let full = report.format_markdown_in(None, Locale::En);
+        assert!(full.starts_with("## Crash report: Panic at src/main.rs:1:1: boom"));
+        assert!(full.contains("| Kind | panic |"));
+        assert!(full.contains("<summary>Backtrace</summary>"));
+        assert!(full.contains("frame 99"));
+
+        // 非英文导出同时保留英文标签
+        let localized = report.format_markdown_in(None, Locale::ZhCn);
+        assert!(localized.contains("| Kind (类型) | panic |"));
+        assert!(report.format_display_in(Locale::ZhCn).starts_with("错误: Panic at"));
+
+        let short = report.format_markdown_in(Some(10), Locale::En);
+        assert!(short.contains("frame 9\n... (truncated)"));
+        assert!(!short.contains("frame 10\n"));
+    }
+
+    #[test]
+    fn test_save_falls_back_when_primary_dir_unwritable() {
+        let root = tempfile::tempdir().unwrap();
+        // 用普通文件占住目录位置，模拟无法创建崩溃目录
+        let blocked = root.path().join("blocked");
+        fs::write(&blocked, "not a directory").unwrap();
+        let fallback = root.path().join("fallback");
+
+        let report = CrashReport::new("boom".to_string(), String::new());
+        let saved = save_with_fallback(&report, &[blocked.join("crashes"), fallback.clone()]).unwrap();
+        assert!(saved.starts_with(&fallback));
+        assert!(saved.exists());
+    }
+
+    #[test]
+    fn test_save_reports_failure_when_all_dirs_unwritable() {
+        let root = tempfile::tempdir().unwrap();
+        let blocked = root.path().join("blocked");
+        fs::write(&blocked, "not a directory").unwrap();
+
+        let report = CrashReport::new("boom".to_string(), String::new());
+        assert!(save_with_fallback(&report, &[blocked.join("a"), blocked.join("b")]).is_none());
+    }
+
+    #[test]
+    fn test_migrate_crash_reports() {
+        let root = tempfile::tempdir().unwrap();
+        let legacy = root.path().join("exe/crashes");
+        let target = root.path().join("home/crashes");
+        fs::create_dir_all(&legacy).unwrap();
+        fs::create_dir_all(&target).unwrap();
+
+        fs::write(legacy.join("crash_1.json"), "old").unwrap();
+        fs::write(legacy.join("crash_2.json"), "legacy").unwrap();
+        fs::write(target.join("crash_2.json"), "current").unwrap();
+
+        let migrated = migrate_crash_reports(&legacy, &target).unwrap();
+
+        assert_eq!(migrated, 1);
+        assert_eq!(fs::read_to_string(target.join("crash_1.json")).unwrap(), "old");
+        // 已存在的报告不会被覆盖
+        assert_eq!(fs::read_to_string(target.join("crash_2.json")).unwrap(), "current");
     }
 }
@@ -0,0 +1,360 @@
+//! 原生崩溃监控模块
+//!
+//! panic hook 只能捕获 Rust panic，WebView 或 C 扩展中的段错误会直接杀死进程。
+//! 这里采用进程外监控：启动时当前进程作为监控者重新拉起自身作为子进程，
+//! 等待子进程退出并根据退出状态判断是否为原生崩溃，写入 `kind: "native"` 的崩溃报告。
+//!
+//! 监控进程同时运行 minidump 服务（minidumper），子进程启动时用 crash-handler 注册信号/异常
+//! 处理（[`attach_minidump_client`]），崩溃时把崩溃上下文发给监控进程，由监控进程读取子进程的
+//! 内存写入崩溃目录中的 `.dmp` 文件（崩溃的进程自身已不可靠），并关联到崩溃报告。
+//! 卡死检测也通过同一通道请求 minidump（[`write_hang_minidump`]），记录卡死时所有线程的堆栈。
+
+use crate::crash_handler::{get_crashes_dir, CrashReport};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::JoinHandle;
+
+/// 标记当前进程是被监控的子进程
+pub const CHILD_ENV: &str = "DAWEI_CRASH_MONITOR_CHILD";
+
+/// 显式开关：`1` 强制启用（调试构建默认关闭），`0` 强制关闭
+pub const MONITOR_ENV: &str = "DAWEI_CRASH_MONITOR";
+
+/// minidump 服务的套接字路径，由监控进程传给子进程
+pub const MINIDUMP_SOCKET_ENV: &str = "DAWEI_MINIDUMP_SOCKET";
+
+/// 子进程请求写入卡死 minidump 的消息，内容为文件名
+const HANG_DUMP_MESSAGE: u32 = 1;
+
+/// 子进程中与监控进程的连接和已注册的崩溃处理
+static MINIDUMP_CLIENT: OnceLock<Arc<minidumper::Client>> = OnceLock::new();
+static CRASH_HANDLER: OnceLock<crash_handler::CrashHandler> = OnceLock::new();
+
+/// 崩溃报告中保留的子进程 stderr 行数
+const STDERR_TAIL_LINES: usize = 200;
+
+/// 是否启用进程外监控
+fn monitor_enabled() -> bool {
+    match std::env::var(MONITOR_ENV).as_deref() {
+        Ok("1") => true,
+        Ok("0") => false,
+        // 调试构建下 `tauri dev` 会直接杀掉父进程，默认不启用以免遗留子进程
+        _ => !cfg!(debug_assertions),
+    }
+}
+
+/// 把子进程的崩溃写成崩溃目录中的 minidump
+struct MinidumpHandler {
+    dir: PathBuf,
+    /// 已写入的原生崩溃 minidump（卡死时写入的不算）
+    written: Arc<Mutex<Option<PathBuf>>>,
+    /// 子进程为下一次卡死 minidump 指定的文件名
+    hang_file: Mutex<Option<String>>,
+}
+
+impl minidumper::ServerHandler for MinidumpHandler {
+    fn create_minidump_file(&self) -> Result<(File, PathBuf), std::io::Error> {
+        std::fs::create_dir_all(&self.dir)?;
+        let hang_file = self.hang_file.lock().unwrap_or_else(|e| e.into_inner()).take();
+        let name = hang_file.unwrap_or_else(|| format!("native_{}_{}.dmp", chrono::Local::now().format("%Y%m%d_%H%M%S"), std::process::id()));
+        let path = self.dir.join(name);
+        Ok((File::create(&path)?, path))
+    }
+
+    fn on_minidump_created(&self, result: Result<minidumper::MinidumpBinary, minidumper::Error>) -> minidumper::LoopAction {
+        match result {
+            Ok(binary) => {
+                tracing::info!("Wrote minidump {:?}", binary.path);
+                if !is_hang_dump(&binary.path) {
+                    *self.written.lock().unwrap_or_else(|e| e.into_inner()) = Some(binary.path);
+                }
+            }
+            Err(e) => tracing::error!("Failed to write minidump: {}", e),
+        }
+        minidumper::LoopAction::Continue
+    }
+
+    fn on_message(&self, kind: u32, buffer: Vec<u8>) {
+        if kind != HANG_DUMP_MESSAGE {
+            return;
+        }
+        // 只接受崩溃目录中的文件名
+        let name = String::from_utf8_lossy(&buffer).to_string();
+        if is_hang_dump(Path::new(&name)) && Path::new(&name).file_name() == Some(name.as_ref()) {
+            *self.hang_file.lock().unwrap_or_else(|e| e.into_inner()) = Some(name);
+        }
+    }
+}
+
+fn is_hang_dump(path: &Path) -> bool {
+    path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with("hang_") && n.ends_with(".dmp"))
+}
+
+/// 监控进程中运行的 minidump 服务
+struct MinidumpServer {
+    socket: PathBuf,
+    shutdown: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+    written: Arc<Mutex<Option<PathBuf>>>,
+}
+
+impl MinidumpServer {
+    /// 启动服务，失败时只记录日志（仍然可以根据退出状态记录崩溃）
+    fn start() -> Option<MinidumpServer> {
+        let socket = std::env::temp_dir().join(format!("dawei-minidump-{}.sock", std::process::id()));
+        let mut server = match minidumper::Server::with_name(socket.as_path()) {
+            Ok(server) => server,
+            Err(e) => {
+                tracing::warn!("Failed to start minidump server: {}", e);
+                return None;
+            }
+        };
+        let written = Arc::new(Mutex::new(None));
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let handler = MinidumpHandler { dir: get_crashes_dir(), written: Arc::clone(&written), hang_file: Mutex::new(None) };
+        let stop = Arc::clone(&shutdown);
+        let thread = std::thread::Builder::new()
+            .name("minidump-server".to_string())
+            .spawn(move || {
+                if let Err(e) = server.run(Box::new(handler), &stop, None) {
+                    tracing::error!("Minidump server stopped: {}", e);
+                }
+            })
+            .ok()?;
+        Some(MinidumpServer { socket, shutdown, thread, written })
+    }
+
+    /// 停止服务，返回子进程崩溃时写入的 minidump
+    fn finish(self) -> Option<PathBuf> {
+        self.shutdown.store(true, Ordering::Relaxed);
+        let _ = self.thread.join();
+        let _ = std::fs::remove_file(&self.socket);
+        self.written.lock().unwrap_or_else(|e| e.into_inner()).take()
+    }
+}
+
+/// 子进程中注册原生崩溃处理：崩溃时请求监控进程写入 minidump
+pub fn attach_minidump_client() {
+    let Some(socket) = std::env::var_os(MINIDUMP_SOCKET_ENV) else {
+        return;
+    };
+    let client = match minidumper::Client::with_name(Path::new(&socket)) {
+        Ok(client) => Arc::new(client),
+        Err(e) => {
+            tracing::warn!("Failed to connect to minidump server: {}", e);
+            return;
+        }
+    };
+    let crash_client = Arc::clone(&client);
+    // SAFETY: 回调只通过已建立的连接发送崩溃上下文并等待监控进程写完，不分配内存也不加锁
+    let on_crash = unsafe {
+        crash_handler::make_crash_event(move |context| crash_handler::CrashEventResult::Handled(crash_client.request_dump(context).is_ok()))
+    };
+    match crash_handler::CrashHandler::attach(on_crash) {
+        // 保存在静态变量中，保持注册直到进程退出
+        Ok(handler) => {
+            let _ = CRASH_HANDLER.set(handler);
+            let _ = MINIDUMP_CLIENT.set(client);
+        }
+        Err(e) => tracing::warn!("Failed to attach native crash handler: {}", e),
+    }
+}
+
+/// 请求监控进程为仍在运行的子进程写入 minidump（包含所有线程的堆栈），用于卡死报告
+///
+/// 没有监控进程时返回 None。
+pub fn write_hang_minidump() -> Option<PathBuf> {
+    let client = MINIDUMP_CLIENT.get()?;
+    let handler = CRASH_HANDLER.get()?;
+    let name = format!("hang_{}_{}.dmp", chrono::Local::now().format("%Y%m%d_%H%M%S"), std::process::id());
+    if let Err(e) = client.send_message(HANG_DUMP_MESSAGE, name.as_bytes()) {
+        tracing::warn!("Failed to request hang minidump: {}", e);
+        return None;
+    }
+
+    // 模拟一次崩溃，由已注册的回调把当前线程的上下文发给监控进程，进程不会退出
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    handler.simulate_signal(libc::SIGUSR1 as u32);
+    #[cfg(any(windows, target_os = "macos"))]
+    handler.simulate_exception(None);
+
+    let path = get_crashes_dir().join(name);
+    path.exists().then_some(path)
+}
+
+/// 作为监控者运行应用
+///
+/// 返回 `None` 表示当前进程应继续正常启动应用（已是子进程或监控被关闭），
+/// 返回 `Some(code)` 表示子进程已结束，调用方应以该退出码退出。
+pub fn supervise() -> Option<i32> {
+    if std::env::var_os(CHILD_ENV).is_some() || !monitor_enabled() {
+        return None;
+    }
+
+    let exe = std::env::current_exe().ok()?;
+    let minidump_server = MinidumpServer::start();
+
+    let mut command = Command::new(&exe);
+    command
+        .args(std::env::args_os().skip(1))
+        .env(CHILD_ENV, "1")
+        .env(crate::session::SESSION_ENV, crate::session::session_id())
+        .stderr(Stdio::piped());
+    if let Some(server) = &minidump_server {
+        command.env(MINIDUMP_SOCKET_ENV, &server.socket);
+    }
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            tracing::warn!("Failed to start monitored process, running without crash monitor: {}", e);
+            minidump_server.map(MinidumpServer::finish);
+            return None;
+        }
+    };
+
+    // 转发子进程 stderr，同时保留最近的输出作为崩溃上下文
+    let tail = Arc::new(Mutex::new(VecDeque::with_capacity(STDERR_TAIL_LINES)));
+    let forwarder = child.stderr.take().map(|stderr| {
+        let tail = Arc::clone(&tail);
+        std::thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                eprintln!("{}", line);
+                if let Ok(mut tail) = tail.lock() {
+                    if tail.len() == STDERR_TAIL_LINES {
+                        tail.pop_front();
+                    }
+                    tail.push_back(line);
+                }
+            }
+        })
+    });
+
+    let status = child.wait();
+    if let Some(forwarder) = forwarder {
+        let _ = forwarder.join();
+    }
+    // 子进程等监控进程写完 minidump 才会退出，此时已写入
+    let minidump = minidump_server.and_then(MinidumpServer::finish);
+
+    match status {
+        Ok(status) => {
+            if let Some(description) = describe_native_crash(&status) {
+                let stderr_tail = tail
+                    .lock()
+                    .map(|t| t.iter().cloned().collect::<Vec<_>>().join("\n"))
+                    .unwrap_or_default();
+
+                let mut report = CrashReport::native(description, stderr_tail);
+                report.minidump = minidump.map(|p| p.display().to_string());
+
+                if let Err(e) = report.save() {
+                    tracing::error!("Failed to save native crash report: {}", e);
+                }
+            }
+            Some(exit_code(&status))
+        }
+        Err(e) => {
+            tracing::error!("Failed to wait for monitored process: {}", e);
+            Some(1)
+        }
+    }
+}
+
+/// 将子进程退出状态转换为监控进程的退出码
+fn exit_code(status: &ExitStatus) -> i32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    status.code().unwrap_or(1)
+}
+
+/// 判断退出状态是否为原生崩溃，返回可读描述
+#[cfg(unix)]
+fn describe_native_crash(status: &ExitStatus) -> Option<String> {
+    use std::os::unix::process::ExitStatusExt;
+
+    let signal = status.signal()?;
+    let name = match signal {
+        libc::SIGSEGV => "SIGSEGV (segmentation fault)",
+        libc::SIGBUS => "SIGBUS (bus error)",
+        libc::SIGILL => "SIGILL (illegal instruction)",
+        libc::SIGFPE => "SIGFPE (floating point exception)",
+        libc::SIGABRT => "SIGABRT (abort)",
+        libc::SIGTRAP => "SIGTRAP (trace/breakpoint trap)",
+        // SIGKILL / SIGTERM 等属于外部终止，不视为崩溃
+        _ => return None,
+    };
+    Some(format!("Native crash: process terminated by signal {} {}", signal, name))
+}
+
+/// 判断退出状态是否为原生崩溃，返回可读描述
+#[cfg(windows)]
+fn describe_native_crash(status: &ExitStatus) -> Option<String> {
+    let code = status.code()? as u32;
+    let name = match code {
+        0xC0000005 => "EXCEPTION_ACCESS_VIOLATION",
+        0xC00000FD => "EXCEPTION_STACK_OVERFLOW",
+        0xC0000409 => "STATUS_STACK_BUFFER_OVERRUN",
+        0xC000001D => "EXCEPTION_ILLEGAL_INSTRUCTION",
+        0xC0000094 => "EXCEPTION_INT_DIVIDE_BY_ZERO",
+        0xC0000374 => "STATUS_HEAP_CORRUPTION",
+        0x80000003 => "EXCEPTION_BREAKPOINT",
+        _ => return None,
+    };
+    Some(format!("Native crash: process exited with exception 0x{:08X} {}", code, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_segfault_is_native_crash() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let status = ExitStatus::from_raw(libc::SIGSEGV);
+        assert!(describe_native_crash(&status).unwrap().contains("SIGSEGV"));
+        assert_eq!(exit_code(&status), 128 + libc::SIGSEGV);
+    }
+
+    #[test]
+    fn test_minidump_file_in_crashes_dir() {
+        use minidumper::ServerHandler;
+
+        let dir = tempfile::tempdir().unwrap();
+        let handler = MinidumpHandler { dir: dir.path().join("crashes"), written: Arc::default(), hang_file: Mutex::new(None) };
+        let (file, path) = handler.create_minidump_file().unwrap();
+        assert!(path.starts_with(dir.path().join("crashes")) && path.extension().unwrap() == "dmp");
+
+        handler.on_minidump_created(Ok(minidumper::MinidumpBinary { file, path: path.clone(), contents: None }));
+        assert_eq!(handler.written.lock().unwrap().as_deref(), Some(path.as_path()));
+
+        // 卡死 minidump 使用子进程指定的文件名，且不作为原生崩溃的 minidump
+        handler.on_message(HANG_DUMP_MESSAGE, b"../hang_escape.dmp".to_vec());
+        assert!(handler.hang_file.lock().unwrap().is_none());
+        handler.on_message(HANG_DUMP_MESSAGE, b"hang_1.dmp".to_vec());
+        let (file, hang) = handler.create_minidump_file().unwrap();
+        assert_eq!(hang, dir.path().join("crashes").join("hang_1.dmp"));
+        handler.on_minidump_created(Ok(minidumper::MinidumpBinary { file, path: hang, contents: None }));
+        assert_eq!(handler.written.lock().unwrap().as_deref(), Some(path.as_path()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_normal_exit_and_kill_are_not_crashes() {
+        use std::os::unix::process::ExitStatusExt;
+
+        assert!(describe_native_crash(&ExitStatus::from_raw(0)).is_none());
+        assert!(describe_native_crash(&ExitStatus::from_raw(libc::SIGKILL)).is_none());
+    }
+}
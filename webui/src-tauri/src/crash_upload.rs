@@ -0,0 +1,404 @@
+//! 崩溃报告上传模块
+//!
+//! 默认只在本地 DAWEI_HOME/crashes 下保存 JSON 报告。
+//! 使用 `sentry` feature 编译，并在设置中开启 crash_upload（或设置 DAWEI_CRASH_UPLOAD=1）后，
+//! 崩溃报告会同时转发到 Sentry 兼容的服务（DSN 来自 DAWEI_SENTRY_DSN，
+//! 运行时未设置时使用编译时的同名环境变量）。
+//!
+//! 待上传的报告先写入 DAWEI_HOME/crash_queue，再按每小时上限发送：
+//! 崩溃循环时不会持续请求服务端，离线时报告保留在队列中，网络恢复后由后台线程重试。
+
+use crate::crash_handler::CrashReport;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// 每小时最多上传的报告数
+const MAX_UPLOADS_PER_HOUR: usize = 10;
+
+/// 队列中最多保留的报告数，超出时丢弃最旧的
+const MAX_QUEUED: usize = 50;
+
+/// 后台重试间隔
+const RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 记录最近上传时间的文件
+const HISTORY_FILE: &str = ".sent";
+
+/// 同一时间只允许一个线程发送队列（后台重试和新崩溃可能同时触发）
+static DRAIN_LOCK: Mutex<()> = Mutex::new(());
+
+/// 上传队列状态
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueStatus {
+    /// 是否开启了上传
+    pub enabled: bool,
+    /// 队列中待上传的报告数
+    pub pending: usize,
+    /// 最近一小时已上传的报告数
+    pub sent_last_hour: usize,
+    /// 每小时上传上限
+    pub limit_per_hour: usize,
+}
+
+/// 是否在运行时开启了上传（DAWEI_CRASH_UPLOAD 优先于设置）
+fn upload_enabled() -> bool {
+    match std::env::var("DAWEI_CRASH_UPLOAD").as_deref().map(str::trim) {
+        Ok("1") | Ok("true") => true,
+        Ok("0") | Ok("false") => false,
+        _ => crate::settings::current().crash_upload,
+    }
+}
+
+/// 上传目标 DSN
+#[cfg_attr(not(feature = "sentry"), allow(dead_code))]
+fn upload_dsn() -> Option<String> {
+    std::env::var("DAWEI_SENTRY_DSN")
+        .ok()
+        .or_else(|| option_env!("DAWEI_SENTRY_DSN").map(str::to_string))
+        .filter(|dsn| !dsn.trim().is_empty())
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// 本地上传队列
+struct UploadQueue {
+    dir: PathBuf,
+}
+
+impl UploadQueue {
+    fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// 加入队列；相同指纹的报告只保留最新一份
+    fn enqueue(&self, report: &CrashReport) -> std::io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+
+        let key = if report.fingerprint.is_empty() {
+            report.filename.trim_end_matches(".json").to_string()
+        } else {
+            report.fingerprint.clone()
+        };
+        fs::write(self.dir.join(format!("{}.json", key)), report.to_json())?;
+
+        let pending = self.pending();
+        for stale in pending.iter().take(pending.len().saturating_sub(MAX_QUEUED)) {
+            let _ = fs::remove_file(stale);
+        }
+        Ok(())
+    }
+
+    /// 待上传的报告文件（从旧到新）
+    fn pending(&self) -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+
+        let mut files: Vec<(SystemTime, PathBuf)> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("json"))
+            .map(|path| {
+                let modified = path.metadata().and_then(|m| m.modified()).unwrap_or(SystemTime::UNIX_EPOCH);
+                (modified, path)
+            })
+            .collect();
+        files.sort();
+        files.into_iter().map(|(_, path)| path).collect()
+    }
+
+    /// 最近一小时内的上传时间
+    fn recent_uploads(&self, now: u64) -> Vec<u64> {
+        fs::read_to_string(self.dir.join(HISTORY_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str::<Vec<u64>>(&content).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|sent| now.saturating_sub(*sent) < 3600)
+            .collect()
+    }
+
+    fn record_upload(&self, now: u64) {
+        let mut history = self.recent_uploads(now);
+        history.push(now);
+        let _ = fs::write(self.dir.join(HISTORY_FILE), serde_json::to_string(&history).unwrap_or_default());
+    }
+
+    /// 在上限内依次发送队列中的报告，返回成功发送的数量
+    ///
+    /// `send` 返回 false 表示发送失败（通常是离线），此时停止并保留剩余报告。
+    fn drain(&self, now: u64, send: impl Fn(&CrashReport) -> bool) -> usize {
+        let _lock = DRAIN_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut sent = 0;
+        for path in self.pending() {
+            if self.recent_uploads(now).len() >= MAX_UPLOADS_PER_HOUR {
+                break;
+            }
+
+            let report = fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| serde_json::from_str::<CrashReport>(&content).ok());
+            let Some(report) = report else {
+                // 损坏的队列文件直接丢弃
+                let _ = fs::remove_file(&path);
+                continue;
+            };
+
+            if !send(&report) {
+                break;
+            }
+            let _ = fs::remove_file(&path);
+            self.record_upload(now);
+            sent += 1;
+        }
+        sent
+    }
+
+    fn status(&self, enabled: bool, now: u64) -> QueueStatus {
+        QueueStatus {
+            enabled,
+            pending: self.pending().len(),
+            sent_last_hour: self.recent_uploads(now).len(),
+            limit_per_hour: MAX_UPLOADS_PER_HOUR,
+        }
+    }
+}
+
+fn queue() -> UploadQueue {
+    UploadQueue::new(crate::get_dawei_home().join("crash_queue"))
+}
+
+#[cfg(feature = "sentry")]
+mod backend {
+    use super::*;
+    use crate::crash_handler::{CrashKind, CrashSource};
+    use std::borrow::Cow;
+    use std::net::{TcpStream, ToSocketAddrs};
+    use std::sync::OnceLock;
+
+    /// 上传单个报告时最多等待的时间（panic 后进程即将退出，必须等待发送完成）
+    const FLUSH_TIMEOUT: Duration = Duration::from_secs(2);
+
+    /// 检测服务端是否可达的连接超时
+    const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+    static GUARD: OnceLock<sentry::ClientInitGuard> = OnceLock::new();
+
+    pub fn init() {
+        if !upload_enabled() {
+            return;
+        }
+        let Some(dsn) = upload_dsn() else {
+            tracing::warn!("Crash upload enabled but DAWEI_SENTRY_DSN is not set");
+            return;
+        };
+
+        let guard = sentry::init((
+            dsn,
+            sentry::ClientOptions {
+                release: Some(format!("dawei-gui@{}", env!("CARGO_PKG_VERSION")).into()),
+                ..Default::default()
+            },
+        ));
+        if guard.is_enabled() {
+            tracing::info!("Crash upload enabled");
+            let _ = GUARD.set(guard);
+        } else {
+            tracing::warn!("Invalid DAWEI_SENTRY_DSN, crash upload disabled");
+        }
+    }
+
+    pub fn enabled() -> bool {
+        GUARD.get().is_some()
+    }
+
+    /// 服务端是否可达（离线时不发送，报告留在队列中）
+    fn reachable() -> bool {
+        let Some(dsn) = GUARD.get().and_then(|_| sentry::Hub::current().client()).and_then(|c| c.dsn().cloned()) else {
+            return false;
+        };
+        let port = dsn.port();
+        (dsn.host(), port)
+            .to_socket_addrs()
+            .map(|mut addrs| addrs.any(|addr| TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).is_ok()))
+            .unwrap_or(false)
+    }
+
+    pub fn send(report: &CrashReport) -> bool {
+        if !enabled() || !reachable() {
+            return false;
+        }
+
+        // Sentry 的堆栈帧从外到内排列，Rust 堆栈需要反转
+        let mut frames: Vec<sentry::protocol::Frame> = report
+            .frames
+            .iter()
+            .map(|frame| sentry::protocol::Frame {
+                function: Some(frame.function.clone()),
+                filename: frame.file.clone(),
+                lineno: frame.line.map(u64::from),
+                ..Default::default()
+            })
+            .collect();
+        if report.source == CrashSource::App {
+            frames.reverse();
+        }
+
+        let exception = sentry::protocol::Exception {
+            ty: format!("{:?}", report.kind),
+            value: Some(report.error_message.clone()),
+            stacktrace: (!frames.is_empty()).then(|| sentry::protocol::Stacktrace { frames, ..Default::default() }),
+            ..Default::default()
+        };
+
+        let mut event = sentry::protocol::Event {
+            level: match report.kind {
+                CrashKind::Exception | CrashKind::Hang => sentry::Level::Error,
+                _ => sentry::Level::Fatal,
+            },
+            message: Some(report.error_message.clone()),
+            exception: vec![exception].into(),
+            release: Some(format!("dawei-gui@{}", report.app_version).into()),
+            ..Default::default()
+        };
+        // 沿用本地指纹，使服务端的分组和本地去重一致
+        if !report.fingerprint.is_empty() {
+            event.fingerprint = Cow::Owned(vec![Cow::Owned(report.fingerprint.clone())]);
+        }
+        for (key, value) in [
+            ("kind", format!("{:?}", report.kind).to_lowercase()),
+            ("source", format!("{:?}", report.source).to_lowercase()),
+            ("app_version", report.app_version.clone()),
+            ("session_id", report.session_id.clone()),
+        ] {
+            event.tags.insert(key.to_string(), value);
+        }
+        event.extra.insert("report_file".to_string(), report.filename.clone().into());
+        event.extra.insert("backtrace".to_string(), report.backtrace.clone().into());
+        if !report.log_tail.is_empty() {
+            event.extra.insert("log_tail".to_string(), report.log_tail.clone().into());
+        }
+
+        sentry::capture_event(event);
+        sentry::Hub::current()
+            .client()
+            .is_some_and(|client| client.flush(Some(FLUSH_TIMEOUT)))
+    }
+}
+
+#[cfg(not(feature = "sentry"))]
+mod backend {
+    use super::*;
+
+    pub fn init() {
+        if upload_enabled() {
+            tracing::warn!("Crash upload requested but this build does not include the `sentry` feature");
+        }
+    }
+
+    pub fn enabled() -> bool {
+        false
+    }
+
+    pub fn send(_report: &CrashReport) -> bool {
+        false
+    }
+}
+
+/// 初始化上传客户端（未开启时不做任何事）
+pub fn init() {
+    backend::init();
+}
+
+/// 启动后台重试线程，网络恢复后发送队列中积压的报告
+pub fn start_retry_worker() {
+    if !backend::enabled() {
+        return;
+    }
+
+    std::thread::spawn(|| loop {
+        let sent = queue().drain(now_secs(), backend::send);
+        if sent > 0 {
+            tracing::info!("Uploaded {} queued crash report(s)", sent);
+        }
+        std::thread::sleep(RETRY_INTERVAL);
+    });
+}
+
+/// 将崩溃报告加入上传队列并尝试发送（未开启时不做任何事），本地报告始终先于上传保存
+pub fn submit(report: &CrashReport) {
+    if !backend::enabled() {
+        return;
+    }
+
+    let queue = queue();
+    if let Err(e) = queue.enqueue(report) {
+        tracing::warn!("Failed to queue crash report for upload: {}", e);
+        return;
+    }
+    queue.drain(now_secs(), backend::send);
+}
+
+/// 上传队列状态
+pub fn queue_status() -> QueueStatus {
+    queue().status(backend::enabled(), now_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn report(error: &str) -> CrashReport {
+        CrashReport::new(error.to_string(), String::new())
+    }
+
+    #[test]
+    fn test_queue_keeps_reports_while_offline() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = UploadQueue::new(dir.path().to_path_buf());
+
+        queue.enqueue(&report("first")).unwrap();
+        queue.enqueue(&report("second")).unwrap();
+        // 相同指纹只保留一份
+        queue.enqueue(&report("second")).unwrap();
+        assert_eq!(queue.pending().len(), 2);
+
+        assert_eq!(queue.drain(1000, |_| false), 0);
+        assert_eq!(queue.pending().len(), 2);
+
+        assert_eq!(queue.drain(1000, |_| true), 2);
+        assert!(queue.pending().is_empty());
+        assert_eq!(queue.status(true, 1000).sent_last_hour, 2);
+    }
+
+    #[test]
+    fn test_drain_respects_hourly_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = UploadQueue::new(dir.path().to_path_buf());
+        for i in 0..MAX_UPLOADS_PER_HOUR + 3 {
+            queue.enqueue(&report(&format!("error {}", i))).unwrap();
+        }
+
+        let attempts = Cell::new(0);
+        let sent = queue.drain(1000, |_| {
+            attempts.set(attempts.get() + 1);
+            true
+        });
+        assert_eq!(sent, MAX_UPLOADS_PER_HOUR);
+        assert_eq!(attempts.get(), MAX_UPLOADS_PER_HOUR);
+        assert_eq!(queue.pending().len(), 3);
+
+        // 一小时后额度恢复
+        assert_eq!(queue.drain(1000 + 3600, |_| true), 3);
+    }
+}
@@ -0,0 +1,104 @@
+//! 崩溃报告查看窗口
+//!
+//! 主 WebView 可能已经处于异常状态，因此崩溃报告使用独立的小窗口展示。
+
+use crate::crash_handler::{find_crash_report, latest_unviewed_crash_report, mark_crash_reports_viewed};
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+
+/// 崩溃查看窗口标签
+pub const WINDOW_LABEL: &str = "crash-viewer";
+
+/// 崩溃查看页面
+const VIEWER_PAGE: &str = "crash-viewer.html";
+
+/// 打开（或聚焦）崩溃查看窗口，必须在主线程或异步命令中调用
+pub fn open_crash_viewer(app: &AppHandle) -> tauri::Result<()> {
+    if let Some(window) = app.get_webview_window(WINDOW_LABEL) {
+        window.show()?;
+        window.set_focus()?;
+        return Ok(());
+    }
+
+    crate::portable::webview(WebviewWindowBuilder::new(app, WINDOW_LABEL, WebviewUrl::App(VIEWER_PAGE.into())))
+        .title("大微 - 崩溃报告")
+        .inner_size(760.0, 560.0)
+        .min_inner_size(480.0, 360.0)
+        .center()
+        .devtools(!crate::policy::is_locked_down())
+        .build()?;
+
+    if let Err(e) = mark_crash_reports_viewed() {
+        tracing::warn!("Failed to mark crash reports as viewed: {}", e);
+    }
+    Ok(())
+}
+
+/// 单个崩溃报告窗口的标签前缀
+const REPORT_LABEL_PREFIX: &str = "crash-report-";
+
+/// 由报告文件名生成窗口标签（标签只允许字母、数字和 `-_/:`）
+fn report_label(filename: &str) -> String {
+    let name: String = filename.chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' }).collect();
+    format!("{}{}", REPORT_LABEL_PREFIX, name)
+}
+
+/// 在独立窗口中打开单个崩溃报告（已打开时聚焦该窗口）
+///
+/// 页面通过初始化脚本注入的 `window.__DAWEI_CRASH_REPORT__` 加载指定的报告。
+pub fn open_crash_window(app: &AppHandle, filename: &str) -> Result<(), String> {
+    let report = find_crash_report(filename).ok_or_else(|| format!("崩溃报告不存在: {}", filename))?;
+
+    let label = report_label(&report.filename);
+    if let Some(window) = app.get_webview_window(&label) {
+        return window.show().and_then(|_| window.set_focus()).map_err(|e| e.to_string());
+    }
+
+    let script = format!(
+        "window.__DAWEI_CRASH_REPORT__ = {};",
+        serde_json::to_string(&report.filename).map_err(|e| e.to_string())?
+    );
+    crate::portable::webview(WebviewWindowBuilder::new(app, &label, WebviewUrl::App(VIEWER_PAGE.into())))
+        .title(format!("大微 - 崩溃报告 {}", report.timestamp_iso))
+        .inner_size(760.0, 560.0)
+        .min_inner_size(480.0, 360.0)
+        .center()
+        .devtools(!crate::policy::is_locked_down())
+        .initialization_script(script)
+        .build()
+        .map_err(|e| format!("无法打开崩溃报告窗口: {}", e))?;
+    Ok(())
+}
+
+/// 从任意线程请求打开崩溃查看窗口（例如 panic hook 中）
+pub fn request_open(app: &AppHandle) {
+    let handle = app.clone();
+    let result = app.run_on_main_thread(move || {
+        if let Err(e) = open_crash_viewer(&handle) {
+            tracing::error!("Failed to open crash viewer: {}", e);
+        }
+    });
+    if let Err(e) = result {
+        tracing::error!("Failed to schedule crash viewer: {}", e);
+    }
+}
+
+/// 启动时如果存在上次未查看的崩溃报告则打开查看窗口
+pub fn open_if_unviewed(app: &AppHandle) {
+    if let Some(report) = latest_unviewed_crash_report() {
+        tracing::warn!("Found unviewed crash report: {}", report.filename);
+        if let Err(e) = open_crash_viewer(app) {
+            tracing::error!("Failed to open crash viewer: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_label() {
+        assert_eq!(report_label("panic_20250101_120000_123.json"), "crash-report-panic_20250101_120000_123_json");
+        assert_eq!(report_label("../x y.dmp"), "crash-report-___x_y_dmp");
+    }
+}
@@ -0,0 +1,237 @@
+//! DAWEI_HOME 迁移
+//!
+//! 应用数据默认在 ~/.dawei，用户可以把它迁移到其他磁盘。迁移时复制全部数据、
+//! 逐个文件校验，全部一致后才写入指针文件 ~/.dawei-home（内容为新目录的路径），
+//! 下次启动时桌面端和后端都从指针文件读取新位置。旧目录保留，由用户确认后自行删除。
+
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// 指针文件中记录的目录（启动后只读取一次，迁移后需要重启才会生效）
+static RELOCATED_HOME: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// 迁移结果
+#[derive(Debug, Clone, Serialize)]
+pub struct RelocationReport {
+    pub old_path: PathBuf,
+    pub new_path: PathBuf,
+    pub files_copied: u64,
+    pub bytes_copied: u64,
+    /// 迁移后需要重启应用才会使用新目录
+    pub restart_required: bool,
+}
+
+/// 指针文件路径
+pub fn pointer_file() -> PathBuf {
+    dirs::home_dir().unwrap_or_else(|| PathBuf::from(".")).join(".dawei-home")
+}
+
+/// 指针文件中记录的 DAWEI_HOME（没有迁移过时为 None）
+pub fn relocated_home() -> Option<PathBuf> {
+    RELOCATED_HOME
+        .get_or_init(|| {
+            let content = fs::read_to_string(pointer_file()).ok()?;
+            let path = PathBuf::from(content.trim());
+            (!content.trim().is_empty() && path.is_dir()).then_some(path)
+        })
+        .clone()
+}
+
+/// 校验迁移目标
+fn validate_target(current: &Path, target: &Path) -> Result<(), String> {
+    if !target.is_absolute() {
+        return Err("目标路径必须是绝对路径".to_string());
+    }
+    let current = resolve_path(current);
+    let target_resolved = resolve_path(target);
+    if target_resolved == current {
+        return Err("目标路径与当前目录相同".to_string());
+    }
+    if target_resolved.starts_with(&current) || current.starts_with(&target_resolved) {
+        return Err("目标路径不能位于当前目录内，也不能包含当前目录".to_string());
+    }
+    if target.exists() {
+        let mut entries = fs::read_dir(target).map_err(|e| format!("无法读取目标目录: {}", e))?;
+        if entries.next().is_some() {
+            return Err("目标目录必须为空".to_string());
+        }
+    }
+
+    // 确认目标可写
+    fs::create_dir_all(target).map_err(|e| format!("无法创建目标目录: {}", e))?;
+    let probe = target.join(".dawei-write-test");
+    fs::write(&probe, b"ok").map_err(|e| format!("目标目录不可写: {}", e))?;
+    let _ = fs::remove_file(probe);
+    Ok(())
+}
+
+/// 规范化路径；目标目录可能还不存在，此时规范化其最近的已存在上级目录
+fn resolve_path(path: &Path) -> PathBuf {
+    let mut existing = path;
+    let mut rest = Vec::new();
+    while !existing.exists() {
+        let (Some(parent), Some(name)) = (existing.parent(), existing.file_name()) else {
+            return path.to_path_buf();
+        };
+        rest.push(name.to_os_string());
+        existing = parent;
+    }
+    let mut resolved = existing.canonicalize().unwrap_or_else(|_| existing.to_path_buf());
+    resolved.extend(rest.iter().rev());
+    resolved
+}
+
+/// 目录下所有文件的总大小和数量
+fn dir_size(dir: &Path) -> io::Result<(u64, u64)> {
+    let mut total = (0, 0);
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            let (bytes, files) = dir_size(&entry.path())?;
+            total.0 += bytes;
+            total.1 += files;
+        } else if file_type.is_file() {
+            total.0 += entry.metadata()?.len();
+            total.1 += 1;
+        }
+    }
+    Ok(total)
+}
+
+/// 递归复制目录（符号链接不复制，只输出警告）
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let target = to.join(entry.file_name());
+        if file_type.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else if file_type.is_file() {
+            fs::copy(entry.path(), &target)?;
+        } else {
+            tracing::warn!("Skipping non-regular file during relocation: {:?}", entry.path());
+        }
+    }
+    Ok(())
+}
+
+/// 逐字节比较两个文件
+fn same_content(a: &Path, b: &Path) -> io::Result<bool> {
+    if fs::metadata(a)?.len() != fs::metadata(b)?.len() {
+        return Ok(false);
+    }
+
+    let (mut fa, mut fb) = (File::open(a)?, File::open(b)?);
+    let (mut ba, mut bb) = (vec![0u8; 64 * 1024], vec![0u8; 64 * 1024]);
+    loop {
+        let n = fa.read(&mut ba)?;
+        if n == 0 {
+            return Ok(true);
+        }
+        fb.read_exact(&mut bb[..n])?;
+        if ba[..n] != bb[..n] {
+            return Ok(false);
+        }
+    }
+}
+
+/// 校验复制结果，返回第一个不一致的文件
+fn verify_copy(from: &Path, to: &Path) -> io::Result<Option<PathBuf>> {
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let target = to.join(entry.file_name());
+        if file_type.is_dir() {
+            if let Some(mismatch) = verify_copy(&entry.path(), &target)? {
+                return Ok(Some(mismatch));
+            }
+        } else if file_type.is_file() && !(target.is_file() && same_content(&entry.path(), &target)?) {
+            return Ok(Some(entry.path()));
+        }
+    }
+    Ok(None)
+}
+
+/// 复制并校验数据，成功后返回复制的字节数和文件数
+fn copy_and_verify(current: &Path, target: &Path) -> Result<(u64, u64), String> {
+    validate_target(current, target)?;
+
+    let (bytes, files) = if current.exists() {
+        dir_size(current).map_err(|e| format!("无法统计当前数据大小: {}", e))?
+    } else {
+        (0, 0)
+    };
+    crate::disk_space::ensure_free(target, bytes, "迁移数据")?;
+
+    if current.exists() {
+        copy_dir(current, target).map_err(|e| format!("复制数据失败: {}", e))?;
+        match verify_copy(current, target) {
+            Ok(None) => {}
+            Ok(Some(path)) => return Err(format!("校验失败，文件不一致: {}", path.display())),
+            Err(e) => return Err(format!("校验失败: {}", e)),
+        }
+    }
+    Ok((bytes, files))
+}
+
+/// 把 DAWEI_HOME 迁移到 `target`
+///
+/// 通过环境变量 DAWEI_HOME 指定目录时不能迁移（指针文件不会生效）。
+pub fn relocate(target: &Path) -> Result<RelocationReport, String> {
+    if crate::portable::is_portable() {
+        return Err("便携模式下数据固定保存在程序旁的 data 目录中".to_string());
+    }
+    if std::env::var_os("DAWEI_HOME").is_some() {
+        return Err("当前通过环境变量 DAWEI_HOME 指定了数据目录，请修改环境变量".to_string());
+    }
+
+    let current = crate::get_dawei_home();
+    let (bytes_copied, files_copied) = copy_and_verify(&current, target)?;
+    // 本次运行的会话标记不属于新目录，否则下次启动会被误判为崩溃
+    let _ = fs::remove_file(target.join(crate::session::MARKER_FILE));
+
+    // 先写临时文件再重命名，避免指针文件损坏
+    let pointer = pointer_file();
+    let tmp = pointer.with_extension("tmp");
+    fs::write(&tmp, target.to_string_lossy().as_bytes())
+        .and_then(|_| fs::rename(&tmp, &pointer))
+        .map_err(|e| format!("写入指针文件失败: {}", e))?;
+
+    crate::breadcrumbs::record("settings", format!("DAWEI_HOME relocated to {}", target.display()));
+    tracing::info!("DAWEI_HOME relocated: {:?} -> {:?}", current, target);
+    Ok(RelocationReport {
+        old_path: current,
+        new_path: target.to_path_buf(),
+        files_copied,
+        bytes_copied,
+        restart_required: true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_and_verify() {
+        let dir = tempfile::tempdir().unwrap();
+        let current = dir.path().join("home");
+        fs::create_dir_all(current.join("crashes")).unwrap();
+        fs::write(current.join("settings.json"), "{}").unwrap();
+        fs::write(current.join("crashes").join("crash_1.json"), "report").unwrap();
+
+        let target = dir.path().join("moved");
+        assert_eq!(copy_and_verify(&current, &target).unwrap(), (8, 2));
+        assert_eq!(fs::read_to_string(target.join("crashes").join("crash_1.json")).unwrap(), "report");
+
+        // 目标非空、位于当前目录内、相对路径都会被拒绝
+        assert!(copy_and_verify(&current, &target).is_err());
+        assert!(copy_and_verify(&current, &current.join("nested")).is_err());
+        assert!(copy_and_verify(&current, Path::new("relative")).is_err());
+    }
+}
@@ -0,0 +1,49 @@
+//! 运行时打开/关闭 DevTools
+//!
+//! 启动时自动打开 DevTools 由 DAWEI_DEVTOOLS 或 `open_devtools` 设置控制；运行中切换需要
+//! 先开启 `devtools_toggle` 设置，之后可以通过 `toggle_devtools` 命令或帮助菜单
+//! （Ctrl+Alt+I，macOS 为 Cmd+Alt+I）切换，便于报告渲染问题。锁定模式和演示模式下不可用。
+
+use crate::settings::{self, SETTINGS_CHANGED_EVENT};
+use tauri::{AppHandle, Listener, WebviewWindow};
+
+/// 当前是否允许切换 DevTools
+pub fn is_allowed() -> bool {
+    settings::current().devtools_toggle && !crate::policy::is_locked_down() && !crate::kiosk::is_enabled()
+}
+
+/// 打开或关闭窗口的 DevTools，返回切换后是否打开
+pub fn toggle(window: &WebviewWindow) -> Result<bool, String> {
+    if crate::policy::is_locked_down() {
+        return Err("锁定模式下不可用".to_string());
+    }
+    if crate::kiosk::is_enabled() {
+        return Err("演示模式下不可用".to_string());
+    }
+    if !settings::current().devtools_toggle {
+        return Err("未开启 DevTools 切换（设置 devtools_toggle）".to_string());
+    }
+
+    let open = !window.is_devtools_open();
+    if open {
+        window.open_devtools();
+    } else {
+        window.close_devtools();
+    }
+    crate::breadcrumbs::record("window", format!("DevTools {} for {}", if open { "opened" } else { "closed" }, window.label()));
+    Ok(open)
+}
+
+/// 按设置更新帮助菜单中 DevTools 菜单项的可用状态
+pub fn init(app: &AppHandle) {
+    crate::menu::set_devtools_enabled(is_allowed());
+
+    app.listen_any(SETTINGS_CHANGED_EVENT, move |event| {
+        let changed = serde_json::from_str::<serde_json::Value>(event.payload())
+            .map(|change| ["devtools_toggle", "locked_down"].iter().any(|key| change["changed"].get(*key).is_some()))
+            .unwrap_or(false);
+        if changed {
+            crate::menu::set_devtools_enabled(is_allowed());
+        }
+    });
+}
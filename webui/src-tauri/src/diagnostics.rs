@@ -0,0 +1,163 @@
+//! 诊断包导出
+//!
+//! 把排查问题需要的信息打包为一个 zip，便于附在问题反馈中：
+//! - `logs/app/`、`logs/agentic/`：最近的桌面端和后端日志
+//! - `settings.json`：设置，密钥类字段已替换为 `[REDACTED]`
+//! - `crashes/`：最近的崩溃报告
+//! - `environment.json`：相关环境变量和 .env 中的变量（密钥只保留掩码）
+//! - `system.json`：组件版本和系统信息（同关于对话框）
+//! - `hardware.json`：CPU、内存和显卡
+//! - `breadcrumbs.json`：最近的关键事件
+
+use crate::env_file::{self, EnvTarget};
+use serde_json::{json, Value};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// 包含的桌面端日志文件数（从新到旧）
+const MAX_APP_LOG_FILES: usize = 5;
+
+/// 包含的后端日志文件数：当前文件和最近一次轮转的文件
+const MAX_BACKEND_LOG_FILES: usize = 2;
+
+/// 包含的崩溃报告数（从新到旧）
+const MAX_CRASH_REPORTS: usize = 20;
+
+/// 导出的环境变量前缀（其他环境变量可能包含无关的个人信息）
+const ENV_PREFIXES: [&str; 7] = ["DAWEI_", "UV_", "PYTHON", "VIRTUAL_ENV", "LANG", "LC_", "PATH"];
+
+/// 替换密钥的占位符
+const REDACTED: &str = "[REDACTED]";
+
+/// 替换名称像密钥的字段的值（递归处理嵌套对象和数组）
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let is_scalar = !value.is_object() && !value.is_array() && !value.is_null();
+                if is_scalar && env_file::is_secret(key) {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+/// 相关环境变量和 .env 中的变量
+fn environment() -> Value {
+    let process: serde_json::Map<String, Value> = std::env::vars()
+        .filter(|(key, _)| ENV_PREFIXES.iter().any(|prefix| key.starts_with(prefix)))
+        .map(|(key, value)| {
+            let value = if env_file::is_secret(&key) { REDACTED.to_string() } else { value };
+            (key, Value::String(value))
+        })
+        .collect();
+    json!({
+        "process": process,
+        "app_env": env_file::list(EnvTarget::App),
+        "home_env": env_file::list(EnvTarget::Home),
+    })
+}
+
+struct Bundle {
+    zip: ZipWriter<File>,
+    options: SimpleFileOptions,
+}
+
+impl Bundle {
+    fn add_bytes(&mut self, name: &str, bytes: &[u8]) -> Result<(), String> {
+        self.zip.start_file(name, self.options).map_err(|e| e.to_string())?;
+        self.zip.write_all(bytes).map_err(|e| e.to_string())
+    }
+
+    fn add_json(&mut self, name: &str, value: &Value) -> Result<(), String> {
+        let content = serde_json::to_vec_pretty(value).map_err(|e| e.to_string())?;
+        self.add_bytes(name, &content)
+    }
+
+    /// 添加文件，文件不可读时跳过
+    fn add_file(&mut self, name: &str, path: &Path) -> Result<(), String> {
+        let Ok(mut file) = File::open(path) else {
+            return Ok(());
+        };
+        self.zip.start_file(name, self.options).map_err(|e| e.to_string())?;
+        std::io::copy(&mut file, &mut self.zip).map_err(|e| format!("无法读取 {}: {}", path.display(), e))?;
+        Ok(())
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+/// 生成诊断包；`system` 为组件版本信息（需要异步查询后端，由调用方提供）
+pub fn export_to(dest: &Path, system: &crate::about::AboutInfo) -> Result<PathBuf, String> {
+    let file = File::create(dest).map_err(|e| format!("无法创建 {}: {}", dest.display(), e))?;
+    let mut bundle = Bundle {
+        zip: ZipWriter::new(file),
+        options: SimpleFileOptions::default().compression_method(CompressionMethod::Deflated),
+    };
+
+    bundle.add_json(
+        "manifest.json",
+        &json!({
+            "format": "dawei-diagnostics",
+            "created_at": chrono::Local::now().to_rfc3339(),
+            "app_version": env!("CARGO_PKG_VERSION"),
+            "session_id": crate::session::session_id(),
+        }),
+    )?;
+    bundle.add_json("system.json", &serde_json::to_value(system).map_err(|e| e.to_string())?)?;
+    bundle.add_json("hardware.json", &serde_json::to_value(crate::hardware::collect()).map_err(|e| e.to_string())?)?;
+
+    let mut settings: Value = std::fs::read_to_string(crate::settings::settings_file())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_else(|| serde_json::to_value(crate::settings::current()).unwrap_or_default());
+    redact(&mut settings);
+    bundle.add_json("settings.json", &settings)?;
+    bundle.add_json("environment.json", &environment())?;
+    bundle.add_json("breadcrumbs.json", &serde_json::to_value(crate::breadcrumbs::snapshot()).unwrap_or_default())?;
+
+    for path in crate::logging::log_files_in(&crate::logging::app_log_dir()).iter().take(MAX_APP_LOG_FILES) {
+        bundle.add_file(&format!("logs/app/{}", file_name(path)), path)?;
+    }
+    let backend_logs = crate::log_timeline::backend_log_files(&crate::backend_crash::backend_log_file());
+    for path in backend_logs.iter().take(MAX_BACKEND_LOG_FILES) {
+        bundle.add_file(&format!("logs/agentic/{}", file_name(path)), path)?;
+    }
+    for report in crate::crash_handler::get_all_crash_reports().iter().take(MAX_CRASH_REPORTS) {
+        bundle.add_bytes(&format!("crashes/{}", report.filename), report.to_json().as_bytes())?;
+    }
+
+    bundle.zip.finish().map_err(|e| e.to_string())?;
+    crate::breadcrumbs::record("app", "Diagnostics bundle exported");
+    Ok(dest.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_secrets() {
+        let mut settings = json!({
+            "zoom": 1.25,
+            "providerProfiles": [{ "name": "openai", "apiKey": "sk-1234567890", "baseUrl": "https://api.openai.com" }],
+            "backend_profiles": { "dev": { "env": { "HF_TOKEN": "hf_abc", "LOG_LEVEL": "debug" } } },
+        });
+        redact(&mut settings);
+        assert_eq!(settings["zoom"], 1.25);
+        assert_eq!(settings["providerProfiles"][0]["apiKey"], REDACTED);
+        assert_eq!(settings["providerProfiles"][0]["baseUrl"], "https://api.openai.com");
+        assert_eq!(settings["backend_profiles"]["dev"]["env"]["HF_TOKEN"], REDACTED);
+        assert_eq!(settings["backend_profiles"]["dev"]["env"]["LOG_LEVEL"], "debug");
+    }
+}
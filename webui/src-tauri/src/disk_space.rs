@@ -0,0 +1,107 @@
+//! 磁盘空间检查
+//!
+//! 同步后端依赖、迁移数据等会写入大量数据的操作开始前先检查目标所在磁盘的剩余空间，
+//! 不足时直接拒绝并说明需要多少空间，避免写到一半失败留下损坏的环境。
+//! 无法确定所在磁盘时（例如网络路径）不阻止操作。
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+const GIB: u64 = 1024 * 1024 * 1024;
+
+/// 同步后端依赖（uv 创建虚拟环境、安装 Python 包）前要求的最小可用空间
+pub const INSTALL_MIN_FREE: u64 = 2 * GIB;
+
+/// 路径所在磁盘的空间
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskUsage {
+    pub path: PathBuf,
+    pub mount_point: PathBuf,
+    pub total: u64,
+    pub available: u64,
+    /// 可用空间低于安装依赖所需的空间
+    pub low: bool,
+}
+
+/// 路径本身或最近的已存在的上级目录（要创建的目录还不存在时按其上级所在磁盘计算）
+fn existing_ancestor(path: &Path) -> PathBuf {
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    absolute.ancestors().find(|dir| dir.exists()).map(Path::to_path_buf).unwrap_or(absolute)
+}
+
+/// 可读的大小，例如 `1.5 GB`
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// 路径所在磁盘的空间，无法确定时返回 None
+pub fn usage(path: &Path) -> Option<DiskUsage> {
+    let target = existing_ancestor(path);
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .filter(|disk| target.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| DiskUsage {
+            path: path.to_path_buf(),
+            mount_point: disk.mount_point().to_path_buf(),
+            total: disk.total_space(),
+            available: disk.available_space(),
+            low: disk.available_space() < INSTALL_MIN_FREE,
+        })
+}
+
+/// 确认路径所在磁盘至少有 `required` 字节可用，`purpose` 用于错误信息（例如"同步后端依赖"）
+pub fn ensure_free(path: &Path, required: u64, purpose: &str) -> Result<(), String> {
+    let Some(usage) = usage(path) else {
+        tracing::warn!("Unable to determine free space for {:?}, skipping check", path);
+        return Ok(());
+    };
+    if usage.available < required {
+        return Err(format!(
+            "磁盘空间不足：{} 所在磁盘（{}）可用 {}，{}至少需要 {}",
+            path.display(),
+            usage.mount_point.display(),
+            format_size(usage.available),
+            purpose,
+            format_size(required)
+        ));
+    }
+    Ok(())
+}
+
+/// 默认查询的路径：DAWEI_HOME 和应用所在目录
+pub fn default_paths() -> Vec<PathBuf> {
+    let mut paths = vec![crate::get_dawei_home()];
+    if let Some(dir) = std::env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf)) {
+        paths.push(dir);
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_size_and_ancestor() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(INSTALL_MIN_FREE), "2.0 GB");
+        assert_eq!(format_size(3 * 1024 * GIB), "3.0 TB");
+
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(existing_ancestor(&dir.path().join("a/b/c")), std::path::absolute(dir.path()).unwrap());
+    }
+}
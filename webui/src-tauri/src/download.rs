@@ -0,0 +1,373 @@
+//! 可断点续传的下载
+//!
+//! 应用更新和 Python 包等大文件在慢速网络上可能下载很久，中途断网或退出应用后不应从头开始。
+//! 下载内容写入 DAWEI_HOME/downloads/<id>.part，进度和服务器的校验标识（ETag / Last-Modified）
+//! 保存在 <id>.json 中；继续下载时用 `Range` 请求剩余部分，`If-Range` 保证文件没有变化，
+//! 服务器不支持续传（返回 200）或文件已变化时从头下载。下载完成后改名为目标文件名。
+//!
+//! 下载过程中发送 `download-progress` 事件（内容为 [`DownloadState`]）。`pause_download` 暂停后
+//! 已下载的部分保留，`resume_download` 继续；失败的下载同样可以继续。
+
+use crate::atomic_file::write_json_atomic;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// 下载进度事件，内容为 [`DownloadState`]
+pub const DOWNLOAD_PROGRESS_EVENT: &str = "download-progress";
+
+/// 下载目录名（位于 DAWEI_HOME）
+const DOWNLOADS_DIR: &str = "downloads";
+
+/// 连接超时；下载本身不限时，读取数据超时视为断线
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// 发送进度事件的最小间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 保存进度的最小间隔（字节），断电时最多重新下载这么多
+const SAVE_INTERVAL: u64 = 4 * 1024 * 1024;
+
+/// 进行中的下载和它们的暂停标志
+static ACTIVE: Mutex<BTreeMap<String, Arc<AtomicBool>>> = Mutex::new(BTreeMap::new());
+
+/// 下载状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadStatus {
+    Downloading,
+    Paused,
+    Completed,
+    Failed,
+}
+
+/// 下载记录（保存在 <id>.json）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DownloadState {
+    pub id: String,
+    pub url: String,
+    /// 完成后的文件名（位于下载目录）
+    pub file_name: String,
+    pub downloaded: u64,
+    /// 总大小，服务器未提供时为 None
+    pub total: Option<u64>,
+    /// 服务器返回的 ETag 或 Last-Modified，续传时用于 If-Range
+    pub validator: Option<String>,
+    pub status: DownloadStatus,
+    pub error: Option<String>,
+    pub updated_at: String,
+}
+
+/// 下载目录
+pub fn downloads_dir() -> PathBuf {
+    crate::get_dawei_home().join(DOWNLOADS_DIR)
+}
+
+/// ID 和文件名只能是单个路径组成部分
+fn check_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\', ':']) && !name.starts_with('.');
+    if valid { Ok(()) } else { Err(format!("无效的名称: {}", name)) }
+}
+
+fn state_file(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{}.json", id))
+}
+
+fn part_file(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{}.part", id))
+}
+
+fn read_state(dir: &Path, id: &str) -> Option<DownloadState> {
+    let content = fs::read_to_string(state_file(dir, id)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_state(dir: &Path, state: &DownloadState) -> Result<(), String> {
+    write_json_atomic(&state_file(dir, &state.id), state).map_err(|e| format!("保存下载进度失败: {}", e))
+}
+
+/// 续传的起始位置：同一地址的未完成下载从已写入的部分继续，否则从头开始
+fn resume_offset(previous: Option<&DownloadState>, url: &str, part_len: Option<u64>) -> u64 {
+    match (previous, part_len) {
+        (Some(state), Some(len)) if state.url == url && state.validator.is_some() => {
+            // 以实际写入的长度为准（进度不是每次写入都保存）
+            state.total.map_or(len, |total| len.min(total))
+        }
+        _ => 0,
+    }
+}
+
+/// 响应的校验标识
+fn validator(headers: &reqwest::header::HeaderMap) -> Option<String> {
+    [reqwest::header::ETAG, reqwest::header::LAST_MODIFIED]
+        .iter()
+        .find_map(|name| headers.get(name).and_then(|value| value.to_str().ok()).map(str::to_string))
+}
+
+/// 取消注册暂停标志
+struct ActiveGuard(String);
+
+impl Drop for ActiveGuard {
+    fn drop(&mut self) {
+        ACTIVE.lock().unwrap_or_else(|e| e.into_inner()).remove(&self.0);
+    }
+}
+
+fn emit(app: &AppHandle, state: &DownloadState) {
+    if let Err(e) = app.emit(DOWNLOAD_PROGRESS_EVENT, state) {
+        tracing::warn!("Failed to emit download-progress: {}", e);
+    }
+}
+
+/// 下载（或继续下载）到下载目录，返回结束时的状态（已完成或已暂停）。
+/// `on_progress` 收到已下载和总字节数，已完成的下载不再请求服务器
+pub async fn run(
+    app: &AppHandle,
+    id: &str,
+    url: &str,
+    file_name: &str,
+    headers: reqwest::header::HeaderMap,
+    mut on_progress: impl FnMut(u64, Option<u64>),
+) -> Result<DownloadState, String> {
+    check_name(id)?;
+    check_name(file_name)?;
+    let dir = downloads_dir();
+    fs::create_dir_all(&dir).map_err(|e| format!("无法创建下载目录: {}", e))?;
+
+    let paused = Arc::new(AtomicBool::new(false));
+    {
+        let mut active = ACTIVE.lock().unwrap_or_else(|e| e.into_inner());
+        if active.contains_key(id) {
+            return Err("该下载正在进行".to_string());
+        }
+        active.insert(id.to_string(), paused.clone());
+    }
+    let _guard = ActiveGuard(id.to_string());
+
+    let previous = read_state(&dir, id);
+    let target = dir.join(file_name);
+    if let Some(state) = previous.as_ref().filter(|state| {
+        state.status == DownloadStatus::Completed && state.url == url && state.file_name == file_name && target.is_file()
+    }) {
+        return Ok(state.clone());
+    }
+
+    let part = part_file(&dir, id);
+    let offset = resume_offset(previous.as_ref(), url, fs::metadata(&part).ok().map(|m| m.len()));
+    let mut state = DownloadState {
+        id: id.to_string(),
+        url: url.to_string(),
+        file_name: file_name.to_string(),
+        downloaded: offset,
+        total: previous.as_ref().filter(|_| offset > 0).and_then(|state| state.total),
+        validator: previous.as_ref().filter(|_| offset > 0).and_then(|state| state.validator.clone()),
+        status: DownloadStatus::Downloading,
+        error: None,
+        updated_at: chrono::Local::now().to_rfc3339(),
+    };
+
+    let result = transfer(app, &dir, &part, &mut state, headers, &paused, &mut on_progress).await;
+    state.updated_at = chrono::Local::now().to_rfc3339();
+    match result {
+        Ok(()) if state.status == DownloadStatus::Paused => {
+            tracing::info!("Paused download {} at {} bytes", id, state.downloaded);
+        }
+        Ok(()) => {
+            fs::rename(&part, &target).map_err(|e| format!("无法保存下载的文件: {}", e))?;
+            state.status = DownloadStatus::Completed;
+            tracing::info!("Downloaded {} ({} bytes)", id, state.downloaded);
+        }
+        Err(ref e) => {
+            state.status = DownloadStatus::Failed;
+            state.error = Some(e.clone());
+            tracing::warn!("Download {} failed at {} bytes: {}", id, state.downloaded, e);
+        }
+    }
+    write_state(&dir, &state)?;
+    emit(app, &state);
+    result.map(|_| state)
+}
+
+/// 请求并写入数据，暂停时把状态设为 Paused 后返回
+async fn transfer(
+    app: &AppHandle,
+    dir: &Path,
+    part: &Path,
+    state: &mut DownloadState,
+    headers: reqwest::header::HeaderMap,
+    paused: &AtomicBool,
+    on_progress: &mut impl FnMut(u64, Option<u64>),
+) -> Result<(), String> {
+    let client = reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .read_timeout(READ_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let mut request = client.get(&state.url).headers(headers);
+    if state.downloaded > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", state.downloaded));
+        if let Some(validator) = &state.validator {
+            request = request.header(reqwest::header::IF_RANGE, validator);
+        }
+    }
+    let mut response = request.send().await.map_err(|e| format!("下载失败: {}", e))?;
+
+    let status = response.status();
+    let mut file = if status == reqwest::StatusCode::PARTIAL_CONTENT && state.downloaded > 0 {
+        state.total = response.content_length().map(|remaining| state.downloaded + remaining).or(state.total);
+        OpenOptions::new().append(true).open(part).map_err(|e| format!("无法打开下载文件: {}", e))?
+    } else if status.is_success() {
+        if state.downloaded > 0 {
+            tracing::info!("Server does not support resuming {}, restarting", state.id);
+        }
+        state.downloaded = 0;
+        state.total = response.content_length();
+        state.validator = validator(response.headers());
+        File::create(part).map_err(|e| format!("无法创建下载文件: {}", e))?
+    } else if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE && state.total == Some(state.downloaded) {
+        // 上次写完后没来得及改名
+        return Ok(());
+    } else {
+        // 保留的部分可能已经无效，下次从头下载
+        state.validator = None;
+        return Err(format!("下载失败: 服务器返回 {}", status));
+    };
+
+    write_state(dir, state)?;
+    emit(app, state);
+    on_progress(state.downloaded, state.total);
+    let mut last_emit = Instant::now();
+    let mut last_saved = state.downloaded;
+    while let Some(chunk) = response.chunk().await.map_err(|e| format!("下载中断: {}", e))? {
+        file.write_all(&chunk).map_err(|e| format!("写入下载文件失败: {}", e))?;
+        state.downloaded += chunk.len() as u64;
+        if state.downloaded - last_saved >= SAVE_INTERVAL {
+            file.flush().map_err(|e| format!("写入下载文件失败: {}", e))?;
+            state.updated_at = chrono::Local::now().to_rfc3339();
+            write_state(dir, state)?;
+            last_saved = state.downloaded;
+        }
+        if last_emit.elapsed() >= PROGRESS_INTERVAL {
+            emit(app, state);
+            on_progress(state.downloaded, state.total);
+            last_emit = Instant::now();
+        }
+        if paused.load(Ordering::SeqCst) {
+            state.status = DownloadStatus::Paused;
+            break;
+        }
+    }
+    file.sync_all().map_err(|e| format!("写入下载文件失败: {}", e))?;
+    on_progress(state.downloaded, state.total);
+    if state.status != DownloadStatus::Paused {
+        if let Some(total) = state.total.filter(|total| *total != state.downloaded) {
+            return Err(format!("下载不完整: {}/{} 字节", state.downloaded, total));
+        }
+    }
+    Ok(())
+}
+
+/// 暂停进行中的下载，下载不存在或未在进行时返回 false
+pub fn pause(id: &str) -> bool {
+    let active = ACTIVE.lock().unwrap_or_else(|e| e.into_inner());
+    match active.get(id) {
+        Some(paused) => {
+            paused.store(true, Ordering::SeqCst);
+            true
+        }
+        None => false,
+    }
+}
+
+/// 是否正在下载
+pub fn is_active(id: &str) -> bool {
+    ACTIVE.lock().unwrap_or_else(|e| e.into_inner()).contains_key(id)
+}
+
+/// 下载记录
+pub fn state(id: &str) -> Option<DownloadState> {
+    check_name(id).ok()?;
+    let mut state = read_state(&downloads_dir(), id)?;
+    // 应用在下载中途退出时记录仍为 Downloading
+    if state.status == DownloadStatus::Downloading && !is_active(id) {
+        state.status = DownloadStatus::Paused;
+    }
+    Some(state)
+}
+
+/// 所有下载记录
+pub fn list() -> Vec<DownloadState> {
+    let Ok(entries) = fs::read_dir(downloads_dir()) else {
+        return Vec::new();
+    };
+    let mut states: Vec<DownloadState> = entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.strip_suffix(".json").map(str::to_string))
+        .filter_map(|id| state(&id))
+        .collect();
+    states.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    states
+}
+
+/// 按记录中的地址继续下载
+pub async fn resume(app: &AppHandle, id: &str) -> Result<DownloadState, String> {
+    let previous = state(id).ok_or_else(|| format!("没有下载记录: {}", id))?;
+    run(app, id, &previous.url, &previous.file_name, reqwest::header::HeaderMap::new(), |_, _| {}).await
+}
+
+/// 删除下载记录、未完成的部分和下载好的文件
+pub fn discard(id: &str) -> Result<(), String> {
+    check_name(id)?;
+    if is_active(id) {
+        return Err("该下载正在进行，请先暂停".to_string());
+    }
+    let dir = downloads_dir();
+    let mut paths = vec![part_file(&dir, id), state_file(&dir, id)];
+    if let Some(state) = read_state(&dir, id).filter(|state| check_name(&state.file_name).is_ok()) {
+        paths.push(dir.join(state.file_name));
+    }
+    for path in paths.into_iter().filter(|path| path.exists()) {
+        fs::remove_file(&path).map_err(|e| format!("无法删除 {}: {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resume_offset_and_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = DownloadState {
+            id: "app-update".to_string(),
+            url: "https://example.com/app-1.2.0.exe".to_string(),
+            file_name: "app-1.2.0.exe".to_string(),
+            downloaded: 1000,
+            total: Some(5000),
+            validator: Some("\"abc\"".to_string()),
+            status: DownloadStatus::Paused,
+            error: None,
+            updated_at: "2026-10-16T10:00:00+08:00".to_string(),
+        };
+        write_state(dir.path(), &state).unwrap();
+        let previous = read_state(dir.path(), "app-update");
+        assert_eq!(previous.as_ref(), Some(&state));
+
+        assert_eq!(resume_offset(previous.as_ref(), &state.url, Some(1500)), 1500);
+        assert_eq!(resume_offset(previous.as_ref(), &state.url, Some(9000)), 5000);
+        assert_eq!(resume_offset(previous.as_ref(), &state.url, None), 0);
+        assert_eq!(resume_offset(previous.as_ref(), "https://example.com/app-1.3.0.exe", Some(1500)), 0);
+        assert_eq!(resume_offset(None, &state.url, Some(1500)), 0);
+
+        assert!(check_name("app-update").is_ok());
+        assert!(check_name("../settings.json").is_err() && check_name("..").is_err() && check_name("").is_err());
+    }
+}
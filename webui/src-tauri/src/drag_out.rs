@@ -0,0 +1,185 @@
+//! 把文件从应用拖到桌面或文件管理器
+//!
+//! 用户在页面中开始拖动工作区中的文件（例如智能体生成的产物）时，前端调用 `start_drag_out`：
+//! 文件先复制到 `<临时目录>/dawei-drag/<ID>/`，再从窗口发起系统原生的拖动。拖出去的是
+//! 暂存的副本（不使用硬链接），目标程序移动或修改文件都不影响工作区中的原文件。
+//!
+//! 取消拖动时立即删除暂存目录；放下后目标程序可能还在复制，等待一段时间后再删除。
+//! 应用在放下前退出时留下的暂存目录在下次启动时清理。拖动结束后向发起拖动的窗口
+//! 发送 `drag-out-finished` 事件。
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{Emitter, Manager, Window};
+
+/// 拖动结束事件，内容为 [`DragOutFinished`]
+pub const DRAG_OUT_FINISHED_EVENT: &str = "drag-out-finished";
+
+/// 暂存目录名（位于系统临时目录中）
+const STAGING_DIR: &str = "dawei-drag";
+
+/// 放下后等待多久删除暂存的文件
+const CLEANUP_DELAY: Duration = Duration::from_secs(60);
+
+/// 启动时清理超过该时间的暂存目录
+const STALE_AFTER: Duration = Duration::from_secs(60 * 60);
+
+/// 拖动时显示的图标
+const DRAG_ICON: &[u8] = include_bytes!("../icons/32x32.png");
+
+/// `drag-out-finished` 事件内容
+#[derive(Debug, Clone, Serialize)]
+pub struct DragOutFinished {
+    /// 是否放下（false 表示取消）
+    pub dropped: bool,
+    /// 拖出的文件名
+    pub files: Vec<String>,
+}
+
+fn staging_root() -> PathBuf {
+    std::env::temp_dir().join(STAGING_DIR)
+}
+
+/// 把文件复制到 `dir`（保留文件名），返回暂存后的路径
+fn stage(files: &[PathBuf], dir: &Path) -> Result<Vec<PathBuf>, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("无法创建暂存目录: {}", e))?;
+    let mut staged: Vec<PathBuf> = Vec::new();
+    for file in files {
+        if !file.is_file() {
+            return Err(format!("只能拖出文件: {}", file.display()));
+        }
+        let name = file.file_name().ok_or_else(|| format!("无效的文件名: {}", file.display()))?;
+        let target = dir.join(name);
+        if staged.contains(&target) {
+            return Err(format!("文件重名: {}", name.to_string_lossy()));
+        }
+        fs::copy(file, &target).map_err(|e| format!("无法暂存 {}: {}", file.display(), e))?;
+        staged.push(target);
+    }
+    Ok(staged)
+}
+
+/// 等待 `delay` 后删除暂存目录
+fn remove_later(dir: PathBuf, delay: Duration) {
+    std::thread::spawn(move || {
+        std::thread::sleep(delay);
+        if let Err(e) = fs::remove_dir_all(&dir) {
+            tracing::debug!("Failed to remove drag staging dir {}: {}", dir.display(), e);
+        }
+    });
+}
+
+fn cleanup_stale_in(root: &Path, max_age: Duration) -> usize {
+    let Ok(entries) = fs::read_dir(root) else {
+        return 0;
+    };
+    let now = SystemTime::now();
+    entries
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            let modified = entry.metadata().and_then(|m| m.modified()).unwrap_or(now);
+            now.duration_since(modified).unwrap_or_default() >= max_age
+        })
+        .filter(|entry| fs::remove_dir_all(entry.path()).is_ok())
+        .count()
+}
+
+/// 清理上次运行遗留的暂存目录（启动时在后台调用）
+pub fn cleanup_stale() {
+    std::thread::spawn(|| {
+        let removed = cleanup_stale_in(&staging_root(), STALE_AFTER);
+        if removed > 0 {
+            tracing::info!("Removed {} stale drag staging dirs", removed);
+        }
+    });
+}
+
+/// 在主线程上发起拖动，拖动结束后发送事件并安排删除暂存目录
+fn begin(window: &Window, staged: Vec<PathBuf>, dir: PathBuf) -> Result<(), String> {
+    let (app, label) = (window.app_handle().clone(), window.label().to_string());
+    let files: Vec<String> =
+        staged.iter().filter_map(|path| path.file_name()).map(|name| name.to_string_lossy().to_string()).collect();
+    let on_drop = move |result: drag::DragResult, _: drag::CursorPosition| {
+        let dropped = matches!(result, drag::DragResult::Dropped);
+        let finished = DragOutFinished { dropped, files: files.clone() };
+        if let Err(e) = app.emit_to(label.as_str(), DRAG_OUT_FINISHED_EVENT, finished) {
+            tracing::warn!("Failed to emit drag-out result: {}", e);
+        }
+        remove_later(dir.clone(), if dropped { CLEANUP_DELAY } else { Duration::ZERO });
+    };
+
+    // Linux 上通过 GTK 窗口发起，其他平台使用原生窗口句柄
+    #[cfg(target_os = "linux")]
+    let gtk_window = window.gtk_window().map_err(|e| e.to_string())?;
+    #[cfg(target_os = "linux")]
+    let target = &gtk_window;
+    #[cfg(not(target_os = "linux"))]
+    let target = window;
+    drag::start_drag(target, drag::DragItem::Files(staged), drag::Image::Raw(DRAG_ICON.to_vec()), on_drop, drag::Options::default())
+        .map_err(|e| format!("无法开始拖动: {}", e))
+}
+
+/// 暂存文件并从窗口发起拖动
+pub async fn start(window: &Window, files: Vec<PathBuf>) -> Result<(), String> {
+    if files.is_empty() {
+        return Err("没有要拖出的文件".to_string());
+    }
+    let dir = staging_root().join(uuid::Uuid::new_v4().to_string());
+    let staged = {
+        let dir = dir.clone();
+        tauri::async_runtime::spawn_blocking(move || stage(&files, &dir)).await.map_err(|e| e.to_string())?
+    };
+    let staged = match staged {
+        Ok(staged) => staged,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&dir);
+            return Err(e);
+        }
+    };
+
+    // 拖动必须在主线程上发起（Windows 上直到放下才返回）
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let (handle, staging) = (window.clone(), dir.clone());
+    window
+        .run_on_main_thread(move || {
+            let _ = tx.send(begin(&handle, staged, staging));
+        })
+        .map_err(|e| e.to_string())?;
+    let result = rx.await.map_err(|e| e.to_string()).and_then(|result| result);
+    if result.is_err() {
+        let _ = fs::remove_dir_all(&dir);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stage_and_cleanup() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().join("ws");
+        fs::create_dir_all(workspace.join("out")).unwrap();
+        fs::write(workspace.join("report.md"), "# report").unwrap();
+        fs::write(workspace.join("out/report.md"), "# other").unwrap();
+
+        let staging = dir.path().join("staging");
+        let staged = stage(&[workspace.join("report.md")], &staging.join("1")).unwrap();
+        assert_eq!(fs::read_to_string(&staged[0]).unwrap(), "# report");
+        // 修改或删除暂存的文件不影响原文件
+        fs::write(&staged[0], "changed").unwrap();
+        assert_eq!(fs::read_to_string(workspace.join("report.md")).unwrap(), "# report");
+        fs::remove_file(&staged[0]).unwrap();
+        assert!(workspace.join("report.md").exists());
+
+        assert!(stage(&[workspace.join("report.md"), workspace.join("out/report.md")], &staging.join("2")).is_err());
+        assert!(stage(&[workspace.join("out")], &staging.join("3")).is_err());
+
+        assert_eq!(cleanup_stale_in(&staging, STALE_AFTER), 0);
+        assert_eq!(cleanup_stale_in(&staging, Duration::ZERO), 3);
+        assert_eq!(cleanup_stale_in(&dir.path().join("missing"), Duration::ZERO), 0);
+    }
+}
@@ -0,0 +1,144 @@
+//! 在编辑器中打开文件
+//!
+//! 按 VS Code、Cursor、Sublime Text 的顺序查找已安装的编辑器：先查 PATH 中的命令行工具，
+//! 再查各平台的默认安装位置（macOS 的 .app 包、Windows 的用户安装目录，这些位置的命令行工具
+//! 通常不在 PATH 中）。指定行号时跳转到该行。都没有安装时用系统默认程序打开（不支持跳转）。
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// 支持的编辑器
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct EditorKind {
+    id: &'static str,
+    name: &'static str,
+    /// PATH 中的命令名（Windows 上为 .cmd 脚本）
+    command: &'static str,
+    /// `path:line` 前需要加 `--goto`
+    goto_flag: bool,
+}
+
+const EDITORS: [EditorKind; 3] = [
+    EditorKind { id: "code", name: "VS Code", command: if cfg!(windows) { "code.cmd" } else { "code" }, goto_flag: true },
+    EditorKind { id: "cursor", name: "Cursor", command: if cfg!(windows) { "cursor.cmd" } else { "cursor" }, goto_flag: true },
+    EditorKind { id: "subl", name: "Sublime Text", command: if cfg!(windows) { "subl.exe" } else { "subl" }, goto_flag: false },
+];
+
+/// 已安装的编辑器
+#[derive(Debug, Clone, Serialize)]
+pub struct Editor {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub path: PathBuf,
+}
+
+/// 默认安装位置中的命令行工具
+fn default_locations(kind: &EditorKind) -> Vec<PathBuf> {
+    #[cfg(target_os = "macos")]
+    {
+        let app = match kind.id {
+            "code" => "Visual Studio Code.app/Contents/Resources/app/bin/code",
+            "cursor" => "Cursor.app/Contents/Resources/app/bin/cursor",
+            _ => "Sublime Text.app/Contents/SharedSupport/bin/subl",
+        };
+        let mut roots = vec![PathBuf::from("/Applications")];
+        roots.extend(dirs::home_dir().map(|home| home.join("Applications")));
+        roots.into_iter().map(|root| root.join(app)).collect()
+    }
+    #[cfg(windows)]
+    {
+        let relative = match kind.id {
+            "code" => r"Programs\Microsoft VS Code\bin\code.cmd",
+            "cursor" => r"Programs\cursor\resources\app\bin\cursor.cmd",
+            _ => return ["ProgramFiles", "ProgramFiles(x86)"]
+                .iter()
+                .filter_map(|var| std::env::var_os(var))
+                .map(|dir| PathBuf::from(dir).join(r"Sublime Text\subl.exe"))
+                .collect(),
+        };
+        std::env::var_os("LOCALAPPDATA").map(|dir| PathBuf::from(dir).join(relative)).into_iter().collect()
+    }
+    #[cfg(not(any(target_os = "macos", windows)))]
+    {
+        let _ = kind;
+        Vec::new()
+    }
+}
+
+/// 在 PATH 中查找命令
+fn find_in_path(command: &str, path_var: Option<&std::ffi::OsStr>) -> Option<PathBuf> {
+    std::env::split_paths(path_var?).map(|dir| dir.join(command)).find(|path| path.is_file())
+}
+
+/// 已安装的编辑器，按优先顺序排列
+pub fn detect() -> Vec<Editor> {
+    let path_var = std::env::var_os("PATH");
+    EDITORS
+        .iter()
+        .filter_map(|kind| {
+            let path = find_in_path(kind.command, path_var.as_deref())
+                .or_else(|| default_locations(kind).into_iter().find(|path| path.is_file()))?;
+            Some(Editor { id: kind.id, name: kind.name, path })
+        })
+        .collect()
+}
+
+/// 编辑器的命令行参数
+fn editor_args(kind: &EditorKind, path: &Path, line: Option<u32>) -> Vec<String> {
+    let target = path.display().to_string();
+    match line {
+        Some(line) if kind.goto_flag => vec!["--goto".to_string(), format!("{}:{}", target, line)],
+        Some(line) => vec![format!("{}:{}", target, line)],
+        None => vec![target],
+    }
+}
+
+/// 在编辑器中打开文件，`editor` 为编辑器 ID（默认为第一个已安装的），返回使用的编辑器名称
+pub fn open(path: &Path, line: Option<u32>, editor: Option<&str>) -> Result<String, String> {
+    let path = std::path::absolute(path).map_err(|e| format!("无效的路径 {}: {}", path.display(), e))?;
+    if !path.exists() {
+        return Err(format!("文件不存在: {}", path.display()));
+    }
+    let editors = detect();
+    let chosen = match editor {
+        Some(id) => Some(editors.iter().find(|e| e.id == id).ok_or_else(|| format!("未安装编辑器: {}", id))?),
+        None => editors.first(),
+    };
+    let Some(chosen) = chosen else {
+        crate::open_path(&path)?;
+        return Ok("系统默认程序".to_string());
+    };
+
+    let kind = EDITORS.iter().find(|kind| kind.id == chosen.id).expect("detected editor is known");
+    let mut command = Command::new(&chosen.path);
+    command.args(editor_args(kind, &path, line));
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    command.spawn().map_err(|e| format!("无法启动 {}: {}", chosen.name, e))?;
+    tracing::info!("Opened {} in {}", path.display(), chosen.name);
+    Ok(chosen.name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_args_and_path_lookup() {
+        let path = Path::new("/work/src/main.py");
+        assert_eq!(editor_args(&EDITORS[0], path, Some(12)), ["--goto", "/work/src/main.py:12"]);
+        assert_eq!(editor_args(&EDITORS[2], path, Some(12)), ["/work/src/main.py:12"]);
+        assert_eq!(editor_args(&EDITORS[1], path, None), ["/work/src/main.py"]);
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("subl"), "").unwrap();
+        let path_var = std::env::join_paths([Path::new("/nonexistent"), dir.path()]).unwrap();
+        assert_eq!(find_in_path("subl", Some(&path_var)), Some(dir.path().join("subl")));
+        assert_eq!(find_in_path("code", Some(&path_var)), None);
+    }
+}
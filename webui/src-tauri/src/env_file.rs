@@ -0,0 +1,259 @@
+//! .env 文件读写
+//!
+//! 应用管理两个 .env：可执行文件目录下的 .env（记录 Python、uv 路径，后端启动时加载）
+//! 和 DAWEI_HOME/.env（用户配置，例如 API Key）。所有修改都经过本模块，
+//! 只改动目标变量所在的行，保留注释、空行和其他变量的顺序。
+//! 读取给前端时，名称像密钥的变量（包含 KEY、TOKEN、SECRET 等）只返回掩码。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 名称包含这些词的变量视为密钥
+const SECRET_MARKERS: [&str; 5] = ["KEY", "TOKEN", "SECRET", "PASSWORD", "CREDENTIAL"];
+
+/// .env 文件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EnvTarget {
+    /// 可执行文件目录下由应用维护的 .env
+    App,
+    /// DAWEI_HOME/.env
+    Home,
+}
+
+impl EnvTarget {
+    pub fn path(self) -> PathBuf {
+        match self {
+            EnvTarget::App => std::env::current_exe()
+                .ok()
+                .and_then(|exe| exe.parent().map(Path::to_path_buf))
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join(".env"),
+            EnvTarget::Home => crate::get_dawei_home().join(".env"),
+        }
+    }
+}
+
+/// 返回给前端的变量
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EnvEntry {
+    pub key: String,
+    /// 密钥只包含掩码
+    pub value: String,
+    pub masked: bool,
+}
+
+/// 变量名是否像密钥
+pub fn is_secret(key: &str) -> bool {
+    let upper = key.to_uppercase();
+    SECRET_MARKERS.iter().any(|marker| upper.contains(marker))
+}
+
+/// 密钥掩码：较长的值保留最后 4 个字符，便于用户辨认
+fn mask(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() > 8 {
+        format!("****{}", chars[chars.len() - 4..].iter().collect::<String>())
+    } else {
+        "****".to_string()
+    }
+}
+
+fn validate_key(key: &str) -> Result<(), String> {
+    let mut chars = key.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("环境变量名无效: {:?}", key))
+    }
+}
+
+/// 值中的换行会变成额外的赋值
+fn validate_value(value: &str) -> Result<(), String> {
+    if value.contains(['\n', '\r']) {
+        return Err("环境变量的值不能包含换行".to_string());
+    }
+    Ok(())
+}
+
+/// 解析一行赋值，返回变量名和值（注释、空行返回 None）
+fn parse_line(line: &str) -> Option<(String, String)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let line = line.strip_prefix("export ").unwrap_or(line);
+    let (key, value) = line.split_once('=')?;
+    Some((key.trim().to_string(), unquote(value.trim())))
+}
+
+fn unquote(value: &str) -> String {
+    if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        value[1..value.len() - 1].replace("\\\"", "\"").replace("\\\\", "\\")
+    } else if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+        value[1..value.len() - 1].to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// 包含空白、引号或 `#` 的值加双引号
+fn quote(value: &str) -> String {
+    if value.chars().any(|c| c.is_whitespace() || matches!(c, '"' | '\'' | '#' | '\\')) {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// 解析 .env 内容，忽略空行和注释
+pub fn parse(content: &str) -> Vec<(String, String)> {
+    content.lines().filter_map(parse_line).collect()
+}
+
+/// 设置变量：替换已有的行（重复定义时删除多余的行），否则追加到末尾
+fn set_in(content: &str, key: &str, value: &str) -> String {
+    let assignment = format!("{}={}", key, quote(value));
+    let mut replaced = false;
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        if parse_line(line).is_some_and(|(k, _)| k == key) {
+            if !replaced {
+                lines.push(assignment.clone());
+                replaced = true;
+            }
+        } else {
+            lines.push(line.to_string());
+        }
+    }
+    if !replaced {
+        lines.push(assignment);
+    }
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+/// 删除变量，返回新内容和是否删除了变量
+fn unset_in(content: &str, key: &str) -> (String, bool) {
+    let mut removed = false;
+    let kept: String = content
+        .lines()
+        .filter(|line| {
+            let matched = parse_line(line).is_some_and(|(k, _)| k == key);
+            removed |= matched;
+            !matched
+        })
+        .map(|line| format!("{}\n", line))
+        .collect();
+    (kept, removed)
+}
+
+fn read(target: EnvTarget) -> String {
+    fs::read_to_string(target.path()).unwrap_or_default()
+}
+
+fn write(target: EnvTarget, content: &str) -> Result<(), String> {
+    let path = target.path();
+    crate::atomic_file::write_atomic(&path, content.as_bytes()).map_err(|e| format!("写入 {} 失败: {}", path.display(), e))
+}
+
+/// 读取全部变量（原始值，仅供应用内部使用）
+pub fn read_vars(target: EnvTarget) -> Vec<(String, String)> {
+    parse(&read(target))
+}
+
+/// 读取全部变量（密钥已掩码）
+pub fn list(target: EnvTarget) -> Vec<EnvEntry> {
+    read_vars(target)
+        .into_iter()
+        .map(|(key, value)| {
+            let masked = is_secret(&key);
+            let value = if masked { mask(&value) } else { value };
+            EnvEntry { key, value, masked }
+        })
+        .collect()
+}
+
+/// 读取单个变量（密钥已掩码）
+pub fn get(target: EnvTarget, key: &str) -> Option<EnvEntry> {
+    list(target).into_iter().rev().find(|entry| entry.key == key)
+}
+
+/// 设置变量
+pub fn set(target: EnvTarget, key: &str, value: &str) -> Result<(), String> {
+    validate_key(key)?;
+    validate_value(value)?;
+    write(target, &set_in(&read(target), key, value))?;
+    crate::breadcrumbs::record("settings", format!("Env var {} set in {:?} .env", key, target));
+    Ok(())
+}
+
+/// 删除变量，返回变量是否存在
+pub fn unset(target: EnvTarget, key: &str) -> Result<bool, String> {
+    validate_key(key)?;
+    let (content, removed) = unset_in(&read(target), key);
+    if removed {
+        write(target, &content)?;
+        crate::breadcrumbs::record("settings", format!("Env var {} removed from {:?} .env", key, target));
+    }
+    Ok(removed)
+}
+
+/// 用给定变量替换文件中的全部变量（注释和空行保留）
+pub fn replace_all(target: EnvTarget, vars: &[(String, String)]) -> Result<(), String> {
+    for (key, value) in vars {
+        validate_key(key)?;
+        validate_value(value).map_err(|e| format!("{}: {}", key, e))?;
+    }
+
+    let mut content = read(target);
+    for (key, _) in parse(&content) {
+        if !vars.iter().any(|(k, _)| *k == key) {
+            content = unset_in(&content, &key).0;
+        }
+    }
+    for (key, value) in vars {
+        content = set_in(&content, key, value);
+    }
+    write(target, &content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_and_unset_preserve_layout() {
+        let content = "# LLM\nexport OPENAI_API_KEY=sk-old\n\nDAWEI_UV_PATH=/usr/bin/uv\n";
+
+        let updated = set_in(content, "OPENAI_API_KEY", "sk new");
+        assert_eq!(updated, "# LLM\nOPENAI_API_KEY=\"sk new\"\n\nDAWEI_UV_PATH=/usr/bin/uv\n");
+        assert_eq!(parse(&updated)[0], ("OPENAI_API_KEY".to_string(), "sk new".to_string()));
+
+        let appended = set_in(&updated, "PROXY", "http://127.0.0.1:7890");
+        assert!(appended.ends_with("PROXY=http://127.0.0.1:7890\n"));
+
+        let (removed, found) = unset_in(&appended, "DAWEI_UV_PATH");
+        assert!(found);
+        assert_eq!(parse(&removed).len(), 2);
+        assert!(removed.starts_with("# LLM\n"));
+    }
+
+    #[test]
+    fn test_secret_masking() {
+        assert!(is_secret("openai_api_key"));
+        assert!(is_secret("GITHUB_TOKEN"));
+        assert!(!is_secret("DAWEI_UV_PATH"));
+        assert_eq!(mask("sk-1234567890abcd"), "****abcd");
+        assert_eq!(mask("short"), "****");
+        assert!(validate_key("1ABC").is_err());
+        assert!(validate_key("MY_VAR").is_ok());
+
+        // 导入的值不能借换行追加赋值（校验在读写文件之前）
+        assert!(validate_value("sk-1\nDAWEI_UV_PATH=/tmp/evil").is_err());
+        let vars = [("OPENAI_API_KEY".to_string(), "sk-1\rPATH=/tmp".to_string())];
+        assert!(replace_all(EnvTarget::Home, &vars).unwrap_err().contains("换行"));
+    }
+}
@@ -0,0 +1,165 @@
+//! 文件和目录选择对话框
+//!
+//! 统一创建 rfd 对话框：从指定的位置（收藏的目录，见 [`crate::bookmarks`]）或上次选择的位置
+//! 打开（保存在 DAWEI_HOME/file_dialog.json，该位置已不存在时回到用户主目录），按前端传入的扩展名过滤。
+//! 选择或保存后记住所选项所在的目录，下次打开任一对话框时从该目录开始。
+//! 返回的路径在 Windows 上经过规范化（见 [`crate::path_normalize`]）。
+
+use crate::atomic_file::write_json_atomic;
+use rfd::AsyncFileDialog;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// 状态文件名
+const STATE_FILE: &str = "file_dialog.json";
+
+/// 读写状态文件时加锁
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// 扩展名过滤条件，例如 `{ "name": "图片", "extensions": ["png", "jpg"] }`
+#[derive(Debug, Clone, Deserialize)]
+pub struct FileFilter {
+    pub name: String,
+    /// 不含点号的扩展名
+    pub extensions: Vec<String>,
+}
+
+impl FileFilter {
+    pub fn new(name: &str, extensions: &[&str]) -> Self {
+        Self { name: name.to_string(), extensions: extensions.iter().map(|ext| ext.to_string()).collect() }
+    }
+}
+
+/// 对话框状态
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct DialogState {
+    /// 上次选择的位置
+    last_dir: Option<PathBuf>,
+}
+
+fn state_file() -> PathBuf {
+    crate::get_dawei_home().join(STATE_FILE)
+}
+
+fn read_state(file: &Path) -> DialogState {
+    fs::read_to_string(file).ok().and_then(|content| serde_json::from_str(&content).ok()).unwrap_or_default()
+}
+
+fn write_state(file: &Path, state: &DialogState) -> Result<(), String> {
+    write_json_atomic(file, state).map_err(|e| e.to_string())
+}
+
+/// 对话框的初始目录：上次选择的位置（仍存在时）或用户主目录
+fn start_dir_in(file: &Path) -> PathBuf {
+    read_state(file)
+        .last_dir
+        .filter(|dir| dir.is_dir())
+        .or_else(dirs::home_dir)
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// 记住所选项所在的目录
+fn remember_in(file: &Path, picked: &Path) {
+    let Some(dir) = picked.parent().filter(|dir| !dir.as_os_str().is_empty()) else {
+        return;
+    };
+    let mut state = read_state(file);
+    if state.last_dir.as_deref() == Some(dir) {
+        return;
+    }
+    state.last_dir = Some(dir.to_path_buf());
+    if let Err(e) = write_state(file, &state) {
+        tracing::warn!("Failed to save {}: {}", STATE_FILE, e);
+    }
+}
+
+fn remember(picked: &Path) {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    remember_in(&state_file(), picked);
+}
+
+/// 从 `start` 或上次的位置打开的对话框
+fn dialog(title: &str, filters: &[FileFilter], start: Option<&Path>) -> AsyncFileDialog {
+    let start = match start {
+        Some(start) => start.to_path_buf(),
+        None => {
+            let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            start_dir_in(&state_file())
+        }
+    };
+    filters
+        .iter()
+        .filter(|filter| !filter.extensions.is_empty())
+        .fold(AsyncFileDialog::new().set_title(title).set_directory(start), |dialog, filter| {
+            dialog.add_filter(&filter.name, &filter.extensions)
+        })
+}
+
+/// 规范化所选路径，记住第一个所选项所在的目录
+fn picked(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let paths: Vec<PathBuf> = paths.iter().map(|path| crate::path_normalize::normalize(path)).collect();
+    if let Some(first) = paths.first() {
+        remember(first);
+    }
+    paths
+}
+
+/// 选择一个目录，`start` 为初始目录（默认为上次的位置）
+pub async fn pick_directory(title: &str, start: Option<&Path>) -> Option<PathBuf> {
+    let folder = dialog(title, &[], start).pick_folder().await?;
+    let folder = picked(vec![folder.path().to_path_buf()]).pop()?;
+    // 用户选择的目录可以作为工作区访问
+    crate::workspace_files::trust(&folder);
+    Some(folder)
+}
+
+/// 选择多个目录
+pub async fn pick_directories(title: &str, start: Option<&Path>) -> Vec<PathBuf> {
+    let folders = dialog(title, &[], start).pick_folders().await.unwrap_or_default();
+    let folders = picked(folders.iter().map(|folder| folder.path().to_path_buf()).collect());
+    folders.iter().for_each(|folder| crate::workspace_files::trust(folder));
+    folders
+}
+
+/// 选择一个文件
+pub async fn pick_file(title: &str, filters: &[FileFilter], start: Option<&Path>) -> Option<PathBuf> {
+    let file = dialog(title, filters, start).pick_file().await?;
+    picked(vec![file.path().to_path_buf()]).pop()
+}
+
+/// 选择多个文件
+pub async fn pick_files(title: &str, filters: &[FileFilter], start: Option<&Path>) -> Vec<PathBuf> {
+    let files = dialog(title, filters, start).pick_files().await.unwrap_or_default();
+    picked(files.iter().map(|file| file.path().to_path_buf()).collect())
+}
+
+/// 选择保存位置，`default_name` 为默认文件名
+pub async fn save_file(title: &str, default_name: &str, filters: &[FileFilter], start: Option<&Path>) -> Option<PathBuf> {
+    let file = dialog(title, filters, start).set_file_name(default_name).save_file().await?;
+    picked(vec![file.path().to_path_buf()]).pop()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remember_last_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(STATE_FILE);
+        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+        assert_eq!(start_dir_in(&file), home);
+
+        let projects = dir.path().join("projects");
+        fs::create_dir(&projects).unwrap();
+        remember_in(&file, &projects.join("report.md"));
+        assert_eq!(start_dir_in(&file), projects);
+
+        // 上次的位置已删除时回到主目录
+        fs::remove_dir(&projects).unwrap();
+        assert_eq!(start_dir_in(&file), home);
+    }
+}
@@ -0,0 +1,180 @@
+//! 拖放文件和文件夹
+//!
+//! 拖到窗口上的文件夹作为工作区在新窗口中打开；文件先校验（存在、是普通文件、大小限制），
+//! 拖到工作区窗口时作为附件上传到该工作区，拖到主窗口时由前端决定上传到哪个工作区
+//! （`upload_dropped_files` 命令）。处理完成后向目标窗口发送 `files-dropped` 事件。
+
+use serde::Serialize;
+use std::path::PathBuf;
+use tauri::{AppHandle, DragDropEvent, Emitter, Manager, Window, WindowEvent};
+
+/// 拖放处理完成后发送给目标窗口的事件
+pub const FILES_DROPPED_EVENT: &str = "files-dropped";
+
+/// 单个附件的大小上限
+const MAX_FILE_SIZE: u64 = 50 * 1024 * 1024;
+
+/// 拖放的文件
+#[derive(Debug, Clone, Serialize)]
+pub struct DroppedFile {
+    pub path: PathBuf,
+    pub name: String,
+    pub size: u64,
+    /// 已上传到工作区时为工作区内的相对路径
+    pub uploaded: Option<String>,
+    /// 上传失败的原因
+    pub error: Option<String>,
+}
+
+/// 被拒绝的路径
+#[derive(Debug, Clone, Serialize)]
+pub struct RejectedPath {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+/// `files-dropped` 事件内容
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FilesDropped {
+    /// 作为工作区打开的文件夹
+    pub workspaces: Vec<crate::workspace_window::WorkspaceWindow>,
+    pub files: Vec<DroppedFile>,
+    pub rejected: Vec<RejectedPath>,
+    /// 上传到的工作区 ID（拖到主窗口时为 None，文件未上传）
+    pub workspace_id: Option<String>,
+    /// 拖放位置（窗口内的逻辑坐标）
+    pub position: (f64, f64),
+}
+
+/// 拖放路径的分类
+#[derive(Debug, Default)]
+struct Classified {
+    folders: Vec<PathBuf>,
+    files: Vec<DroppedFile>,
+    rejected: Vec<RejectedPath>,
+}
+
+/// 校验并区分文件夹和文件
+fn classify(paths: &[PathBuf]) -> Classified {
+    let mut result = Classified::default();
+    for path in paths {
+        let reject = |reason: String| RejectedPath { path: path.clone(), reason };
+        let (path, metadata) = match path.canonicalize().and_then(|p| std::fs::metadata(&p).map(|m| (p, m))) {
+            Ok(found) => found,
+            Err(e) => {
+                result.rejected.push(reject(format!("无法访问: {}", e)));
+                continue;
+            }
+        };
+        if metadata.is_dir() {
+            result.folders.push(path);
+        } else if !metadata.is_file() {
+            result.rejected.push(reject("不是普通文件".to_string()));
+        } else if metadata.len() > MAX_FILE_SIZE {
+            result.rejected.push(reject(format!("文件超过 {} MB", MAX_FILE_SIZE / 1024 / 1024)));
+        } else {
+            let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            result.files.push(DroppedFile { path, name, size: metadata.len(), uploaded: None, error: None });
+        }
+    }
+    result
+}
+
+/// 上传单个文件到工作区根目录，返回工作区内的相对路径
+async fn upload(client: &reqwest::Client, workspace_id: &str, file: &DroppedFile) -> Result<String, String> {
+    let data = tokio::fs::read(&file.path).await.map_err(|e| format!("无法读取文件: {}", e))?;
+    let form = reqwest::multipart::Form::new()
+        .text("parent_path", "")
+        .part("file", reqwest::multipart::Part::bytes(data).file_name(file.name.clone()));
+    let url = format!(
+        "http://127.0.0.1:{}/api/workspaces/{}/files/upload",
+        crate::backend_profile::port(),
+        workspace_id
+    );
+    let response = client.post(url).multipart(form).send().await.map_err(|e| format!("后端不可用: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("上传失败: HTTP {}", response.status()));
+    }
+    let body: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+    Ok(body["path"].as_str().unwrap_or(&file.name).to_string())
+}
+
+/// 把文件作为附件上传到工作区，结果写入每个文件的 `uploaded` / `error`
+pub async fn upload_files(workspace_id: &str, files: &mut [DroppedFile]) {
+    let client = reqwest::Client::new();
+    for file in files.iter_mut() {
+        match upload(&client, workspace_id, file).await {
+            Ok(path) => file.uploaded = Some(path),
+            Err(e) => file.error = Some(e),
+        }
+    }
+}
+
+/// 校验路径后上传到工作区（前端处理拖到主窗口的文件时使用）
+pub async fn upload_paths(workspace_id: &str, paths: &[PathBuf]) -> FilesDropped {
+    let Classified { files, mut rejected, folders } = classify(paths);
+    rejected.extend(folders.into_iter().map(|path| RejectedPath { path, reason: "不能上传文件夹".to_string() }));
+    let mut dropped = FilesDropped { files, rejected, workspace_id: Some(workspace_id.to_string()), ..Default::default() };
+    upload_files(workspace_id, &mut dropped.files).await;
+    dropped
+}
+
+fn on_drop(app: &AppHandle, label: String, paths: &[PathBuf], position: (f64, f64)) {
+    let Classified { folders, files, mut rejected } = classify(paths);
+    crate::breadcrumbs::record("window", format!("{} path(s) dropped on {}", paths.len(), label));
+
+    let mut workspaces = Vec::new();
+    for folder in folders {
+        match crate::workspace_window::open(app, &folder) {
+            Ok(info) => workspaces.push(info),
+            Err(reason) => rejected.push(RejectedPath { path: folder, reason }),
+        }
+    }
+
+    let workspace_id = crate::workspace_window::get(&label).and_then(|info| info.id);
+    let mut dropped = FilesDropped { workspaces, files, rejected, workspace_id, position };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Some(workspace_id) = dropped.workspace_id.clone() {
+            upload_files(&workspace_id, &mut dropped.files).await;
+        }
+        if let Err(e) = app.emit_to(&label, FILES_DROPPED_EVENT, dropped) {
+            tracing::warn!("Failed to emit files-dropped: {}", e);
+        }
+    });
+}
+
+/// 处理拖放到主窗口和工作区窗口的文件
+pub fn on_window_event(window: &Window, event: &WindowEvent) {
+    let WindowEvent::DragDrop(DragDropEvent::Drop { paths, position }) = event else {
+        return;
+    };
+    let label = window.label();
+    if label != "main" && crate::workspace_window::get(label).is_none() {
+        return;
+    }
+    let scale = window.scale_factor().unwrap_or(1.0);
+    let position = position.to_logical::<f64>(scale);
+    on_drop(window.app_handle(), label.to_string(), paths, (position.x, position.y));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("notes.txt");
+        std::fs::write(&file, "hello").unwrap();
+        let missing = dir.path().join("missing.txt");
+
+        let result = classify(&[dir.path().to_path_buf(), file.clone(), missing.clone()]);
+        assert_eq!(result.folders, vec![dir.path().canonicalize().unwrap()]);
+        assert_eq!(result.files.len(), 1);
+        assert_eq!(result.files[0].name, "notes.txt");
+        assert_eq!(result.files[0].size, 5);
+        assert_eq!(result.rejected.len(), 1);
+        assert_eq!(result.rejected[0].path, missing);
+    }
+}
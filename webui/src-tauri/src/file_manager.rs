@@ -0,0 +1,90 @@
+//! 在系统文件管理器中显示文件
+//!
+//! 打开文件所在的目录并选中该文件（目录则在其上级目录中选中），各平台的处理：
+//! - Windows：`explorer /select,"路径"`。explorer 自己解析命令行，参数需原样传入，
+//!   路径必须使用反斜杠且不能带 `\\?\` 前缀（canonicalize 的结果会带）；explorer 成功时也返回非零退出码，因此不检查退出码。
+//! - macOS：`open -R`。
+//! - Linux：通过 D-Bus 调用 freedesktop 的 `org.freedesktop.FileManager1.ShowItems`
+//!   （Nautilus、Dolphin、Nemo 等支持），参数为百分号编码的 file URI；
+//!   没有实现该接口的文件管理器时打开所在目录。
+
+use std::path::{Path, PathBuf};
+
+/// 显示前规范化路径：必须存在，转为绝对路径
+fn normalize(path: &Path) -> Result<PathBuf, String> {
+    let path = std::path::absolute(path).map_err(|e| format!("无效的路径 {}: {}", path.display(), e))?;
+    if !path.exists() {
+        return Err(format!("文件不存在: {}", path.display()));
+    }
+    Ok(path)
+}
+
+/// 百分号编码的 file URI（保留 `/` 和 RFC 3986 的非保留字符）
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn file_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+/// 在文件管理器中显示并选中 `path`
+pub fn reveal(path: &Path) -> Result<(), String> {
+    let path = normalize(path)?;
+
+    #[cfg(target_os = "windows")]
+    let result = {
+        use std::os::windows::process::CommandExt;
+        let display = path.to_string_lossy().replace('/', "\\");
+        // \\?\UNC\server\share 还原为 \\server\share
+        let display = match display.strip_prefix(r"\\?\UNC\") {
+            Some(unc) => format!(r"\\{}", unc),
+            None => display.strip_prefix(r"\\?\").unwrap_or(&display).to_string(),
+        };
+        std::process::Command::new("explorer").raw_arg(format!("/select,\"{}\"", display)).spawn().map(|_| ())
+    };
+    #[cfg(target_os = "macos")]
+    let result = std::process::Command::new("open").arg("-R").arg(&path).spawn().map(|_| ());
+    #[cfg(target_os = "linux")]
+    let result: std::io::Result<()> = {
+        // --print-reply 等待文件管理器响应，没有实现该接口时返回非零退出码
+        let shown = std::process::Command::new("dbus-send")
+            .args([
+                "--session",
+                "--print-reply",
+                "--reply-timeout=3000",
+                "--dest=org.freedesktop.FileManager1",
+                "/org/freedesktop/FileManager1",
+                "org.freedesktop.FileManager1.ShowItems",
+                &format!("array:string:{}", file_uri(&path)),
+                "string:",
+            ])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success());
+        if shown {
+            Ok(())
+        } else {
+            return crate::open_path(path.parent().unwrap_or(&path));
+        }
+    };
+
+    result.map_err(|e| format!("无法显示 {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_uri_and_normalize() {
+        assert_eq!(file_uri(Path::new("/home/user/a b/报告#1.md")), "file:///home/user/a%20b/%E6%8A%A5%E5%91%8A%231.md");
+        assert_eq!(file_uri(Path::new("/tmp/x_y-z.txt")), "file:///tmp/x_y-z.txt");
+        assert!(normalize(Path::new("/nonexistent/dawei-reveal-test")).is_err());
+    }
+}
@@ -0,0 +1,157 @@
+//! 首次运行状态
+//!
+//! 状态保存在 DAWEI_HOME/first_run.json：引导是否完成，以及已完成的引导步骤。
+//! 引导中途退出后，下次启动可以从未完成的步骤继续。
+//! 从没有该文件的旧版本升级时，如果 DAWEI_HOME 中已有设置或工作区，视为已完成引导。
+
+use crate::atomic_file::write_json_atomic;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 状态文件名
+const STATE_FILE: &str = "first_run.json";
+
+/// 存在任一文件说明不是首次运行（旧版本升级）
+const EXISTING_DATA: [&str; 2] = ["settings.json", "workspaces.json"];
+
+/// 首次运行状态
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FirstRunState {
+    /// 引导是否已完成
+    pub completed: bool,
+    /// 已完成的引导步骤（按完成顺序）
+    pub steps: Vec<String>,
+    pub started_at: Option<String>,
+    pub completed_at: Option<String>,
+}
+
+fn state_file() -> PathBuf {
+    crate::get_dawei_home().join(STATE_FILE)
+}
+
+fn read_state(path: &Path) -> Option<FirstRunState> {
+    let content = fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(state) => Some(state),
+        Err(e) => {
+            tracing::warn!("Invalid {}: {}", STATE_FILE, e);
+            None
+        }
+    }
+}
+
+fn write_state(path: &Path, state: &FirstRunState) -> Result<(), String> {
+    write_json_atomic(path, state).map_err(|e| format!("保存首次运行状态失败: {}", e))
+}
+
+/// 启动时创建状态文件（必须在写入 DAWEI_HOME 的其他模块之前调用）
+fn init_in(home: &Path) -> FirstRunState {
+    let path = home.join(STATE_FILE);
+    if let Some(state) = read_state(&path) {
+        return state;
+    }
+
+    let now = chrono::Local::now().to_rfc3339();
+    let state = if EXISTING_DATA.iter().any(|name| home.join(name).exists()) {
+        FirstRunState { completed: true, completed_at: Some(now), ..Default::default() }
+    } else {
+        FirstRunState { started_at: Some(now), ..Default::default() }
+    };
+    if let Err(e) = write_state(&path, &state) {
+        tracing::warn!("{}", e);
+    }
+    state
+}
+
+/// 启动时调用
+pub fn init() {
+    let state = init_in(&crate::get_dawei_home());
+    if !state.completed {
+        tracing::info!("First run: onboarding not completed ({} steps done)", state.steps.len());
+    }
+}
+
+/// 当前状态
+pub fn state() -> FirstRunState {
+    read_state(&state_file()).unwrap_or_default()
+}
+
+/// 是否首次运行（引导尚未完成）
+pub fn is_first_run() -> bool {
+    !state().completed
+}
+
+fn complete_step_in(path: &Path, step: &str) -> Result<FirstRunState, String> {
+    let step = step.trim();
+    if step.is_empty() {
+        return Err("引导步骤名称不能为空".to_string());
+    }
+
+    let mut state = read_state(path).unwrap_or_default();
+    if !state.steps.iter().any(|s| s == step) {
+        state.steps.push(step.to_string());
+        write_state(path, &state)?;
+    }
+    Ok(state)
+}
+
+fn finish_in(path: &Path) -> Result<FirstRunState, String> {
+    let mut state = read_state(path).unwrap_or_default();
+    if !state.completed {
+        state.completed = true;
+        state.completed_at = Some(chrono::Local::now().to_rfc3339());
+        write_state(path, &state)?;
+    }
+    Ok(state)
+}
+
+/// 记录完成的引导步骤（重复记录会被忽略）
+pub fn complete_step(step: &str) -> Result<FirstRunState, String> {
+    complete_step_in(&state_file(), step)
+}
+
+/// 标记引导已完成
+pub fn finish() -> Result<FirstRunState, String> {
+    let state = finish_in(&state_file())?;
+    crate::breadcrumbs::record("settings", "Onboarding completed");
+    Ok(state)
+}
+
+/// 重新开始引导（恢复出厂设置时调用）
+pub fn reset() -> Result<(), String> {
+    let state = FirstRunState { started_at: Some(chrono::Local::now().to_rfc3339()), ..Default::default() };
+    write_state(&state_file(), &state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_run_and_resume() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path();
+        let path = home.join(STATE_FILE);
+
+        assert!(!init_in(home).completed);
+        complete_step_in(&path, "llm").unwrap();
+        complete_step_in(&path, "workspace").unwrap();
+        let state = complete_step_in(&path, "llm").unwrap();
+        assert_eq!(state.steps, ["llm", "workspace"]);
+        assert!(complete_step_in(&path, " ").is_err());
+
+        // 中途退出后再次启动，保留已完成的步骤
+        assert_eq!(init_in(home).steps, ["llm", "workspace"]);
+        assert!(finish_in(&path).unwrap().completed);
+        assert!(init_in(home).completed);
+    }
+
+    #[test]
+    fn test_existing_install_is_not_first_run() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("settings.json"), "{}").unwrap();
+        assert!(init_in(dir.path()).completed);
+    }
+}
@@ -0,0 +1,179 @@
+//! 卡死检测模块
+//!
+//! 后台线程定期向主线程（事件循环）投递心跳任务，如果主线程长时间没有执行心跳，
+//! 说明界面已经卡死，此时写入 `kind: "hang"` 的崩溃报告。
+//! 有崩溃监控进程时同时请求写入 minidump，记录卡死时包括主线程在内所有线程的真实堆栈。
+//! 用于诊断"界面无响应但从不 panic"的问题。
+
+use crate::breadcrumbs;
+use crate::crash_handler::{CrashKind, CrashReport};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+/// 心跳间隔
+const PING_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 卡死判定阈值，来自设置中的 hang_timeout_secs，可通过 DAWEI_HANG_TIMEOUT_SECS 覆盖，0 表示关闭检测
+fn hang_timeout() -> Option<Duration> {
+    let secs = std::env::var("DAWEI_HANG_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or_else(|| crate::settings::current().hang_timeout_secs);
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// 卡死状态的变化
+#[derive(Debug, PartialEq)]
+enum HangEvent {
+    /// 主线程超过阈值没有响应，内容为已无响应的时长
+    Hung(Duration),
+    /// 卡死后主线程恢复响应
+    Recovered,
+}
+
+/// 根据主线程最近一次响应心跳的时间判断卡死，同一次卡死只报告一次
+struct HangDetector {
+    timeout: Duration,
+    hang_reported: bool,
+}
+
+impl HangDetector {
+    fn new(timeout: Duration) -> HangDetector {
+        HangDetector { timeout, hang_reported: false }
+    }
+
+    /// `last_pong` 和 `now` 都是相对检测开始的时间，还没有过心跳时 `last_pong` 为 None
+    fn observe(&mut self, last_pong: Option<Duration>, now: Duration) -> Option<HangEvent> {
+        // 第一次心跳前没有参照，避免启动阶段误报
+        let since_pong = now.saturating_sub(last_pong?);
+        if since_pong < self.timeout {
+            if self.hang_reported {
+                self.hang_reported = false;
+                return Some(HangEvent::Recovered);
+            }
+            return None;
+        }
+        if self.hang_reported {
+            return None;
+        }
+        self.hang_reported = true;
+        Some(HangEvent::Hung(since_pong))
+    }
+}
+
+/// 启动卡死检测线程
+pub fn start(app: &AppHandle) {
+    let Some(timeout) = hang_timeout() else {
+        tracing::info!("Hang watchdog disabled");
+        return;
+    };
+
+    let app = app.clone();
+    let started = Instant::now();
+    // 主线程最近一次响应心跳的时间（相对 started 的毫秒数）
+    let last_pong = Arc::new(AtomicU64::new(0));
+
+    std::thread::spawn(move || {
+        let mut detector = HangDetector::new(timeout);
+
+        loop {
+            std::thread::sleep(PING_INTERVAL);
+
+            let pong = Arc::clone(&last_pong);
+            let ping = app.run_on_main_thread(move || {
+                pong.store(started.elapsed().as_millis() as u64, Ordering::Relaxed);
+            });
+            if ping.is_err() {
+                // 事件循环已退出
+                break;
+            }
+
+            let last = last_pong.load(Ordering::Relaxed);
+            let last = (last > 0).then(|| Duration::from_millis(last));
+            match detector.observe(last, started.elapsed()) {
+                Some(HangEvent::Hung(since_pong)) => report_hang(since_pong),
+                Some(HangEvent::Recovered) => breadcrumbs::record("window", "Main thread responsive again"),
+                None => {}
+            }
+        }
+    });
+}
+
+/// 写入卡死报告
+fn report_hang(unresponsive_for: Duration) {
+    let last_command = breadcrumbs::snapshot()
+        .into_iter()
+        .rev()
+        .find(|crumb| crumb.category == "command")
+        .map(|crumb| crumb.message)
+        .unwrap_or_else(|| "none".to_string());
+
+    // 指纹只依赖最后执行的命令，同一原因的卡死会合并计数
+    let message = format!("Application not responding (last command: {})", last_command);
+    let details = format!(
+        "Main thread did not respond for {:.1}s\n\nThreads:\n{}",
+        unresponsive_for.as_secs_f64(),
+        thread_snapshot()
+    );
+
+    tracing::error!("{}", message);
+    let mut report = CrashReport::with_kind(CrashKind::Hang, message, details);
+    report.minidump = crate::crash_monitor::write_hang_minidump().map(|p| p.display().to_string());
+    if let Err(e) = report.save() {
+        tracing::error!("Failed to save hang report: {}", e);
+    }
+}
+
+/// 当前进程所有线程的状态（Linux 下读取 /proc，线程堆栈见 minidump）
+#[cfg(target_os = "linux")]
+fn thread_snapshot() -> String {
+    let Ok(entries) = std::fs::read_dir("/proc/self/task") else {
+        return "Thread information unavailable".to_string();
+    };
+
+    let mut threads: Vec<String> = entries
+        .flatten()
+        .map(|entry| {
+            let dir = entry.path();
+            let tid = entry.file_name().to_string_lossy().to_string();
+            let name = std::fs::read_to_string(dir.join("comm")).unwrap_or_default();
+            // stat 格式: pid (comm) state ...，comm 可能包含空格，从最后一个 ')' 之后解析
+            let stat = std::fs::read_to_string(dir.join("stat")).unwrap_or_default();
+            let state = stat.rsplit_once(')').and_then(|(_, rest)| rest.split_whitespace().next()).unwrap_or("?").to_string();
+            let wchan = std::fs::read_to_string(dir.join("wchan")).unwrap_or_default();
+            format!("{:>8} {:<20} state={} wchan={}", tid, name.trim(), state, wchan.trim())
+        })
+        .collect();
+    threads.sort();
+    threads.join("\n")
+}
+
+/// 当前进程所有线程的状态（Linux 下读取 /proc，线程堆栈见 minidump）
+#[cfg(not(target_os = "linux"))]
+fn thread_snapshot() -> String {
+    "See the attached minidump for thread backtraces".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hang_reported_once_until_recovered() {
+        let secs = Duration::from_secs;
+        let mut detector = HangDetector::new(secs(5));
+
+        // 还没有心跳
+        assert_eq!(detector.observe(None, secs(30)), None);
+        assert_eq!(detector.observe(Some(secs(2)), secs(4)), None);
+        assert_eq!(detector.observe(Some(secs(2)), secs(7)), Some(HangEvent::Hung(secs(5))));
+        // 持续卡死不重复报告
+        assert_eq!(detector.observe(Some(secs(2)), secs(20)), None);
+        assert_eq!(detector.observe(Some(secs(21)), secs(22)), Some(HangEvent::Recovered));
+        assert_eq!(detector.observe(Some(secs(23)), secs(24)), None);
+        // 再次卡死重新报告
+        assert_eq!(detector.observe(Some(secs(23)), secs(30)), Some(HangEvent::Hung(secs(7))));
+    }
+}
@@ -0,0 +1,171 @@
+//! 硬件信息
+//!
+//! 通过 sysinfo 读取 CPU 和内存，通过 wgpu 枚举显卡（Vulkan / Metal / DX12），
+//! 供界面和后端判断是否提供本地模型等功能，也写入诊断包便于排查性能问题。
+//! 显卡列表在首次查询时枚举并缓存（枚举需要加载图形驱动，较慢）。
+
+use serde::Serialize;
+use std::sync::OnceLock;
+use sysinfo::{CpuRefreshKind, MemoryRefreshKind, RefreshKind, System};
+
+const GIB: u64 = 1024 * 1024 * 1024;
+
+/// 只有内存时运行本地模型所需的内存
+const LOCAL_MODEL_MIN_MEMORY: u64 = 16 * GIB;
+
+/// 有独立显卡时运行本地模型所需的内存
+const LOCAL_MODEL_MIN_MEMORY_WITH_GPU: u64 = 8 * GIB;
+
+/// 枚举到的显卡（首次查询时枚举）
+static GPUS: OnceLock<Vec<GpuInfo>> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CpuInfo {
+    pub brand: String,
+    pub vendor: String,
+    pub arch: String,
+    pub physical_cores: Option<usize>,
+    pub logical_cores: usize,
+    pub frequency_mhz: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoryInfo {
+    pub total: u64,
+    pub available: u64,
+    pub swap_total: u64,
+}
+
+/// 显卡类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GpuType {
+    Discrete,
+    Integrated,
+    Virtual,
+    /// 软件渲染（例如 llvmpipe）
+    Cpu,
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GpuInfo {
+    pub name: String,
+    /// 厂商名称，未知厂商为 PCI 厂商 ID
+    pub vendor: String,
+    pub device_type: GpuType,
+    /// 枚举到该显卡的图形接口，例如 Vulkan
+    pub backend: String,
+    pub driver: String,
+}
+
+/// 硬件信息
+#[derive(Debug, Clone, Serialize)]
+pub struct HardwareInfo {
+    pub cpu: CpuInfo,
+    pub memory: MemoryInfo,
+    pub gpus: Vec<GpuInfo>,
+    /// 硬件是否足以运行本地模型
+    pub local_models: bool,
+}
+
+/// PCI 厂商 ID 对应的名称
+fn vendor_name(id: u32) -> String {
+    match id {
+        0x10de => "NVIDIA".to_string(),
+        0x1002 | 0x1022 => "AMD".to_string(),
+        0x8086 => "Intel".to_string(),
+        0x106b => "Apple".to_string(),
+        0x5143 => "Qualcomm".to_string(),
+        0x13b5 => "ARM".to_string(),
+        _ => format!("0x{:04x}", id),
+    }
+}
+
+/// 是否建议提供本地模型：内存足够大，或有独立显卡（Apple Silicon 为集成显卡、统一内存，按内存判断）
+fn supports_local_models(memory_total: u64, gpus: &[GpuInfo]) -> bool {
+    let has_discrete_gpu = gpus.iter().any(|gpu| gpu.device_type == GpuType::Discrete);
+    memory_total >= LOCAL_MODEL_MIN_MEMORY || has_discrete_gpu && memory_total >= LOCAL_MODEL_MIN_MEMORY_WITH_GPU
+}
+
+async fn enumerate_gpus() -> Vec<GpuInfo> {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::PRIMARY,
+        // 不加载验证层，只用于枚举
+        flags: wgpu::InstanceFlags::empty(),
+        ..wgpu::InstanceDescriptor::new_without_display_handle()
+    });
+    let mut gpus: Vec<GpuInfo> = Vec::new();
+    for adapter in instance.enumerate_adapters(wgpu::Backends::PRIMARY).await {
+        let info = adapter.get_info();
+        // 同一块显卡可能同时通过多个图形接口枚举到
+        if gpus.iter().any(|gpu| gpu.name == info.name) {
+            continue;
+        }
+        let device_type = match info.device_type {
+            wgpu::DeviceType::DiscreteGpu => GpuType::Discrete,
+            wgpu::DeviceType::IntegratedGpu => GpuType::Integrated,
+            wgpu::DeviceType::VirtualGpu => GpuType::Virtual,
+            wgpu::DeviceType::Cpu => GpuType::Cpu,
+            wgpu::DeviceType::Other => GpuType::Other,
+        };
+        gpus.push(GpuInfo {
+            name: info.name,
+            vendor: vendor_name(info.vendor),
+            device_type,
+            backend: info.backend.to_string(),
+            driver: [info.driver, info.driver_info].join(" ").trim().to_string(),
+        });
+    }
+    gpus
+}
+
+/// 收集硬件信息（阻塞调用，首次调用需要枚举显卡）
+pub fn collect() -> HardwareInfo {
+    let system = System::new_with_specifics(
+        RefreshKind::nothing()
+            .with_cpu(CpuRefreshKind::nothing().with_frequency())
+            .with_memory(MemoryRefreshKind::everything()),
+    );
+    let first_cpu = system.cpus().first();
+    let cpu = CpuInfo {
+        brand: first_cpu.map(|cpu| cpu.brand().trim().to_string()).unwrap_or_default(),
+        vendor: first_cpu.map(|cpu| cpu.vendor_id().to_string()).unwrap_or_default(),
+        arch: System::cpu_arch(),
+        physical_cores: System::physical_core_count(),
+        logical_cores: system.cpus().len(),
+        frequency_mhz: first_cpu.map(|cpu| cpu.frequency()).unwrap_or_default(),
+    };
+    let memory = MemoryInfo {
+        total: system.total_memory(),
+        available: system.available_memory(),
+        swap_total: system.total_swap(),
+    };
+    let gpus = GPUS.get_or_init(|| tauri::async_runtime::block_on(enumerate_gpus())).clone();
+    let local_models = supports_local_models(memory.total, &gpus);
+    HardwareInfo { cpu, memory, gpus, local_models }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_supports_local_models() {
+        let gpu = |device_type| GpuInfo {
+            name: "GPU".to_string(),
+            vendor: vendor_name(0x10de),
+            device_type,
+            backend: "vulkan".to_string(),
+            driver: String::new(),
+        };
+        assert_eq!(vendor_name(0x10de), "NVIDIA");
+        assert_eq!(vendor_name(0x1234), "0x1234");
+
+        assert!(supports_local_models(32 * GIB, &[]));
+        assert!(!supports_local_models(8 * GIB, &[]));
+        assert!(supports_local_models(8 * GIB, &[gpu(GpuType::Discrete)]));
+        assert!(!supports_local_models(8 * GIB, &[gpu(GpuType::Integrated)]));
+        assert!(!supports_local_models(4 * GIB, &[gpu(GpuType::Discrete)]));
+    }
+}
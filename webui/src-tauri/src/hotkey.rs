@@ -0,0 +1,87 @@
+//! 全局快捷键
+//!
+//! 默认 Ctrl+Shift+D（macOS 为 Cmd+Shift+D）显示/隐藏主窗口，显示时向主窗口发送
+//! `focus-chat-input` 事件，由前端聚焦输入框。快捷键保存在设置中，可以修改或关闭。
+
+use crate::settings::{self, SETTINGS_CHANGED_EVENT};
+use std::str::FromStr;
+use tauri::{AppHandle, Emitter, Listener};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+/// 默认快捷键
+pub const DEFAULT_SHORTCUT: &str = "CommandOrControl+Shift+D";
+
+/// 主窗口通过快捷键显示后发送的事件
+pub const FOCUS_CHAT_INPUT_EVENT: &str = "focus-chat-input";
+
+/// 解析快捷键，例如 `CommandOrControl+Shift+D`
+fn parse(shortcut: &str) -> Result<Shortcut, String> {
+    Shortcut::from_str(shortcut).map_err(|e| format!("快捷键无效 {:?}: {}", shortcut, e))
+}
+
+/// 注销旧的快捷键并注册新的快捷键
+fn apply(app: &AppHandle, shortcut: Option<&str>) -> Result<(), String> {
+    let manager = app.global_shortcut();
+    manager.unregister_all().map_err(|e| e.to_string())?;
+    // 演示模式下不注册快捷键，退出时重新注册
+    let Some(shortcut) = shortcut.filter(|_| !crate::kiosk::is_enabled()) else {
+        return Ok(());
+    };
+
+    let parsed = parse(shortcut)?;
+    manager
+        .on_shortcut(parsed, |app, _, event| {
+            if event.state() != ShortcutState::Pressed {
+                return;
+            }
+            if crate::tray::toggle_main_window(app) {
+                let _ = app.emit_to("main", FOCUS_CHAT_INPUT_EVENT, ());
+            }
+        })
+        .map_err(|e| format!("无法注册快捷键 {}（可能已被其他程序占用）: {}", shortcut, e))
+}
+
+/// 按当前设置重新注册快捷键
+pub fn refresh(app: &AppHandle) {
+    if let Err(e) = apply(app, settings::current().global_shortcut.as_deref()) {
+        tracing::warn!("{}", e);
+    }
+}
+
+/// 启动时注册快捷键，并在设置中的快捷键变化时重新注册
+pub fn init(app: &AppHandle) {
+    refresh(app);
+
+    let handle = app.clone();
+    app.listen_any(SETTINGS_CHANGED_EVENT, move |event| {
+        let changed = serde_json::from_str::<serde_json::Value>(event.payload())
+            .map(|change| change["changed"].get("global_shortcut").is_some())
+            .unwrap_or(false);
+        if changed {
+            refresh(&handle);
+        }
+    });
+}
+
+/// 修改快捷键，`None` 表示关闭；注册失败（例如被占用）时不保存
+pub fn set_shortcut(app: &AppHandle, shortcut: Option<String>) -> Result<Option<String>, String> {
+    if let Err(e) = apply(app, shortcut.as_deref()) {
+        // 恢复原来的快捷键
+        let _ = apply(app, settings::current().global_shortcut.as_deref());
+        return Err(e);
+    }
+    settings::update(app, serde_json::json!({ "global_shortcut": shortcut }))?;
+    Ok(shortcut)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_shortcut() {
+        assert!(parse(DEFAULT_SHORTCUT).is_ok());
+        assert!(parse("Alt+Space").is_ok());
+        assert!(parse("Ctrl+Nope").is_err());
+    }
+}
@@ -0,0 +1,189 @@
+//! 界面文字本地化
+//!
+//! 目前只覆盖崩溃报告和启动画面中面向用户的文字。语言优先级：环境变量 DAWEI_LOCALE
+//! （如 `zh-CN`、`en`）> 设置中的 locale > 系统语言，无法识别时使用中文。
+//! 首次运行时把检测到的系统语言写入设置，启动后端时通过 DAWEI_LOCALE 传给后端，
+//! 保证前后端使用同一种语言。
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+/// 向后端传递语言的环境变量
+pub const LOCALE_ENV: &str = "DAWEI_LOCALE";
+
+/// 支持的语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+    #[serde(rename = "en")]
+    En,
+}
+
+impl Locale {
+    /// 解析语言标识，例如 `zh_CN.UTF-8`、`en-US`
+    pub fn parse(tag: &str) -> Option<Self> {
+        let tag = tag.trim().to_lowercase();
+        if tag.starts_with("zh") {
+            Some(Self::ZhCn)
+        } else if tag.starts_with("en") {
+            Some(Self::En)
+        } else {
+            None
+        }
+    }
+
+    /// 语言标识
+    pub fn tag(self) -> &'static str {
+        match self {
+            Self::ZhCn => "zh-CN",
+            Self::En => "en",
+        }
+    }
+
+    /// 系统语言
+    pub fn detect_os() -> Option<Self> {
+        sys_locale::get_locale()
+            .and_then(|tag| Self::parse(&tag))
+            .or_else(|| {
+                ["LC_ALL", "LANG"]
+                    .iter()
+                    .filter_map(|var| std::env::var(var).ok())
+                    .find_map(|tag| Self::parse(&tag))
+            })
+    }
+
+    /// 当前使用的语言
+    pub fn current() -> Self {
+        std::env::var(LOCALE_ENV)
+            .ok()
+            .and_then(|tag| Self::parse(&tag))
+            .or_else(|| crate::settings::current().locale)
+            .or_else(Self::detect_os)
+            .unwrap_or_default()
+    }
+}
+
+/// 首次运行时检测系统语言并写入设置
+pub fn init(app: &AppHandle) {
+    if crate::settings::current().locale.is_some() {
+        return;
+    }
+
+    let detected = Locale::detect_os().unwrap_or_default();
+    match crate::settings::update(app, serde_json::json!({ "locale": detected })) {
+        Ok(_) => tracing::info!("Detected locale: {}", detected.tag()),
+        Err(e) => tracing::warn!("Failed to save detected locale: {}", e),
+    }
+}
+
+/// 修改语言
+pub fn set_locale(app: &AppHandle, locale: Locale) -> Result<Locale, String> {
+    crate::settings::update(app, serde_json::json!({ "locale": locale }))?;
+    Ok(locale)
+}
+
+/// 崩溃报告和启动画面中的文字
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Text {
+    CrashReport,
+    Error,
+    Kind,
+    Source,
+    Platform,
+    Version,
+    Time,
+    FirstSeen,
+    LastSeen,
+    Occurrences,
+    Session,
+    ReportFile,
+    Backtrace,
+    Breadcrumbs,
+    LogTail,
+    SplashDetecting,
+    SplashCheckingBackend,
+    SplashStartingBackend,
+    SplashBackendOffline,
+    SplashReady,
+}
+
+impl Text {
+    /// 指定语言的文字
+    pub fn in_locale(self, locale: Locale) -> &'static str {
+        match locale {
+            Locale::En => self.english(),
+            Locale::ZhCn => match self {
+                Text::CrashReport => "崩溃报告",
+                Text::Error => "错误",
+                Text::Kind => "类型",
+                Text::Source => "来源",
+                Text::Platform => "平台",
+                Text::Version => "版本",
+                Text::Time => "时间",
+                Text::FirstSeen => "首次出现",
+                Text::LastSeen => "最近出现",
+                Text::Occurrences => "出现次数",
+                Text::Session => "会话",
+                Text::ReportFile => "报告文件",
+                Text::Backtrace => "堆栈",
+                Text::Breadcrumbs => "崩溃前事件",
+                Text::LogTail => "日志尾部",
+                Text::SplashDetecting => "正在检测运行环境…",
+                Text::SplashCheckingBackend => "正在连接后端服务…",
+                Text::SplashStartingBackend => "正在启动后端服务…",
+                Text::SplashBackendOffline => "后端服务未运行",
+                Text::SplashReady => "准备就绪",
+            },
+        }
+    }
+
+    /// 英文原文（导出时与本地化文字一并保留，便于开发者检索）
+    pub fn english(self) -> &'static str {
+        match self {
+            Text::CrashReport => "Crash report",
+            Text::Error => "Error",
+            Text::Kind => "Kind",
+            Text::Source => "Source",
+            Text::Platform => "Platform",
+            Text::Version => "Version",
+            Text::Time => "Time",
+            Text::FirstSeen => "First seen",
+            Text::LastSeen => "Last seen",
+            Text::Occurrences => "Occurrences",
+            Text::Session => "Session",
+            Text::ReportFile => "Report file",
+            Text::Backtrace => "Backtrace",
+            Text::Breadcrumbs => "Breadcrumbs",
+            Text::LogTail => "Log tail",
+            Text::SplashDetecting => "Detecting environment…",
+            Text::SplashCheckingBackend => "Connecting to backend…",
+            Text::SplashStartingBackend => "Starting backend…",
+            Text::SplashBackendOffline => "Backend is not running",
+            Text::SplashReady => "Ready",
+        }
+    }
+
+    /// 导出用的标签：英文原文，非英文时附带本地化文字，例如 `Kind (类型)`
+    pub fn export_label(self, locale: Locale) -> String {
+        match locale {
+            Locale::En => self.english().to_string(),
+            _ => format!("{} ({})", self.english(), self.in_locale(locale)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_locale() {
+        assert_eq!(Locale::parse("zh_CN.UTF-8"), Some(Locale::ZhCn));
+        assert_eq!(Locale::parse("en-US"), Some(Locale::En));
+        assert_eq!(Locale::parse("C"), None);
+        assert_eq!(Text::Kind.export_label(Locale::ZhCn), "Kind (类型)");
+        assert_eq!(Text::Kind.export_label(Locale::En), "Kind");
+    }
+}
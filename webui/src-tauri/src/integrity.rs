@@ -0,0 +1,177 @@
+//! 内置资源完整性校验
+//!
+//! 打包时 `scripts/generate-resource-manifest.py` 生成 resources/integrity.json，记录运行必需的
+//! 文件（uv、Python 解释器、pyvenv.cfg）的 SHA-256。启动后在后台逐个校验，有文件缺失或被修改
+//! （杀毒软件隔离、磁盘错误、解压不完整）时保存结果并发送 `installation-corrupted` 事件，
+//! 前端提示用户修复：`repair_installation` 重新下载并安装当前版本（见 [`crate::updater::reinstall`]）。
+//! 没有清单的安装（开发模式、非独立版）不校验。
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+/// 校验失败事件，内容为 [`IntegrityReport`]
+pub const INSTALLATION_CORRUPTED_EVENT: &str = "installation-corrupted";
+
+/// 清单文件名（位于 resources 目录）
+const MANIFEST_FILE: &str = "integrity.json";
+
+/// 最近一次校验结果
+static REPORT: Mutex<Option<IntegrityReport>> = Mutex::new(None);
+
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    files: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestEntry {
+    /// 相对 resources 目录的路径
+    path: String,
+    sha256: String,
+    size: u64,
+}
+
+/// 问题类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Problem {
+    Missing,
+    Modified,
+    Unreadable,
+}
+
+/// 不一致的文件
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileProblem {
+    pub path: String,
+    pub problem: Problem,
+}
+
+/// 校验结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IntegrityReport {
+    /// 校验的文件数
+    pub checked: usize,
+    pub problems: Vec<FileProblem>,
+    pub checked_at: String,
+}
+
+impl IntegrityReport {
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+fn resources_dir() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    Some(exe.parent()?.join("resources"))
+}
+
+fn sha256(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn check_entry(resources: &Path, entry: &ManifestEntry) -> Option<Problem> {
+    let path = resources.join(&entry.path);
+    let Ok(metadata) = std::fs::metadata(&path) else {
+        return Some(Problem::Missing);
+    };
+    // 大小不同时不必计算哈希
+    if metadata.len() != entry.size {
+        return Some(Problem::Modified);
+    }
+    match sha256(&path) {
+        Ok(hash) if hash.eq_ignore_ascii_case(&entry.sha256) => None,
+        Ok(_) => Some(Problem::Modified),
+        Err(_) => Some(Problem::Unreadable),
+    }
+}
+
+/// 按清单校验，没有清单时返回 None
+fn verify_in(resources: &Path) -> Option<IntegrityReport> {
+    let content = std::fs::read_to_string(resources.join(MANIFEST_FILE)).ok()?;
+    let manifest: Manifest = match serde_json::from_str(&content) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            tracing::warn!("Invalid {}: {}", MANIFEST_FILE, e);
+            return None;
+        }
+    };
+    let problems = manifest
+        .files
+        .iter()
+        .filter_map(|entry| check_entry(resources, entry).map(|problem| FileProblem { path: entry.path.clone(), problem }))
+        .collect();
+    Some(IntegrityReport { checked: manifest.files.len(), problems, checked_at: chrono::Local::now().to_rfc3339() })
+}
+
+/// 启动时在后台校验
+pub fn verify_in_background(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let Some(report) = resources_dir().and_then(|dir| verify_in(&dir)) else {
+            return;
+        };
+        if report.is_ok() {
+            tracing::info!("Verified {} bundled resource files", report.checked);
+        } else {
+            tracing::error!("Installation corrupted: {:?}", report.problems);
+            crate::breadcrumbs::record("app", format!("Installation corrupted: {} file(s)", report.problems.len()));
+            if let Err(e) = app.emit(INSTALLATION_CORRUPTED_EVENT, &report) {
+                tracing::warn!("Failed to emit installation-corrupted: {}", e);
+            }
+        }
+        *REPORT.lock().unwrap_or_else(|e| e.into_inner()) = Some(report);
+    });
+}
+
+/// 最近一次校验结果（尚未校验完成或没有清单时为 None），供错过事件的页面查询
+pub fn report() -> Option<IntegrityReport> {
+    REPORT.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(verify_in(dir.path()).is_none());
+
+        std::fs::create_dir_all(dir.path().join("python-env/bin")).unwrap();
+        std::fs::write(dir.path().join("python-env/bin/uv"), "uv").unwrap();
+        std::fs::write(dir.path().join("python-env/pyvenv.cfg"), "home = /usr").unwrap();
+        let manifest = serde_json::json!({ "files": [
+            { "path": "python-env/bin/uv", "sha256": sha256(&dir.path().join("python-env/bin/uv")).unwrap(), "size": 2 },
+            { "path": "python-env/pyvenv.cfg", "sha256": "0".repeat(64), "size": 11 },
+            { "path": "python-env/bin/python", "sha256": "0".repeat(64), "size": 10 },
+        ] });
+        std::fs::write(dir.path().join(MANIFEST_FILE), manifest.to_string()).unwrap();
+
+        let report = verify_in(dir.path()).unwrap();
+        assert_eq!(report.checked, 3);
+        assert_eq!(
+            report.problems,
+            [
+                FileProblem { path: "python-env/pyvenv.cfg".to_string(), problem: Problem::Modified },
+                FileProblem { path: "python-env/bin/python".to_string(), problem: Problem::Missing },
+            ]
+        );
+    }
+}
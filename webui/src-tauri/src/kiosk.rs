@@ -0,0 +1,85 @@
+//! 演示（全屏）模式
+//!
+//! 在共享屏幕上演示时使用：主窗口全屏并隐藏标题栏和菜单栏，关闭 DevTools，
+//! 暂停全局快捷键，并在页面中拦截打开 DevTools、刷新、查看源码等浏览器快捷键。
+//! 演示模式只在本次运行中有效，不保存到设置。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter, Manager, Webview};
+
+/// 演示模式变化时发送的事件，内容为是否开启
+pub const KIOSK_CHANGED_EVENT: &str = "kiosk-mode-changed";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// 演示模式下拦截的浏览器快捷键（只安装一次，由 `__DAWEI_KIOSK__` 控制是否生效）
+const KEY_GUARD_SCRIPT: &str = r#"
+if (!window.__DAWEI_KIOSK_GUARD__) {
+    window.__DAWEI_KIOSK_GUARD__ = true;
+    window.addEventListener('keydown', (e) => {
+        if (!window.__DAWEI_KIOSK__) return;
+        const mod = e.ctrlKey || e.metaKey;
+        const key = e.key.toLowerCase();
+        const blocked = e.key === 'F12' || e.key === 'F5'
+            || (mod && e.shiftKey && ['i', 'j', 'c'].includes(key))
+            || (mod && e.altKey && key === 'i')
+            || (mod && ['r', 'u'].includes(key));
+        if (blocked) {
+            e.preventDefault();
+            e.stopImmediatePropagation();
+        }
+    }, true);
+}
+"#;
+
+/// 演示模式是否开启
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::SeqCst)
+}
+
+/// 在页面中设置演示模式标记并安装快捷键拦截
+fn inject(webview: &Webview, enabled: bool) {
+    let script = format!("window.__DAWEI_KIOSK__ = {};{}", enabled, KEY_GUARD_SCRIPT);
+    if let Err(e) = webview.eval(script) {
+        tracing::warn!("Failed to inject kiosk script: {}", e);
+    }
+}
+
+/// 页面重新加载后恢复快捷键拦截
+pub fn on_page_load(webview: &Webview) {
+    if is_enabled() && webview.label() == "main" {
+        inject(webview, true);
+    }
+}
+
+/// 开启或关闭演示模式
+pub fn set(app: &AppHandle, enabled: bool) -> Result<bool, String> {
+    let window = app.get_webview_window("main").ok_or("主窗口不存在")?;
+    ENABLED.store(enabled, Ordering::SeqCst);
+
+    if enabled {
+        window.close_devtools();
+        window.unminimize().and_then(|_| window.show()).and_then(|_| window.set_focus()).map_err(|e| e.to_string())?;
+        window.set_decorations(false).map_err(|e| e.to_string())?;
+    } else {
+        crate::titlebar::apply(&window);
+    }
+    window.set_fullscreen(enabled).map_err(|e| e.to_string())?;
+    // macOS 全屏时自动隐藏菜单栏
+    #[cfg(not(target_os = "macos"))]
+    {
+        let result = if enabled { window.hide_menu() } else { window.show_menu() };
+        if let Err(e) = result {
+            tracing::warn!("Failed to toggle menu bar: {}", e);
+        }
+    }
+    inject(window.as_ref(), enabled);
+    crate::hotkey::refresh(app);
+    crate::menu::set_devtools_enabled(crate::devtools::is_allowed());
+
+    crate::breadcrumbs::record("window", format!("Kiosk mode {}", if enabled { "enabled" } else { "disabled" }));
+    if let Err(e) = app.emit(KIOSK_CHANGED_EVENT, enabled) {
+        tracing::warn!("Failed to emit kiosk mode change: {}", e);
+    }
+    Ok(enabled)
+}
@@ -0,0 +1,261 @@
+//! 桌面端日志查询
+//!
+//! 解析 DAWEI_HOME/logs/app 下的日志文件（行格式见 [`crate::logging`]），按级别、模块
+//! （target 前缀）和时间范围过滤，从新到旧分页返回，供应用内的日志查看器使用。
+//! 多行消息（例如启动后端的输出）的后续行合并到上一条日志中。
+
+use crate::logging::TIMESTAMP_FORMAT;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::str::FromStr;
+use tracing::Level;
+
+/// 默认每页条数
+const DEFAULT_LIMIT: usize = 200;
+
+/// 每页最多条数
+const MAX_LIMIT: usize = 1000;
+
+/// 查询条件，所有字段都可省略
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LogQuery {
+    /// 最低级别，例如 `warn` 返回 WARN 和 ERROR
+    pub level: Option<String>,
+    /// target 前缀，例如 `dawei_gui::settings`、`backend`
+    pub module: Option<String>,
+    /// 开始时间（RFC 3339 或本地时间 `YYYY-MM-DD HH:MM:SS`）
+    pub since: Option<String>,
+    /// 结束时间（格式同 since）
+    pub until: Option<String>,
+    /// 上一页返回的 `next_cursor`
+    pub cursor: Option<String>,
+    /// 每页条数，默认 200，最多 1000
+    pub limit: Option<usize>,
+}
+
+/// 一条日志
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogEntry {
+    /// 本地时间，格式同日志文件
+    pub timestamp: String,
+    pub level: String,
+    pub target: String,
+    /// span 上下文，例如 `command{name="start_backend"} > start_backend`
+    pub spans: Option<String>,
+    pub message: String,
+}
+
+/// 一页日志（从新到旧）
+#[derive(Debug, Clone, Serialize)]
+pub struct LogPage {
+    pub entries: Vec<LogEntry>,
+    /// 还有更早的日志时用于查询下一页
+    pub next_cursor: Option<String>,
+}
+
+/// 解析后的查询条件
+struct Filter {
+    level: Option<Level>,
+    module: Option<String>,
+    since: Option<NaiveDateTime>,
+    until: Option<NaiveDateTime>,
+}
+
+impl Filter {
+    fn from_query(query: &LogQuery) -> Result<Self, String> {
+        let level = match query.level.as_deref().map(str::trim).filter(|level| !level.is_empty()) {
+            Some(level) => Some(Level::from_str(level).map_err(|_| format!("无效的日志级别: {}", level))?),
+            None => None,
+        };
+        Ok(Self {
+            level,
+            module: query.module.clone().filter(|module| !module.is_empty()),
+            since: query.since.as_deref().map(parse_time).transpose()?,
+            until: query.until.as_deref().map(parse_time).transpose()?,
+        })
+    }
+
+    fn matches(&self, entry: &ParsedEntry) -> bool {
+        // tracing 中越详细的级别越大
+        self.level.is_none_or(|level| entry.level <= level)
+            && self.module.as_deref().is_none_or(|module| entry.entry.target.starts_with(module))
+            && self.until.is_none_or(|until| entry.time <= until)
+    }
+}
+
+/// 解析查询中的时间，带时区的时间转换为本地时间
+pub fn parse_time(value: &str) -> Result<NaiveDateTime, String> {
+    let value = value.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Local).naive_local());
+    }
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok().and_then(|date| date.and_hms_opt(0, 0, 0)))
+        .ok_or_else(|| format!("无效的时间: {}", value))
+}
+
+/// 日志及其在文件中的位置
+struct ParsedEntry {
+    offset: usize,
+    time: NaiveDateTime,
+    level: Level,
+    entry: LogEntry,
+}
+
+/// 解析日志的第一行，不是日志开头（多行消息的后续行）时返回 None
+fn parse_header(line: &str, offset: usize) -> Option<ParsedEntry> {
+    let (time, rest) = NaiveDateTime::parse_and_remainder(line, TIMESTAMP_FORMAT).ok()?;
+    let (level_text, rest) = rest.trim_start().split_once(' ')?;
+    let level = Level::from_str(level_text).ok()?;
+
+    let (target, spans, message) = match rest.split_once(' ') {
+        Some((target, message)) if target.ends_with(':') => (target.trim_end_matches(':'), None, message),
+        Some((target, spans)) if spans.starts_with('[') => {
+            let (spans, message) = spans[1..].split_once("]: ")?;
+            (target, Some(spans.to_string()), message)
+        }
+        // 消息为空
+        None if rest.ends_with(':') => (rest.trim_end_matches(':'), None, ""),
+        _ => return None,
+    };
+    Some(ParsedEntry {
+        offset,
+        time,
+        level,
+        entry: LogEntry {
+            timestamp: time.format(TIMESTAMP_FORMAT).to_string(),
+            level: level.to_string(),
+            target: target.to_string(),
+            spans,
+            message: message.to_string(),
+        },
+    })
+}
+
+/// 读取日志文件中的所有日志（从旧到新）
+fn read_entries(path: &Path) -> Vec<ParsedEntry> {
+    let Ok(bytes) = std::fs::read(path) else {
+        return Vec::new();
+    };
+    let content = String::from_utf8_lossy(&bytes);
+    let mut entries: Vec<ParsedEntry> = Vec::new();
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let text = line.trim_end_matches(['\r', '\n']);
+        match parse_header(text, offset) {
+            Some(entry) => entries.push(entry),
+            // 文件开头不完整的行被忽略
+            None => {
+                if let Some(last) = entries.last_mut() {
+                    last.entry.message.push('\n');
+                    last.entry.message.push_str(text);
+                }
+            }
+        }
+        offset += line.len();
+    }
+    entries
+}
+
+/// 解析游标 `<文件名>:<偏移>`
+fn parse_cursor(cursor: &str) -> Result<(&str, usize), String> {
+    cursor
+        .rsplit_once(':')
+        .and_then(|(file, offset)| Some((file, offset.parse().ok()?)))
+        .ok_or_else(|| format!("无效的游标: {}", cursor))
+}
+
+/// 查询目录中的日志
+fn query_in(dir: &Path, query: &LogQuery) -> Result<LogPage, String> {
+    let filter = Filter::from_query(query)?;
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let cursor = query.cursor.as_deref().map(parse_cursor).transpose()?;
+
+    let mut files = crate::logging::log_files_in(dir);
+    if let Some((name, _)) = cursor {
+        // 游标指向的文件已被清理时没有更早的日志
+        let Some(start) = files.iter().position(|path| path.file_name().is_some_and(|n| n == name)) else {
+            return Ok(LogPage { entries: Vec::new(), next_cursor: None });
+        };
+        files.drain(..start);
+    }
+
+    let mut entries = Vec::new();
+    for path in files {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let before = cursor.filter(|(file, _)| *file == name).map(|(_, offset)| offset);
+        for parsed in read_entries(&path).into_iter().rev() {
+            if before.is_some_and(|before| parsed.offset >= before) {
+                continue;
+            }
+            // 日志按时间顺序写入，之后的都更早
+            if filter.since.is_some_and(|since| parsed.time < since) {
+                return Ok(LogPage { entries, next_cursor: None });
+            }
+            if !filter.matches(&parsed) {
+                continue;
+            }
+            entries.push(parsed.entry);
+            if entries.len() == limit {
+                return Ok(LogPage { entries, next_cursor: Some(format!("{}:{}", name, parsed.offset)) });
+            }
+        }
+    }
+    Ok(LogPage { entries, next_cursor: None })
+}
+
+/// 查询桌面端日志，从新到旧分页返回
+pub fn query(query: &LogQuery) -> Result<LogPage, String> {
+    query_in(&crate::logging::app_log_dir(), query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_filters_and_paginates() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("dawei-gui.2024-01-30.log"),
+            "2024-01-30 23:59:00.000  INFO dawei_gui::session: Session ID: abc\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("dawei-gui.2024-01-31.log"),
+            concat!(
+                "2024-01-31 10:00:00.000  WARN dawei_gui::settings: Failed to parse settings\n",
+                "2024-01-31 10:00:01.000  INFO dawei_gui [command{name=\"start_backend\"} > start_backend]: Starting backend\n",
+                "Using backend profile: dev\n",
+                "2024-01-31 10:00:02.000 ERROR backend: Traceback\n",
+            ),
+        )
+        .unwrap();
+
+        let all = query_in(dir.path(), &LogQuery::default()).unwrap();
+        assert_eq!(all.entries.len(), 4);
+        assert_eq!(all.entries[0].target, "backend");
+        assert_eq!(all.entries[1].spans.as_deref(), Some("command{name=\"start_backend\"} > start_backend"));
+        assert_eq!(all.entries[1].message, "Starting backend\nUsing backend profile: dev");
+        assert_eq!(all.entries[3].timestamp, "2024-01-30 23:59:00.000");
+
+        let warnings = LogQuery { level: Some("warn".to_string()), ..Default::default() };
+        let levels: Vec<_> = query_in(dir.path(), &warnings).unwrap().entries.into_iter().map(|e| e.level).collect();
+        assert_eq!(levels, ["ERROR", "WARN"]);
+
+        let module = LogQuery { module: Some("dawei_gui::".to_string()), since: Some("2024-01-31".to_string()), ..Default::default() };
+        assert_eq!(query_in(dir.path(), &module).unwrap().entries.len(), 1);
+
+        // 分页跨文件
+        let mut page = query_in(dir.path(), &LogQuery { limit: Some(3), ..Default::default() }).unwrap();
+        assert_eq!(page.entries.len(), 3);
+        page = query_in(dir.path(), &LogQuery { limit: Some(3), cursor: page.next_cursor, ..Default::default() }).unwrap();
+        assert_eq!(page.entries.len(), 1);
+        assert_eq!(page.entries[0].target, "dawei_gui::session");
+        assert!(page.next_cursor.is_none());
+    }
+}
@@ -0,0 +1,304 @@
+//! 日志搜索
+//!
+//! 供日志查看器的搜索框使用：用正则表达式逐行搜索桌面端日志和后端日志（包括轮转的文件），
+//! 可按来源（见 [`LogSource`]）和时间范围过滤，返回最近的匹配行及其前后几行。
+//! 文件逐行读取，只保留最近的匹配，不会把整个日志目录读入内存。
+//! 多行日志的后续行沿用所在日志的时间和来源；桌面端日志中转发的后端 stderr 不搜索
+//! （与后端日志文件重复）。
+
+use crate::log_query;
+use crate::log_timeline::{self, LogSource, BACKEND_TARGET};
+use crate::logging::TIMESTAMP_FORMAT;
+use chrono::{DateTime, Local, NaiveDateTime};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tracing::Level;
+
+/// 最多返回的匹配数
+const MAX_MATCHES: usize = 200;
+
+/// 匹配行前后各返回的行数
+const CONTEXT_LINES: usize = 2;
+
+/// 时间范围，两端都可省略
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TimeRange {
+    /// 开始时间（格式同 `get_app_logs`）
+    pub since: Option<String>,
+    /// 结束时间
+    pub until: Option<String>,
+}
+
+/// 一个匹配行
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchMatch {
+    pub source: LogSource,
+    /// 日志文件名
+    pub file: String,
+    /// 行号（从 1 开始）
+    pub line: usize,
+    /// 所在日志的时间（格式同桌面端日志），文件开头不完整的日志为 None
+    pub timestamp: Option<String>,
+    pub text: String,
+    /// 前面的行（同一文件内）
+    pub before: Vec<String>,
+    /// 后面的行
+    pub after: Vec<String>,
+}
+
+/// 搜索结果（从新到旧）
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResult {
+    pub matches: Vec<SearchMatch>,
+    /// 达到匹配数上限，更早的日志没有全部搜索
+    pub truncated: bool,
+}
+
+/// 日志文件格式
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FileKind {
+    App,
+    Backend,
+}
+
+/// 解析后的搜索条件
+struct Search {
+    regex: Regex,
+    sources: Vec<LogSource>,
+    since: Option<NaiveDateTime>,
+    until: Option<NaiveDateTime>,
+    limit: usize,
+}
+
+impl Search {
+    fn in_range(&self, time: Option<NaiveDateTime>) -> bool {
+        match time {
+            Some(time) => self.since.is_none_or(|since| time >= since) && self.until.is_none_or(|until| time <= until),
+            None => self.since.is_none() && self.until.is_none(),
+        }
+    }
+}
+
+/// 日志第一行的时间和来源，不是日志开头时返回 None；转发的后端 stderr 来源为 None
+fn parse_header(kind: FileKind, line: &str) -> Option<(NaiveDateTime, Option<LogSource>)> {
+    match kind {
+        FileKind::App => {
+            let (time, rest) = NaiveDateTime::parse_and_remainder(line, TIMESTAMP_FORMAT).ok()?;
+            let (level, rest) = rest.trim_start().split_once(' ')?;
+            Level::from_str(level).ok()?;
+            let target = rest.split(' ').next()?.trim_end_matches(':');
+            let source = (target != BACKEND_TARGET).then(|| log_timeline::source_for_target(target));
+            Some((time, source))
+        }
+        FileKind::Backend => {
+            let (time, _) = NaiveDateTime::parse_and_remainder(line, log_timeline::BACKEND_TIMESTAMP_FORMAT).ok()?;
+            Some((time, Some(LogSource::Backend)))
+        }
+    }
+}
+
+/// 文件最后修改时间（本地时间）
+fn modified(path: &Path) -> Option<NaiveDateTime> {
+    let modified = std::fs::metadata(path).and_then(|meta| meta.modified()).ok()?;
+    Some(DateTime::<Local>::from(modified).naive_local())
+}
+
+/// 在一个文件中搜索，返回最近的 `limit` 个匹配（从旧到新）和是否丢弃了更早的匹配
+fn search_file(kind: FileKind, path: &Path, search: &Search, limit: usize) -> (VecDeque<SearchMatch>, bool) {
+    let mut matches = VecDeque::new();
+    let Ok(file) = std::fs::File::open(path) else {
+        return (matches, false);
+    };
+    let file_name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let mut reader = std::io::BufReader::new(file);
+    let mut buf = Vec::new();
+    let mut before: VecDeque<String> = VecDeque::with_capacity(CONTEXT_LINES + 1);
+    let mut time = None;
+    // 文件开头不完整的日志无法确定来源，桌面端日志按桌面端处理
+    let mut source = Some(match kind {
+        FileKind::App => LogSource::Tauri,
+        FileKind::Backend => LogSource::Backend,
+    });
+    let mut truncated = false;
+    let mut number = 0;
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        number += 1;
+        let text = String::from_utf8_lossy(&buf).trim_end_matches(['\r', '\n']).to_string();
+        if let Some((header_time, header_source)) = parse_header(kind, &text) {
+            time = Some(header_time);
+            source = header_source;
+        }
+
+        // 补全前面匹配的后文
+        for found in matches.iter_mut().rev().take(CONTEXT_LINES) {
+            if found.line + CONTEXT_LINES >= number {
+                found.after.push(text.clone());
+            }
+        }
+
+        if source.is_some_and(|source| search.sources.contains(&source))
+            && search.in_range(time)
+            && search.regex.is_match(&text)
+        {
+            matches.push_back(SearchMatch {
+                source: source.unwrap_or(LogSource::Tauri),
+                file: file_name.clone(),
+                line: number,
+                timestamp: time.map(|time| time.format(TIMESTAMP_FORMAT).to_string()),
+                text: text.clone(),
+                before: before.iter().cloned().collect(),
+                after: Vec::new(),
+            });
+            if matches.len() > limit {
+                matches.pop_front();
+                truncated = true;
+            }
+        }
+
+        before.push_back(text);
+        if before.len() > CONTEXT_LINES {
+            before.pop_front();
+        }
+    }
+    (matches, truncated)
+}
+
+/// 按从新到旧的顺序搜索同一格式的文件，返回最近的匹配（从新到旧）和是否截断
+fn search_files(kind: FileKind, files: &[PathBuf], search: &Search) -> (Vec<SearchMatch>, bool) {
+    let mut result: Vec<SearchMatch> = Vec::new();
+    let mut truncated = false;
+    for path in files {
+        // 文件从新到旧排列，最后修改时间早于开始时间后，剩下的文件都不在范围内
+        if search.since.is_some_and(|since| modified(path).is_some_and(|modified| modified < since)) {
+            break;
+        }
+        if result.len() == search.limit {
+            truncated = true;
+            break;
+        }
+        let (matches, file_truncated) = search_file(kind, path, search, search.limit - result.len());
+        truncated |= file_truncated;
+        result.extend(matches.into_iter().rev());
+    }
+    (result, truncated)
+}
+
+/// 按正则搜索日志，返回最近的匹配（从新到旧）
+pub fn search(pattern: &str, sources: Option<Vec<LogSource>>, range: &TimeRange) -> Result<SearchResult, String> {
+    if pattern.is_empty() {
+        return Err("搜索内容不能为空".to_string());
+    }
+    let search = Search {
+        regex: Regex::new(pattern).map_err(|e| format!("无效的正则表达式: {}", e))?,
+        sources: sources.unwrap_or_else(|| vec![LogSource::Tauri, LogSource::Webview, LogSource::Backend]),
+        since: range.since.as_deref().map(log_query::parse_time).transpose()?,
+        until: range.until.as_deref().map(log_query::parse_time).transpose()?,
+        limit: MAX_MATCHES,
+    };
+
+    let mut groups = Vec::new();
+    if search.sources.contains(&LogSource::Tauri) || search.sources.contains(&LogSource::Webview) {
+        groups.push(search_files(FileKind::App, &crate::logging::log_files_in(&crate::logging::app_log_dir()), &search));
+    }
+    if search.sources.contains(&LogSource::Backend) {
+        let files = log_timeline::backend_log_files(&crate::backend_crash::backend_log_file());
+        groups.push(search_files(FileKind::Backend, &files, &search));
+    }
+    Ok(merge(groups, search.limit))
+}
+
+/// 合并各来源的结果，按时间从新到旧排列，保留最近的 `limit` 个
+fn merge(groups: Vec<(Vec<SearchMatch>, bool)>, limit: usize) -> SearchResult {
+    let mut truncated = false;
+    let mut matches = Vec::new();
+    for (group, group_truncated) in groups {
+        truncated |= group_truncated;
+        matches.extend(group);
+    }
+    // 稳定排序：同一时间的匹配保持各来源内的顺序；时间格式固定，可以按字符串比较
+    matches.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    if matches.len() > limit {
+        matches.truncate(limit);
+        truncated = true;
+    }
+    SearchResult { matches, truncated }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = dir.path().join("dawei-gui.2024-01-31.log");
+        std::fs::write(
+            &app,
+            concat!(
+                "2024-01-31 10:00:00.000  INFO dawei_gui: Starting\n",
+                "2024-01-31 10:00:01.000  WARN webview: [main] Request failed: timeout\n",
+                "2024-01-31 10:00:02.000 ERROR backend: Request failed in backend\n",
+                "2024-01-31 10:00:03.000 ERROR dawei_gui::backend_process: Request failed\n",
+                "caused by: timeout\n",
+                "2024-01-31 10:00:04.000  INFO dawei_gui: Done\n",
+            ),
+        )
+        .unwrap();
+        let backend = dir.path().join("agentic.log");
+        std::fs::write(&backend, "2024-01-31 10:00:02,500 - dawei.server - ERROR - Request failed\n").unwrap();
+        let (app_files, backend_files) = (vec![app], vec![backend]);
+
+        let search = |pattern: &str, sources: Vec<LogSource>, since: Option<&str>| Search {
+            regex: Regex::new(pattern).unwrap(),
+            sources,
+            since: since.map(|since| log_query::parse_time(since).unwrap()),
+            until: None,
+            limit: 2,
+        };
+        let all = vec![LogSource::Tauri, LogSource::Webview, LogSource::Backend];
+
+        // 转发的后端 stderr 不搜索；超过上限时保留最近的匹配
+        let (matches, truncated) = search_files(FileKind::App, &app_files, &search("Request failed", all.clone(), None));
+        assert!(!truncated);
+        assert_eq!(matches.iter().map(|m| m.line).collect::<Vec<_>>(), [4, 2]);
+        assert_eq!(matches[0].source, LogSource::Tauri);
+        assert_eq!(matches[0].before.len(), CONTEXT_LINES);
+        assert_eq!(matches[0].after, ["caused by: timeout", "2024-01-31 10:00:04.000  INFO dawei_gui: Done"]);
+        assert_eq!(matches[1].source, LogSource::Webview);
+        assert_eq!(matches[1].before, ["2024-01-31 10:00:00.000  INFO dawei_gui: Starting"]);
+
+        let (matches, truncated) = search_files(FileKind::App, &app_files, &search("INFO|WARN|ERROR", all.clone(), None));
+        assert!(truncated);
+        assert_eq!(matches.iter().map(|m| m.line).collect::<Vec<_>>(), [6, 4]);
+
+        // 后续行沿用所在日志的时间和来源；按时间过滤
+        let since = Some("2024-01-31 10:00:02");
+        let (matches, _) = search_files(FileKind::App, &app_files, &search("time(out)?", all.clone(), since));
+        assert_eq!(matches.iter().map(|m| m.line).collect::<Vec<_>>(), [5]);
+        assert_eq!(matches[0].timestamp.as_deref(), Some("2024-01-31 10:00:03.000"));
+
+        let (matches, _) =
+            search_files(FileKind::App, &app_files, &search("failed", vec![LogSource::Webview], None));
+        assert_eq!(matches.iter().map(|m| m.line).collect::<Vec<_>>(), [2]);
+
+        let groups = vec![
+            search_files(FileKind::App, &app_files, &search("failed", all.clone(), None)),
+            search_files(FileKind::Backend, &backend_files, &search("failed", all, None)),
+        ];
+        let result = merge(groups, 2);
+        assert!(result.truncated);
+        let sources: Vec<_> = result.matches.iter().map(|m| m.source).collect();
+        assert_eq!(sources, [LogSource::Tauri, LogSource::Backend]);
+        assert_eq!(result.matches[1].timestamp.as_deref(), Some("2024-01-31 10:00:02.500"));
+    }
+}
@@ -0,0 +1,198 @@
+//! 通过 Channel 订阅实时日志
+//!
+//! `subscribe_logs` 为每个订阅保存一个有界队列：新日志（与时间线相同的来源，见
+//! [`crate::log_timeline`]）按订阅的过滤条件放入队列，后台任务每 200 毫秒把队列中的日志
+//! 整批通过 Channel 发送给前端。队列满时丢弃最旧的日志并累计丢弃数（随下一批发送），
+//! 后端短时间内大量输出日志时内存占用不会无限增长。页面关闭导致发送失败时自动取消订阅。
+
+use crate::log_timeline::{LogSource, TimelineEntry};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::ipc::Channel;
+use tracing::Level;
+
+/// 每个订阅最多缓存的日志条数
+const QUEUE_CAPACITY: usize = 1000;
+
+/// 发送间隔
+const FLUSH_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 是否有订阅（没有订阅时日志不需要复制）
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// 后台发送任务是否已启动
+static FLUSHER_STARTED: AtomicBool = AtomicBool::new(false);
+
+static NEXT_ID: AtomicU32 = AtomicU32::new(1);
+
+static SUBSCRIPTIONS: Mutex<Vec<Subscription>> = Mutex::new(Vec::new());
+
+/// 订阅的过滤条件，所有字段都可省略
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LogFilter {
+    /// 包含的来源，默认全部
+    pub sources: Option<Vec<LogSource>>,
+    /// 最低级别，例如 `warn`
+    pub level: Option<String>,
+}
+
+/// 一次发送的日志
+#[derive(Debug, Clone, Serialize)]
+pub struct LogBatch {
+    pub entries: Vec<TimelineEntry>,
+    /// 自上一批以来因队列已满丢弃的日志数
+    pub dropped: u64,
+}
+
+struct Subscription {
+    id: u32,
+    sources: Option<Vec<LogSource>>,
+    level: Option<Level>,
+    queue: VecDeque<TimelineEntry>,
+    dropped: u64,
+    channel: Channel<LogBatch>,
+}
+
+impl Subscription {
+    fn matches(&self, entry: &TimelineEntry) -> bool {
+        // tracing 中越详细的级别越大
+        self.sources.as_ref().is_none_or(|sources| sources.contains(&entry.source))
+            && self.level.is_none_or(|min| Level::from_str(&entry.level).is_ok_and(|level| level <= min))
+    }
+
+    /// 放入队列，队列满时丢弃最旧的
+    fn push(&mut self, entry: TimelineEntry) {
+        if self.queue.len() >= QUEUE_CAPACITY {
+            self.queue.pop_front();
+            self.dropped += 1;
+        }
+        self.queue.push_back(entry);
+    }
+
+    /// 取出待发送的日志，没有新日志也没有丢弃时返回 None
+    fn take_batch(&mut self) -> Option<LogBatch> {
+        if self.queue.is_empty() && self.dropped == 0 {
+            return None;
+        }
+        Some(LogBatch { entries: self.queue.drain(..).collect(), dropped: std::mem::take(&mut self.dropped) })
+    }
+}
+
+/// 是否有订阅
+pub fn active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+/// 把新日志放入匹配的订阅的队列
+pub fn publish(entry: &TimelineEntry) {
+    if !active() {
+        return;
+    }
+    let mut subscriptions = SUBSCRIPTIONS.lock().unwrap_or_else(|e| e.into_inner());
+    for subscription in subscriptions.iter_mut().filter(|subscription| subscription.matches(entry)) {
+        subscription.push(entry.clone());
+    }
+}
+
+/// 发送所有订阅队列中的日志，发送失败的订阅被移除
+fn flush() {
+    let batches: Vec<(u32, Channel<LogBatch>, LogBatch)> = SUBSCRIPTIONS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter_mut()
+        .filter_map(|subscription| Some((subscription.id, subscription.channel.clone(), subscription.take_batch()?)))
+        .collect();
+    // 发送时不持锁：发送过程中产生的日志还要放入队列
+    let failed: Vec<u32> = batches
+        .into_iter()
+        .filter_map(|(id, channel, batch)| channel.send(batch).is_err().then_some(id))
+        .collect();
+    for id in failed {
+        tracing::debug!("Log subscription {} closed", id);
+        unsubscribe(id);
+    }
+}
+
+/// 订阅实时日志，返回订阅 ID
+pub fn subscribe(filter: LogFilter, channel: Channel<LogBatch>) -> Result<u32, String> {
+    let level = match filter.level.as_deref().map(str::trim).filter(|level| !level.is_empty()) {
+        Some(level) => Some(Level::from_str(level).map_err(|_| format!("无效的日志级别: {}", level))?),
+        None => None,
+    };
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    {
+        let mut subscriptions = SUBSCRIPTIONS.lock().unwrap_or_else(|e| e.into_inner());
+        subscriptions.push(Subscription {
+            id,
+            sources: filter.sources,
+            level,
+            queue: VecDeque::new(),
+            dropped: 0,
+            channel,
+        });
+        ACTIVE.store(true, Ordering::Relaxed);
+    }
+
+    if !FLUSHER_STARTED.swap(true, Ordering::SeqCst) {
+        tauri::async_runtime::spawn(async {
+            loop {
+                tokio::time::sleep(FLUSH_INTERVAL).await;
+                if active() {
+                    flush();
+                }
+            }
+        });
+    }
+    Ok(id)
+}
+
+/// 取消订阅，订阅不存在时返回 false
+pub fn unsubscribe(id: u32) -> bool {
+    let mut subscriptions = SUBSCRIPTIONS.lock().unwrap_or_else(|e| e.into_inner());
+    let before = subscriptions.len();
+    subscriptions.retain(|subscription| subscription.id != id);
+    ACTIVE.store(!subscriptions.is_empty(), Ordering::Relaxed);
+    subscriptions.len() != before
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounded_queue_counts_drops() {
+        let entry = |source, level: &str, message: String| TimelineEntry {
+            source,
+            timestamp: "2024-01-31 10:00:00.000".to_string(),
+            level: level.to_string(),
+            target: String::new(),
+            message,
+        };
+        let mut subscription = Subscription {
+            id: 1,
+            sources: Some(vec![LogSource::Backend]),
+            level: Some(Level::INFO),
+            queue: VecDeque::new(),
+            dropped: 0,
+            channel: Channel::new(|_| Ok(())),
+        };
+        assert!(subscription.matches(&entry(LogSource::Backend, "WARN", String::new())));
+        assert!(!subscription.matches(&entry(LogSource::Backend, "DEBUG", String::new())));
+        assert!(!subscription.matches(&entry(LogSource::Tauri, "ERROR", String::new())));
+
+        assert!(subscription.take_batch().is_none());
+        for i in 0..QUEUE_CAPACITY + 5 {
+            subscription.push(entry(LogSource::Backend, "INFO", i.to_string()));
+        }
+        let batch = subscription.take_batch().unwrap();
+        assert_eq!(batch.dropped, 5);
+        assert_eq!(batch.entries.len(), QUEUE_CAPACITY);
+        assert_eq!(batch.entries[0].message, "5");
+        assert!(subscription.take_batch().is_none());
+    }
+}
@@ -0,0 +1,437 @@
+//! 统一日志时间线
+//!
+//! 把桌面端（Rust）、WebView（页面控制台）和后端（Python）的日志按时间合并成一个序列，
+//! 每条日志标明来源，用于排查跨进程的问题：
+//! - 桌面端：DAWEI_HOME/logs/app 下的日志（target 为 `webview` 的是页面转发的日志）
+//! - WebView：页面的 console.warn/error 和未捕获的异常通过 `log_webview` 命令写入桌面端日志
+//! - 后端：DAWEI_HOME/logs/agentic/agentic.log（及轮转的 .1-.5）。桌面端日志中转发的后端
+//!   stderr（target 为 `backend`）与后端日志文件重复，不计入时间线
+//!
+//! 开启 `set_log_streaming` 后，新日志实时通过 `log-timeline-entry` 事件发送给所有窗口。
+
+use crate::log_query::{self, LogQuery};
+use crate::logging::TIMESTAMP_FORMAT;
+use chrono::{Local, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use tauri::{AppHandle, Emitter};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::Layer;
+
+/// 实时日志事件，内容为 [`TimelineEntry`]
+pub const LOG_TIMELINE_EVENT: &str = "log-timeline-entry";
+
+/// 页面转发的日志的 target
+pub const WEBVIEW_TARGET: &str = "webview";
+
+/// 桌面端转发的后端 stderr 的 target
+pub const BACKEND_TARGET: &str = "backend";
+
+/// 默认返回条数
+const DEFAULT_LIMIT: usize = 500;
+
+/// 最多返回条数
+const MAX_LIMIT: usize = 5000;
+
+/// 后端日志的轮转文件数（与后端 RotatingFileHandler 的 backupCount 一致）
+const BACKEND_BACKUP_COUNT: usize = 5;
+
+/// 后端日志的时间格式（Python logging 默认格式）
+pub const BACKEND_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S,%3f";
+
+/// 把页面的 console.warn/error 和未捕获的异常转发到 `log_webview` 命令
+pub const CONSOLE_FORWARD_SCRIPT: &str = r#"
+(() => {
+    if (window.__DAWEI_CONSOLE_FORWARD__) return;
+    window.__DAWEI_CONSOLE_FORWARD__ = true;
+    const text = (value) => {
+        if (value instanceof Error) return value.stack || value.message;
+        if (typeof value === 'string') return value;
+        try { return JSON.stringify(value); } catch (_) { return String(value); }
+    };
+    const send = (level, args) => {
+        try {
+            const message = args.map(text).join(' ');
+            window.__TAURI_INTERNALS__.invoke('log_webview', { level, message }).catch(() => {});
+        } catch (_) {}
+    };
+    for (const level of ['warn', 'error']) {
+        const original = console[level];
+        console[level] = (...args) => {
+            send(level, args);
+            original.apply(console, args);
+        };
+    }
+    window.addEventListener('error', (e) => send('error', [e.error || e.message]));
+    window.addEventListener('unhandledrejection', (e) => send('error', ['Unhandled rejection:', e.reason]));
+})();
+"#;
+
+/// 是否实时发送新日志
+static STREAMING: AtomicBool = AtomicBool::new(false);
+
+static APP: OnceLock<AppHandle> = OnceLock::new();
+
+thread_local! {
+    /// 发送事件时产生的日志不再发送，避免递归
+    static EMITTING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// 日志来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogSource {
+    Tauri,
+    Webview,
+    Backend,
+}
+
+/// 时间线中的一条日志
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimelineEntry {
+    pub source: LogSource,
+    /// 本地时间，格式同桌面端日志
+    pub timestamp: String,
+    pub level: String,
+    /// 桌面端为 tracing target，后端为 logger 名称
+    pub target: String,
+    pub message: String,
+}
+
+/// 时间线查询条件，所有字段都可省略
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TimelineQuery {
+    /// 包含的来源，默认全部
+    pub sources: Option<Vec<LogSource>>,
+    /// 最低级别，例如 `warn`
+    pub level: Option<String>,
+    /// 开始时间（格式同 `get_app_logs`）
+    pub since: Option<String>,
+    /// 结束时间
+    pub until: Option<String>,
+    /// 返回最近的多少条，默认 500，最多 5000
+    pub limit: Option<usize>,
+}
+
+pub fn source_for_target(target: &str) -> LogSource {
+    if target == WEBVIEW_TARGET {
+        LogSource::Webview
+    } else {
+        LogSource::Tauri
+    }
+}
+
+/// 把 Python logging 的级别转换为 tracing 的级别
+fn backend_level(level: &str) -> Option<Level> {
+    match level {
+        "WARNING" => Some(Level::WARN),
+        "CRITICAL" | "FATAL" => Some(Level::ERROR),
+        other => Level::from_str(other).ok(),
+    }
+}
+
+/// 解析后端日志的第一行：`<时间> - [<会话 ID>] - <logger> - <级别> - <消息>`（会话 ID 可省略）
+fn parse_backend_line(line: &str) -> Option<(NaiveDateTime, Level, TimelineEntry)> {
+    let (time, rest) = NaiveDateTime::parse_and_remainder(line, BACKEND_TIMESTAMP_FORMAT).ok()?;
+    let mut rest = rest.strip_prefix(" - ")?;
+    if rest.starts_with('[') {
+        rest = rest.split_once("] - ")?.1;
+    }
+    let (target, rest) = rest.split_once(" - ")?;
+    let (level_text, message) = rest.split_once(" - ").unwrap_or((rest, ""));
+    let level = backend_level(level_text)?;
+    let entry = TimelineEntry {
+        source: LogSource::Backend,
+        timestamp: time.format(TIMESTAMP_FORMAT).to_string(),
+        level: level.to_string(),
+        target: target.to_string(),
+        message: message.to_string(),
+    };
+    Some((time, level, entry))
+}
+
+/// 存在的后端日志文件，从新到旧
+pub fn backend_log_files(main: &Path) -> Vec<PathBuf> {
+    let mut files = vec![main.to_path_buf()];
+    files.extend((1..=BACKEND_BACKUP_COUNT).map(|i| PathBuf::from(format!("{}.{}", main.display(), i))));
+    files.into_iter().filter(|path| path.is_file()).collect()
+}
+
+/// 来源当前写入的日志文件（页面日志写在桌面端日志中），还没有日志时返回 None
+pub fn current_log_file(source: LogSource) -> Option<PathBuf> {
+    match source {
+        LogSource::Tauri | LogSource::Webview => crate::logging::current_log_file(),
+        LogSource::Backend => Some(crate::backend_crash::backend_log_file()).filter(|path| path.is_file()),
+    }
+}
+
+/// 解析后的查询条件
+struct Filter {
+    level: Option<Level>,
+    since: Option<NaiveDateTime>,
+    until: Option<NaiveDateTime>,
+    limit: usize,
+}
+
+/// 读取后端日志中满足条件的最近 `limit` 条（从新到旧）
+fn backend_entries(files: &[PathBuf], filter: &Filter) -> Vec<(NaiveDateTime, TimelineEntry)> {
+    let mut result = Vec::new();
+    for path in files {
+        let Ok(bytes) = std::fs::read(path) else {
+            continue;
+        };
+        let content = String::from_utf8_lossy(&bytes);
+        let mut entries: Vec<(NaiveDateTime, Level, TimelineEntry)> = Vec::new();
+        for line in content.lines() {
+            match parse_backend_line(line) {
+                Some(entry) => entries.push(entry),
+                None => {
+                    if let Some((_, _, last)) = entries.last_mut() {
+                        last.message.push('\n');
+                        last.message.push_str(line);
+                    }
+                }
+            }
+        }
+        for (time, level, entry) in entries.into_iter().rev() {
+            if filter.since.is_some_and(|since| time < since) {
+                return result;
+            }
+            if filter.until.is_some_and(|until| time > until) || filter.level.is_some_and(|min| level > min) {
+                continue;
+            }
+            result.push((time, entry));
+            if result.len() == filter.limit {
+                return result;
+            }
+        }
+    }
+    result
+}
+
+/// 读取桌面端日志中满足条件的最近 `limit` 条（从新到旧），跳过转发的后端 stderr
+fn app_entries(query: &TimelineQuery, filter: &Filter, sources: &[LogSource]) -> Result<Vec<(NaiveDateTime, TimelineEntry)>, String> {
+    let mut result = Vec::new();
+    let mut page_query = LogQuery {
+        level: query.level.clone(),
+        since: query.since.clone(),
+        until: query.until.clone(),
+        limit: Some(filter.limit),
+        ..Default::default()
+    };
+    loop {
+        let page = log_query::query(&page_query)?;
+        for entry in page.entries {
+            let source = source_for_target(&entry.target);
+            if entry.target == BACKEND_TARGET || !sources.contains(&source) {
+                continue;
+            }
+            let Ok(time) = NaiveDateTime::parse_from_str(&entry.timestamp, TIMESTAMP_FORMAT) else {
+                continue;
+            };
+            // 页面日志都在 log_webview 命令的 span 中，不需要显示
+            let message = match entry.spans {
+                Some(spans) if source == LogSource::Tauri => format!("[{}] {}", spans, entry.message),
+                _ => entry.message,
+            };
+            result.push((
+                time,
+                TimelineEntry { source, timestamp: entry.timestamp, level: entry.level, target: entry.target, message },
+            ));
+            if result.len() == filter.limit {
+                return Ok(result);
+            }
+        }
+        match page.next_cursor {
+            Some(cursor) => page_query.cursor = Some(cursor),
+            None => return Ok(result),
+        }
+    }
+}
+
+/// 合并各来源的日志，返回满足条件的最近 `limit` 条，按时间从旧到新排列
+pub fn query(query: &TimelineQuery) -> Result<Vec<TimelineEntry>, String> {
+    let sources = query.sources.clone().unwrap_or_else(|| vec![LogSource::Tauri, LogSource::Webview, LogSource::Backend]);
+    let filter = Filter {
+        level: match query.level.as_deref().map(str::trim).filter(|level| !level.is_empty()) {
+            Some(level) => Some(Level::from_str(level).map_err(|_| format!("无效的日志级别: {}", level))?),
+            None => None,
+        },
+        since: query.since.as_deref().map(log_query::parse_time).transpose()?,
+        until: query.until.as_deref().map(log_query::parse_time).transpose()?,
+        limit: query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT),
+    };
+
+    let mut entries = Vec::new();
+    if sources.contains(&LogSource::Tauri) || sources.contains(&LogSource::Webview) {
+        entries.extend(app_entries(query, &filter, &sources)?);
+    }
+    if sources.contains(&LogSource::Backend) {
+        entries.extend(backend_entries(&backend_log_files(&crate::backend_crash::backend_log_file()), &filter));
+    }
+    // 稳定排序：同一时间的日志保持各来源内的顺序
+    entries.sort_by_key(|(time, _)| *time);
+    let skip = entries.len().saturating_sub(filter.limit);
+    Ok(entries.into_iter().skip(skip).map(|(_, entry)| entry).collect())
+}
+
+/// 记录页面转发的日志
+pub fn log_webview(window: &str, level: &str, message: &str) {
+    match level {
+        "error" => tracing::error!(target: WEBVIEW_TARGET, "[{}] {}", window, message),
+        "warn" => tracing::warn!(target: WEBVIEW_TARGET, "[{}] {}", window, message),
+        "debug" => tracing::debug!(target: WEBVIEW_TARGET, "[{}] {}", window, message),
+        _ => tracing::info!(target: WEBVIEW_TARGET, "[{}] {}", window, message),
+    }
+}
+
+/// 是否需要实时转发新日志（开启了实时日志事件或有 Channel 订阅）
+fn streaming() -> bool {
+    STREAMING.load(Ordering::Relaxed) || crate::log_stream::active()
+}
+
+fn emit(entry: TimelineEntry) {
+    EMITTING.with(|emitting| {
+        if emitting.replace(true) {
+            return;
+        }
+        crate::log_stream::publish(&entry);
+        if let Some(app) = APP.get().filter(|_| STREAMING.load(Ordering::Relaxed)) {
+            let _ = app.emit(LOG_TIMELINE_EVENT, entry);
+        }
+        emitting.set(false);
+    });
+}
+
+/// 开启或关闭实时日志事件
+pub fn set_streaming(enabled: bool) {
+    STREAMING.store(enabled, Ordering::SeqCst);
+}
+
+/// 保存 AppHandle 用于发送实时日志事件
+pub fn init(app: &AppHandle) {
+    let _ = APP.set(app.clone());
+}
+
+/// 读取日志消息
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: Vec<String>,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.fields.push(format!("{}={:?}", field.name(), value));
+        }
+    }
+}
+
+/// 把桌面端的新日志实时发送到时间线
+pub struct TimelineLayer;
+
+impl<S: Subscriber> Layer<S> for TimelineLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        if !streaming() || metadata.target() == BACKEND_TARGET {
+            return;
+        }
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let mut message = visitor.message;
+        for field in visitor.fields {
+            message.push(' ');
+            message.push_str(&field);
+        }
+        emit(TimelineEntry {
+            source: source_for_target(metadata.target()),
+            timestamp: Local::now().format(TIMESTAMP_FORMAT).to_string(),
+            level: metadata.level().to_string(),
+            target: metadata.target().to_string(),
+            message,
+        });
+    }
+}
+
+/// 把后端日志文件中新增的行实时发送到时间线
+#[derive(Default)]
+pub struct BackendStream {
+    /// 上一条日志的级别和 logger，多行消息的后续行沿用
+    last: Option<(String, String)>,
+}
+
+impl BackendStream {
+    pub fn feed(&mut self, line: &str) {
+        if !streaming() {
+            return;
+        }
+        let entry = match parse_backend_line(line) {
+            Some((_, _, entry)) => {
+                self.last = Some((entry.level.clone(), entry.target.clone()));
+                entry
+            }
+            None => {
+                let (level, target) = self.last.clone().unwrap_or_else(|| (Level::INFO.to_string(), String::new()));
+                TimelineEntry {
+                    source: LogSource::Backend,
+                    timestamp: Local::now().format(TIMESTAMP_FORMAT).to_string(),
+                    level,
+                    target,
+                    message: line.to_string(),
+                }
+            }
+        };
+        emit(entry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backend_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = dir.path().join("agentic.log");
+        std::fs::write(
+            dir.path().join("agentic.log.1"),
+            "2024-01-31 09:00:00,000 - dawei.server - INFO - Server started\n",
+        )
+        .unwrap();
+        std::fs::write(
+            &main,
+            concat!(
+                "2024-01-31 10:00:00,500 - [abc] - dawei.agentic - WARNING - Slow response\n",
+                "2024-01-31 10:00:01,000 - [abc] - dawei.agentic - ERROR - Task failed\n",
+                "Traceback (most recent call last):\n",
+                "ValueError: boom\n",
+            ),
+        )
+        .unwrap();
+
+        let files = backend_log_files(&main);
+        assert_eq!(files.len(), 2);
+        let filter = Filter { level: None, since: None, until: None, limit: 10 };
+        let entries = backend_entries(&files, &filter);
+        let levels: Vec<_> = entries.iter().map(|(_, e)| e.level.as_str()).collect();
+        assert_eq!(levels, ["ERROR", "WARN", "INFO"]);
+        assert_eq!(entries[0].1.target, "dawei.agentic");
+        assert_eq!(entries[0].1.timestamp, "2024-01-31 10:00:01.000");
+        assert!(entries[0].1.message.ends_with("ValueError: boom"));
+
+        let filter = Filter { level: Some(Level::WARN), since: None, until: None, limit: 1 };
+        let entries = backend_entries(&files, &filter);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].1.message.lines().next(), Some("Task failed"));
+    }
+}
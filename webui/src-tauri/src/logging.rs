@@ -0,0 +1,348 @@
+//! 应用日志模块
+//!
+//! 桌面端日志统一保存在 DAWEI_HOME/logs/app 下（后端日志在 DAWEI_HOME/logs/agentic）。
+//! 日志通过 `tracing` 同时输出到 stderr 和按天滚动的日志文件，单个文件超过大小上限时
+//! 当天的日志继续写入新的序号文件，只保留最近的若干个文件。
+//! 日志级别由 `log_level` 设置控制，`DAWEI_LOG` 环境变量在启动时覆盖设置，格式同
+//! `tracing_subscriber::filter::Targets`，例如 `debug` 或 `dawei_gui=debug,info`，默认为 `info`。
+//! 运行中修改设置（`set_log_level` 命令或直接编辑设置文件）立即生效，无需重启。
+
+use chrono::{Local, NaiveDate};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use tauri::{AppHandle, Listener};
+use tracing::level_filters::LevelFilter;
+use tracing::{Event, Subscriber};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::fmt::format::{FormatEvent, FormatFields, Writer};
+use tracing_subscriber::fmt::{time::FormatTime, FmtContext, FormattedFields};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{reload, Registry};
+
+/// 读取日志尾部时最多读取的字节数，避免超大日志拖慢崩溃处理
+const TAIL_MAX_BYTES: u64 = 512 * 1024;
+
+/// 单个日志文件的大小上限
+const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// 最多保留的日志文件数
+const MAX_FILES: usize = 30;
+
+/// 日志文件名前缀，完整文件名为 `dawei-gui.2024-01-31.log`、`dawei-gui.2024-01-31.1.log`
+const FILE_PREFIX: &str = "dawei-gui";
+
+/// 日志时间戳格式（本地时间）
+pub const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
+
+/// 控制日志级别的环境变量
+const LOG_ENV: &str = "DAWEI_LOG";
+
+/// 默认日志级别
+const DEFAULT_LEVEL: &str = "info";
+
+/// 运行中修改日志级别的句柄
+static FILTER: OnceLock<reload::Handle<Targets, Registry>> = OnceLock::new();
+
+/// 桌面端日志目录
+pub fn app_log_dir() -> PathBuf {
+    crate::get_dawei_home().join("logs").join("app")
+}
+
+fn log_file_name(date: NaiveDate, index: u32) -> String {
+    if index == 0 {
+        format!("{}.{}.log", FILE_PREFIX, date)
+    } else {
+        format!("{}.{}.{}.log", FILE_PREFIX, date, index)
+    }
+}
+
+/// 按天和大小滚动的日志文件
+struct RollingFile {
+    dir: PathBuf,
+    max_size: u64,
+    max_files: usize,
+    date: NaiveDate,
+    index: u32,
+    file: Option<File>,
+    size: u64,
+}
+
+impl RollingFile {
+    fn new(dir: PathBuf, max_size: u64, max_files: usize) -> Self {
+        Self { dir, max_size, max_files, date: Local::now().date_naive(), index: 0, file: None, size: 0 }
+    }
+
+    /// 返回当前应写入的文件，日期变化或超过大小上限时切换到新文件
+    fn current(&mut self) -> std::io::Result<&mut File> {
+        let today = Local::now().date_naive();
+        if today != self.date {
+            self.date = today;
+            self.index = 0;
+            self.file = None;
+        } else if self.file.is_some() && self.size >= self.max_size {
+            self.index += 1;
+            self.file = None;
+        }
+
+        if self.file.is_none() {
+            std::fs::create_dir_all(&self.dir)?;
+            // 跳过当天已写满的文件（例如重启后）
+            let mut path = self.dir.join(log_file_name(self.date, self.index));
+            let mut size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            while size >= self.max_size {
+                self.index += 1;
+                path = self.dir.join(log_file_name(self.date, self.index));
+                size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            }
+            self.file = Some(OpenOptions::new().create(true).append(true).open(&path)?);
+            self.size = size;
+            prune_logs(&self.dir, self.max_files);
+        }
+        Ok(self.file.as_mut().expect("log file opened above"))
+    }
+}
+
+impl Write for RollingFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.current()?.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self.file.as_mut() {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 从日志文件名中解析日期和序号
+fn parse_log_file_name(name: &str) -> Option<(NaiveDate, u32)> {
+    let stem = name.strip_prefix(FILE_PREFIX)?.strip_prefix('.')?.strip_suffix(".log")?;
+    let (date, index) = match stem.split_once('.') {
+        Some((date, index)) => (date, index.parse().ok()?),
+        None => (stem, 0),
+    };
+    Some((date.parse().ok()?, index))
+}
+
+/// 目录中的日志文件，从新到旧排列
+pub fn log_files_in(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut logs: Vec<_> = entries
+        .flatten()
+        .filter_map(|entry| Some((parse_log_file_name(&entry.file_name().to_string_lossy())?, entry.path())))
+        .collect();
+    logs.sort_by_key(|(key, _)| std::cmp::Reverse(*key));
+    logs.into_iter().map(|(_, path)| path).collect()
+}
+
+/// 删除最旧的日志文件，只保留 `keep` 个
+fn prune_logs(dir: &Path, keep: usize) {
+    for path in log_files_in(dir).into_iter().skip(keep) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// 使用本地时间的日志时间戳
+struct LocalTime;
+
+impl FormatTime for LocalTime {
+    fn format_time(&self, w: &mut Writer<'_>) -> std::fmt::Result {
+        write!(w, "{}", Local::now().format(TIMESTAMP_FORMAT))
+    }
+}
+
+/// 日志文件的行格式：`<时间> <级别> <target> [<span> > <span>]: <消息>`，
+/// 没有 span 时省略方括号部分，便于 [`crate::log_query`] 解析
+struct FileFormat;
+
+impl<S, N> FormatEvent<S, N> for FileFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> std::fmt::Result {
+        let metadata = event.metadata();
+        write!(writer, "{} {:>5} {}", Local::now().format(TIMESTAMP_FORMAT), metadata.level(), metadata.target())?;
+        if let Some(scope) = ctx.event_scope() {
+            write!(writer, " [")?;
+            for (i, span) in scope.from_root().enumerate() {
+                if i > 0 {
+                    write!(writer, " > ")?;
+                }
+                write!(writer, "{}", span.name())?;
+                let extensions = span.extensions();
+                if let Some(fields) = extensions.get::<FormattedFields<N>>().filter(|fields| !fields.is_empty()) {
+                    write!(writer, "{{{}}}", fields)?;
+                }
+            }
+            write!(writer, "]")?;
+        }
+        write!(writer, ": ")?;
+        ctx.field_format().format_fields(writer.by_ref(), event)?;
+        writeln!(writer)
+    }
+}
+
+/// 解析日志级别，None 或空字符串为默认级别
+fn parse_level(level: Option<&str>) -> Result<Targets, String> {
+    let level = level.map(str::trim).filter(|level| !level.is_empty()).unwrap_or(DEFAULT_LEVEL);
+    level.parse::<Targets>().map_err(|e| format!("无效的日志级别 {:?}: {}", level, e))
+}
+
+/// 初始化日志：输出到 stderr 和 DAWEI_HOME/logs/app
+///
+/// 只能调用一次，应尽早调用（在命令行参数处理之后），之前的日志不会被记录。
+pub fn init() {
+    // 设置在日志初始化之后读取，加载设置时的警告才会被记录
+    let env_level = std::env::var(LOG_ENV).ok();
+    let filter = parse_level(env_level.as_deref()).unwrap_or_else(|e| {
+        eprintln!("{}: {}", LOG_ENV, e);
+        Targets::new().with_default(LevelFilter::INFO)
+    });
+    let (filter, handle) = reload::Layer::new(filter);
+    let file = RollingFile::new(app_log_dir(), MAX_FILE_SIZE, MAX_FILES);
+
+    // 日志级别只过滤输出，慢命令检测需要的 span 不受影响
+    let output = tracing_subscriber::fmt::layer()
+        .with_timer(LocalTime)
+        .with_writer(std::io::stderr)
+        .and_then(crate::log_timeline::TimelineLayer)
+        .and_then(tracing_subscriber::fmt::layer().event_format(FileFormat).with_ansi(false).with_writer(Mutex::new(file)))
+        .with_filter(filter);
+    let result = tracing_subscriber::registry().with(output).with(crate::slow_command::layer()).try_init();
+    if let Err(e) = result {
+        eprintln!("Failed to initialize logging: {}", e);
+        return;
+    }
+    let _ = FILTER.set(handle);
+
+    if env_level.is_none() {
+        if let Some(level) = crate::settings::current().log_level {
+            if let Err(e) = apply(Some(&level)) {
+                tracing::warn!("{}", e);
+            }
+        }
+    }
+}
+
+/// 修改当前的日志级别（不保存到设置）
+fn apply(level: Option<&str>) -> Result<String, String> {
+    let filter = parse_level(level)?;
+    let description = filter.to_string();
+    let handle = FILTER.get().ok_or("日志未初始化")?;
+    handle.reload(filter).map_err(|e| e.to_string())?;
+    Ok(description)
+}
+
+/// 设置中的日志级别变化时立即生效（包括直接编辑设置文件）
+pub fn watch(app: &AppHandle) {
+    app.listen_any(crate::settings::SETTINGS_CHANGED_EVENT, |event| {
+        let Ok(change) = serde_json::from_str::<serde_json::Value>(event.payload()) else {
+            return;
+        };
+        if change["changed"].get("log_level").is_none() {
+            return;
+        }
+        match apply(change["settings"]["log_level"].as_str()) {
+            Ok(level) => tracing::info!("Log level changed to {}", level),
+            Err(e) => tracing::warn!("{}", e),
+        }
+    });
+}
+
+/// 修改日志级别并保存到设置，None 恢复默认级别，返回生效的级别
+pub fn set_level(app: &AppHandle, level: Option<String>) -> Result<String, String> {
+    // 先校验，避免把无效的级别写入设置
+    let description = parse_level(level.as_deref())?.to_string();
+    // 设置变更事件会触发 apply
+    crate::settings::update(app, serde_json::json!({ "log_level": level }))?;
+    Ok(description)
+}
+
+/// 当前（最近修改的）桌面端日志文件
+pub fn current_log_file() -> Option<PathBuf> {
+    std::fs::read_dir(app_log_dir())
+        .ok()?
+        .flatten()
+        .filter(|entry| entry.file_type().map(|t| t.is_file()).unwrap_or(false))
+        .filter_map(|entry| {
+            let modified = entry.metadata().and_then(|m| m.modified()).ok()?;
+            Some((modified, entry.path()))
+        })
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+}
+
+/// 读取文件最后 `max_lines` 行
+pub fn tail_lines(path: &Path, max_lines: usize) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let start = len.saturating_sub(TAIL_MAX_BYTES);
+    file.seek(SeekFrom::Start(start))?;
+
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf)?;
+    let content = String::from_utf8_lossy(&buf);
+
+    let mut lines: Vec<&str> = content.lines().collect();
+    // 从文件中间开始读时第一行可能不完整
+    if start > 0 && !lines.is_empty() {
+        lines.remove(0);
+    }
+    let skip = lines.len().saturating_sub(max_lines);
+    Ok(lines[skip..].join("\n"))
+}
+
+/// 当前桌面端日志的最后 `max_lines` 行，没有日志时返回空字符串
+pub fn app_log_tail(max_lines: usize) -> String {
+    current_log_file()
+        .and_then(|path| tail_lines(&path, max_lines).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tail_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        let content: Vec<String> = (0..1000).map(|i| format!("line {}", i)).collect();
+        std::fs::write(&path, content.join("\n")).unwrap();
+
+        let tail = tail_lines(&path, 3).unwrap();
+        assert_eq!(tail, "line 997\nline 998\nline 999");
+    }
+
+    #[test]
+    fn test_parse_level() {
+        assert_eq!(parse_level(None).unwrap().to_string(), "info");
+        assert_eq!(parse_level(Some(" ")).unwrap().to_string(), "info");
+        assert_eq!(parse_level(Some("dawei_gui=debug,warn")).unwrap().default_level(), Some(LevelFilter::WARN));
+        assert!(parse_level(Some("dawei_gui=loud")).is_err());
+    }
+
+    #[test]
+    fn test_rolling_file_rolls_over_at_size_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut file = RollingFile::new(dir.path().to_path_buf(), 8, 2);
+        for _ in 0..3 {
+            file.write_all(b"12345678\n").unwrap();
+        }
+
+        let date = Local::now().date_naive();
+        // 第三个文件创建时最旧的文件被删除
+        assert!(!dir.path().join(log_file_name(date, 0)).exists());
+        assert_eq!(std::fs::read_to_string(dir.path().join(log_file_name(date, 1))).unwrap(), "12345678\n");
+        assert_eq!(std::fs::read_to_string(dir.path().join(log_file_name(date, 2))).unwrap(), "12345678\n");
+    }
+}
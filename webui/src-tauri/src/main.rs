@@ -8,9 +8,95 @@ use tauri::Manager;
 use tauri::Emitter;
 
 // ==================== 崩溃处理模块 ====================
+mod backend_crash;
+mod breadcrumbs;
 mod crash_handler;
+mod crash_monitor;
+mod crash_upload;
+mod crash_viewer;
+mod hang_watchdog;
 use crash_handler::{setup_panic_hook, get_all_crash_reports, clear_all_crash_reports};
 
+// ==================== 功能模块 ====================
+mod about;
+mod always_on_top;
+mod atomic_file;
+mod attachments;
+mod backend_process;
+mod backend_profile;
+mod backend_snapshot;
+mod backend_stats;
+mod backend_update;
+mod bookmarks;
+mod changelog;
+mod cleanup;
+mod cli;
+mod config_transfer;
+mod connectivity;
+mod dawei_home;
+mod devtools;
+mod diagnostics;
+mod disk_space;
+mod download;
+mod drag_out;
+mod editor;
+mod env_file;
+mod file_dialog;
+mod file_drop;
+mod file_manager;
+mod first_run;
+mod hardware;
+mod hotkey;
+mod i18n;
+mod integrity;
+mod kiosk;
+mod log_query;
+mod log_search;
+mod log_stream;
+mod log_timeline;
+mod logging;
+mod menu;
+mod migrations;
+mod offline_wheels;
+mod path_normalize;
+mod pdf_export;
+mod policy;
+mod port_check;
+mod portable;
+mod quit_guard;
+mod recent_workspaces;
+mod recycle_bin;
+mod related_processes;
+mod reset;
+mod self_test;
+mod session;
+mod settings;
+mod single_instance;
+mod slow_command;
+mod splash;
+mod startup_metrics;
+mod symlink_policy;
+mod task_progress;
+mod telemetry;
+mod theme;
+mod titlebar;
+mod tray;
+mod update_schedule;
+mod updater;
+mod upgrade;
+mod window_state;
+mod workspace_archive;
+mod workspace_check;
+mod workspace_files;
+mod workspace_lock;
+mod workspace_search;
+mod workspace_stats;
+mod workspace_template;
+mod workspace_tree;
+mod workspace_watcher;
+mod workspace_window;
+mod zoom;
+
 /// Get UV executable path (shared helper function)
 fn get_uv_path() -> PathBuf {
     use std::process::Command;
@@ -46,12 +132,11 @@ fn get_uv_path() -> PathBuf {
     let uv_path_abs = if is_standalone_uv {
         // Standalone UV is already an absolute path (exe_dir + resources/...)
         // Try canonicalize, but if it fails, use the path as-is
-        uv_path.canonicalize().unwrap_or_else(|_| uv_path.clone())
+        symlink_policy::canonicalize(&uv_path).unwrap_or_else(|_| uv_path.clone())
     } else {
         // System UV: try canonicalize first
-        let canonical = uv_path.canonicalize();
-        if canonical.is_ok() {
-            canonical.unwrap()
+        if let Ok(canonical) = symlink_policy::canonicalize(&uv_path) {
+            canonical
         } else {
             // If canonicalize fails (e.g., "uv" is not absolute), find it in PATH
             #[cfg(unix)]
@@ -93,48 +178,47 @@ fn get_uv_path() -> PathBuf {
     uv_path_abs
 }
 
-/// Get Python information (version and path) using uv
-#[tauri::command]
-async fn get_python_info() -> Result<String, String> {
+/// Find the Python interpreter: bundled python-env first, then `uv python find`
+fn find_python(uv_path: &std::path::Path) -> Option<PathBuf> {
     use std::process::Command;
-    use std::path::PathBuf;
-    use std::fs::OpenOptions;
-    use std::io::Write;
-
-    // Get UV path using shared helper
-    let uv_path_final = get_uv_path();
 
     // Check if standalone Python environment exists
-    let exe_path = std::env::current_exe().unwrap();
-    let exe_dir = exe_path.parent().unwrap();
+    let exe_path = std::env::current_exe().ok()?;
+    let exe_dir = exe_path.parent()?;
 
     #[cfg(unix)]
     let standalone_python = exe_dir.join("resources/python-env/bin/python");
     #[cfg(windows)]
     let standalone_python = exe_dir.join("resources/python-env/Scripts/python.exe");
 
-    let python_path = if standalone_python.exists() {
+    if standalone_python.exists() {
         // Use standalone Python environment
-        standalone_python
-    } else {
-        // Fallback: use uv to find Python
-        let python_output = Command::new(&uv_path_final)
-            .args(["python", "find"])
-            .output();
-
-        match python_output {
-            Ok(output) if output.status.success() => {
-                let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                PathBuf::from(path)
-            }
-            _ => {
-                return Err("无法找到 Python 环境".to_string());
-            }
-        }
+        return Some(standalone_python);
+    }
+
+    // Fallback: use uv to find Python
+    let output = Command::new(uv_path).args(["python", "find"]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!path.is_empty()).then(|| PathBuf::from(path))
+}
+
+/// Get Python information (version and path) using uv
+#[tauri::command]
+async fn get_python_info() -> Result<String, String> {
+    use std::process::Command;
+
+    // Get UV path using shared helper
+    let uv_path_final = get_uv_path();
+
+    let Some(python_path) = find_python(&uv_path_final) else {
+        return Err("无法找到 Python 环境".to_string());
     };
 
     // Get absolute path for Python
-    let python_path_abs = python_path.canonicalize().unwrap_or_else(|_| python_path.clone());
+    let python_path_abs = symlink_policy::canonicalize(&python_path).unwrap_or_else(|_| python_path.clone());
 
     // Get Python version directly from Python
     let version_output = Command::new(&python_path_abs)
@@ -148,31 +232,11 @@ async fn get_python_info() -> Result<String, String> {
             let uv_path_str = uv_path_final.display().to_string();
 
             // Write paths to .env file in the davybot executable directory
-            let exe_path = std::env::current_exe().unwrap();
-            let exe_dir = exe_path.parent().unwrap();
-            let env_file = exe_dir.join(".env");
-
-            let env_content = format!(
-                "DAWEI_PYTHON_PATH={}\nDAWEI_UV_PATH={}\n",
-                python_path_str, uv_path_str
-            );
-
-            match OpenOptions::new()
-                .write(true)
-                .create(true)
-                .truncate(true)
-                .open(&env_file)
-            {
-                Ok(mut file) => {
-                    if let Err(e) = file.write_all(env_content.as_bytes()) {
-                        eprintln!("Warning: Failed to write .env file: {}", e);
-                    } else {
-                        eprintln!("✓ Environment paths written to: {:?}", env_file);
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Warning: Failed to create .env file: {}", e);
-                }
+            let written = env_file::set(env_file::EnvTarget::App, "DAWEI_PYTHON_PATH", &python_path_str)
+                .and_then(|_| env_file::set(env_file::EnvTarget::App, "DAWEI_UV_PATH", &uv_path_str));
+            match written {
+                Ok(()) => tracing::info!("Environment paths written to: {:?}", env_file::EnvTarget::App.path()),
+                Err(e) => tracing::warn!("Failed to write .env file: {}", e),
             }
 
             Ok(format!("{} @ {}\nUV: {}", version_str, python_path_str, uv_path_str))
@@ -183,13 +247,27 @@ async fn get_python_info() -> Result<String, String> {
     }
 }
 
+/// 任务栏进度中的后端启动任务
+const BACKEND_TASK: &str = "backend";
+
+/// 等待后端启动完成（可以访问）的最长时间
+const BACKEND_START_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
 /// Start backend command - unified for both dev and standalone
 #[tauri::command]
+#[tracing::instrument(skip_all)]
 async fn start_backend(app: tauri::AppHandle) -> Result<String, String> {
-    use std::process::Command;
+    use std::process::{Command, Stdio};
+
+    // 协同升级正在更新后端环境，完成后由升级流程启动后端
+    if upgrade::is_upgrading_backend() {
+        return Err("正在升级后端，完成后会自动启动".to_string());
+    }
 
     let mut logs = Vec::new();
     logs.push("🚀 [start_backend] Starting backend server...".to_string());
+    splash::progress(&app, i18n::Text::SplashStartingBackend);
+    task_progress::update(&app, BACKEND_TASK, task_progress::TaskState::Running(None));
 
     // Get UV path using shared helper (ensures consistency with get_python_info)
     let uv_path = get_uv_path();
@@ -200,27 +278,72 @@ async fn start_backend(app: tauri::AppHandle) -> Result<String, String> {
     let exe_dir = exe_path.parent().unwrap();
     logs.push(format!("✓ [start_backend] Executable location: {:?}", exe_path));
 
-    // Detect if running in dev mode using debug_assertions
-    let is_dev = cfg!(debug_assertions);
+    // Backend profile selected in settings (port, log level, env vars, source checkout)
+    let (profile_name, profile) = backend_profile::active()
+        .map(|(name, profile)| (Some(name), profile))
+        .unwrap_or_default();
+    let server_args = profile.server_args();
+    if let Some(name) = &profile_name {
+        logs.push(format!("✓ [start_backend] Using backend profile: {}", name));
+    }
+
+    // The backend cannot bind an occupied port; record who holds it so the user can act on it
+    let port = profile.port.unwrap_or(backend_profile::DEFAULT_PORT);
+    if let Ok(diagnosis) = tauri::async_runtime::spawn_blocking(move || port_check::diagnose(port)).await {
+        if diagnosis.in_use {
+            let holder = diagnosis
+                .owner
+                .map(|owner| format!("{} (PID: {})", owner.name, owner.pid))
+                .unwrap_or_else(|| "unknown process".to_string());
+            logs.push(format!("⚠️ [start_backend] Port {} is already in use by {}", port, holder));
+        }
+    }
+
+    // Run from a source checkout when the profile points to one, or in dev mode (debug_assertions);
+    // never in locked-down deployments
+    let source_dir = if policy::is_locked_down() {
+        None
+    } else {
+        profile.agent_dir.clone().or_else(|| {
+            cfg!(debug_assertions).then(|| PathBuf::from("/home/dev007/ws/davybot-proxy/agent"))
+        })
+    };
 
-    let result = if is_dev {
+    let result = if let Some(agent_dir) = source_dir {
         // Dev mode: use project's agent directory as working directory
-        let agent_dir = PathBuf::from("/home/dev007/ws/davybot-proxy/agent");
-        logs.push(format!("✓ [start_backend] Detected dev mode"));
+        logs.push("✓ [start_backend] Detected dev mode".to_string());
 
-        let full_command = format!("{} run --directory {} dawei server start",
-            uv_path.display(), agent_dir.display());
+        // The logged command is built from the same arguments that are passed to uv
+        let mut uv_args: Vec<std::ffi::OsString> = vec!["run".into()];
+        uv_args.extend(offline_wheels::index_args(None));
+        uv_args.extend(["--directory".into(), agent_dir.clone().into_os_string()]);
+        uv_args.extend(["dawei", "server", "start"].map(Into::into));
+        uv_args.extend(server_args.iter().map(Into::into));
+        let full_command = format!("{} {}",
+            uv_path.display(), uv_args.iter().map(|arg| arg.to_string_lossy()).collect::<Vec<_>>().join(" "));
 
         logs.push(format!("📁 [start_backend] Working directory: {:?}", agent_dir));
+        if let Some(offline) = offline_wheels::detect() {
+            logs.push(format!("✓ [start_backend] Installing from offline wheels: {:?}", offline.dir));
+        }
         logs.push(format!("⏳ [start_backend] Full command: {}", full_command));
 
-        Command::new(&uv_path)
-            .args(["run", "--directory", agent_dir.to_str().unwrap(), "dawei", "server", "start"])
-            .current_dir(&agent_dir)
-            .spawn()
+        // `uv run` syncs the virtual environment first, which can download hundreds of MB
+        match disk_space::ensure_free(&agent_dir, disk_space::INSTALL_MIN_FREE, "同步后端依赖") {
+            Err(e) => Err(std::io::Error::other(e)),
+            Ok(()) => Command::new(&uv_path)
+                .args(&uv_args)
+                .envs(&profile.env)
+                .current_dir(&agent_dir)
+                .env(session::SESSION_ENV, session::session_id())
+                .env(i18n::LOCALE_ENV, i18n::Locale::current().tag())
+                .env("DAWEI_HOME", get_dawei_home())
+                .stderr(Stdio::piped())
+                .spawn(),
+        }
     } else {
         // Standalone mode: use tauri app directory as working directory
-        logs.push(format!("✓ [start_backend] Detected standalone mode"));
+        logs.push("✓ [start_backend] Detected standalone mode".to_string());
 
         let venv_path = exe_dir.join("resources/python-env");
 
@@ -258,20 +381,26 @@ async fn start_backend(app: tauri::AppHandle) -> Result<String, String> {
 
         // Method 1: Try direct dawei.exe execution
         if dawei_exe.exists() {
-            logs.push(format!("🎯 [start_backend] Method 1: Trying direct dawei.exe execution"));
-            let full_command = format!("{:?} server start", dawei_exe);
+            logs.push("🎯 [start_backend] Method 1: Trying direct dawei.exe execution".to_string());
+            let full_command = format!("{:?} server start {}", dawei_exe, server_args.join(" "));
             logs.push(format!("⏳ [start_backend] Full command: {}", full_command));
 
             spawn_result = Some(Command::new(&dawei_exe)
                 .args(["server", "start"])
+                .args(&server_args)
+                .envs(&profile.env)
                 .env("VIRTUAL_ENV", &venv_path)
                 .env("PATH", &path_with_venv)
+                .env(session::SESSION_ENV, session::session_id())
+                .env(i18n::LOCALE_ENV, i18n::Locale::current().tag())
+                .env("DAWEI_HOME", get_dawei_home())
                 .current_dir(exe_dir)
+                .stderr(Stdio::piped())
                 .spawn());
 
             match &spawn_result {
                 Some(Ok(_)) => {
-                    logs.push(format!("✅ [start_backend] Method 1 (direct exe) succeeded"));
+                    logs.push("✅ [start_backend] Method 1 (direct exe) succeeded".to_string());
                 }
                 Some(Err(e)) => {
                     logs.push(format!("⚠️  [start_backend] Method 1 (direct exe) failed: {}", e));
@@ -279,13 +408,13 @@ async fn start_backend(app: tauri::AppHandle) -> Result<String, String> {
                 None => {}
             }
         } else {
-            logs.push(format!("⚠️  [start_backend] dawei.exe not found, skipping Method 1"));
+            logs.push("⚠️  [start_backend] dawei.exe not found, skipping Method 1".to_string());
         }
 
         // Method 2: Python module invocation (fallback)
         if spawn_result.is_none() || spawn_result.as_ref().unwrap().is_err() {
-            logs.push(format!("🎯 [start_backend] Method 2: Trying Python module invocation"));
-            let full_command = format!("{:?} -m dawei.cli.dawei server start", python_executable);
+            logs.push("🎯 [start_backend] Method 2: Trying Python module invocation".to_string());
+            let full_command = format!("{:?} -m dawei.cli.dawei server start {}", python_executable, server_args.join(" "));
 
             logs.push(format!("📁 [start_backend] Working directory: {:?}", exe_dir));
             logs.push(format!("🐍 [start_backend] Python executable: {:?}", python_executable));
@@ -293,14 +422,20 @@ async fn start_backend(app: tauri::AppHandle) -> Result<String, String> {
 
             spawn_result = Some(Command::new(&python_executable)
                 .args(["-m", "dawei.cli.dawei", "server", "start"])
+                .args(&server_args)
+                .envs(&profile.env)
                 .env("VIRTUAL_ENV", &venv_path)
                 .env("PATH", &path_with_venv)
+                .env(session::SESSION_ENV, session::session_id())
+                .env(i18n::LOCALE_ENV, i18n::Locale::current().tag())
+                .env("DAWEI_HOME", get_dawei_home())
                 .current_dir(exe_dir)
+                .stderr(Stdio::piped())
                 .spawn());
 
             match &spawn_result {
                 Some(Ok(_)) => {
-                    logs.push(format!("✅ [start_backend] Method 2 (Python module) succeeded"));
+                    logs.push("✅ [start_backend] Method 2 (Python module) succeeded".to_string());
                 }
                 Some(Err(e)) => {
                     logs.push(format!("❌ [start_backend] Method 2 (Python module) failed: {}", e));
@@ -321,13 +456,35 @@ async fn start_backend(app: tauri::AppHandle) -> Result<String, String> {
     };
 
     match result {
-        Ok(child) => {
+        Ok(mut child) => {
             logs.push(format!("✅ [start_backend] Backend process started successfully (PID: {:?})", child.id()));
 
+            breadcrumbs::record("backend", format!("Backend started (PID: {})", child.id()));
+
+            // 转发后端 stderr 并提取 Python traceback 到崩溃报告
+            backend_crash::capture_backend_stderr(&mut child);
+            backend_process::track(child);
+            startup_metrics::record(&app, startup_metrics::Phase::BackendSpawned);
+
+            // 后端可以访问后结束任务栏进度
+            let handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let state = if backend_process::wait_until_healthy(BACKEND_START_TIMEOUT).await {
+                    startup_metrics::record(&handle, startup_metrics::Phase::BackendReady);
+                    // 通过健康检查的环境记为最后可用的快照
+                    tauri::async_runtime::spawn_blocking(backend_snapshot::mark_good);
+                    task_progress::TaskState::Done
+                } else {
+                    task_progress::TaskState::Failed
+                };
+                task_progress::update(&handle, BACKEND_TASK, state);
+            });
+
             // Emit logs to frontend via app log event
             let log_message = logs.join("\n");
+            tracing::info!("{}", log_message);
             if let Err(e) = app.emit("app-log", log_message.clone()) {
-                eprintln!("Failed to emit app-log: {}", e);
+                tracing::warn!("Failed to emit app-log: {}", e);
             }
 
             Ok(log_message)
@@ -335,11 +492,14 @@ async fn start_backend(app: tauri::AppHandle) -> Result<String, String> {
         Err(e) => {
             let error_msg = format!("❌ [start_backend] Failed to start backend: {}", e);
             logs.push(error_msg.clone());
+            breadcrumbs::record("backend", format!("Backend failed to start: {}", e));
+            task_progress::update(&app, BACKEND_TASK, task_progress::TaskState::Failed);
 
             // Emit error logs to frontend
             let log_message = logs.join("\n");
+            tracing::error!("{}", log_message);
             if let Err(e) = app.emit("app-log", log_message.clone()) {
-                eprintln!("Failed to emit app-log: {}", e);
+                tracing::warn!("Failed to emit app-log: {}", e);
             }
 
             Err(error_msg)
@@ -347,6 +507,29 @@ async fn start_backend(app: tauri::AppHandle) -> Result<String, String> {
     }
 }
 
+/// 停止由桌面端启动的后端，返回是否有正在运行的后端
+#[tauri::command]
+async fn stop_backend() -> Result<bool, String> {
+    tauri::async_runtime::spawn_blocking(backend_process::stop)
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 重启后端：停止由桌面端启动的后端后重新启动
+#[tauri::command]
+#[tracing::instrument(skip_all)]
+async fn restart_backend(app: tauri::AppHandle) -> Result<String, String> {
+    stop_backend().await?;
+    start_backend(app).await
+}
+
+/// 更新耗时任务（依赖同步、下载等）的进度，显示在任务栏/程序坞上
+#[tauri::command]
+async fn update_task_progress(app: tauri::AppHandle, task: String, state: task_progress::TaskState) -> Result<(), String> {
+    task_progress::update(&app, &task, state);
+    Ok(())
+}
+
 // ==================== 崩溃报告 Tauri Commands ====================
 
 /// 导航到主应用
@@ -356,52 +539,162 @@ async fn navigate_to_main() -> Result<(), String> {
     Ok(())
 }
 
-/// 选择目录（跨平台支持）
+/// 选择目录（跨平台支持），`bookmark` 为收藏的目录路径，指定时从该目录打开
+#[tauri::command]
+async fn select_directory(bookmark: Option<String>) -> Result<Option<String>, String> {
+    let start = bookmark.as_deref().map(bookmarks::start_dir).transpose()?;
+    let folder = file_dialog::pick_directory("选择工作区目录", start.as_deref()).await;
+    Ok(folder.map(|path| path.to_string_lossy().to_string()))
+}
+
+/// 选择多个目录，取消时返回空列表
+#[tauri::command]
+async fn select_directories(title: Option<String>, bookmark: Option<String>) -> Result<Vec<String>, String> {
+    let start = bookmark.as_deref().map(bookmarks::start_dir).transpose()?;
+    let folders = file_dialog::pick_directories(title.as_deref().unwrap_or("选择目录"), start.as_deref()).await;
+    Ok(folders.iter().map(|path| path.to_string_lossy().to_string()).collect())
+}
+
+/// 选择一个文件，`filters` 为扩展名过滤条件
 #[tauri::command]
-async fn select_directory() -> Result<Option<String>, String> {
-    use rfd::AsyncFileDialog;
+async fn select_file(
+    title: Option<String>,
+    filters: Option<Vec<file_dialog::FileFilter>>,
+    bookmark: Option<String>,
+) -> Result<Option<String>, String> {
+    let start = bookmark.as_deref().map(bookmarks::start_dir).transpose()?;
+    let file = file_dialog::pick_file(title.as_deref().unwrap_or("选择文件"), &filters.unwrap_or_default(), start.as_deref()).await;
+    Ok(file.map(|path| path.to_string_lossy().to_string()))
+}
 
-    // 获取用户主目录作为默认位置
-    let home_dir = dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("."));
+/// 选择多个文件，`filters` 为扩展名过滤条件，取消时返回空列表
+#[tauri::command]
+async fn select_files(
+    title: Option<String>,
+    filters: Option<Vec<file_dialog::FileFilter>>,
+    bookmark: Option<String>,
+) -> Result<Vec<String>, String> {
+    let start = bookmark.as_deref().map(bookmarks::start_dir).transpose()?;
+    let files = file_dialog::pick_files(title.as_deref().unwrap_or("选择文件"), &filters.unwrap_or_default(), start.as_deref()).await;
+    Ok(files.iter().map(|path| path.to_string_lossy().to_string()).collect())
+}
 
-    // 创建文件对话框，设置初始目录为用户主目录
-    let mut file_dialog = AsyncFileDialog::new()
-        .set_directory(&home_dir);
+/// 选择保存位置（不写入文件），`default_name` 为默认文件名，取消时返回 None
+#[tauri::command]
+async fn save_file_dialog(
+    default_name: Option<String>,
+    filters: Option<Vec<file_dialog::FileFilter>>,
+    title: Option<String>,
+    bookmark: Option<String>,
+) -> Result<Option<String>, String> {
+    let start = bookmark.as_deref().map(bookmarks::start_dir).transpose()?;
+    let path = file_dialog::save_file(
+        title.as_deref().unwrap_or("保存文件"),
+        default_name.as_deref().unwrap_or_default(),
+        &filters.unwrap_or_default(),
+        start.as_deref(),
+    )
+    .await;
+    Ok(path.map(|path| path.to_string_lossy().to_string()))
+}
 
-    // 平台特定优化
-    #[cfg(target_os = "macos")]
-    {
-        // macOS显示优化
-        file_dialog = file_dialog.set_title("选择工作区目录");
-    }
+/// 收藏的目录
+#[tauri::command]
+async fn list_bookmarks() -> Result<Vec<bookmarks::Bookmark>, String> {
+    Ok(bookmarks::list())
+}
 
-    #[cfg(target_os = "windows")]
-    {
-        // Windows显示优化
-        file_dialog = file_dialog.set_title("选择工作区目录");
-    }
+/// 收藏目录，`name` 默认为目录名，已收藏时更新名称
+#[tauri::command]
+async fn add_bookmark(app: tauri::AppHandle, path: String, name: Option<String>) -> Result<bookmarks::Bookmark, String> {
+    bookmarks::add(&app, &PathBuf::from(path), name.as_deref())
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        // Linux显示优化
-        file_dialog = file_dialog.set_title("选择工作区目录");
-    }
+/// 取消收藏，不在收藏中时返回 false
+#[tauri::command]
+async fn remove_bookmark(app: tauri::AppHandle, path: String) -> Result<bool, String> {
+    bookmarks::remove(&app, &PathBuf::from(path))
+}
 
-    let folder = file_dialog.pick_folder().await;
+/// 把文件或目录移到系统回收站，返回每个路径的结果
+#[tauri::command]
+async fn trash_paths(paths: Vec<PathBuf>) -> Result<Vec<recycle_bin::TrashResult>, String> {
+    tauri::async_runtime::spawn_blocking(move || recycle_bin::trash_paths(&paths))
+        .await
+        .map_err(|e| e.to_string())
+}
 
-    match folder {
-        Some(path) => {
-            let path_str = path.path().to_string_lossy().to_string();
-            Ok(Some(path_str))
-        }
-        None => Ok(None)
-    }
+/// 在系统文件管理器中显示并选中文件或目录
+#[tauri::command]
+async fn reveal_in_file_manager(path: String) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || file_manager::reveal(&PathBuf::from(path)))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 已安装的编辑器（VS Code、Cursor、Sublime Text），按优先顺序排列
+#[tauri::command]
+async fn list_editors() -> Result<Vec<editor::Editor>, String> {
+    tauri::async_runtime::spawn_blocking(editor::detect).await.map_err(|e| e.to_string())
+}
+
+/// 在编辑器中打开文件并跳转到 `line`；`editor` 为编辑器 ID，默认为第一个已安装的，都没有安装时用系统默认程序打开，返回使用的编辑器名称
+#[tauri::command]
+async fn open_in_editor(path: String, line: Option<u32>, editor: Option<String>) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || editor::open(&PathBuf::from(path), line, editor.as_deref()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 检查选择的工作区目录（可写、不是系统目录、不在安装目录中、磁盘空间、是否被其他实例打开）
+#[tauri::command]
+async fn validate_workspace(path: String) -> Result<workspace_check::WorkspaceValidation, String> {
+    tauri::async_runtime::spawn_blocking(move || workspace_check::validate(&PathBuf::from(path)))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 可用的工作区模板（用户模板和随应用发布的模板）
+#[tauri::command]
+async fn list_workspace_templates() -> Result<Vec<workspace_template::TemplateInfo>, String> {
+    tauri::async_runtime::spawn_blocking(workspace_template::list).await.map_err(|e| e.to_string())
+}
+
+/// 从模板（名称或模板目录的绝对路径）创建工作区，替换 `{{project_name}}`、`{{author}}` 并记入最近的工作区，返回工作区路径
+#[tauri::command]
+async fn create_workspace_from_template(template: String, dest: String, author: Option<String>) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || workspace_template::create(&template, &PathBuf::from(dest), author))
+        .await
+        .map_err(|e| e.to_string())?
+        .map(|path| path.display().to_string())
+}
+
+/// 最近打开的工作区（固定的在前，再按最后打开时间倒序），跳过目录已不存在的
+#[tauri::command]
+async fn get_recent_workspaces() -> Result<Vec<recent_workspaces::RecentWorkspace>, String> {
+    tauri::async_runtime::spawn_blocking(recent_workspaces::list).await.map_err(|e| e.to_string())
+}
+
+/// 固定或取消固定最近的工作区
+#[tauri::command]
+async fn pin_workspace(path: String, pinned: bool) -> Result<(), String> {
+    recent_workspaces::pin(&PathBuf::from(path), pinned)
+}
+
+/// 从最近的工作区中移除，不在列表中时返回 false
+#[tauri::command]
+async fn remove_recent(path: String) -> Result<bool, String> {
+    recent_workspaces::remove(&PathBuf::from(path))
 }
 
 /// 使用系统默认浏览器打开 URL
 #[tauri::command]
 async fn open_by_system_browser(url: String) -> Result<(), String> {
+    open_url(&url)
+}
+
+/// 使用系统默认浏览器打开 URL（共享辅助函数）
+fn open_url(url: &str) -> Result<(), String> {
     // 验证 URL 格式
     if !url.starts_with("http://") && !url.starts_with("https://") {
         return Err("URL 必须以 http:// 或 https:// 开头".to_string());
@@ -410,8 +703,10 @@ async fn open_by_system_browser(url: String) -> Result<(), String> {
     // 使用系统默认浏览器打开 URL
     #[cfg(target_os = "windows")]
     {
+        // cmd 会把 URL 中的 & 当作命令分隔符，需要转义
+        let escaped = url.replace('&', "^&");
         std::process::Command::new("cmd")
-            .args(["/C", "start", "", &url])
+            .args(["/C", "start", "", &escaped])
             .spawn()
             .map_err(|e| format!("无法打开浏览器: {}", e))?;
     }
@@ -419,7 +714,7 @@ async fn open_by_system_browser(url: String) -> Result<(), String> {
     #[cfg(target_os = "macos")]
     {
         std::process::Command::new("open")
-            .arg(&url)
+            .arg(url)
             .spawn()
             .map_err(|e| format!("无法打开浏览器: {}", e))?;
     }
@@ -432,7 +727,7 @@ async fn open_by_system_browser(url: String) -> Result<(), String> {
 
         for cmd in open_commands {
             if std::process::Command::new(cmd)
-                .arg(&url)
+                .arg(url)
                 .spawn()
                 .is_ok()
             {
@@ -449,19 +744,291 @@ async fn open_by_system_browser(url: String) -> Result<(), String> {
     Ok(())
 }
 
+/// 使用系统文件管理器打开目录或文件（共享辅助函数）
+fn open_path(path: &std::path::Path) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    let program = "explorer";
+    #[cfg(target_os = "macos")]
+    let program = "open";
+    #[cfg(target_os = "linux")]
+    let program = "xdg-open";
+
+    std::process::Command::new(program)
+        .arg(path)
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("无法打开 {}: {}", path.display(), e))
+}
+
 /// 获取所有崩溃报告
 #[tauri::command]
 async fn get_crash_reports() -> Result<Vec<crash_handler::CrashReport>, String> {
     Ok(get_all_crash_reports())
 }
 
+/// 获取最新的崩溃报告
+#[tauri::command]
+async fn get_latest_crash_report() -> Result<Option<crash_handler::CrashReport>, String> {
+    Ok(crash_handler::latest_crash_report())
+}
+
+/// 按文件名获取崩溃报告
+#[tauri::command]
+async fn get_crash_report(filename: String) -> Result<Option<crash_handler::CrashReport>, String> {
+    Ok(crash_handler::find_crash_report(&filename))
+}
+
+/// 获取格式化后的崩溃报告文本
+#[tauri::command]
+async fn format_crash_report(filename: String) -> Result<String, String> {
+    crash_handler::find_crash_report(&filename)
+        .map(|report| report.format_display())
+        .ok_or_else(|| format!("崩溃报告不存在: {}", filename))
+}
+
+/// 新建 issue 的地址
+const ISSUE_URL: &str = "https://github.com/geluzhiwei1/davybot/issues/new";
+
+/// 预填到 issue URL 中的堆栈行数（URL 过长时浏览器和 GitHub 会拒绝）
+const ISSUE_URL_BACKTRACE_LINES: usize = 40;
+
+/// 将崩溃报告导出为 GitHub issue 格式的 Markdown
+///
+/// `open_issue` 为 true 时同时在浏览器中打开预填好的新建 issue 页面。
+#[tauri::command]
+async fn format_crash_as_markdown(filename: String, open_issue: Option<bool>) -> Result<String, String> {
+    let report = crash_handler::find_crash_report(&filename)
+        .ok_or_else(|| format!("崩溃报告不存在: {}", filename))?;
+
+    if open_issue.unwrap_or(false) {
+        let title = format!("[Crash] {}", report.error_message.lines().next().unwrap_or_default());
+        let body = report.format_markdown(Some(ISSUE_URL_BACKTRACE_LINES));
+        let url = tauri::Url::parse_with_params(ISSUE_URL, [("title", title.as_str()), ("body", body.as_str())])
+            .map_err(|e| format!("无法生成 issue 地址: {}", e))?;
+        open_url(url.as_str())?;
+    }
+
+    Ok(report.format_markdown(None))
+}
+
+/// 通过保存对话框导出崩溃报告（.md 保存为 Markdown，其他为文本），返回保存的路径
+#[tauri::command]
+async fn export_crash_report(filename: String) -> Result<Option<String>, String> {
+    let report = crash_handler::find_crash_report(&filename)
+        .ok_or_else(|| format!("崩溃报告不存在: {}", filename))?;
+    let default_name = format!("{}.md", filename.trim_end_matches(".json"));
+    let filters = [file_dialog::FileFilter::new("Markdown", &["md"]), file_dialog::FileFilter::new("Text", &["txt"])];
+    let Some(path) = file_dialog::save_file("导出崩溃报告", &default_name, &filters, None).await else {
+        return Ok(None);
+    };
+    let content = match path.extension().and_then(|e| e.to_str()) {
+        Some("md") => report.format_markdown(None),
+        _ => report.format_display(),
+    };
+    fs::write(&path, content).map_err(|e| format!("无法写入 {}: {}", path.display(), e))?;
+    Ok(Some(path.display().to_string()))
+}
+
+/// 在独立窗口中打开单个崩溃报告
+#[tauri::command]
+async fn open_crash_window(app: tauri::AppHandle, filename: String) -> Result<(), String> {
+    crash_viewer::open_crash_window(&app, &filename)
+}
+
+/// 查询桌面端日志（按级别、模块、时间范围过滤），从新到旧分页返回
+#[tauri::command]
+async fn get_app_logs(query: Option<log_query::LogQuery>) -> Result<log_query::LogPage, String> {
+    tauri::async_runtime::spawn_blocking(move || log_query::query(&query.unwrap_or_default()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 查询合并了桌面端、页面和后端日志的时间线，按时间从旧到新返回最近的日志
+#[tauri::command]
+async fn get_log_timeline(query: Option<log_timeline::TimelineQuery>) -> Result<Vec<log_timeline::TimelineEntry>, String> {
+    tauri::async_runtime::spawn_blocking(move || log_timeline::query(&query.unwrap_or_default()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 按正则搜索桌面端和后端日志（可按来源、时间范围过滤），返回最近的匹配行及其上下文
+#[tauri::command]
+async fn search_logs(
+    pattern: String,
+    sources: Option<Vec<log_timeline::LogSource>>,
+    range: Option<log_search::TimeRange>,
+) -> Result<log_search::SearchResult, String> {
+    tauri::async_runtime::spawn_blocking(move || log_search::search(&pattern, sources, &range.unwrap_or_default()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 开启或关闭实时日志事件（`log-timeline-entry`）
+#[tauri::command]
+async fn set_log_streaming(enabled: bool) -> Result<(), String> {
+    log_timeline::set_streaming(enabled);
+    Ok(())
+}
+
+/// 订阅新日志，日志按 `filter` 过滤后每 200 毫秒通过 `on_log` 整批发送，返回订阅 ID
+#[tauri::command]
+async fn subscribe_logs(
+    filter: Option<log_stream::LogFilter>,
+    on_log: tauri::ipc::Channel<log_stream::LogBatch>,
+) -> Result<u32, String> {
+    log_stream::subscribe(filter.unwrap_or_default(), on_log)
+}
+
+/// 取消日志订阅，订阅不存在时返回 false
+#[tauri::command]
+async fn unsubscribe_logs(id: u32) -> Result<bool, String> {
+    Ok(log_stream::unsubscribe(id))
+}
+
+/// 记录页面的日志（页面的 console.warn/error 会自动转发）
+#[tauri::command]
+fn log_webview(window: tauri::WebviewWindow, level: String, message: String) {
+    log_timeline::log_webview(window.label(), &level, &message);
+}
+
+/// 用系统默认程序打开当前的日志文件，`reveal` 为 true 时在文件管理器中显示；`which` 默认为桌面端日志，返回文件路径
+#[tauri::command]
+async fn open_log_file(which: Option<log_timeline::LogSource>, reveal: Option<bool>) -> Result<String, String> {
+    let which = which.unwrap_or(log_timeline::LogSource::Tauri);
+    let name = if which == log_timeline::LogSource::Backend { "后端" } else { "桌面端" };
+    let path = log_timeline::current_log_file(which).ok_or_else(|| format!("还没有{}日志", name))?;
+    if reveal.unwrap_or(false) {
+        file_manager::reveal(&path)?;
+    } else {
+        open_path(&path)?;
+    }
+    Ok(path.display().to_string())
+}
+
+/// 导出诊断包（日志、脱敏的设置、崩溃报告、环境和系统信息）；未指定 dest 时显示保存对话框，返回保存的路径
+#[tauri::command]
+async fn export_diagnostics(dest: Option<PathBuf>) -> Result<Option<String>, String> {
+    let dest = match dest {
+        Some(dest) => dest,
+        None => {
+            let default_name = format!("dawei-diagnostics-{}.zip", chrono::Local::now().format("%Y%m%d-%H%M%S"));
+            let filters = [file_dialog::FileFilter::new("Zip", &["zip"])];
+            let Some(path) = file_dialog::save_file("导出诊断包", &default_name, &filters, None).await else {
+                return Ok(None);
+            };
+            path
+        }
+    };
+    let system = about::collect().await;
+    let path = tauri::async_runtime::spawn_blocking(move || diagnostics::export_to(&dest, &system))
+        .await
+        .map_err(|e| e.to_string())??;
+    Ok(Some(path.display().to_string()))
+}
+
+/// 运行自检（路径解析、启动 Python、读写 DAWEI_HOME、后端端口、崩溃报告），返回每项的结果
+#[tauri::command]
+async fn run_self_test() -> Result<self_test::SelfTestReport, String> {
+    tauri::async_runtime::spawn_blocking(self_test::run).await.map_err(|e| e.to_string())
+}
+
+/// 并行检查 DNS、外网、PyPI 镜像和 LLM API 地址的连通性，`targets` 为空时检查全部
+/// （可选 `dns`、`internet`、`pypi`、`llm` 或 `llm:<配置名>`）
+#[tauri::command]
+async fn check_connectivity(targets: Option<Vec<String>>) -> Result<Vec<connectivity::TargetStatus>, String> {
+    Ok(connectivity::check(targets).await)
+}
+
+/// 查询路径所在磁盘的总空间和可用空间，`paths` 为空时查询 DAWEI_HOME 和应用目录（无法确定磁盘的路径不返回）
+#[tauri::command]
+async fn get_disk_usage(paths: Option<Vec<PathBuf>>) -> Result<Vec<disk_space::DiskUsage>, String> {
+    let paths = paths.filter(|paths| !paths.is_empty()).unwrap_or_else(disk_space::default_paths);
+    tauri::async_runtime::spawn_blocking(move || paths.iter().filter_map(|path| disk_space::usage(path)).collect())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 把当前页面导出为 PDF（对话记录、报告等）；未指定 dest 时显示保存对话框，返回保存的路径
+#[tauri::command]
+async fn export_view_pdf(window: tauri::WebviewWindow, dest: Option<PathBuf>) -> Result<Option<String>, String> {
+    let dest = match dest {
+        Some(dest) => dest,
+        None => {
+            let title = window.title().unwrap_or_default();
+            let default_name = format!("{}.pdf", if title.trim().is_empty() { "dawei" } else { title.trim() });
+            let filters = [file_dialog::FileFilter::new("PDF", &["pdf"])];
+            let Some(path) = file_dialog::save_file("导出 PDF", &default_name, &filters, None).await else {
+                return Ok(None);
+            };
+            path
+        }
+    };
+    let path = pdf_export::export(&window, &dest).await?;
+    Ok(Some(path.display().to_string()))
+}
+
+/// 打开崩溃报告查看窗口
+#[tauri::command]
+async fn open_crash_viewer(app: tauri::AppHandle) -> Result<(), String> {
+    crash_viewer::open_crash_viewer(&app).map_err(|e| e.to_string())
+}
+
+/// 获取崩溃报告上传队列状态
+#[tauri::command]
+async fn get_crash_upload_status() -> Result<crash_upload::QueueStatus, String> {
+    Ok(crash_upload::queue_status())
+}
+
+/// 下次发送的匿名使用统计的完整内容，没有待发送的数据时返回 null
+#[tauri::command]
+async fn preview_telemetry() -> Result<Option<telemetry::TelemetryPayload>, String> {
+    tauri::async_runtime::spawn_blocking(telemetry::preview).await.map_err(|e| e.to_string())
+}
+
+/// 清除待发送的匿名使用统计
+#[tauri::command]
+async fn purge_telemetry() -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(telemetry::purge).await.map_err(|e| e.to_string())?
+}
+
+/// 获取上次会话的崩溃信息（上次运行未正常退出时返回）
+///
+/// 前端据此显示恢复对话框：查看报告 / 重启后端 / 重置设置。
+#[tauri::command]
+async fn get_last_session_crash() -> Result<Option<session::LastSessionCrash>, String> {
+    Ok(session::last_session_crash())
+}
+
+/// 获取当前会话 ID（与日志、崩溃报告和 server.start 中的会话 ID 一致）
+#[tauri::command]
+async fn get_session_id() -> Result<String, String> {
+    Ok(session::session_id().to_string())
+}
+
+/// 恢复对话框关闭后清除上次会话的崩溃信息
+#[tauri::command]
+async fn acknowledge_last_session_crash() -> Result<(), String> {
+    session::acknowledge_last_session_crash();
+    Ok(())
+}
+
 /// 获取 DAWEI_HOME 目录
 fn get_dawei_home() -> PathBuf {
-    // 优先从环境变量读取
+    // 便携模式下固定使用可执行文件旁的 data 目录
+    if let Some(data) = portable::data_dir() {
+        return data;
+    }
+
+    // 其次从环境变量读取
     if let Ok(home) = std::env::var("DAWEI_HOME") {
         return PathBuf::from(home);
     }
 
+    // 其次使用迁移后指针文件记录的目录
+    if let Some(home) = dawei_home::relocated_home() {
+        return home;
+    }
+
     // 默认使用用户主目录下的 .dawei
     #[cfg(target_os = "windows")]
     let base_dir = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
@@ -481,6 +1048,32 @@ async fn get_dawei_home_command() -> Result<String, String> {
         .ok_or_else(|| "Failed to convert DAWEI_HOME to string".to_string())
 }
 
+/// 把 DAWEI_HOME 迁移到新目录（复制并校验数据，重启后生效）
+#[tauri::command]
+async fn relocate_dawei_home(new_path: String) -> Result<dawei_home::RelocationReport, String> {
+    tauri::async_runtime::spawn_blocking(move || dawei_home::relocate(&PathBuf::from(new_path)))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 启动画面当前显示的进度
+#[tauri::command]
+async fn get_startup_progress() -> Result<Option<String>, String> {
+    Ok(splash::current_progress())
+}
+
+/// 获取目前记录的启动耗时（各阶段距进程启动的毫秒数）
+#[tauri::command]
+async fn get_startup_metrics() -> Result<startup_metrics::StartupMetrics, String> {
+    Ok(startup_metrics::current())
+}
+
+/// 后端健康检查的延迟（p50/p95）和错误率（最近 100 次检查）
+#[tauri::command]
+async fn get_backend_stats() -> Result<backend_stats::BackendStats, String> {
+    Ok(backend_stats::current())
+}
+
 /// 读取服务器启动信息
 #[tauri::command]
 async fn get_server_start_info() -> Result<Option<Value>, String> {
@@ -506,82 +1099,678 @@ async fn clear_crash_reports() -> Result<(), String> {
     clear_all_crash_reports().map_err(|e| e.to_string())
 }
 
-// ==================== 页面缩放功能 ====================
+// ==================== 设置 ====================
+
+/// 获取当前设置
+#[tauri::command]
+async fn get_settings() -> Result<settings::Settings, String> {
+    Ok(settings::current())
+}
 
-/// 放大页面
+/// 修改设置，`patch` 只需包含要修改的字段
 #[tauri::command]
-async fn zoom_in(window: tauri::Window) -> Result<String, String> {
-    use tauri::Emitter;
+async fn update_settings(app: tauri::AppHandle, patch: Value) -> Result<settings::Settings, String> {
+    settings::update(&app, patch)
+}
 
-    // 计算新的缩放级别 (最大300%)
-    let new_zoom = 1.1; // 每次增加10%
+/// 获取工作区的实际配置（全局设置叠加 `<工作区>/.dawei/settings.json`）
+#[tauri::command]
+async fn get_effective_settings(workspace_path: String) -> Result<settings::EffectiveSettings, String> {
+    settings::effective_for_workspace(&PathBuf::from(workspace_path))
+}
 
-    // 发送缩放事件到前端
-    window.emit("zoom-change", new_zoom)
-        .map_err(|e| format!("Failed to emit zoom event: {}", e))?;
+/// 获取主题偏好和实际主题
+#[tauri::command]
+async fn get_theme(app: tauri::AppHandle) -> Result<theme::ThemeInfo, String> {
+    Ok(theme::current(&app))
+}
 
-    Ok(format!("Zoom in to {:.0}%", new_zoom * 100.0))
+/// 设置主题偏好：light / dark / system
+#[tauri::command]
+async fn set_theme(app: tauri::AppHandle, theme: theme::ThemePreference) -> Result<theme::ThemeInfo, String> {
+    theme::set_theme(&app, theme)
 }
 
-/// 缩小页面
+/// 获取界面语言
 #[tauri::command]
-async fn zoom_out(window: tauri::Window) -> Result<String, String> {
-    use tauri::Emitter;
+async fn get_locale() -> Result<i18n::Locale, String> {
+    Ok(i18n::Locale::current())
+}
 
-    // 计算新的缩放级别 (最小50%)
-    let new_zoom = 0.9; // 每次减少10%
+/// 设置界面语言：zh-CN / en
+#[tauri::command]
+async fn set_locale(app: tauri::AppHandle, locale: i18n::Locale) -> Result<i18n::Locale, String> {
+    i18n::set_locale(&app, locale)
+}
 
-    // 发送缩放事件到前端
-    window.emit("zoom-change", new_zoom)
-        .map_err(|e| format!("Failed to emit zoom event: {}", e))?;
+/// 修改日志级别（例如 `debug`，格式同 DAWEI_LOG），立即生效并保存在设置中；空值恢复默认
+#[tauri::command]
+async fn set_log_level(app: tauri::AppHandle, level: Option<String>) -> Result<String, String> {
+    logging::set_level(&app, level)
+}
 
-    Ok(format!("Zoom out to {:.0}%", new_zoom * 100.0))
+/// 导出设置、环境变量和工作区索引到文件；默认不包含密钥，`include_secrets` 为 true 时包含
+#[tauri::command]
+async fn export_config(path: String, include_secrets: Option<bool>) -> Result<(), String> {
+    config_transfer::export_to(&PathBuf::from(path), include_secrets.unwrap_or(false))
 }
 
-/// 重置缩放
+/// 从导出文件导入配置，返回导入报告；`overwrite` 为 true 时冲突项使用导入的值
+#[tauri::command]
+async fn import_config(
+    app: tauri::AppHandle,
+    path: String,
+    overwrite: Option<bool>,
+) -> Result<config_transfer::ImportReport, String> {
+    config_transfer::import_from(&app, &PathBuf::from(path), overwrite.unwrap_or(false))
+}
+
+/// 获取首次运行状态（引导是否完成、已完成的步骤）
+#[tauri::command]
+async fn get_first_run_state() -> Result<first_run::FirstRunState, String> {
+    Ok(first_run::state())
+}
+
+/// 是否首次运行（引导尚未完成）
+#[tauri::command]
+async fn is_first_run() -> Result<bool, String> {
+    Ok(first_run::is_first_run())
+}
+
+/// 记录完成的引导步骤
+#[tauri::command]
+async fn complete_onboarding_step(step: String) -> Result<first_run::FirstRunState, String> {
+    first_run::complete_step(&step)
+}
+
+/// 标记引导已完成
+#[tauri::command]
+async fn finish_onboarding() -> Result<first_run::FirstRunState, String> {
+    first_run::finish()
+}
+
+/// 列出 .env 中的变量（密钥只返回掩码）；`target` 为 app 或 home
+#[tauri::command]
+async fn list_env_vars(target: env_file::EnvTarget) -> Result<Vec<env_file::EnvEntry>, String> {
+    Ok(env_file::list(target))
+}
+
+/// 读取 .env 中的单个变量（密钥只返回掩码）
+#[tauri::command]
+async fn get_env_var(target: env_file::EnvTarget, key: String) -> Result<Option<env_file::EnvEntry>, String> {
+    Ok(env_file::get(target, &key))
+}
+
+/// 设置 .env 中的变量（重启后端后生效）
+#[tauri::command]
+async fn set_env_var(target: env_file::EnvTarget, key: String, value: String) -> Result<(), String> {
+    env_file::set(target, &key, &value)
+}
+
+/// 删除 .env 中的变量，返回变量是否存在
+#[tauri::command]
+async fn unset_env_var(target: env_file::EnvTarget, key: String) -> Result<bool, String> {
+    env_file::unset(target, &key)
+}
+
+/// 获取恢复出厂设置的确认令牌（一分钟内有效，只能使用一次）
+#[tauri::command]
+async fn request_reset_token(scope: reset::ResetScope) -> Result<String, String> {
+    Ok(reset::request_token(scope))
+}
+
+/// 恢复出厂设置：按范围清除设置、崩溃报告、日志、缓存或全部
+#[tauri::command]
+async fn reset_app_data(
+    app: tauri::AppHandle,
+    scope: reset::ResetScope,
+    token: String,
+) -> Result<reset::ResetReport, String> {
+    reset::reset(&app, scope, &token)
+}
+
+/// 获取清理应用数据的确认令牌（一分钟内有效，只能使用一次，只对同一组选项有效）
+#[tauri::command]
+async fn request_cleanup_token(options: cleanup::CleanupOptions) -> Result<String, String> {
+    Ok(cleanup::request_token(options))
+}
+
+/// 清理内置 Python 环境、缓存、日志或整个 DAWEI_HOME，返回释放的空间
+#[tauri::command]
+async fn cleanup_app_data(options: cleanup::CleanupOptions, token: String) -> Result<cleanup::CleanupReport, String> {
+    tauri::async_runtime::spawn_blocking(move || cleanup::cleanup(options, &token))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 切换后端启动配置，`name` 为空时恢复默认（下次启动后端时生效）
+#[tauri::command]
+async fn set_active_profile(app: tauri::AppHandle, name: Option<String>) -> Result<Option<String>, String> {
+    backend_profile::set_active(&app, name)
+}
+
+/// 诊断后端端口占用：占用进程、附近的空闲端口和可以采取的处理方式，`port` 为空时使用当前配置的端口
+#[tauri::command]
+async fn diagnose_port(port: Option<u16>) -> Result<port_check::PortDiagnosis, String> {
+    let port = port.unwrap_or_else(backend_profile::port);
+    tauri::async_runtime::spawn_blocking(move || port_check::diagnose(port))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 结束占用端口的残留 dawei 进程，返回结束的进程 PID（不会结束其他程序）
+#[tauri::command]
+async fn kill_port_owner(port: u16) -> Result<u32, String> {
+    tauri::async_runtime::spawn_blocking(move || port_check::kill_owner(port))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 列出本应用启动的子进程和之前的会话遗留的 dawei 进程（PID、CPU、内存、启动时间）
+#[tauri::command]
+async fn list_related_processes() -> Result<Vec<related_processes::RelatedProcess>, String> {
+    tauri::async_runtime::spawn_blocking(related_processes::list).await.map_err(|e| e.to_string())
+}
+
+/// 结束之前的会话遗留的 dawei 进程，`pids` 为空时结束全部，返回已结束的 PID
+#[tauri::command]
+async fn terminate_stale_processes(pids: Option<Vec<u32>>) -> Result<Vec<u32>, String> {
+    tauri::async_runtime::spawn_blocking(move || related_processes::terminate_stale(pids.as_deref()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+// ==================== 窗口 ====================
+
+/// 在新窗口中打开工作区（已打开时聚焦该窗口）
+#[tauri::command]
+async fn open_workspace_window(app: tauri::AppHandle, path: String) -> Result<workspace_window::WorkspaceWindow, String> {
+    workspace_window::open(&app, &PathBuf::from(path))
+}
+
+/// 读取工作区中的文本文件；工作区窗口使用窗口对应的工作区，其他窗口需指定 `workspace`
+#[tauri::command]
+async fn read_workspace_file(window: tauri::Window, rel_path: String, workspace: Option<String>) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = workspace_files::workspace_root(window.label(), workspace.as_deref())?;
+        workspace_files::read(&root, &rel_path)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// 写入工作区中的文本文件；工作区窗口使用窗口对应的工作区，其他窗口需指定 `workspace`
+#[tauri::command]
+async fn write_workspace_file(
+    window: tauri::Window,
+    rel_path: String,
+    contents: String,
+    workspace: Option<String>,
+) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = workspace_files::workspace_root(window.label(), workspace.as_deref())?;
+        workspace_files::write(&root, &rel_path, &contents)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// 列出工作区中 `path`（相对路径，默认为工作区根目录）下 `depth` 层以内的文件，按 .gitignore 和 `ignore` 规则过滤，分页返回
+#[tauri::command]
+async fn list_workspace_tree(
+    window: tauri::Window,
+    path: Option<String>,
+    depth: Option<usize>,
+    ignore: Option<Vec<String>>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    workspace: Option<String>,
+) -> Result<workspace_tree::TreePage, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = workspace_files::workspace_root(window.label(), workspace.as_deref())?;
+        let dir = match path.as_deref().filter(|path| !path.is_empty()) {
+            Some(path) => workspace_files::resolve(&root, path)?,
+            None => root.clone(),
+        };
+        let page = workspace_tree::Page { offset: offset.unwrap_or(0), limit };
+        workspace_tree::list(&root, &dir, depth.unwrap_or(1), &ignore.unwrap_or_default(), page)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// 搜索工作区中的文件内容（`glob` 为逗号分隔的文件匹配规则），匹配结果通过 `workspace-search-matches` 事件发给当前窗口
+#[tauri::command]
+async fn search_in_workspace(
+    app: tauri::AppHandle,
+    window: tauri::Window,
+    query: String,
+    glob: Option<String>,
+    regex: Option<bool>,
+    workspace: Option<String>,
+) -> Result<workspace_search::SearchSummary, String> {
+    let root = workspace_files::workspace_root(window.label(), workspace.as_deref())?;
+    let options = workspace_search::SearchOptions {
+        query,
+        globs: glob.map(|glob| glob.split(',').map(str::to_string).collect()).unwrap_or_default(),
+        regex: regex.unwrap_or(false),
+    };
+    workspace_search::search(&app, window.label(), &root, options).await
+}
+
+/// 取消当前窗口正在进行的工作区搜索，没有在搜索时返回 false
+#[tauri::command]
+async fn cancel_workspace_search(window: tauri::Window) -> Result<bool, String> {
+    Ok(workspace_search::cancel(window.label()))
+}
+
+/// 把工作区中的文件（相对路径）暂存后从当前窗口拖出到桌面或文件管理器，结束后发送 `drag-out-finished` 事件
+#[tauri::command]
+async fn start_drag_out(window: tauri::Window, paths: Vec<String>, workspace: Option<String>) -> Result<(), String> {
+    let root = workspace_files::workspace_root(window.label(), workspace.as_deref())?;
+    let files = paths.iter().map(|path| workspace_files::resolve(&root, path)).collect::<Result<Vec<_>, _>>()?;
+    drag_out::start(&window, files).await
+}
+
+/// 统计工作区的总大小、文件数和占用最大的目录（`workspace-stats-progress` 事件报告进度）
+#[tauri::command]
+async fn get_workspace_stats(app: tauri::AppHandle, path: String) -> Result<workspace_stats::WorkspaceStats, String> {
+    workspace_stats::stats(&app, &PathBuf::from(path)).await
+}
+
+/// 取消正在进行的工作区统计，没有在统计时返回 false
+#[tauri::command]
+async fn cancel_workspace_stats(path: String) -> Result<bool, String> {
+    Ok(workspace_stats::cancel(&PathBuf::from(path)))
+}
+
+/// 把工作区导出为 zip（按 .gitignore 和 `exclude` 规则排除文件，`workspace-archive-progress` 事件报告进度）
+#[tauri::command]
+async fn export_workspace_zip(
+    app: tauri::AppHandle,
+    path: String,
+    dest: String,
+    exclude: Option<Vec<String>>,
+) -> Result<workspace_archive::ArchiveResult, String> {
+    workspace_archive::export(&app, PathBuf::from(path), PathBuf::from(dest), exclude.unwrap_or_default()).await
+}
+
+/// 把 zip 解压到不存在或为空的目录作为工作区，并记入最近的工作区
+#[tauri::command]
+async fn import_workspace_zip(
+    app: tauri::AppHandle,
+    archive: String,
+    dest: String,
+) -> Result<workspace_archive::ArchiveResult, String> {
+    workspace_archive::import(&app, PathBuf::from(archive), PathBuf::from(dest)).await
+}
+
+/// 监听工作区的文件变化（`workspace-files-changed` 事件），返回规范化后的工作区路径
+#[tauri::command]
+async fn watch_workspace(app: tauri::AppHandle, path: String) -> Result<String, String> {
+    workspace_watcher::watch(&app, &PathBuf::from(path)).map(|path| path.display().to_string())
+}
+
+/// 停止监听工作区，没有在监听时返回 false
+#[tauri::command]
+async fn unwatch_workspace(path: String) -> Result<bool, String> {
+    Ok(workspace_watcher::unwatch(&PathBuf::from(path)))
+}
+
+/// 当前窗口对应的工作区（非工作区窗口返回 None）
 #[tauri::command]
-async fn zoom_reset(window: tauri::Window) -> Result<String, String> {
-    use tauri::Emitter;
+async fn get_window_workspace(window: tauri::Window) -> Result<Option<workspace_window::WorkspaceWindow>, String> {
+    Ok(workspace_window::get(window.label()))
+}
 
-    // 重置为100%
-    let new_zoom = 1.0;
+/// 设置主窗口是否置顶（保存在设置中）
+#[tauri::command]
+async fn set_always_on_top(app: tauri::AppHandle, enabled: bool) -> Result<bool, String> {
+    always_on_top::set(&app, enabled)
+}
 
-    // 发送缩放事件到前端
-    window.emit("zoom-change", new_zoom)
-        .map_err(|e| format!("Failed to emit zoom event: {}", e))?;
+/// 修改显示/隐藏主窗口的全局快捷键，`shortcut` 为空时关闭
+#[tauri::command]
+async fn set_global_shortcut(app: tauri::AppHandle, shortcut: Option<String>) -> Result<Option<String>, String> {
+    hotkey::set_shortcut(&app, shortcut)
+}
 
-    Ok("Zoom reset to 100%".to_string())
+/// 开启或关闭演示模式：主窗口全屏、隐藏标题栏，禁用 DevTools 和快捷键
+#[tauri::command]
+async fn set_kiosk_mode(app: tauri::AppHandle, enabled: bool) -> Result<bool, String> {
+    kiosk::set(&app, enabled)
 }
 
-/// 设置指定缩放级别
+/// 最小化当前窗口（自定义标题栏）
 #[tauri::command]
-async fn set_zoom(window: tauri::Window, mut zoom_level: f64) -> Result<String, String> {
-    use tauri::Emitter;
+async fn window_minimize(window: tauri::Window) -> Result<(), String> {
+    window.minimize().map_err(|e| e.to_string())
+}
 
-    // 限制在50%-300%范围
-    zoom_level = zoom_level.max(0.5).min(3.0);
+/// 最大化或还原当前窗口，返回是否已最大化
+#[tauri::command]
+async fn window_toggle_maximize(window: tauri::Window) -> Result<bool, String> {
+    let maximized = window.is_maximized().map_err(|e| e.to_string())?;
+    if maximized {
+        window.unmaximize().map_err(|e| e.to_string())?;
+    } else {
+        window.maximize().map_err(|e| e.to_string())?;
+    }
+    Ok(!maximized)
+}
 
-    // 发送缩放事件到前端
-    window.emit("zoom-change", zoom_level)
-        .map_err(|e| format!("Failed to emit zoom event: {}", e))?;
+/// 关闭当前窗口（与点击系统关闭按钮相同，开启 close_to_tray 时主窗口隐藏到托盘）
+#[tauri::command]
+async fn window_close(window: tauri::Window) -> Result<(), String> {
+    window.close().map_err(|e| e.to_string())
+}
 
-    Ok(format!("Zoom set to {:.0}%", zoom_level * 100.0))
+/// 开始拖动当前窗口，在自定义标题栏的 mousedown 事件中调用
+#[tauri::command]
+async fn start_dragging(window: tauri::Window) -> Result<(), String> {
+    window.start_dragging().map_err(|e| e.to_string())
+}
+
+/// 开启或关闭自定义标题栏（保存在设置中）
+#[tauri::command]
+async fn set_custom_titlebar(app: tauri::AppHandle, enabled: bool) -> Result<titlebar::TitlebarInfo, String> {
+    titlebar::set(&app, enabled)
+}
+
+/// 获取标题栏信息（是否自定义、macOS 红绿灯按钮需要留出的位置）
+#[tauri::command]
+async fn get_titlebar_info() -> Result<titlebar::TitlebarInfo, String> {
+    Ok(titlebar::info())
+}
+
+/// 打开或关闭当前窗口的 DevTools（需要开启 devtools_toggle 设置），返回是否打开
+#[tauri::command]
+async fn toggle_devtools(window: tauri::WebviewWindow) -> Result<bool, String> {
+    devtools::toggle(&window)
+}
+
+/// 获取关于信息：应用、Tauri、WebView、系统、后端、uv 和 Python 的版本及 DAWEI_HOME
+#[tauri::command]
+async fn get_about_info() -> Result<about::AboutInfo, String> {
+    Ok(about::collect().await)
+}
+
+/// 获取 CPU、内存和显卡信息，以及硬件是否足以运行本地模型
+#[tauri::command]
+async fn get_hardware_info() -> Result<hardware::HardwareInfo, String> {
+    tauri::async_runtime::spawn_blocking(hardware::collect).await.map_err(|e| e.to_string())
+}
+
+/// 确认或取消退出（响应 quit-requested 事件）
+#[tauri::command]
+async fn confirm_quit(confirmed: bool) -> Result<(), String> {
+    quit_guard::confirm(confirmed)
+}
+
+/// 把拖放到主窗口的文件作为附件上传到工作区，返回与 files-dropped 事件相同的结果
+#[tauri::command]
+async fn upload_dropped_files(workspace_id: String, paths: Vec<PathBuf>) -> Result<file_drop::FilesDropped, String> {
+    Ok(file_drop::upload_paths(&workspace_id, &paths).await)
+}
+
+/// 把用户选择的文件复制到会话 `session` 的附件暂存区，返回每个文件的附件句柄或失败原因
+#[tauri::command]
+async fn stage_attachments(session: String, paths: Vec<PathBuf>) -> Result<Vec<attachments::StageResult>, String> {
+    tauri::async_runtime::spawn_blocking(move || attachments::stage(&session, &paths))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 删除暂存的附件，不存在时返回 false
+#[tauri::command]
+async fn remove_attachment(session: String, id: String) -> Result<bool, String> {
+    attachments::remove(&session, &id)
+}
+
+/// 删除会话的所有暂存附件
+#[tauri::command]
+async fn clear_attachments(session: String) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || attachments::clear(&session))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 检查后端（PyPI 包）是否有新版本，返回已安装版本、最新版本和更新说明链接
+#[tauri::command]
+async fn check_backend_updates() -> Result<backend_update::BackendUpdateInfo, String> {
+    backend_update::check().await
+}
+
+/// 获取比 since_version 新、不超过当前版本的更新说明（打包的更新日志和 GitHub 发布说明）
+#[tauri::command]
+async fn get_changelog(since_version: Option<String>) -> Result<changelog::Changelog, String> {
+    Ok(changelog::get(since_version.as_deref()).await)
+}
+
+/// 获取启动时内置资源完整性校验的结果（未校验完成或没有清单时返回 None）
+#[tauri::command]
+async fn get_integrity_report() -> Result<Option<integrity::IntegrityReport>, String> {
+    Ok(integrity::report())
+}
+
+/// 修复损坏的安装：重新下载并安装当前版本后重启
+#[tauri::command]
+async fn repair_installation(app: tauri::AppHandle) -> Result<(), String> {
+    updater::reinstall(&app).await
+}
+
+/// 获取更新后数据迁移的记录（已执行和失败的迁移）
+#[tauri::command]
+async fn get_migration_status() -> Result<migrations::MigrationRecord, String> {
+    Ok(migrations::status())
+}
+
+/// 获取最后可用的后端环境快照，没有时返回 None
+#[tauri::command]
+async fn get_backend_snapshot() -> Result<Option<backend_snapshot::BackendSnapshot>, String> {
+    Ok(backend_snapshot::current())
+}
+
+/// 把后端环境回滚到最后可用的快照并重新启动后端
+#[tauri::command]
+async fn rollback_backend(app: tauri::AppHandle) -> Result<backend_snapshot::BackendSnapshot, String> {
+    backend_snapshot::rollback(&app).await
+}
+
+/// 按设置中的更新通道检查应用更新，没有新版本时返回 None
+#[tauri::command]
+async fn check_for_updates(app: tauri::AppHandle) -> Result<Option<updater::UpdateInfo>, String> {
+    updater::check(&app).await
+}
+
+/// 获取后台定期检查更新的记录（上次检查时间和结果）
+#[tauri::command]
+async fn get_update_check_status() -> Result<update_schedule::UpdateCheckRecord, String> {
+    Ok(update_schedule::status())
+}
+
+/// 下载最近一次检查到的更新（`update-download-progress` 事件报告进度）
+#[tauri::command]
+async fn download_update(app: tauri::AppHandle) -> Result<updater::UpdateInfo, String> {
+    updater::download(&app).await
+}
+
+/// 下载 Python 包等文件到 DAWEI_HOME/downloads（可断点续传，`download-progress` 事件报告进度），
+/// 暂停时返回已暂停的状态
+#[tauri::command]
+async fn start_download(app: tauri::AppHandle, id: String, url: String, file_name: String) -> Result<download::DownloadState, String> {
+    download::run(&app, &id, &url, &file_name, Default::default(), |_, _| {}).await
+}
+
+/// 暂停进行中的下载，已下载的部分保留；下载未在进行时返回 false
+#[tauri::command]
+async fn pause_download(id: String) -> Result<bool, String> {
+    Ok(download::pause(&id))
+}
+
+/// 从断点继续暂停或失败的下载（包括应用更新）
+#[tauri::command]
+async fn resume_download(app: tauri::AppHandle, id: String) -> Result<Option<download::DownloadState>, String> {
+    if id == updater::DOWNLOAD_ID {
+        updater::download(&app).await?;
+        return Ok(download::state(&id));
+    }
+    download::resume(&app, &id).await.map(Some)
+}
+
+/// 获取所有下载记录
+#[tauri::command]
+async fn list_downloads() -> Result<Vec<download::DownloadState>, String> {
+    Ok(download::list())
+}
+
+/// 删除下载记录和已下载的文件
+#[tauri::command]
+async fn discard_download(id: String) -> Result<(), String> {
+    download::discard(&id)
+}
+
+/// 安装已下载的更新并重启应用
+#[tauri::command]
+async fn install_and_restart(app: tauri::AppHandle) -> Result<(), String> {
+    updater::install_and_restart(&app)
+}
+
+/// 安装已下载的更新；新版本要求更新的后端时停止后端、安装并重启，启动后升级后端
+#[tauri::command]
+async fn start_coordinated_upgrade(app: tauri::AppHandle) -> Result<(), String> {
+    upgrade::start(&app).await
+}
+
+/// 获取未完成的协同升级状态，没有时返回 None
+#[tauri::command]
+async fn get_upgrade_status() -> Result<Option<upgrade::UpgradeState>, String> {
+    Ok(upgrade::status())
+}
+
+/// 重试协同升级中失败的阶段
+#[tauri::command]
+async fn retry_upgrade(app: tauri::AppHandle) -> Result<(), String> {
+    upgrade::retry(&app).await
+}
+
+/// 把后端安装为设置中固定的版本（backend_version_pin）并重新启动后端
+#[tauri::command]
+async fn install_pinned_backend(app: tauri::AppHandle) -> Result<String, String> {
+    upgrade::install_pinned(&app).await
+}
+
+/// 放弃未完成的协同升级
+#[tauri::command]
+async fn abandon_upgrade() -> Result<(), String> {
+    upgrade::abandon()
+}
+
+// ==================== 页面缩放功能 ====================
+
+/// 放大页面，返回新的缩放比例
+#[tauri::command]
+async fn zoom_in(app: tauri::AppHandle) -> Result<f64, String> {
+    zoom::zoom_in(&app)
+}
+
+/// 缩小页面，返回新的缩放比例
+#[tauri::command]
+async fn zoom_out(app: tauri::AppHandle) -> Result<f64, String> {
+    zoom::zoom_out(&app)
+}
+
+/// 重置缩放
+#[tauri::command]
+async fn zoom_reset(app: tauri::AppHandle) -> Result<f64, String> {
+    zoom::reset(&app)
+}
+
+/// 设置缩放比例（限制在 50%-300%，保存在设置中），返回实际应用的比例
+#[tauri::command]
+async fn set_zoom(app: tauri::AppHandle, level: f64) -> Result<f64, String> {
+    zoom::set(&app, level)
+}
+
+/// 获取当前缩放比例
+#[tauri::command]
+async fn get_zoom() -> Result<f64, String> {
+    Ok(zoom::current())
 }
 
 fn main() {
+    startup_metrics::mark_launch();
+
+    // ==================== 命令行参数 ====================
+    // 无界面的诊断参数，在启动监控进程和 Tauri 之前处理
+    if let Some(code) = cli::handle_args() {
+        std::process::exit(code);
+    }
+
+    // ==================== 日志 ====================
+    // 监控进程和界面进程都写入 DAWEI_HOME/logs/app
+    logging::init();
+
+    // 可选的崩溃上传（需要 sentry feature 和 DAWEI_CRASH_UPLOAD=1），
+    // 在监控进程中也初始化，以便上传原生崩溃
+    crash_upload::init();
+
+    // ==================== 原生崩溃监控 ====================
+    // 监控进程只负责等待子进程退出并记录原生崩溃，不启动界面
+    if let Some(code) = crash_monitor::supervise() {
+        std::process::exit(code);
+    }
+    crash_monitor::attach_minidump_client();
+
     // ==================== 设置 Panic Hook ====================
     setup_panic_hook();
+    crash_handler::collect_fallback_crash_reports();
+
+    // 首次运行状态（须在其他模块写入设置之前检测）
+    first_run::init();
+
+    // DevTools 配置 - 锁定模式下禁用，其他模式下都可用
+    // 通过设置中的 open_devtools 或环境变量 DAWEI_DEVTOOLS=1 控制是否自动打开
+    let devtools_enabled = !policy::is_locked_down();
+    let auto_open_devtools = devtools_enabled
+        && match std::env::var("DAWEI_DEVTOOLS") {
+            Ok(value) => value == "1",
+            Err(_) => settings::current().open_devtools,
+        };
 
-    // DevTools 配置 - 所有模式下都可用
-    // 通过环境变量 DAWEI_DEVTOOLS=1 控制是否自动打开
-    let auto_open_devtools = std::env::var("DAWEI_DEVTOOLS")
-        .unwrap_or_default()
-        == "1";
-
-    let builder = tauri::Builder::default();
+    // 单实例插件必须最先注册：第二次启动时聚焦已有窗口并转发参数，然后直接退出
+    let builder = tauri::Builder::default().plugin(tauri_plugin_single_instance::init(single_instance::on_second_instance));
+    let builder = builder.plugin(tauri_plugin_global_shortcut::Builder::new().build());
+    let updater_plugin = match updater::PUBKEY {
+        Some(pubkey) => tauri_plugin_updater::Builder::new().pubkey(pubkey),
+        None => tauri_plugin_updater::Builder::new(),
+    };
+    let builder = builder.plugin(updater_plugin.build());
 
     // 设置窗口事件和 DevTools
     let builder = builder.setup(move |app| {
+        // 检测上次会话是否异常结束，并标记本次会话开始
+        // （在单实例检查之后，避免第二个进程覆盖正在运行的会话标记）
+        session::begin_session();
+
+        // 更新后首次启动时迁移数据（在创建窗口、读取缓存之前）
+        migrations::run_pending(app.handle());
+
+        // 主窗口在配置中设置为不自动创建，这里按锁定模式决定是否允许 DevTools
+        if let Some(config) = app.config().app.windows.iter().find(|w| w.label == "main") {
+            // 主窗口先隐藏，页面和后端就绪后由启动画面显示
+            let window = portable::webview(tauri::WebviewWindowBuilder::from_config(app.handle(), config)?)
+                .devtools(devtools_enabled)
+                .initialization_script(log_timeline::CONSOLE_FORWARD_SCRIPT)
+                .visible(false)
+                .build()?;
+            window_state::restore(&window);
+            if let Err(e) = splash::show(app.handle()) {
+                tracing::warn!("Failed to show splash window: {}", e);
+                splash::finish(app.handle());
+            }
+        }
+
         // 自动打开 DevTools（如果环境变量设置）
         if auto_open_devtools {
             if let Some(window) = app.get_webview_window("main") {
@@ -589,32 +1778,248 @@ fn main() {
             }
         }
 
+        settings::watch(app.handle());
+        logging::watch(app.handle());
+        slow_command::init(app.handle());
+        log_timeline::init(app.handle());
+        if let Err(e) = menu::init(app.handle()) {
+            tracing::warn!("Failed to create menu: {}", e);
+        }
+        if let Err(e) = tray::init(app.handle()) {
+            tracing::warn!("Failed to create tray icon: {}", e);
+        }
+        always_on_top::init(app.handle());
+        hotkey::init(app.handle());
+        zoom::init(app.handle());
+        titlebar::init(app.handle());
+        devtools::init(app.handle());
+        theme::init(app.handle());
+        i18n::init(app.handle());
+
+        // 崩溃后在独立窗口中展示报告，上次运行遗留的未查看报告在启动时展示
+        let handle = app.handle().clone();
+        crash_handler::set_crash_listener(move |_| crash_viewer::request_open(&handle));
+        crash_viewer::open_if_unviewed(app.handle());
+        backend_crash::watch_backend_log();
+        hang_watchdog::start(app.handle());
+        backend_stats::start(app.handle());
+        crash_upload::start_retry_worker();
+        telemetry::init(app.handle());
+        drag_out::cleanup_stale();
+        upgrade::resume(app.handle());
+        integrity::verify_in_background(app.handle());
+        update_schedule::start(app.handle());
+
         Ok(())
     });
 
+    // 记录窗口和页面事件作为崩溃面包屑（移动/缩放过于频繁，不记录）
+    let builder = builder
+        .on_window_event(|window, event| {
+            if !matches!(event, tauri::WindowEvent::Moved(_) | tauri::WindowEvent::Resized(_)) {
+                breadcrumbs::record("window", format!("{}: {:?}", window.label(), event));
+            }
+            window_state::on_window_event(window, event);
+            // 隐藏到托盘时不是退出，不需要确认
+            if !tray::on_window_event(window, event) {
+                quit_guard::on_window_event(window, event);
+            }
+            workspace_window::on_window_event(window, event);
+            file_drop::on_window_event(window, event);
+            if let tauri::WindowEvent::ThemeChanged(system_theme) = event {
+                theme::on_system_theme_changed(window.app_handle(), *system_theme);
+            }
+        })
+        .on_page_load(|webview, payload| {
+            breadcrumbs::record("page", format!("{}: {:?} {}", webview.label(), payload.event(), payload.url()));
+            if webview.label() == "main" && payload.event() == tauri::webview::PageLoadEvent::Finished {
+                splash::on_main_loaded();
+            }
+            kiosk::on_page_load(webview);
+        });
+
+    let handler: fn(tauri::ipc::Invoke<tauri::Wry>) -> bool = tauri::generate_handler![
+        // 导航命令
+        navigate_to_main,
+        // 文件操作命令
+        select_directory,
+        select_directories,
+        select_file,
+        select_files,
+        save_file_dialog,
+        list_bookmarks,
+        add_bookmark,
+        remove_bookmark,
+        trash_paths,
+        reveal_in_file_manager,
+        list_editors,
+        open_in_editor,
+        validate_workspace,
+        get_recent_workspaces,
+        pin_workspace,
+        remove_recent,
+        list_workspace_templates,
+        create_workspace_from_template,
+        // 系统浏览器命令
+        open_by_system_browser,
+        // 崩溃报告命令
+        get_crash_reports,
+        get_latest_crash_report,
+        get_crash_report,
+        format_crash_report,
+        format_crash_as_markdown,
+        open_crash_viewer,
+        open_crash_window,
+        export_crash_report,
+        export_view_pdf,
+        clear_crash_reports,
+        get_crash_upload_status,
+        preview_telemetry,
+        purge_telemetry,
+        get_last_session_crash,
+        acknowledge_last_session_crash,
+        get_session_id,
+        get_about_info,
+        get_hardware_info,
+        // 日志和诊断命令
+        get_app_logs,
+        get_log_timeline,
+        search_logs,
+        set_log_streaming,
+        subscribe_logs,
+        unsubscribe_logs,
+        open_log_file,
+        log_webview,
+        export_diagnostics,
+        check_connectivity,
+        run_self_test,
+        get_disk_usage,
+        // 服务器信息命令
+        get_dawei_home_command,
+        relocate_dawei_home,
+        get_server_start_info,
+        get_startup_progress,
+        get_startup_metrics,
+        get_backend_stats,
+        get_python_info,
+        // 后端管理命令
+        start_backend,
+        stop_backend,
+        restart_backend,
+        update_task_progress,
+        set_active_profile,
+        diagnose_port,
+        kill_port_owner,
+        list_related_processes,
+        terminate_stale_processes,
+        // 设置命令
+        get_settings,
+        update_settings,
+        get_effective_settings,
+        get_theme,
+        set_theme,
+        get_locale,
+        set_locale,
+        set_log_level,
+        export_config,
+        import_config,
+        get_first_run_state,
+        is_first_run,
+        complete_onboarding_step,
+        finish_onboarding,
+        list_env_vars,
+        get_env_var,
+        set_env_var,
+        unset_env_var,
+        request_reset_token,
+        reset_app_data,
+        request_cleanup_token,
+        cleanup_app_data,
+        // 窗口命令
+        open_workspace_window,
+        get_window_workspace,
+        read_workspace_file,
+        write_workspace_file,
+        list_workspace_tree,
+        search_in_workspace,
+        cancel_workspace_search,
+        start_drag_out,
+        get_workspace_stats,
+        cancel_workspace_stats,
+        export_workspace_zip,
+        import_workspace_zip,
+        watch_workspace,
+        unwatch_workspace,
+        set_always_on_top,
+        set_global_shortcut,
+        set_kiosk_mode,
+        window_minimize,
+        window_toggle_maximize,
+        window_close,
+        start_dragging,
+        set_custom_titlebar,
+        get_titlebar_info,
+        toggle_devtools,
+        confirm_quit,
+        upload_dropped_files,
+        stage_attachments,
+        remove_attachment,
+        clear_attachments,
+        check_for_updates,
+        check_backend_updates,
+        start_coordinated_upgrade,
+        get_upgrade_status,
+        retry_upgrade,
+        abandon_upgrade,
+        install_pinned_backend,
+        get_changelog,
+        get_migration_status,
+        get_integrity_report,
+        repair_installation,
+        get_backend_snapshot,
+        rollback_backend,
+        get_update_check_status,
+        download_update,
+        start_download,
+        pause_download,
+        resume_download,
+        list_downloads,
+        discard_download,
+        install_and_restart,
+        // 页面缩放命令
+        zoom_in,
+        zoom_out,
+        zoom_reset,
+        set_zoom,
+        get_zoom,
+    ];
+
     builder
-        .invoke_handler(tauri::generate_handler![
-            // 导航命令
-            navigate_to_main,
-            // 文件操作命令
-            select_directory,
-            // 系统浏览器命令
-            open_by_system_browser,
-            // 崩溃报告命令
-            get_crash_reports,
-            clear_crash_reports,
-            // 服务器信息命令
-            get_dawei_home_command,
-            get_server_start_info,
-            get_python_info,
-            // 后端管理命令
-            start_backend,
-            // 页面缩放命令
-            zoom_in,
-            zoom_out,
-            zoom_reset,
-            set_zoom,
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .invoke_handler(move |invoke| {
+            // 同步命令和异步命令的启动阶段都在该 span 内，日志带上命令名
+            let _span = tracing::info_span!("command", name = invoke.message.command()).entered();
+            // 页面日志转发很频繁，不记录为面包屑
+            if invoke.message.command() != "log_webview" {
+                breadcrumbs::record("command", invoke.message.command());
+            }
+            telemetry::record_command(invoke.message.command());
+            if policy::blocks_command(invoke.message.command()) {
+                invoke.resolver.reject("锁定模式下不可用");
+                return true;
+            }
+            let _timing = slow_command::span(invoke.message.command(), invoke.message.payload()).map(|span| span.entered());
+            handler(invoke)
+        })
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            quit_guard::on_run_event(app, &event);
+            // 正常退出时清除会话标记，下次启动不会进入崩溃恢复流程
+            if let tauri::RunEvent::Exit = event {
+                window_state::save();
+                workspace_lock::release_all();
+                telemetry::save();
+                session::end_session();
+            }
+        });
 }
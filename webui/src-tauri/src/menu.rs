@@ -0,0 +1,335 @@
+//! 应用菜单栏
+//!
+//! 文件（打开工作区、最近的工作区）、编辑、视图（缩放）、后端（启动/停止/重启）和帮助（日志、崩溃报告、关于）。
+//! macOS 上设置为应用菜单；Windows/Linux 上只附加到主窗口和工作区窗口，
+//! 启动画面、崩溃报告等小窗口不显示菜单栏。菜单操作复用对应命令的实现。
+
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use tauri::menu::{AboutMetadata, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::{AppHandle, Emitter, Manager, Wry};
+
+// 菜单项 ID
+const MENU_OPEN_WORKSPACE: &str = "menu-open-workspace";
+const MENU_RECENT_PREFIX: &str = "menu-recent:";
+const MENU_ZOOM_IN: &str = "menu-zoom-in";
+const MENU_ZOOM_OUT: &str = "menu-zoom-out";
+const MENU_ZOOM_RESET: &str = "menu-zoom-reset";
+const MENU_BACKEND_START: &str = "menu-backend-start";
+const MENU_BACKEND_STOP: &str = "menu-backend-stop";
+const MENU_BACKEND_RESTART: &str = "menu-backend-restart";
+const MENU_OPEN_LOGS: &str = "menu-open-logs";
+const MENU_CRASH_REPORTS: &str = "menu-crash-reports";
+const MENU_TOGGLE_DEVTOOLS: &str = "menu-toggle-devtools";
+
+/// 最近的工作区最多显示的数量
+const MAX_RECENT: usize = 10;
+
+/// 已创建的菜单（Windows/Linux 上新建工作区窗口时附加）
+static MENU: OnceLock<Menu<Wry>> = OnceLock::new();
+
+/// DevTools 菜单项（按 devtools_toggle 设置启用）
+static DEVTOOLS_ITEM: OnceLock<MenuItem<Wry>> = OnceLock::new();
+
+/// 最近的工作区：(名称, 路径)，最近添加的在前
+fn recent_workspaces(index: &Value) -> Vec<(String, String)> {
+    let Some(workspaces) = index["workspaces"].as_array() else {
+        return Vec::new();
+    };
+    workspaces
+        .iter()
+        .rev()
+        .filter_map(|ws| {
+            let path = ws.get("path").and_then(Value::as_str)?;
+            let name = ["display_name", "name"]
+                .iter()
+                .find_map(|key| ws.get(*key).and_then(Value::as_str).filter(|s| !s.is_empty()))
+                .unwrap_or(path);
+            Some((name.to_string(), path.to_string()))
+        })
+        .take(MAX_RECENT)
+        .collect()
+}
+
+fn recent_submenu(app: &AppHandle) -> tauri::Result<Submenu<Wry>> {
+    let index = std::fs::read_to_string(crate::get_dawei_home().join("workspaces.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or(Value::Null);
+    let recent = recent_workspaces(&index);
+
+    let submenu = Submenu::new(app, "最近的工作区", !recent.is_empty())?;
+    for (name, path) in recent {
+        submenu.append(&MenuItem::with_id(app, format!("{}{}", MENU_RECENT_PREFIX, path), name, true, None::<&str>)?)?;
+    }
+    Ok(submenu)
+}
+
+fn build(app: &AppHandle) -> tauri::Result<Menu<Wry>> {
+    let package = app.package_info();
+    let about = AboutMetadata {
+        name: Some("大微".to_string()),
+        version: Some(package.version.to_string()),
+        comments: Some(package.description.to_string()),
+        ..Default::default()
+    };
+
+    let file = Submenu::with_items(
+        app,
+        "文件",
+        true,
+        &[
+            &MenuItem::with_id(app, MENU_OPEN_WORKSPACE, "打开工作区…", true, Some("CmdOrCtrl+O"))?,
+            &recent_submenu(app)?,
+            &PredefinedMenuItem::separator(app)?,
+            &PredefinedMenuItem::close_window(app, Some("关闭窗口"))?,
+            #[cfg(not(target_os = "macos"))]
+            &PredefinedMenuItem::quit(app, Some("退出"))?,
+        ],
+    )?;
+    let edit = Submenu::with_items(
+        app,
+        "编辑",
+        true,
+        &[
+            &PredefinedMenuItem::undo(app, Some("撤销"))?,
+            &PredefinedMenuItem::redo(app, Some("重做"))?,
+            &PredefinedMenuItem::separator(app)?,
+            &PredefinedMenuItem::cut(app, Some("剪切"))?,
+            &PredefinedMenuItem::copy(app, Some("复制"))?,
+            &PredefinedMenuItem::paste(app, Some("粘贴"))?,
+            &PredefinedMenuItem::select_all(app, Some("全选"))?,
+        ],
+    )?;
+    let view = Submenu::with_items(
+        app,
+        "视图",
+        true,
+        &[
+            &MenuItem::with_id(app, MENU_ZOOM_IN, "放大", true, Some("CmdOrCtrl+="))?,
+            &MenuItem::with_id(app, MENU_ZOOM_OUT, "缩小", true, Some("CmdOrCtrl+-"))?,
+            &MenuItem::with_id(app, MENU_ZOOM_RESET, "实际大小", true, Some("CmdOrCtrl+0"))?,
+        ],
+    )?;
+    let backend = Submenu::with_items(
+        app,
+        "后端",
+        true,
+        &[
+            &MenuItem::with_id(app, MENU_BACKEND_START, "启动后端", true, None::<&str>)?,
+            &MenuItem::with_id(app, MENU_BACKEND_STOP, "停止后端", true, None::<&str>)?,
+            &MenuItem::with_id(app, MENU_BACKEND_RESTART, "重启后端", true, None::<&str>)?,
+        ],
+    )?;
+    let devtools = MenuItem::with_id(
+        app,
+        MENU_TOGGLE_DEVTOOLS,
+        "开发者工具",
+        crate::devtools::is_allowed(),
+        Some("CmdOrCtrl+Alt+I"),
+    )?;
+    let help = Submenu::with_items(
+        app,
+        "帮助",
+        true,
+        &[
+            &MenuItem::with_id(app, MENU_OPEN_LOGS, "打开日志目录", true, None::<&str>)?,
+            &MenuItem::with_id(app, MENU_CRASH_REPORTS, "崩溃报告", true, None::<&str>)?,
+            &devtools,
+            &PredefinedMenuItem::separator(app)?,
+            &PredefinedMenuItem::about(app, Some("关于大微"), Some(about.clone()))?,
+        ],
+    )?;
+
+    // macOS 的第一个子菜单是应用菜单
+    #[cfg(target_os = "macos")]
+    let menu = Menu::with_items(
+        app,
+        &[
+            &Submenu::with_items(
+                app,
+                "大微",
+                true,
+                &[
+                    &PredefinedMenuItem::about(app, Some("关于大微"), Some(about))?,
+                    &PredefinedMenuItem::separator(app)?,
+                    &PredefinedMenuItem::services(app, None)?,
+                    &PredefinedMenuItem::separator(app)?,
+                    &PredefinedMenuItem::hide(app, None)?,
+                    &PredefinedMenuItem::hide_others(app, None)?,
+                    &PredefinedMenuItem::show_all(app, None)?,
+                    &PredefinedMenuItem::separator(app)?,
+                    &PredefinedMenuItem::quit(app, Some("退出大微"))?,
+                ],
+            )?,
+            &file,
+            &edit,
+            &view,
+            &backend,
+            &help,
+        ],
+    )?;
+    #[cfg(not(target_os = "macos"))]
+    let menu = Menu::with_items(app, &[&file, &edit, &view, &backend, &help])?;
+
+    let _ = DEVTOOLS_ITEM.set(devtools);
+    Ok(menu)
+}
+
+/// 更新 DevTools 菜单项的可用状态
+pub fn set_devtools_enabled(enabled: bool) {
+    if let Some(item) = DEVTOOLS_ITEM.get() {
+        let _ = item.set_enabled(enabled);
+    }
+}
+
+/// 把菜单栏附加到窗口（macOS 使用应用菜单，无需附加）
+pub fn attach(window: &tauri::WebviewWindow) {
+    #[cfg(not(target_os = "macos"))]
+    if let Some(menu) = MENU.get() {
+        if let Err(e) = window.set_menu(menu.clone()) {
+            tracing::warn!("Failed to set window menu: {}", e);
+        }
+    }
+    #[cfg(target_os = "macos")]
+    let _ = window;
+}
+
+/// 创建菜单栏，macOS 上设置为应用菜单，其他平台附加到主窗口
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    let menu = build(app)?;
+    #[cfg(target_os = "macos")]
+    app.set_menu(menu.clone())?;
+    let _ = MENU.set(menu);
+
+    #[cfg(not(target_os = "macos"))]
+    if let Some(window) = app.get_webview_window("main") {
+        attach(&window);
+    }
+
+    app.on_menu_event(on_menu_event);
+    Ok(())
+}
+
+/// 把菜单操作的结果以日志形式发送给前端
+fn report(app: &AppHandle, result: Result<String, String>) {
+    let message = result.unwrap_or_else(|e| {
+        tracing::error!("{}", e);
+        e
+    });
+    if let Err(e) = app.emit("app-log", message) {
+        tracing::warn!("Failed to emit app-log: {}", e);
+    }
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+    let id = event.id().as_ref();
+    let handle = app.clone();
+
+    if let Some(path) = id.strip_prefix(MENU_RECENT_PREFIX) {
+        if let Err(e) = crate::workspace_window::open(app, &PathBuf::from(path)) {
+            report(app, Err(e));
+        }
+        return;
+    }
+
+    match id {
+        MENU_OPEN_WORKSPACE => {
+            tauri::async_runtime::spawn(async move {
+                match crate::select_directory(None).await {
+                    Ok(Some(path)) => {
+                        if let Err(e) = crate::workspace_window::open(&handle, &PathBuf::from(path)) {
+                            report(&handle, Err(e));
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => report(&handle, Err(e)),
+                }
+            });
+        }
+        MENU_ZOOM_IN | MENU_ZOOM_OUT | MENU_ZOOM_RESET => {
+            let result = match id {
+                MENU_ZOOM_IN => crate::zoom::zoom_in(app),
+                MENU_ZOOM_OUT => crate::zoom::zoom_out(app),
+                _ => crate::zoom::reset(app),
+            };
+            if let Err(e) = result {
+                report(app, Err(e));
+            }
+        }
+        MENU_BACKEND_START => {
+            tauri::async_runtime::spawn(async move {
+                let result = crate::start_backend(handle.clone()).await;
+                // start_backend 已经发送了启动日志，这里只处理错误
+                if let Err(e) = result {
+                    tracing::error!("{}", e);
+                }
+            });
+        }
+        MENU_BACKEND_STOP => {
+            tauri::async_runtime::spawn(async move {
+                let result = crate::stop_backend().await.map(|stopped| {
+                    if stopped { "✅ 后端已停止" } else { "ℹ️  没有由桌面端启动的后端" }.to_string()
+                });
+                report(&handle, result);
+            });
+        }
+        MENU_BACKEND_RESTART => {
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = crate::restart_backend(handle.clone()).await {
+                    tracing::error!("{}", e);
+                }
+            });
+        }
+        MENU_OPEN_LOGS => {
+            let logs_dir = crate::get_dawei_home().join("logs");
+            let result = std::fs::create_dir_all(&logs_dir)
+                .map_err(|e| format!("无法创建日志目录: {}", e))
+                .and_then(|_| crate::open_path(&logs_dir));
+            if let Err(e) = result {
+                report(app, Err(e));
+            }
+        }
+        MENU_TOGGLE_DEVTOOLS => {
+            // 切换当前获得焦点的窗口
+            let focused = app.webview_windows().into_values().find(|w| w.is_focused().unwrap_or(false));
+            if let Some(window) = focused {
+                if let Err(e) = crate::devtools::toggle(&window) {
+                    report(app, Err(e));
+                }
+            }
+        }
+        MENU_CRASH_REPORTS => {
+            if let Err(e) = crate::crash_viewer::open_crash_viewer(app) {
+                report(app, Err(format!("无法打开崩溃报告窗口: {}", e)));
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_recent_workspaces() {
+        let index = json!({ "workspaces": [
+            { "id": "a", "name": "a", "path": "/ws/a" },
+            { "id": "b", "name": "b", "display_name": "项目 B", "path": "/ws/b" },
+            { "id": "c", "name": "", "path": "/ws/c" },
+            { "id": "d" },
+        ]});
+        assert_eq!(
+            recent_workspaces(&index),
+            vec![
+                ("/ws/c".to_string(), "/ws/c".to_string()),
+                ("项目 B".to_string(), "/ws/b".to_string()),
+                ("a".to_string(), "/ws/a".to_string()),
+            ]
+        );
+        assert!(recent_workspaces(&Value::Null).is_empty());
+    }
+}
@@ -0,0 +1,191 @@
+//! 应用更新后的数据迁移
+//!
+//! 每个迁移有唯一 ID 和引入它的应用版本，按列表顺序执行，执行过的迁移记录在
+//! DAWEI_HOME/migrations.json 中，不会重复执行。应用版本变化后的首次启动（包括全新安装）
+//! 执行所有未执行、且不晚于当前版本的迁移；上次失败的迁移在之后每次启动时重试。
+//! 单个迁移失败不影响其他迁移，失败原因保存在记录中，由 `get_migration_status` 返回给前端。
+//! 协同升级（[`crate::upgrade`]）的迁移阶段也调用这里。
+
+use crate::atomic_file::write_json_atomic;
+use crate::backend_update::compare_versions;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+/// 记录文件名
+const RECORD_FILE: &str = "migrations.json";
+
+/// 迁移
+struct Migration {
+    id: &'static str,
+    /// 引入迁移的应用版本
+    version: &'static str,
+    run: fn(&AppHandle) -> Result<(), String>,
+}
+
+/// 所有迁移（按版本排列）
+const MIGRATIONS: [Migration; 3] = [
+    Migration { id: "crashes-to-dawei-home", version: "0.1.0", run: move_crash_reports },
+    Migration { id: "settings-schema", version: "0.1.0", run: convert_settings },
+    Migration { id: "clear-stale-caches", version: "0.1.0", run: clear_caches },
+];
+
+/// 失败的迁移
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MigrationFailure {
+    pub error: String,
+    /// 失败时的应用版本
+    pub app_version: String,
+    pub failed_at: String,
+    pub attempts: u32,
+}
+
+/// 迁移记录
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MigrationRecord {
+    /// 上次检查迁移时的应用版本
+    pub app_version: Option<String>,
+    /// 已执行的迁移和执行时间
+    pub applied: BTreeMap<String, String>,
+    /// 失败的迁移
+    pub failed: BTreeMap<String, MigrationFailure>,
+}
+
+fn record_file() -> PathBuf {
+    crate::get_dawei_home().join(RECORD_FILE)
+}
+
+fn read_record(path: &Path) -> MigrationRecord {
+    let Ok(content) = fs::read_to_string(path) else {
+        return MigrationRecord::default();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        tracing::warn!("Invalid {}: {}", RECORD_FILE, e);
+        MigrationRecord::default()
+    })
+}
+
+fn write_record(path: &Path, record: &MigrationRecord) -> Result<(), String> {
+    write_json_atomic(path, record).map_err(|e| format!("保存迁移记录失败: {}", e))
+}
+
+/// 旧版本把崩溃报告保存在可执行文件目录下
+fn move_crash_reports(_app: &AppHandle) -> Result<(), String> {
+    let count = crate::crash_handler::migrate_legacy_crash_reports()?;
+    if count > 0 {
+        tracing::info!("Migrated {} crash report(s) to DAWEI_HOME", count);
+    }
+    Ok(())
+}
+
+/// 把设置文件转换为当前版本的格式
+fn convert_settings(_app: &AppHandle) -> Result<(), String> {
+    crate::settings::ensure_migrated()
+}
+
+/// 清除旧版本留下的缓存（WebView 缓存等，内容可以重建）
+fn clear_caches(app: &AppHandle) -> Result<(), String> {
+    let Some(cache_dir) = crate::portable::cache_dir(app) else {
+        return Ok(());
+    };
+    let Ok(entries) = fs::read_dir(&cache_dir) else {
+        return Ok(());
+    };
+    let failed: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let result = if entry.file_type().is_ok_and(|t| t.is_dir()) { fs::remove_dir_all(&path) } else { fs::remove_file(&path) };
+            result.err().map(|e| format!("{}: {}", path.display(), e))
+        })
+        .collect();
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("无法删除缓存: {}", failed.join("; ")))
+    }
+}
+
+/// 需要执行的迁移的下标：未执行且不晚于当前版本
+fn pending(record: &MigrationRecord, versions: &[(&str, &str)], app_version: &str) -> Vec<usize> {
+    versions
+        .iter()
+        .enumerate()
+        .filter(|(_, (id, version))| !record.applied.contains_key(*id) && compare_versions(version, app_version).is_le())
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// 执行迁移并更新记录
+fn apply(record: &mut MigrationRecord, id: &str, result: Result<(), String>, app_version: &str) {
+    let now = chrono::Local::now().to_rfc3339();
+    match result {
+        Ok(()) => {
+            tracing::info!("Applied migration {}", id);
+            record.failed.remove(id);
+            record.applied.insert(id.to_string(), now);
+        }
+        Err(error) => {
+            tracing::error!("Migration {} failed: {}", id, error);
+            crate::breadcrumbs::record("app", format!("Migration {} failed: {}", id, error));
+            let attempts = record.failed.get(id).map_or(0, |failure| failure.attempts) + 1;
+            let failure = MigrationFailure { error, app_version: app_version.to_string(), failed_at: now, attempts };
+            record.failed.insert(id.to_string(), failure);
+        }
+    }
+}
+
+/// 应用版本变化或有失败的迁移时执行迁移，返回本次仍然失败的迁移
+pub fn run_pending(app: &AppHandle) -> BTreeMap<String, MigrationFailure> {
+    let path = record_file();
+    let app_version = env!("CARGO_PKG_VERSION");
+    let mut record = read_record(&path);
+    if record.app_version.as_deref() == Some(app_version) && record.failed.is_empty() {
+        return BTreeMap::new();
+    }
+
+    let versions: Vec<(&str, &str)> = MIGRATIONS.iter().map(|m| (m.id, m.version)).collect();
+    for index in pending(&record, &versions, app_version) {
+        let migration = &MIGRATIONS[index];
+        apply(&mut record, migration.id, (migration.run)(app), app_version);
+    }
+    record.app_version = Some(app_version.to_string());
+    if let Err(e) = write_record(&path, &record) {
+        tracing::warn!("{}", e);
+    }
+    record.failed
+}
+
+/// 迁移记录
+pub fn status() -> MigrationRecord {
+    read_record(&record_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_and_record() {
+        let versions = [("a", "0.1.0"), ("b", "0.2.0"), ("c", "0.3.0")];
+        let mut record = MigrationRecord::default();
+        assert_eq!(pending(&record, &versions, "0.2.0"), [0, 1]);
+
+        apply(&mut record, "a", Ok(()), "0.2.0");
+        apply(&mut record, "b", Err("disk full".to_string()), "0.2.0");
+        apply(&mut record, "b", Err("disk full".to_string()), "0.2.0");
+        assert_eq!(record.failed["b"].attempts, 2);
+        assert_eq!(pending(&record, &versions, "0.3.0"), [1, 2]);
+
+        apply(&mut record, "b", Ok(()), "0.3.0");
+        assert!(record.failed.is_empty() && record.applied.contains_key("b"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(RECORD_FILE);
+        write_record(&path, &record).unwrap();
+        assert_eq!(read_record(&path), record);
+    }
+}
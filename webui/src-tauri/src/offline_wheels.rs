@@ -0,0 +1,87 @@
+//! 离线安装包
+//!
+//! 隔离网络中的部署在安装目录的 resources/offline-wheels/ 下放入后端及其依赖的 wheel 文件。
+//! 检测到该目录时，所有安装后端依赖的 uv 命令（协同升级、固定版本、回滚、源码目录的 `uv run`）
+//! 都加上 `--no-index --find-links <目录>`，只从离线包安装、不访问网络索引。
+//! 检测结果在自检（`run_self_test`）的 `offline_wheels` 项中给出。
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// 离线包目录（位于 resources 目录）
+const OFFLINE_WHEELS_DIR: &str = "offline-wheels";
+
+/// 检测到的离线包
+#[derive(Debug, Clone, PartialEq)]
+pub struct OfflineWheels {
+    pub dir: PathBuf,
+    /// 目录中的 wheel 和源码包数量
+    pub packages: usize,
+}
+
+/// 检查 resources 目录下的离线包，目录不存在时返回 None
+fn detect_in(resources: &Path) -> Option<OfflineWheels> {
+    let dir = resources.join(OFFLINE_WHEELS_DIR);
+    let entries = std::fs::read_dir(&dir).ok()?;
+    let packages = entries
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_lowercase();
+            name.ends_with(".whl") || name.ends_with(".tar.gz") || name.ends_with(".zip")
+        })
+        .count();
+    Some(OfflineWheels { dir, packages })
+}
+
+/// 安装目录中的离线包
+pub fn detect() -> Option<OfflineWheels> {
+    let exe = std::env::current_exe().ok()?;
+    detect_in(&exe.parent()?.join("resources"))
+}
+
+/// uv 的索引参数：有离线包时只从离线目录安装，否则使用 `default_index`（None 时沿用项目自己的配置）
+fn index_args_for(offline: Option<&OfflineWheels>, default_index: Option<String>) -> Vec<OsString> {
+    match (offline, default_index) {
+        (Some(offline), _) => vec!["--no-index".into(), "--find-links".into(), offline.dir.clone().into()],
+        (None, Some(index)) => vec!["--default-index".into(), index.into()],
+        (None, None) => Vec::new(),
+    }
+}
+
+/// 当前安装的 uv 索引参数，见 [`index_args_for`]
+pub fn index_args(default_index: Option<String>) -> Vec<OsString> {
+    index_args_for(detect().as_ref(), default_index)
+}
+
+/// 自检项：离线目录存在但没有安装包时安装必然失败
+pub fn describe() -> Result<String, String> {
+    match detect() {
+        Some(offline) if offline.packages == 0 => Err(format!("离线安装包目录为空: {}", offline.dir.display())),
+        Some(offline) => Ok(format!("离线安装：{}（{} 个安装包），安装依赖时不访问网络", offline.dir.display(), offline.packages)),
+        None => Ok(format!("未检测到离线安装包，从 {} 安装依赖", crate::connectivity::pypi_index())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_and_args() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(detect_in(dir.path()).is_none());
+        assert_eq!(index_args_for(None, Some("https://pypi.org/simple".to_string())), ["--default-index", "https://pypi.org/simple"]);
+        assert!(index_args_for(None, None).is_empty());
+
+        let wheels = dir.path().join(OFFLINE_WHEELS_DIR);
+        std::fs::create_dir_all(&wheels).unwrap();
+        assert_eq!(detect_in(dir.path()).unwrap().packages, 0);
+        std::fs::write(wheels.join("davybot-0.3.2-py3-none-any.whl"), "").unwrap();
+        std::fs::write(wheels.join("pyyaml-6.0.2.tar.gz"), "").unwrap();
+        std::fs::write(wheels.join("README.txt"), "").unwrap();
+        let offline = detect_in(dir.path()).unwrap();
+        assert_eq!(offline.packages, 2);
+        let args = index_args_for(Some(&offline), Some("https://pypi.org/simple".to_string()));
+        assert_eq!(args, [OsString::from("--no-index"), "--find-links".into(), wheels.into()]);
+    }
+}
@@ -0,0 +1,108 @@
+//! Windows 路径规范化
+//!
+//! 文件对话框在 Windows 上返回的路径直接交给 uv 和后端时可能出错，选择后统一处理：
+//! - 映射的网络驱动器（`Z:\...`）转换为 UNC 路径（`\\server\share\...`）：
+//!   驱动器映射只对当前登录会话有效，以管理员身份或作为服务运行的后端看不到；
+//! - 超过 MAX_PATH 的路径加上 `\\?\`（UNC 为 `\\?\UNC\`）前缀，绕过 260 个字符的限制；
+//!   未超过时去掉该前缀，保持用户熟悉的形式。
+//!
+//! 其他平台原样返回。
+
+use std::path::{Path, PathBuf};
+
+/// 目录路径的长度上限：MAX_PATH（260）减去 8.3 文件名所需的 12 个字符
+const MAX_DIR_PATH: usize = 248;
+
+/// 去掉 `\\?\` 前缀，统一使用反斜杠
+#[cfg_attr(not(windows), allow(dead_code))]
+fn simplify(path: &str) -> String {
+    let path = path.replace('/', "\\");
+    if let Some(unc) = path.strip_prefix(r"\\?\UNC\") {
+        return format!(r"\\{}", unc);
+    }
+    path.strip_prefix(r"\\?\").map(str::to_string).unwrap_or(path)
+}
+
+/// 路径的驱动器盘符部分（`Z:`），其余部分以 `\` 开头或为空
+#[cfg_attr(not(windows), allow(dead_code))]
+fn drive(path: &str) -> Option<(&str, &str)> {
+    let bytes = path.as_bytes();
+    let is_drive = bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+    let rest = path.get(2..)?;
+    (is_drive && (rest.is_empty() || rest.starts_with('\\'))).then(|| (&path[..2], rest))
+}
+
+/// 超过长度上限时加上 `\\?\` 前缀（只处理绝对路径）
+#[cfg_attr(not(windows), allow(dead_code))]
+fn extend_long(path: &str) -> String {
+    if path.encode_utf16().count() < MAX_DIR_PATH {
+        return path.to_string();
+    }
+    if let Some(unc) = path.strip_prefix(r"\\") {
+        return format!(r"\\?\UNC\{}", unc);
+    }
+    match drive(path) {
+        Some(_) => format!(r"\\?\{}", path),
+        None => path.to_string(),
+    }
+}
+
+/// 映射的网络驱动器对应的 UNC 路径（`\\server\share`），本地驱动器返回 None
+#[cfg(windows)]
+fn remote_name(drive: &str) -> Option<String> {
+    use windows_sys::Win32::NetworkManagement::WNet::WNetGetConnectionW;
+    let local: Vec<u16> = drive.encode_utf16().chain(std::iter::once(0)).collect();
+    let mut buffer = vec![0u16; 1024];
+    let mut length = buffer.len() as u32;
+    // SAFETY: local 以 NUL 结尾，buffer 的长度与 length 一致
+    let result = unsafe { WNetGetConnectionW(local.as_ptr(), buffer.as_mut_ptr(), &mut length) };
+    if result != 0 {
+        return None;
+    }
+    let end = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    Some(String::from_utf16_lossy(&buffer[..end])).filter(|remote| remote.starts_with(r"\\"))
+}
+
+/// 规范化用户选择的路径
+pub fn normalize(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    {
+        let simple = simplify(&path.to_string_lossy());
+        let mapped = match drive(&simple).and_then(|(letter, rest)| Some((remote_name(letter)?, rest))) {
+            Some((remote, rest)) => {
+                let unc = format!("{}{}", remote.trim_end_matches('\\'), rest);
+                tracing::info!("Mapped drive path {} resolved to {}", simple, unc);
+                unc
+            }
+            None => simple,
+        };
+        PathBuf::from(extend_long(&mapped))
+    }
+    #[cfg(not(windows))]
+    {
+        path.to_path_buf()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simplify_and_extend() {
+        assert_eq!(simplify(r"\\?\C:\Users\a"), r"C:\Users\a");
+        assert_eq!(simplify(r"\\?\UNC\server\share\a"), r"\\server\share\a");
+        assert_eq!(simplify("C:/Users/a"), r"C:\Users\a");
+        assert_eq!(drive(r"Z:\projects"), Some(("Z:", r"\projects")));
+        assert_eq!(drive("Z:"), Some(("Z:", "")));
+        assert_eq!(drive(r"\\server\share"), None);
+        assert_eq!(drive("Zx:"), None);
+
+        let long = format!(r"C:\{}", "a".repeat(300));
+        assert_eq!(extend_long(&long), format!(r"\\?\{}", long));
+        let long_unc = format!(r"\\server\share\{}", "b".repeat(300));
+        assert_eq!(extend_long(&long_unc), format!(r"\\?\UNC\server\share\{}", "b".repeat(300)));
+        assert_eq!(extend_long(r"C:\short"), r"C:\short");
+        assert_eq!(simplify(&extend_long(&long_unc)), long_unc);
+    }
+}
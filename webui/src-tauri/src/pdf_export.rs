@@ -0,0 +1,142 @@
+//! 导出当前页面为 PDF
+//!
+//! 使用各平台 WebView 自带的打印/导出能力：Windows 为 WebView2 `PrintToPdf`，
+//! macOS 为 WKWebView `createPDF`，Linux 为 WebKitGTK 打印到文件。
+//! 用于把 Agent 对话记录、报告等保存为 PDF。
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::WebviewWindow;
+use tokio::sync::oneshot;
+
+/// 等待导出完成的时间（长对话排版可能较慢）
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// 补全 `.pdf` 扩展名
+fn with_pdf_extension(dest: &Path) -> PathBuf {
+    match dest.extension().and_then(|e| e.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("pdf") => dest.to_path_buf(),
+        _ => {
+            let mut name = dest.as_os_str().to_owned();
+            name.push(".pdf");
+            PathBuf::from(name)
+        }
+    }
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+fn print_to_pdf(webview: tauri::webview::PlatformWebview, dest: PathBuf, done: oneshot::Sender<Result<(), String>>) {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use webkit2gtk::PrintOperationExt;
+
+    let uri = match gtk::glib::filename_to_uri(&dest, None) {
+        Ok(uri) => uri,
+        Err(e) => {
+            let _ = done.send(Err(e.to_string()));
+            return;
+        }
+    };
+    let settings = gtk::PrintSettings::new();
+    settings.set_printer("Print to File");
+    settings.set("output-file-format", Some("pdf"));
+    settings.set("output-uri", Some(uri.as_str()));
+
+    let operation = webkit2gtk::PrintOperation::new(&webview.inner());
+    operation.set_print_settings(&settings);
+    // 失败时先触发 failed 再触发 finished，只上报第一个结果
+    let done = Rc::new(RefCell::new(Some(done)));
+    let failed = done.clone();
+    operation.connect_failed(move |_, e| {
+        if let Some(done) = failed.borrow_mut().take() {
+            let _ = done.send(Err(e.to_string()));
+        }
+    });
+    operation.connect_finished(move |_| {
+        if let Some(done) = done.borrow_mut().take() {
+            let _ = done.send(Ok(()));
+        }
+    });
+    operation.print();
+}
+
+#[cfg(windows)]
+fn print_to_pdf(webview: tauri::webview::PlatformWebview, dest: PathBuf, done: oneshot::Sender<Result<(), String>>) {
+    use webview2_com::Microsoft::Web::WebView2::Win32::{ICoreWebView2PrintSettings, ICoreWebView2_7};
+    use webview2_com::PrintToPdfCompletedHandler;
+    use windows_core::{Interface, HSTRING};
+
+    let start = |done: oneshot::Sender<Result<(), String>>| -> windows_core::Result<()> {
+        let core = unsafe { webview.controller().CoreWebView2()? }.cast::<ICoreWebView2_7>()?;
+        let handler = PrintToPdfCompletedHandler::create(Box::new(move |result, success| {
+            let _ = done.send(match result {
+                Ok(()) if success => Ok(()),
+                Ok(()) => Err("WebView2 未能生成 PDF".to_string()),
+                Err(e) => Err(e.message()),
+            });
+            Ok(())
+        }));
+        unsafe { core.PrintToPdf(&HSTRING::from(dest.as_os_str()), None::<&ICoreWebView2PrintSettings>, &handler) }
+    };
+    // 调用失败时 done 已移入回调，回调不会再被调用，接收端会收到通道关闭
+    if let Err(e) = start(done) {
+        tracing::warn!("PrintToPdf failed: {}", e.message());
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn print_to_pdf(webview: tauri::webview::PlatformWebview, dest: PathBuf, done: oneshot::Sender<Result<(), String>>) {
+    use block2::RcBlock;
+    use objc2_foundation::{NSData, NSError};
+    use objc2_web_kit::WKWebView;
+    use std::sync::Mutex;
+
+    let done = Mutex::new(Some(done));
+    let block = RcBlock::new(move |data: *mut NSData, error: *mut NSError| {
+        let Some(done) = done.lock().unwrap_or_else(|e| e.into_inner()).take() else {
+            return;
+        };
+        let result = match (unsafe { data.as_ref() }, unsafe { error.as_ref() }) {
+            (Some(data), _) => std::fs::write(&dest, data.to_vec()).map_err(|e| format!("无法写入 {}: {}", dest.display(), e)),
+            (None, Some(error)) => Err(error.localizedDescription().to_string()),
+            (None, None) => Err("WKWebView 未能生成 PDF".to_string()),
+        };
+        let _ = done.send(result);
+    });
+    let view = unsafe { &*(webview.inner() as *const WKWebView) };
+    unsafe { view.createPDFWithConfiguration_completionHandler(None, &block) };
+}
+
+/// 把窗口当前显示的页面导出为 PDF，返回保存的路径
+pub async fn export(window: &WebviewWindow, dest: &Path) -> Result<PathBuf, String> {
+    let dest = with_pdf_extension(dest);
+    let (sender, receiver) = oneshot::channel();
+    let target = dest.clone();
+    window.with_webview(move |webview| print_to_pdf(webview, target, sender)).map_err(|e| e.to_string())?;
+
+    match tokio::time::timeout(EXPORT_TIMEOUT, receiver).await {
+        Ok(Ok(result)) => result.map_err(|e| format!("导出 PDF 失败: {}", e))?,
+        Ok(Err(_)) => return Err("导出 PDF 失败".to_string()),
+        Err(_) => return Err("导出 PDF 超时".to_string()),
+    }
+    crate::breadcrumbs::record("window", format!("Exported {} to PDF", window.label()));
+    Ok(dest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_pdf_extension() {
+        assert_eq!(with_pdf_extension(Path::new("/tmp/chat")), PathBuf::from("/tmp/chat.pdf"));
+        assert_eq!(with_pdf_extension(Path::new("/tmp/chat.PDF")), PathBuf::from("/tmp/chat.PDF"));
+        assert_eq!(with_pdf_extension(Path::new("/tmp/report.v2")), PathBuf::from("/tmp/report.v2.pdf"));
+    }
+}
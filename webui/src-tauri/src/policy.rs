@@ -0,0 +1,251 @@
+//! 生产环境加固
+//!
+//! 锁定模式下不允许打开 DevTools、不以开发模式启动后端，并只允许 [`LOCKDOWN_ALLOWED`] 中的命令。
+//! 开启方式（任一即可）：
+//! - 系统级策略文件中 `{"locked_down": true}`，用于企业部署，普通用户无法修改，
+//!   开启后环境变量和设置都不能关闭；
+//! - 环境变量 DAWEI_LOCKDOWN=1；
+//! - 设置中的 `locked_down`。
+
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// 锁定模式环境变量
+const LOCKDOWN_ENV: &str = "DAWEI_LOCKDOWN";
+
+/// 锁定模式下允许的命令
+///
+/// 使用允许列表：新增的命令在加入这里之前锁定模式下都不可用，不会因为忘记登记而暴露
+/// 诊断信息。不在列表中的命令会暴露本机路径、环境变量、日志、硬件或进程信息，
+/// 或修改环境变量、日志级别、结束进程等。
+const LOCKDOWN_ALLOWED: [&str; 119] = [
+    // 导航命令
+    "navigate_to_main",
+    // 文件操作命令
+    "select_directory",
+    "select_directories",
+    "select_file",
+    "select_files",
+    "save_file_dialog",
+    "list_bookmarks",
+    "add_bookmark",
+    "remove_bookmark",
+    "trash_paths",
+    "reveal_in_file_manager",
+    "list_editors",
+    "open_in_editor",
+    "validate_workspace",
+    "get_recent_workspaces",
+    "pin_workspace",
+    "remove_recent",
+    "list_workspace_templates",
+    "create_workspace_from_template",
+    // 系统浏览器命令
+    "open_by_system_browser",
+    // 崩溃报告命令
+    "get_crash_reports",
+    "get_latest_crash_report",
+    "get_crash_report",
+    "format_crash_report",
+    "format_crash_as_markdown",
+    "open_crash_viewer",
+    "open_crash_window",
+    "export_crash_report",
+    "export_view_pdf",
+    "clear_crash_reports",
+    "get_crash_upload_status",
+    "preview_telemetry",
+    "purge_telemetry",
+    "get_last_session_crash",
+    "acknowledge_last_session_crash",
+    "get_session_id",
+    "get_about_info",
+    // 日志和诊断命令
+    "unsubscribe_logs",
+    "log_webview",
+    "get_disk_usage",
+    // 服务器信息命令
+    "relocate_dawei_home",
+    "get_startup_progress",
+    "get_backend_stats",
+    // 后端管理命令
+    "start_backend",
+    "stop_backend",
+    "restart_backend",
+    "update_task_progress",
+    "set_active_profile",
+    // 设置命令
+    "get_settings",
+    "update_settings",
+    "get_effective_settings",
+    "get_theme",
+    "set_theme",
+    "get_locale",
+    "set_locale",
+    "export_config",
+    "import_config",
+    "get_first_run_state",
+    "is_first_run",
+    "complete_onboarding_step",
+    "finish_onboarding",
+    "request_reset_token",
+    "reset_app_data",
+    "request_cleanup_token",
+    "cleanup_app_data",
+    // 窗口命令
+    "open_workspace_window",
+    "get_window_workspace",
+    "read_workspace_file",
+    "write_workspace_file",
+    "list_workspace_tree",
+    "search_in_workspace",
+    "cancel_workspace_search",
+    "start_drag_out",
+    "get_workspace_stats",
+    "cancel_workspace_stats",
+    "export_workspace_zip",
+    "import_workspace_zip",
+    "watch_workspace",
+    "unwatch_workspace",
+    "set_always_on_top",
+    "set_global_shortcut",
+    "set_kiosk_mode",
+    "window_minimize",
+    "window_toggle_maximize",
+    "window_close",
+    "start_dragging",
+    "set_custom_titlebar",
+    "get_titlebar_info",
+    "confirm_quit",
+    "upload_dropped_files",
+    "stage_attachments",
+    "remove_attachment",
+    "clear_attachments",
+    "check_for_updates",
+    "check_backend_updates",
+    "start_coordinated_upgrade",
+    "get_upgrade_status",
+    "retry_upgrade",
+    "abandon_upgrade",
+    "install_pinned_backend",
+    "get_changelog",
+    "get_migration_status",
+    "get_integrity_report",
+    "repair_installation",
+    "get_backend_snapshot",
+    "rollback_backend",
+    "get_update_check_status",
+    "download_update",
+    "start_download",
+    "pause_download",
+    "resume_download",
+    "list_downloads",
+    "discard_download",
+    "install_and_restart",
+    // 页面缩放命令
+    "zoom_in",
+    "zoom_out",
+    "zoom_reset",
+    "set_zoom",
+    "get_zoom",
+];
+
+/// 策略文件中的配置（启动时读取一次）
+static POLICY: OnceLock<Policy> = OnceLock::new();
+
+/// 系统级策略
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct Policy {
+    locked_down: bool,
+}
+
+/// 系统级策略文件路径
+fn policy_file() -> PathBuf {
+    #[cfg(target_os = "windows")]
+    let dir = std::env::var_os("ProgramData")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(r"C:\ProgramData"))
+        .join("Dawei");
+    #[cfg(target_os = "macos")]
+    let dir = PathBuf::from("/Library/Application Support/Dawei");
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let dir = PathBuf::from("/etc/dawei");
+
+    dir.join("policy.json")
+}
+
+fn read_policy(path: &Path) -> Policy {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return Policy::default();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        // 策略文件存在但无法解析时按锁定处理，避免配置错误导致限制失效
+        tracing::warn!("Invalid policy file {:?}, assuming locked down: {}", path, e);
+        Policy { locked_down: true }
+    })
+}
+
+/// 是否处于锁定模式
+pub fn is_locked_down() -> bool {
+    if POLICY.get_or_init(|| read_policy(&policy_file())).locked_down {
+        return true;
+    }
+    match std::env::var(LOCKDOWN_ENV) {
+        Ok(value) => value == "1" || value.eq_ignore_ascii_case("true"),
+        Err(_) => crate::settings::current().locked_down,
+    }
+}
+
+/// 命令是否被锁定模式禁止
+pub fn blocks_command(command: &str) -> bool {
+    !LOCKDOWN_ALLOWED.contains(&command) && is_locked_down()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_policy() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("policy.json");
+        assert!(!read_policy(&path).locked_down);
+
+        std::fs::write(&path, r#"{"locked_down": true}"#).unwrap();
+        assert!(read_policy(&path).locked_down);
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(read_policy(&path).locked_down);
+    }
+
+    #[test]
+    fn test_lockdown_allow_list() {
+        // 允许列表中的命令都已注册，避免命令改名后留下失效的条目
+        let main = include_str!("main.rs");
+        let handlers = &main[main.find("generate_handler![").unwrap()..];
+        let handlers = &handlers[..handlers.find("];").unwrap()];
+        let registered: Vec<&str> = handlers
+            .lines()
+            .map(|line| line.trim().trim_end_matches(','))
+            .filter(|line| !line.is_empty() && !line.starts_with("//"))
+            .collect();
+        for command in LOCKDOWN_ALLOWED {
+            assert!(registered.contains(&command), "{} is not a registered command", command);
+        }
+
+        for command in [
+            "list_env_vars",
+            "set_env_var",
+            "unset_env_var",
+            "set_log_level",
+            "diagnose_port",
+            "terminate_stale_processes",
+            "get_hardware_info",
+            "export_diagnostics",
+        ] {
+            assert!(registered.contains(&command) && !LOCKDOWN_ALLOWED.contains(&command), "{}", command);
+        }
+    }
+}
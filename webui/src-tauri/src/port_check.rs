@@ -0,0 +1,262 @@
+//! 端口占用诊断
+//!
+//! 后端端口被占用时找出监听该端口的进程（Linux 解析 /proc/net/tcp，macOS 使用 lsof，
+//! Windows 解析 `netstat -ano`），通过 sysinfo 读取进程名和命令行，并给出处理方式：
+//! 结束残留的 dawei 后端进程，或改用附近的空闲端口。
+
+use serde::{Deserialize, Serialize};
+use std::net::TcpListener;
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, ProcessesToUpdate, System};
+
+/// 查找空闲端口时向后尝试的端口数
+const FREE_PORT_SEARCH_RANGE: u16 = 100;
+
+/// 结束进程后等待端口释放的时间
+const RELEASE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 占用端口的进程
+#[derive(Debug, Clone, Serialize)]
+pub struct PortOwner {
+    pub pid: u32,
+    pub name: String,
+    /// 完整命令行
+    pub cmd: String,
+    /// 是否为 dawei 后端进程（只有这类进程允许结束）
+    pub is_dawei: bool,
+    /// 是否为桌面端启动的后端进程
+    pub managed: bool,
+}
+
+/// 可以采取的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PortAction {
+    /// 结束占用端口的 dawei 进程（`kill_port_owner`）
+    KillProcess,
+    /// 在后端配置中改用 `free_port`
+    UseAnotherPort,
+}
+
+/// 端口诊断结果
+#[derive(Debug, Clone, Serialize)]
+pub struct PortDiagnosis {
+    pub port: u16,
+    pub in_use: bool,
+    /// 找不到占用进程时为 None（例如进程属于其他用户）
+    pub owner: Option<PortOwner>,
+    /// 附近的空闲端口
+    pub free_port: Option<u16>,
+    pub actions: Vec<PortAction>,
+}
+
+/// 端口是否已被占用
+fn in_use(port: u16) -> bool {
+    TcpListener::bind(("127.0.0.1", port)).is_err()
+}
+
+/// 从 port 之后查找空闲端口
+fn find_free_port(port: u16) -> Option<u16> {
+    (1..=FREE_PORT_SEARCH_RANGE)
+        .filter_map(|offset| port.checked_add(offset))
+        .find(|&candidate| !in_use(candidate))
+}
+
+/// 解析 /proc/net/tcp(6)，返回监听 port 的 socket inode
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_proc_net_tcp(content: &str, port: u16) -> Vec<u64> {
+    // 列：sl local_address rem_address st ... inode，st 为 0A 表示 LISTEN
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (_, local_port) = fields.get(1)?.rsplit_once(':')?;
+            if u16::from_str_radix(local_port, 16).ok()? != port || *fields.get(3)? != "0A" {
+                return None;
+            }
+            fields.get(9)?.parse().ok()
+        })
+        .collect()
+}
+
+/// 解析 `netstat -ano` 的输出，返回监听 port 的进程 PID
+#[cfg_attr(not(windows), allow(dead_code))]
+fn parse_netstat(output: &str, port: u16) -> Vec<u32> {
+    // 状态列会被本地化，监听中的连接通过远程地址端口为 0 识别
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() != 5 || !fields[0].eq_ignore_ascii_case("tcp") && !fields[0].eq_ignore_ascii_case("tcpv6") {
+                return None;
+            }
+            let (_, local_port) = fields[1].rsplit_once(':')?;
+            if local_port.parse::<u16>().ok()? != port || !fields[2].ends_with(":0") {
+                return None;
+            }
+            fields[4].parse().ok()
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn listening_pids(port: u16) -> Vec<u32> {
+    let inodes: Vec<u64> = ["/proc/net/tcp", "/proc/net/tcp6"]
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .flat_map(|content| parse_proc_net_tcp(&content, port))
+        .collect();
+    if inodes.is_empty() {
+        return Vec::new();
+    }
+    let sockets: Vec<String> = inodes.iter().map(|inode| format!("socket:[{}]", inode)).collect();
+
+    // 只能读取当前用户进程的 fd
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .filter(|pid| {
+            std::fs::read_dir(format!("/proc/{}/fd", pid)).is_ok_and(|fds| {
+                fds.flatten().any(|fd| {
+                    std::fs::read_link(fd.path()).is_ok_and(|target| sockets.iter().any(|s| target.as_os_str() == s.as_str()))
+                })
+            })
+        })
+        .collect()
+}
+
+#[cfg(windows)]
+fn listening_pids(port: u16) -> Vec<u32> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    std::process::Command::new("netstat")
+        .args(["-ano", "-p", "TCP"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map(|output| parse_netstat(&String::from_utf8_lossy(&output.stdout), port))
+        .unwrap_or_default()
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn listening_pids(port: u16) -> Vec<u32> {
+    std::process::Command::new("lsof")
+        .args(["-nP", &format!("-iTCP:{}", port), "-sTCP:LISTEN", "-t"])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).lines().filter_map(|line| line.trim().parse().ok()).collect())
+        .unwrap_or_default()
+}
+
+/// 读取进程信息
+fn owner(system: &mut System, pid: u32) -> Option<PortOwner> {
+    let sys_pid = Pid::from_u32(pid);
+    system.refresh_processes(ProcessesToUpdate::Some(&[sys_pid]), true);
+    let process = system.process(sys_pid)?;
+    let name = process.name().to_string_lossy().to_string();
+    let cmd = process.cmd().iter().map(|arg| arg.to_string_lossy()).collect::<Vec<_>>().join(" ");
+    let managed = crate::backend_process::pid() == Some(pid);
+    // 只有后端运行时才允许结束，避免误杀打开了 dawei 目录的编辑器等
+    let is_dawei =
+        managed || pid != std::process::id() && crate::related_processes::is_backend_runtime(&name, &cmd);
+    Some(PortOwner { pid, name, cmd, is_dawei, managed })
+}
+
+/// 诊断端口占用情况（阻塞调用）
+pub fn diagnose(port: u16) -> PortDiagnosis {
+    if !in_use(port) {
+        return PortDiagnosis { port, in_use: false, owner: None, free_port: None, actions: Vec::new() };
+    }
+
+    let mut system = System::new();
+    let owner = listening_pids(port).into_iter().find_map(|pid| owner(&mut system, pid));
+    let free_port = find_free_port(port);
+    let mut actions = Vec::new();
+    if owner.as_ref().is_some_and(|owner| owner.is_dawei) {
+        actions.push(PortAction::KillProcess);
+    }
+    if free_port.is_some() {
+        actions.push(PortAction::UseAnotherPort);
+    }
+    PortDiagnosis { port, in_use: true, owner, free_port, actions }
+}
+
+/// 结束占用端口的 dawei 进程并等待端口释放，返回结束的进程 PID（阻塞调用）
+pub fn kill_owner(port: u16) -> Result<u32, String> {
+    let diagnosis = diagnose(port);
+    let owner = match diagnosis.owner {
+        Some(owner) => owner,
+        None if diagnosis.in_use => return Err(format!("无法确定占用端口 {} 的进程", port)),
+        None => return Err(format!("端口 {} 未被占用", port)),
+    };
+    if !owner.is_dawei {
+        return Err(format!("端口 {} 被 {}（PID: {}）占用，不是 dawei 进程，请手动处理", port, owner.name, owner.pid));
+    }
+
+    crate::breadcrumbs::record("backend", format!("Killing {} (PID: {}) holding port {}", owner.name, owner.pid, port));
+    if owner.managed {
+        crate::backend_process::stop()?;
+    } else {
+        let mut system = System::new();
+        let pid = Pid::from_u32(owner.pid);
+        system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+        let process = system.process(pid).ok_or_else(|| format!("进程 {} 已退出", owner.pid))?;
+        if !crate::related_processes::terminate(process) {
+            return Err(format!("无法结束进程 {}", owner.pid));
+        }
+    }
+
+    let deadline = Instant::now() + RELEASE_TIMEOUT;
+    while in_use(port) {
+        if Instant::now() >= deadline {
+            return Err(format!("进程 {} 已结束，但端口 {} 仍未释放", owner.pid, port));
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    }
+    Ok(owner.pid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_listeners() {
+        let proc_net_tcp = concat!(
+            "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n",
+            "   0: 0100007F:2111 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41231 1 0\n",
+            "   1: 0100007F:2111 0100007F:C350 01 00000000:00000000 00:00000000 00000000  1000        0 41232 1 0\n",
+            "   2: 00000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 1201 1 0\n",
+        );
+        assert_eq!(parse_proc_net_tcp(proc_net_tcp, 8465), [41231]);
+
+        let netstat = concat!(
+            "Active Connections\n\n",
+            "  Proto  Local Address          Foreign Address        State           PID\n",
+            "  TCP    127.0.0.1:8465         0.0.0.0:0              LISTENING       4242\n",
+            "  TCP    127.0.0.1:8465         127.0.0.1:50000        ESTABLISHED     4242\n",
+            "  TCP    127.0.0.1:50000        127.0.0.1:8465         ESTABLISHED     777\n",
+            "  TCP    [::]:8466              [::]:0                 LISTENING       9\n",
+        );
+        assert_eq!(parse_netstat(netstat, 8465), [4242]);
+    }
+
+    #[test]
+    fn test_diagnose_port_in_use() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let diagnosis = diagnose(port);
+        assert!(diagnosis.in_use);
+        assert!(diagnosis.free_port.is_some_and(|free| free > port));
+        assert!(diagnosis.actions.contains(&PortAction::UseAnotherPort));
+        #[cfg(target_os = "linux")]
+        assert_eq!(diagnosis.owner.map(|owner| owner.pid), Some(std::process::id()));
+
+        drop(listener);
+        assert!(!diagnose(port).in_use);
+    }
+}
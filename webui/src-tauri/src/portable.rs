@@ -0,0 +1,74 @@
+//! 便携模式
+//!
+//! 可执行文件旁有 `portable.flag` 文件或以 `--portable` 参数启动时，所有数据都保存在
+//! 可执行文件旁的 `data/` 目录中，不写入用户目录，可以从 U 盘运行：
+//! - DAWEI_HOME（设置、日志、崩溃报告等，见 [`crate::get_dawei_home`]）为 `data/`，
+//!   优先于环境变量 DAWEI_HOME 和迁移指针文件，后端也使用该目录；
+//! - WebView 的数据和缓存在 `data/webview/` 和 `data/cache/`。
+//!
+//! 便携模式下不能迁移数据目录。
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::{AppHandle, Manager, Runtime, WebviewWindowBuilder};
+
+/// 可执行文件旁的标记文件
+const PORTABLE_FLAG: &str = "portable.flag";
+
+/// 启动参数
+const PORTABLE_ARG: &str = "--portable";
+
+/// 数据目录名（位于可执行文件旁）
+const DATA_DIR: &str = "data";
+
+static DATA: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+fn detect(exe_dir: &Path, args: &[String]) -> Option<PathBuf> {
+    let portable = exe_dir.join(PORTABLE_FLAG).is_file() || args.iter().any(|arg| arg == PORTABLE_ARG);
+    portable.then(|| exe_dir.join(DATA_DIR))
+}
+
+/// 便携模式的数据目录，非便携模式为 None
+pub fn data_dir() -> Option<PathBuf> {
+    DATA.get_or_init(|| {
+        let exe = std::env::current_exe().ok()?;
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        detect(exe.parent()?, &args)
+    })
+    .clone()
+}
+
+pub fn is_portable() -> bool {
+    data_dir().is_some()
+}
+
+/// 应用缓存目录：便携模式下为 `data/cache`
+pub fn cache_dir(app: &AppHandle) -> Option<PathBuf> {
+    match data_dir() {
+        Some(dir) => Some(dir.join("cache")),
+        None => app.path().app_cache_dir().ok(),
+    }
+}
+
+/// 便携模式下把窗口的 WebView 数据目录设为 `data/webview`
+pub fn webview<'a, R: Runtime, M: Manager<R>>(builder: WebviewWindowBuilder<'a, R, M>) -> WebviewWindowBuilder<'a, R, M> {
+    match data_dir() {
+        Some(dir) => builder.data_directory(dir.join("webview")),
+        None => builder,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(detect(dir.path(), &[]), None);
+        assert_eq!(detect(dir.path(), &["--portable".to_string()]), Some(dir.path().join("data")));
+
+        std::fs::write(dir.path().join(PORTABLE_FLAG), "").unwrap();
+        assert_eq!(detect(dir.path(), &[]), Some(dir.path().join("data")));
+    }
+}
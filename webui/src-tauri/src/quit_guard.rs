@@ -0,0 +1,145 @@
+//! 退出确认
+//!
+//! 退出应用（托盘菜单退出、关闭最后的主窗口）前查询后端是否有正在执行的 Agent 任务。
+//! 有任务时显示主窗口并发送 `quit-requested` 事件，等待前端调用 `confirm_quit`；
+//! 前端没有响应时超时后照常退出。确认退出后停止由桌面端启动的后端。
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, RunEvent, Window, WindowEvent};
+use tokio::sync::oneshot;
+
+/// 有任务在执行时发送给主窗口的事件
+pub const QUIT_REQUESTED_EVENT: &str = "quit-requested";
+
+/// 等待前端确认的时间，超时后退出
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 查询后端任务的超时时间
+const QUERY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 已确认退出，不再拦截
+static CONFIRMED: AtomicBool = AtomicBool::new(false);
+
+/// 等待前端确认的请求
+static PENDING: Mutex<Option<oneshot::Sender<bool>>> = Mutex::new(None);
+
+/// `quit-requested` 事件内容
+#[derive(Debug, Clone, Serialize)]
+pub struct QuitRequest {
+    /// 正在执行的任务数
+    pub running: u64,
+    /// 等待确认的秒数，超时后退出
+    pub timeout_secs: u64,
+}
+
+/// 确认后执行的操作
+enum Quit {
+    Exit(i32),
+    CloseMain,
+}
+
+/// 后端正在执行的任务数，后端不可访问时为 0
+async fn running_tasks() -> u64 {
+    let Ok(client) = reqwest::Client::builder().timeout(QUERY_TIMEOUT).build() else {
+        return 0;
+    };
+    let url = format!("http://127.0.0.1:{}/api/tasks/running", crate::backend_profile::port());
+    let response = match client.get(url).send().await {
+        Ok(response) if response.status().is_success() => response,
+        _ => return 0,
+    };
+    response.json::<serde_json::Value>().await.ok().and_then(|body| body["running"].as_u64()).unwrap_or(0)
+}
+
+/// 前端的确认结果：true 退出，false 取消
+pub fn confirm(confirmed: bool) -> Result<(), String> {
+    let sender = PENDING.lock().unwrap_or_else(|e| e.into_inner()).take().ok_or("没有等待确认的退出请求")?;
+    let _ = sender.send(confirmed);
+    Ok(())
+}
+
+fn request(app: &AppHandle, quit: Quit) {
+    let (sender, receiver) = oneshot::channel();
+    {
+        let mut pending = PENDING.lock().unwrap_or_else(|e| e.into_inner());
+        // 已经在等待确认，忽略重复的退出请求
+        if pending.is_some() {
+            return;
+        }
+        *pending = Some(sender);
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let running = running_tasks().await;
+        let confirmed = if running == 0 {
+            true
+        } else {
+            crate::breadcrumbs::record("app", format!("Quit requested with {} running task(s)", running));
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.unminimize().and_then(|_| window.show()).and_then(|_| window.set_focus());
+            }
+            let request = QuitRequest { running, timeout_secs: CONFIRM_TIMEOUT.as_secs() };
+            if let Err(e) = app.emit_to("main", QUIT_REQUESTED_EVENT, request) {
+                tracing::warn!("Failed to emit quit request: {}", e);
+            }
+            // 超时或前端异常时照常退出
+            tokio::time::timeout(CONFIRM_TIMEOUT, receiver).await.ok().and_then(Result::ok).unwrap_or(true)
+        };
+        PENDING.lock().unwrap_or_else(|e| e.into_inner()).take();
+
+        if !confirmed {
+            crate::breadcrumbs::record("app", "Quit cancelled");
+            return;
+        }
+        CONFIRMED.store(true, Ordering::SeqCst);
+        match quit {
+            Quit::Exit(code) => app.exit(code),
+            Quit::CloseMain => {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.close();
+                }
+            }
+        }
+    });
+}
+
+/// 关闭主窗口且没有其他工作窗口时（即将退出应用），先确认再关闭
+pub fn on_window_event(window: &Window, event: &WindowEvent) {
+    let WindowEvent::CloseRequested { api, .. } = event else {
+        return;
+    };
+    if window.label() != "main" || CONFIRMED.load(Ordering::SeqCst) {
+        return;
+    }
+    let app = window.app_handle();
+    let other_windows = app.webview_windows().keys().any(|label| crate::workspace_window::get(label).is_some());
+    if other_windows {
+        return;
+    }
+    api.prevent_close();
+    request(app, Quit::CloseMain);
+}
+
+/// 处理应用退出事件：`app.exit()` 先确认；退出时停止由桌面端启动的后端
+pub fn on_run_event(app: &AppHandle, event: &RunEvent) {
+    match event {
+        // code 为 None 表示所有窗口都已关闭，此时无法再询问
+        // 重启（例如安装更新后）不需要确认
+        RunEvent::ExitRequested { code: Some(code), api, .. }
+            if *code != tauri::RESTART_EXIT_CODE && !CONFIRMED.load(Ordering::SeqCst) =>
+        {
+            api.prevent_exit();
+            request(app, Quit::Exit(*code));
+        }
+        RunEvent::Exit => {
+            if let Err(e) = crate::backend_process::stop() {
+                tracing::warn!("{}", e);
+            }
+        }
+        _ => {}
+    }
+}
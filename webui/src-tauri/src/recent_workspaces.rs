@@ -0,0 +1,171 @@
+//! 最近打开的工作区
+//!
+//! 保存在 DAWEI_HOME/recent_workspaces.json：路径、最后打开时间和是否固定。
+//! 固定的工作区排在前面且不会因数量超出上限被挤掉；未固定的最多保留 [`MAX_RECENT`] 个。
+//! 返回列表时跳过目录已不存在的工作区，但不从文件中删除（例如移动硬盘暂时未连接）。
+
+use crate::atomic_file::write_json_atomic;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// 记录文件名
+const RECENT_FILE: &str = "recent_workspaces.json";
+
+/// 未固定的工作区最多保留的数量
+const MAX_RECENT: usize = 20;
+
+/// 读写记录文件时加锁，避免多个窗口同时打开工作区时互相覆盖
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// 最近的工作区
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentWorkspace {
+    pub path: PathBuf,
+    /// 最后打开时间（RFC 3339）
+    pub last_opened: String,
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+fn recent_file() -> PathBuf {
+    crate::get_dawei_home().join(RECENT_FILE)
+}
+
+fn read_in(file: &Path) -> Vec<RecentWorkspace> {
+    let Ok(content) = fs::read_to_string(file) else {
+        return Vec::new();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        tracing::warn!("Invalid {}: {}", RECENT_FILE, e);
+        Vec::new()
+    })
+}
+
+/// 排序（固定的在前，再按最后打开时间倒序）并去掉超出上限的未固定工作区后保存
+fn write_in(file: &Path, mut recent: Vec<RecentWorkspace>) -> Result<(), String> {
+    recent.sort_by(|a, b| b.pinned.cmp(&a.pinned).then_with(|| b.last_opened.cmp(&a.last_opened)));
+    let mut unpinned = 0;
+    recent.retain(|workspace| {
+        unpinned += usize::from(!workspace.pinned);
+        workspace.pinned || unpinned <= MAX_RECENT
+    });
+
+    write_json_atomic(file, &recent).map_err(|e| format!("保存最近的工作区失败: {}", e))
+}
+
+/// 统一路径写法，同一目录只记录一次
+fn normalize(path: &Path) -> Result<PathBuf, String> {
+    let path = crate::symlink_policy::canonicalize(path).map_err(|e| format!("工作区目录不存在: {}", e))?;
+    if !path.is_dir() {
+        return Err(format!("不是目录: {}", path.display()));
+    }
+    Ok(path)
+}
+
+pub(crate) fn record_in(file: &Path, path: &Path) -> Result<(), String> {
+    let path = normalize(path)?;
+    let mut recent = read_in(file);
+    let pinned = recent.iter().any(|workspace| workspace.path == path && workspace.pinned);
+    recent.retain(|workspace| workspace.path != path);
+    recent.push(RecentWorkspace { path, last_opened: chrono::Local::now().to_rfc3339(), pinned });
+    write_in(file, recent)
+}
+
+fn list_in(file: &Path) -> Vec<RecentWorkspace> {
+    read_in(file).into_iter().filter(|workspace| workspace.path.is_dir()).collect()
+}
+
+pub(crate) fn pin_in(file: &Path, path: &Path, pinned: bool) -> Result<(), String> {
+    let path = normalize(path)?;
+    let mut recent = read_in(file);
+    // 只能固定已在列表中的工作区，不能借此加入没有打开过的目录
+    let workspace = recent
+        .iter_mut()
+        .find(|workspace| workspace.path == path)
+        .ok_or_else(|| format!("不是最近打开过的工作区: {}", path.display()))?;
+    workspace.pinned = pinned;
+    write_in(file, recent)
+}
+
+fn remove_in(file: &Path, path: &Path) -> Result<bool, String> {
+    // 目录已删除时无法 canonicalize，按原样比较
+    let path = crate::symlink_policy::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut recent = read_in(file);
+    let before = recent.len();
+    recent.retain(|workspace| workspace.path != path);
+    if recent.len() == before {
+        return Ok(false);
+    }
+    write_in(file, recent).map(|_| true)
+}
+
+/// 记录打开了工作区
+pub fn record(path: &Path) {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if let Err(e) = record_in(&recent_file(), path) {
+        tracing::warn!("Failed to record recent workspace {}: {}", path.display(), e);
+    }
+}
+
+/// 最近的工作区（固定的在前，再按最后打开时间倒序），跳过目录已不存在的
+pub fn list() -> Vec<RecentWorkspace> {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    list_in(&recent_file())
+}
+
+/// 固定或取消固定最近打开过的工作区，不在列表中时返回错误
+pub fn pin(path: &Path, pinned: bool) -> Result<(), String> {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    pin_in(&recent_file(), path, pinned)
+}
+
+/// 从最近的工作区中移除，不在列表中时返回 false
+pub fn remove(path: &Path) -> Result<bool, String> {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    remove_in(&recent_file(), path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_pin_and_remove() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(RECENT_FILE);
+        let workspaces: Vec<PathBuf> = (0..3)
+            .map(|i| {
+                let path = dir.path().join(format!("ws{}", i));
+                fs::create_dir(&path).unwrap();
+                path.canonicalize().unwrap()
+            })
+            .collect();
+        for path in &workspaces {
+            record_in(&file, path).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        let paths = |file| list_in(file).into_iter().map(|workspace| workspace.path).collect::<Vec<_>>();
+        assert_eq!(paths(&file), [workspaces[2].clone(), workspaces[1].clone(), workspaces[0].clone()]);
+
+        // 固定的排在前面，再次打开时保持固定
+        pin_in(&file, &workspaces[0], true).unwrap();
+        record_in(&file, &workspaces[0]).unwrap();
+        assert_eq!(paths(&file), [workspaces[0].clone(), workspaces[2].clone(), workspaces[1].clone()]);
+        assert!(list_in(&file)[0].pinned);
+
+        // 没有打开过的目录不能固定
+        let never_opened = dir.path().canonicalize().unwrap();
+        assert!(pin_in(&file, &never_opened, true).is_err());
+        assert!(read_in(&file).iter().all(|workspace| workspace.path != never_opened));
+
+        // 目录不存在时不返回，也不能记录
+        fs::remove_dir(&workspaces[2]).unwrap();
+        assert_eq!(paths(&file), [workspaces[0].clone(), workspaces[1].clone()]);
+        assert!(record_in(&file, &workspaces[2]).is_err());
+        assert!(remove_in(&file, &workspaces[2]).unwrap());
+        assert!(!remove_in(&file, &workspaces[2]).unwrap());
+        assert_eq!(read_in(&file).len(), 2);
+    }
+}
@@ -0,0 +1,78 @@
+//! 移到回收站
+//!
+//! 页面发起的删除操作把文件移到系统回收站（Windows 回收站、macOS 废纸篓、Linux 按
+//! freedesktop 规范的 Trash），误删后可以恢复。每个路径单独处理并返回结果，
+//! 部分失败不影响其他路径。磁盘根目录、用户主目录和 DAWEI_HOME 本身不允许删除。
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// 单个路径的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct TrashResult {
+    pub path: PathBuf,
+    pub trashed: bool,
+    /// 失败原因
+    pub error: Option<String>,
+}
+
+/// 不允许删除的路径说明，允许时返回 None
+fn protected(path: &Path, protected_dirs: &[PathBuf]) -> Option<String> {
+    if path.parent().is_none() {
+        return Some("不能删除磁盘根目录".to_string());
+    }
+    protected_dirs
+        .iter()
+        .any(|dir| path == dir || dir.starts_with(path))
+        .then(|| format!("不能删除 {}", path.display()))
+}
+
+fn trash_one(path: &Path, protected_dirs: &[PathBuf]) -> Result<(), String> {
+    let path = path.canonicalize().map_err(|e| format!("文件不存在: {}", e))?;
+    if let Some(reason) = protected(&path, protected_dirs) {
+        return Err(reason);
+    }
+    trash::delete(&path).map_err(|e| format!("无法移到回收站: {}", e))
+}
+
+/// 把路径移到回收站（阻塞调用）
+pub fn trash_paths(paths: &[PathBuf]) -> Vec<TrashResult> {
+    let protected_dirs: Vec<PathBuf> = [dirs::home_dir(), Some(crate::get_dawei_home())]
+        .into_iter()
+        .flatten()
+        .filter_map(|dir| dir.canonicalize().ok())
+        .collect();
+    paths
+        .iter()
+        .map(|path| {
+            let result = trash_one(path, &protected_dirs);
+            match &result {
+                Ok(()) => tracing::info!("Moved to trash: {}", path.display()),
+                Err(e) => tracing::warn!("Failed to move {} to trash: {}", path.display(), e),
+            }
+            TrashResult { path: path.clone(), trashed: result.is_ok(), error: result.err() }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protected_paths() {
+        let home = PathBuf::from("/home/user");
+        let dawei_home = home.join(".dawei");
+        let protected_dirs = [home.clone(), dawei_home.clone()];
+        assert!(protected(Path::new("/"), &protected_dirs).is_some());
+        assert!(protected(&home, &protected_dirs).is_some());
+        assert!(protected(&dawei_home, &protected_dirs).is_some());
+        // 包含 DAWEI_HOME 的上级目录
+        assert!(protected(Path::new("/home"), &protected_dirs).is_some());
+        assert!(protected(&home.join("project/old.txt"), &protected_dirs).is_none());
+        assert!(protected(&dawei_home.join("templates/demo"), &protected_dirs).is_none());
+
+        let missing = trash_one(Path::new("/nonexistent/dawei-trash-test"), &protected_dirs);
+        assert!(missing.unwrap_err().contains("不存在"));
+    }
+}
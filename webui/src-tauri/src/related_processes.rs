@@ -0,0 +1,248 @@
+//! dawei 相关进程
+//!
+//! 扫描进程表，找出本应用启动的子进程（后端的 uv、python 等，包括子进程的子进程）和之前的
+//! 会话遗留的 dawei 进程，返回资源占用情况，供"进程"面板排查后端残留、占用端口等问题。
+//! 遗留进程的判断：命令行包含 dawei 的 python/uv 进程或继承了其他会话的 DAWEI_SESSION_ID，且不在任何
+//! 仍在运行的本应用进程之下；只有这类进程允许通过 `terminate_stale_processes` 结束。
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use sysinfo::{Pid, Process, ProcessRefreshKind, ProcessesToUpdate, Signal, System, UpdateKind};
+
+/// 相关进程
+#[derive(Debug, Clone, Serialize)]
+pub struct RelatedProcess {
+    pub pid: u32,
+    pub parent_pid: Option<u32>,
+    pub name: String,
+    /// 完整命令行
+    pub cmd: String,
+    /// CPU 占用（百分比，多核时可能超过 100）
+    pub cpu_usage: f32,
+    /// 内存占用（字节）
+    pub memory: u64,
+    /// 启动时间（Unix 时间戳，秒）
+    pub start_time: u64,
+    /// 是否为桌面端启动的后端进程
+    pub managed: bool,
+    /// 是否为之前的会话遗留的进程
+    pub stale: bool,
+}
+
+/// 分类所需的进程信息
+#[derive(Debug, Clone)]
+struct ProcessInfo {
+    pid: u32,
+    parent: Option<u32>,
+    name: String,
+    cmd: String,
+    exe: Option<PathBuf>,
+    /// 继承的 DAWEI_SESSION_ID
+    session: Option<String>,
+}
+
+/// 本应用的进程信息
+struct Current<'a> {
+    pid: u32,
+    exe: Option<&'a std::path::Path>,
+    session: &'a str,
+}
+
+/// 后端运行时的进程名（python、uv 或 dawei 自己的可执行文件），避免把打开了 dawei 目录的编辑器等算进来
+fn is_runtime(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.starts_with("python") || name.starts_with("uv") || name.contains("dawei")
+}
+
+/// 是否为 dawei 后端运行时：运行时进程且命令行包含 dawei
+pub(crate) fn is_backend_runtime(name: &str, cmd: &str) -> bool {
+    is_runtime(name) && cmd.to_lowercase().contains("dawei")
+}
+
+/// 分类结果：相关进程的 PID 及是否遗留
+fn classify(processes: &[ProcessInfo], current: &Current) -> Vec<(u32, bool)> {
+    let by_pid: HashMap<u32, &ProcessInfo> = processes.iter().map(|process| (process.pid, process)).collect();
+    // 本应用的进程（包括崩溃监控进程和其他实例）
+    let is_app = |process: &ProcessInfo| {
+        process.pid == current.pid || current.exe.is_some_and(|exe| process.exe.as_deref() == Some(exe))
+    };
+    let ancestors = |process: &ProcessInfo| {
+        let mut chain = Vec::new();
+        let mut parent = process.parent;
+        // 限制深度，避免 PID 复用形成环
+        while let Some(info) = parent.and_then(|pid| by_pid.get(&pid)).filter(|_| chain.len() < 64) {
+            chain.push(*info);
+            parent = info.parent;
+        }
+        chain
+    };
+
+    processes
+        .iter()
+        .filter(|process| !is_app(process))
+        .filter_map(|process| {
+            let ancestors = ancestors(process);
+            if ancestors.iter().any(|ancestor| ancestor.pid == current.pid) || process.session.as_deref() == Some(current.session) {
+                return Some((process.pid, false));
+            }
+            let dawei = process.session.is_some() || is_backend_runtime(&process.name, &process.cmd);
+            if !dawei {
+                return None;
+            }
+            // 其他仍在运行的本应用进程启动的进程不算遗留
+            Some((process.pid, !ancestors.iter().any(|ancestor| is_app(ancestor))))
+        })
+        .collect()
+}
+
+fn command_line(process: &Process) -> String {
+    process.cmd().iter().map(|arg| arg.to_string_lossy()).collect::<Vec<_>>().join(" ")
+}
+
+fn session_env(process: &Process) -> Option<String> {
+    let prefix = format!("{}=", crate::session::SESSION_ENV);
+    process
+        .environ()
+        .iter()
+        .find_map(|var| var.to_str()?.strip_prefix(&prefix).map(str::to_string))
+}
+
+/// 读取进程表；`cpu` 为 true 时间隔采样两次以计算 CPU 占用
+fn scan(cpu: bool) -> System {
+    let mut system = System::new();
+    let kind = ProcessRefreshKind::nothing()
+        .with_cpu()
+        .with_memory()
+        .with_cmd(UpdateKind::OnlyIfNotSet)
+        .with_exe(UpdateKind::OnlyIfNotSet)
+        .with_environ(UpdateKind::OnlyIfNotSet);
+    system.refresh_processes_specifics(ProcessesToUpdate::All, true, kind);
+    if cpu {
+        std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+        system.refresh_processes_specifics(ProcessesToUpdate::All, true, kind);
+    }
+    system
+}
+
+/// 相关进程及其在进程表中的信息
+fn related(system: &System) -> Vec<(&Process, bool)> {
+    let processes: Vec<ProcessInfo> = system
+        .processes()
+        .values()
+        // Linux 上线程也作为进程列出
+        .filter(|process| process.thread_kind().is_none())
+        .map(|process| ProcessInfo {
+            pid: process.pid().as_u32(),
+            parent: process.parent().map(Pid::as_u32),
+            name: process.name().to_string_lossy().to_string(),
+            cmd: command_line(process),
+            exe: process.exe().map(PathBuf::from),
+            session: session_env(process),
+        })
+        .collect();
+    let exe = std::env::current_exe().ok();
+    let current = Current { pid: std::process::id(), exe: exe.as_deref(), session: crate::session::session_id() };
+    classify(&processes, &current)
+        .into_iter()
+        .filter_map(|(pid, stale)| Some((system.process(Pid::from_u32(pid))?, stale)))
+        .collect()
+}
+
+/// 列出相关进程，按启动时间排列（阻塞调用，需要采样 CPU 占用）
+pub fn list() -> Vec<RelatedProcess> {
+    let system = scan(true);
+    let managed = crate::backend_process::pid();
+    let mut processes: Vec<RelatedProcess> = related(&system)
+        .into_iter()
+        .map(|(process, stale)| RelatedProcess {
+            pid: process.pid().as_u32(),
+            parent_pid: process.parent().map(Pid::as_u32),
+            name: process.name().to_string_lossy().to_string(),
+            cmd: command_line(process),
+            cpu_usage: process.cpu_usage(),
+            memory: process.memory(),
+            start_time: process.start_time(),
+            managed: managed == Some(process.pid().as_u32()),
+            stale,
+        })
+        .collect();
+    processes.sort_by_key(|process| (process.start_time, process.pid));
+    processes
+}
+
+/// 结束进程（Windows 不支持 SIGTERM，直接结束）
+pub fn terminate(process: &Process) -> bool {
+    process.kill_with(Signal::Term) == Some(true) || process.kill()
+}
+
+/// 结束遗留进程，`pids` 为空时结束全部遗留进程，返回已结束的 PID（不是遗留进程的 PID 被忽略）
+pub fn terminate_stale(pids: Option<&[u32]>) -> Result<Vec<u32>, String> {
+    let system = scan(false);
+    let mut terminated = Vec::new();
+    let mut failed = Vec::new();
+    for (process, stale) in related(&system) {
+        let pid = process.pid().as_u32();
+        if !stale || pids.is_some_and(|pids| !pids.contains(&pid)) {
+            continue;
+        }
+        let name = process.name().to_string_lossy().to_string();
+        crate::breadcrumbs::record("backend", format!("Terminating stale process {} (PID: {})", name, pid));
+        if terminate(process) {
+            terminated.push(pid);
+        } else {
+            failed.push(format!("{}（PID: {}）", name, pid));
+        }
+    }
+    if !failed.is_empty() {
+        return Err(format!("无法结束进程: {}", failed.join(", ")));
+    }
+    Ok(terminated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        let app = PathBuf::from("/opt/dawei/dawei-gui");
+        let info = |pid, parent, cmd: &str, exe: Option<&PathBuf>, session: Option<&str>| ProcessInfo {
+            pid,
+            parent,
+            name: cmd.split(' ').next().unwrap_or_default().to_string(),
+            cmd: cmd.to_string(),
+            exe: exe.cloned(),
+            session: session.map(str::to_string),
+        };
+        let processes = [
+            info(1, None, "init", None, None),
+            info(10, Some(1), "dawei-gui", Some(&app), Some("current")),
+            // 本应用启动的 uv 和 python
+            info(11, Some(10), "uv run dawei server", None, Some("current")),
+            info(12, Some(11), "python -m dawei.server", None, None),
+            // 上次会话遗留的后端（父进程已退出）
+            info(20, Some(1), "python -m dawei.server", None, Some("previous")),
+            // 其他实例（例如另一个用户会话）启动的后端
+            info(30, Some(1), "dawei-gui", Some(&app), Some("other")),
+            info(31, Some(30), "python -m dawei.server", None, Some("other")),
+            // 无关进程
+            info(40, Some(1), "python manage.py runserver", None, None),
+            info(41, Some(1), "code /home/user/dawei-notes", None, None),
+        ];
+        let current = Current { pid: 10, exe: Some(&app), session: "current" };
+        let mut result = classify(&processes, &current);
+        result.sort();
+        assert_eq!(result, [(11, false), (12, false), (20, true), (31, false)]);
+    }
+
+    #[test]
+    fn test_is_backend_runtime() {
+        assert!(is_backend_runtime("python3", "python3 -m dawei.server"));
+        assert!(is_backend_runtime("uv", "uv run dawei server"));
+        assert!(is_backend_runtime("dawei", "/opt/dawei/dawei server"));
+        assert!(!is_backend_runtime("code", "code /home/user/dawei-notes"));
+        assert!(!is_backend_runtime("node", "node /srv/dawei-docs/server.js"));
+        assert!(!is_backend_runtime("python3", "python3 -m http.server"));
+    }
+}
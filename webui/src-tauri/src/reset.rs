@@ -0,0 +1,145 @@
+//! 恢复出厂设置
+//!
+//! 按范围清除应用数据，用于安装处于异常状态时的技术支持。
+//! 清除前必须先调用 [`request_token`] 获取确认令牌，令牌只对同一范围有效、
+//! 只能使用一次并在一分钟后过期，避免前端误调用直接删除数据。
+//! 工作区、.env 和 DAWEI_HOME 中的其他用户数据不在任何范围内。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+/// 确认令牌有效期
+const TOKEN_TTL: Duration = Duration::from_secs(60);
+
+/// 尚未使用的确认令牌
+static PENDING: Mutex<Option<(String, ResetScope, Instant)>> = Mutex::new(None);
+
+/// 清除范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResetScope {
+    /// settings.json（恢复默认设置）
+    Settings,
+    /// 崩溃报告和待上传队列
+    CrashReports,
+    /// 桌面端和后端日志
+    Logs,
+    /// WebView 等缓存
+    Caches,
+    /// 以上全部，并重新开始首次运行引导
+    All,
+}
+
+impl ResetScope {
+    fn includes(self, scope: ResetScope) -> bool {
+        self == ResetScope::All || self == scope
+    }
+}
+
+/// 清除结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ResetReport {
+    /// 已清除的项
+    pub cleared: Vec<String>,
+    /// 未能删除的文件（例如正在使用的日志）
+    pub errors: Vec<String>,
+}
+
+/// 生成确认令牌，前端向用户确认后连同范围一起传给 [`reset`]
+pub fn request_token(scope: ResetScope) -> String {
+    let token = uuid::Uuid::new_v4().to_string();
+    *PENDING.lock().unwrap_or_else(|e| e.into_inner()) = Some((token.clone(), scope, Instant::now()));
+    token
+}
+
+/// 校验并消耗确认令牌
+fn take_token(scope: ResetScope, token: &str) -> Result<(), String> {
+    let pending = PENDING.lock().unwrap_or_else(|e| e.into_inner()).take();
+    match pending {
+        Some((expected, expected_scope, issued))
+            if expected == token && expected_scope == scope && issued.elapsed() < TOKEN_TTL =>
+        {
+            Ok(())
+        }
+        _ => Err("确认令牌无效或已过期，请重新确认".to_string()),
+    }
+}
+
+/// 删除目录下的所有内容，删除失败的文件记录到报告中
+fn clear_dir(dir: &Path, report: &mut ResetReport) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let result = if entry.file_type().is_ok_and(|t| t.is_dir()) {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        };
+        if let Err(e) = result {
+            report.errors.push(format!("{}: {}", path.display(), e));
+        }
+    }
+}
+
+/// 清除应用数据并恢复默认值
+pub fn reset(app: &AppHandle, scope: ResetScope, token: &str) -> Result<ResetReport, String> {
+    take_token(scope, token)?;
+    let mut report = ResetReport::default();
+    let home = crate::get_dawei_home();
+
+    if scope.includes(ResetScope::Settings) {
+        crate::settings::reset(app)?;
+        // 重新检测系统语言
+        crate::i18n::init(app);
+        report.cleared.push("settings".to_string());
+    }
+    if scope.includes(ResetScope::CrashReports) {
+        if let Err(e) = crate::crash_handler::clear_all_crash_reports() {
+            report.errors.push(format!("crashes: {}", e));
+        }
+        clear_dir(&home.join("crash_queue"), &mut report);
+        crate::session::acknowledge_last_session_crash();
+        report.cleared.push("crash_reports".to_string());
+    }
+    if scope.includes(ResetScope::Logs) {
+        clear_dir(&home.join("logs"), &mut report);
+        report.cleared.push("logs".to_string());
+    }
+    if scope.includes(ResetScope::Caches) {
+        if let Some(cache_dir) = crate::portable::cache_dir(app) {
+            clear_dir(&cache_dir, &mut report);
+        }
+        report.cleared.push("caches".to_string());
+    }
+    if scope == ResetScope::All {
+        crate::first_run::reset()?;
+        report.cleared.push("first_run".to_string());
+    }
+
+    crate::breadcrumbs::record("settings", format!("App data reset: {:?}", scope));
+    tracing::info!("App data reset ({:?}), {} errors", scope, report.errors.len());
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_is_single_use_and_scoped() {
+        // 范围不一致时令牌作废
+        let token = request_token(ResetScope::Logs);
+        assert!(take_token(ResetScope::All, &token).is_err());
+        assert!(take_token(ResetScope::Logs, &token).is_err());
+
+        let token = request_token(ResetScope::Logs);
+        assert!(take_token(ResetScope::Logs, &token).is_ok());
+        assert!(take_token(ResetScope::Logs, &token).is_err());
+    }
+}
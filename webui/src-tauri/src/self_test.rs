@@ -0,0 +1,170 @@
+//! 自检
+//!
+//! 依次检查应用运行的关键环节，每项单独给出通过/失败和说明，便于用户在反馈问题前自查、
+//! 也便于支持人员远程定位：
+//! - `paths`：解析 uv 和 Python 的路径
+//! - `python`：用解析到的 Python 运行一个最简单的脚本
+//! - `dawei_home`：在 DAWEI_HOME 中写入并读回临时文件
+//! - `backend_port`：后端端口可以监听（或正由桌面端启动的后端使用）
+//! - `crash_report`：在崩溃报告目录中创建并删除一份测试报告
+//! - `offline_wheels`：是否检测到离线安装包（见 [`crate::offline_wheels`]），有目录但没有安装包时失败
+//!
+//! 检查不会修改设置或 .env，产生的临时文件在检查结束时删除。
+
+use crate::crash_handler::CrashReport;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Instant;
+
+/// 测试脚本的输出
+const PYTHON_PROBE: &str = "dawei-self-test";
+
+/// 单项检查结果
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    pub id: &'static str,
+    pub passed: bool,
+    /// 通过时为检查到的信息（例如路径），失败时为原因
+    pub detail: String,
+    pub duration_ms: u64,
+}
+
+/// 自检结果
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestReport {
+    /// 所有检查都通过
+    pub passed: bool,
+    pub checks: Vec<CheckResult>,
+}
+
+fn run_check(id: &'static str, check: impl FnOnce() -> Result<String, String>) -> CheckResult {
+    let start = Instant::now();
+    let (passed, detail) = match check() {
+        Ok(detail) => (true, detail),
+        Err(detail) => (false, detail),
+    };
+    CheckResult { id, passed, detail, duration_ms: start.elapsed().as_millis() as u64 }
+}
+
+/// 解析 uv 和 Python 的路径，返回 Python 路径
+fn resolve_paths() -> Result<(PathBuf, String), String> {
+    let uv_path = crate::get_uv_path();
+    if !uv_path.is_file() {
+        return Err(format!("找不到 uv: {}", uv_path.display()));
+    }
+    let python = crate::find_python(&uv_path).ok_or_else(|| "无法找到 Python 环境".to_string())?;
+    let detail = format!("uv: {}\nPython: {}", uv_path.display(), python.display());
+    Ok((python, detail))
+}
+
+/// 用 Python 运行最简单的脚本
+fn spawn_python(python: &Path) -> Result<String, String> {
+    let mut command = Command::new(python);
+    command.args(["-c", &format!("import sys; print('{}', sys.version.split()[0])", PYTHON_PROBE)]);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let output = command.output().map_err(|e| format!("无法启动 Python: {}", e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() {
+        return Err(format!("Python 异常退出（{}）: {}", output.status, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    match stdout.trim().strip_prefix(PYTHON_PROBE) {
+        Some(version) => Ok(format!("Python {}", version.trim())),
+        None => Err(format!("Python 输出不符合预期: {}", stdout.trim())),
+    }
+}
+
+/// 在目录中写入、读回并删除临时文件
+fn write_read(dir: &Path) -> Result<String, String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("无法创建 {}: {}", dir.display(), e))?;
+    let path = dir.join(format!(".self-test-{}", uuid::Uuid::new_v4()));
+    let content = format!("{} {}", PYTHON_PROBE, chrono::Local::now().to_rfc3339());
+    let result = std::fs::write(&path, &content)
+        .map_err(|e| format!("无法写入 {}: {}", dir.display(), e))
+        .and_then(|_| std::fs::read_to_string(&path).map_err(|e| format!("无法读取 {}: {}", path.display(), e)))
+        .and_then(|read| if read == content { Ok(()) } else { Err("读回的内容与写入的不一致".to_string()) });
+    let _ = std::fs::remove_file(&path);
+    result.map(|_| dir.display().to_string())
+}
+
+/// 后端端口可以监听，或正由桌面端启动的后端使用
+fn check_port() -> Result<String, String> {
+    let port = crate::backend_profile::port();
+    let diagnosis = crate::port_check::diagnose(port);
+    if !diagnosis.in_use {
+        return Ok(format!("端口 {} 可用", port));
+    }
+    match diagnosis.owner {
+        Some(owner) if owner.managed => Ok(format!("端口 {} 正由后端使用（PID: {}）", port, owner.pid)),
+        Some(owner) => Err(format!("端口 {} 被 {}（PID: {}）占用", port, owner.name, owner.pid)),
+        None => Err(format!("端口 {} 被占用", port)),
+    }
+}
+
+/// 创建并删除一份测试崩溃报告（不上传、不计入统计、不打开查看窗口）
+fn check_crash_report() -> Result<String, String> {
+    let dir = crate::crash_handler::get_crashes_dir();
+    let report = CrashReport::new(format!("Self test {}", uuid::Uuid::new_v4()), String::new());
+    let path = report.save_to(&dir).map_err(|e| format!("无法保存崩溃报告到 {}: {}", dir.display(), e))?;
+    let read = std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| serde_json::from_str::<CrashReport>(&content).map_err(|e| e.to_string()));
+    let removed = std::fs::remove_file(&path);
+    match read {
+        Ok(saved) if saved.error_message == report.error_message => {}
+        Ok(_) => return Err("读回的崩溃报告与保存的不一致".to_string()),
+        Err(e) => return Err(format!("无法读取崩溃报告: {}", e)),
+    }
+    removed.map_err(|e| format!("无法删除测试崩溃报告 {}: {}", path.display(), e))?;
+    Ok(dir.display().to_string())
+}
+
+/// 运行所有检查（阻塞调用，需要启动 Python 进程）
+pub fn run() -> SelfTestReport {
+    let mut python = None;
+    let mut checks = vec![run_check("paths", || {
+        let (path, detail) = resolve_paths()?;
+        python = Some(path);
+        Ok(detail)
+    })];
+    checks.push(run_check("python", || match &python {
+        Some(python) => spawn_python(python),
+        None => Err("未找到 Python，跳过".to_string()),
+    }));
+    checks.push(run_check("dawei_home", || write_read(&crate::get_dawei_home())));
+    checks.push(run_check("backend_port", check_port));
+    checks.push(run_check("crash_report", check_crash_report));
+    checks.push(run_check("offline_wheels", crate::offline_wheels::describe));
+
+    let passed = checks.iter().all(|check| check.passed);
+    let failed: Vec<&str> = checks.iter().filter(|check| !check.passed).map(|check| check.id).collect();
+    if passed {
+        tracing::info!("Self test passed");
+    } else {
+        tracing::warn!("Self test failed: {}", failed.join(", "));
+    }
+    crate::breadcrumbs::record("diagnostics", format!("Self test: {}", if passed { "passed" } else { "failed" }));
+    SelfTestReport { passed, checks }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let check = run_check("dawei_home", || write_read(&dir.path().join("home")));
+        assert!(check.passed, "{}", check.detail);
+        assert_eq!(std::fs::read_dir(dir.path().join("home")).unwrap().count(), 0);
+
+        let file = dir.path().join("file");
+        std::fs::write(&file, "").unwrap();
+        assert!(!run_check("dawei_home", || write_read(&file)).passed);
+    }
+}
@@ -0,0 +1,177 @@
+//! 会话状态模块
+//!
+//! 启动时在 DAWEI_HOME 写入运行标记，正常退出时删除。
+//! 如果启动时发现上次的标记仍然存在，说明上次运行以崩溃结束，
+//! 前端可据此显示"查看报告 / 重启后端 / 重置设置"的恢复对话框。
+//!
+//! 每次启动生成一个会话 ID，写入崩溃报告、运行标记，并通过环境变量传给后端
+//! （后端写入日志和 server.start），用于把用户的日志和崩溃报告对应起来。
+
+use crate::crash_handler::{get_all_crash_reports, CrashReport};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// 传递会话 ID 的环境变量（崩溃监控进程和后端进程继承同一个会话 ID）
+pub const SESSION_ENV: &str = "DAWEI_SESSION_ID";
+
+/// 运行标记文件名
+pub const MARKER_FILE: &str = "session.running";
+
+/// 上次异常结束的会话信息，确认后清空
+static LAST_SESSION_CRASH: Mutex<Option<LastSessionCrash>> = Mutex::new(None);
+
+static SESSION_ID: OnceLock<String> = OnceLock::new();
+
+/// 当前会话 ID
+pub fn session_id() -> &'static str {
+    SESSION_ID.get_or_init(|| {
+        std::env::var(SESSION_ENV)
+            .ok()
+            .filter(|id| !id.trim().is_empty())
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string())
+    })
+}
+
+/// 运行标记内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SessionMarker {
+    pid: u32,
+    #[serde(default)]
+    session_id: String,
+    /// 启动时间戳（秒）
+    started_at: u64,
+    started_at_iso: String,
+}
+
+/// 上次异常结束的会话
+#[derive(Debug, Clone, Serialize)]
+pub struct LastSessionCrash {
+    /// 上次会话的 ID
+    pub session_id: String,
+    /// 上次会话的启动时间
+    pub started_at: String,
+    /// 上次会话期间记录的最新崩溃报告（可能没有，例如被强制结束）
+    pub report: Option<CrashReport>,
+}
+
+fn marker_path() -> PathBuf {
+    crate::get_dawei_home().join(MARKER_FILE)
+}
+
+/// 开始新会话：检测上次会话是否异常结束，并写入新的运行标记
+pub fn begin_session() {
+    let path = marker_path();
+    tracing::info!("Session ID: {}", session_id());
+
+    if let Some(previous) = read_previous_marker(&path) {
+        // 优先按会话 ID 匹配，旧标记没有会话 ID 时按时间匹配
+        let report = get_all_crash_reports().into_iter().find(|r| {
+            if previous.session_id.is_empty() {
+                r.last_seen_timestamp() >= previous.started_at
+            } else {
+                r.session_id == previous.session_id
+            }
+        });
+        let crash = LastSessionCrash {
+            session_id: previous.session_id.clone(),
+            started_at: previous.started_at_iso.clone(),
+            report,
+        };
+        tracing::warn!("Previous session (started {}) did not exit cleanly", crash.started_at);
+        crate::breadcrumbs::record("session", format!("Previous session started {} ended abnormally", crash.started_at));
+        *LAST_SESSION_CRASH.lock().unwrap_or_else(|e| e.into_inner()) = Some(crash);
+    }
+
+    if let Err(e) = write_marker(&path) {
+        tracing::warn!("Failed to write session marker: {}", e);
+    }
+}
+
+/// 正常退出时删除运行标记
+pub fn end_session() {
+    let path = marker_path();
+    // 只删除自己的标记，避免误删其他实例的标记
+    if read_marker(&path).is_some_and(|m| m.pid == std::process::id()) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// 上次会话的崩溃信息
+pub fn last_session_crash() -> Option<LastSessionCrash> {
+    LAST_SESSION_CRASH.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// 前端处理完恢复流程后清除崩溃信息
+pub fn acknowledge_last_session_crash() {
+    LAST_SESSION_CRASH.lock().unwrap_or_else(|e| e.into_inner()).take();
+}
+
+fn read_marker(path: &Path) -> Option<SessionMarker> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+/// 读取遗留的运行标记；标记所属进程仍在运行（另一个实例）时不视为崩溃
+fn read_previous_marker(path: &Path) -> Option<SessionMarker> {
+    let marker = read_marker(path)?;
+    (marker.pid != std::process::id() && !process_alive(marker.pid)).then_some(marker)
+}
+
+fn write_marker(path: &Path) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let now = chrono::Local::now();
+    let marker = SessionMarker {
+        pid: std::process::id(),
+        session_id: session_id().to_string(),
+        started_at: now.timestamp().max(0) as u64,
+        started_at_iso: now.to_rfc3339(),
+    };
+    fs::write(path, serde_json::to_string_pretty(&marker).unwrap_or_default())
+}
+
+/// 进程是否仍在运行
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    // 信号 0 只做存在性和权限检查
+    unsafe { libc::kill(pid as libc::pid_t, 0) == 0 }
+}
+
+/// 进程是否仍在运行
+#[cfg(windows)]
+fn process_alive(pid: u32) -> bool {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    std::process::Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stale_marker_is_detected_as_crash() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(MARKER_FILE);
+
+        // 没有标记：上次正常退出
+        assert!(read_previous_marker(&path).is_none());
+
+        // 自己写的标记不算上次会话
+        write_marker(&path).unwrap();
+        assert!(read_previous_marker(&path).is_none());
+
+        // 已退出进程遗留的标记
+        let stale = SessionMarker { pid: u32::MAX - 1, session_id: String::new(), started_at: 1, started_at_iso: "then".to_string() };
+        fs::write(&path, serde_json::to_string(&stale).unwrap()).unwrap();
+        assert_eq!(read_previous_marker(&path).unwrap().started_at_iso, "then");
+    }
+}
@@ -0,0 +1,657 @@
+//! 应用设置模块
+//!
+//! 设置保存在 DAWEI_HOME/settings.json，内存中保留一份当前设置，
+//! 修改时先写临时文件再重命名，避免写到一半崩溃导致设置文件损坏。
+//! 缺失的字段使用默认值，环境变量仍可临时覆盖对应设置。
+//!
+//! 工作区可以在 `<工作区>/.dawei/settings.json` 中覆盖全局设置（后端也从这里读取
+//! 工作区级的模型配置），[`effective_for_workspace`] 返回合并后的实际配置。
+//!
+//! 后端也在同一文件中保存 LLM 配置（providerProfiles 等），这里不认识的字段
+//! 原样保留，修改前重新读取文件，避免覆盖后端在此期间写入的内容。文件无法解析时
+//! （手动编辑出错、后端正在写入）拒绝修改，不会用默认值覆盖整个文件。
+//!
+//! 用户手动编辑或同步工具修改设置文件时，[`watch`] 会重新加载并发送变更事件，
+//! 无需重启应用；无法解析的中间状态会被忽略。
+//!
+//! 锁定模式来自设置（`locked_down`）时，页面不能通过修改、导入或重置设置关闭它，
+//! 只能由管理员直接编辑设置文件。
+//!
+//! 设置文件带有 `schema_version`，加载旧版本文件时依次执行迁移，
+//! 迁移前的原文件备份为 `settings.json.v{版本}.bak`。
+
+use crate::atomic_file::write_json_atomic;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::RwLock;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// 设置变更事件，发送给所有窗口，内容为 [`SettingsChange`]
+pub const SETTINGS_CHANGED_EVENT: &str = "settings-changed";
+
+/// 当前设置文件版本
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// 设置迁移，`MIGRATIONS[n]` 将版本 n 升级到 n + 1
+type Migration = fn(&mut Map<String, Value>);
+
+const MIGRATIONS: [Migration; SCHEMA_VERSION as usize] = [migrate_v0_to_v1];
+
+/// 外部修改后等待文件写完的时间（编辑器保存时可能连续触发多个事件）
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// 当前设置（首次访问时从文件加载）
+static SETTINGS: RwLock<Option<Settings>> = RwLock::new(None);
+
+/// 应用设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// 设置文件版本
+    pub schema_version: u32,
+    /// 启动时自动打开 DevTools（DAWEI_DEVTOOLS 覆盖）
+    pub open_devtools: bool,
+    /// 允许运行中打开/关闭 DevTools（toggle_devtools 命令和帮助菜单）
+    pub devtools_toggle: bool,
+    /// 锁定模式：禁用 DevTools 和诊断命令（DAWEI_LOCKDOWN 覆盖，系统策略文件优先）
+    pub locked_down: bool,
+    /// 卡死判定阈值（秒），0 表示关闭检测（DAWEI_HANG_TIMEOUT_SECS 覆盖）
+    pub hang_timeout_secs: u64,
+    /// 上传崩溃报告（需要 sentry feature，DAWEI_CRASH_UPLOAD 覆盖）
+    pub crash_upload: bool,
+    /// 发送匿名使用统计（DAWEI_TELEMETRY 覆盖）
+    pub telemetry: bool,
+    /// 使用统计的发送地址，None 表示使用内置地址（DAWEI_TELEMETRY_ENDPOINT 覆盖）
+    pub telemetry_endpoint: Option<String>,
+    /// 日志级别，格式同 DAWEI_LOG（例如 `debug`），None 表示默认的 info（DAWEI_LOG 覆盖）
+    pub log_level: Option<String>,
+    /// 主窗口置顶
+    pub always_on_top: bool,
+    /// 关闭主窗口时隐藏到托盘（后端继续运行），只能从托盘菜单退出
+    pub close_to_tray: bool,
+    /// 使用前端绘制的标题栏（去掉系统标题栏）
+    pub custom_titlebar: bool,
+    /// 页面缩放比例（0.5-3.0）
+    pub zoom: f64,
+    /// 显示/隐藏主窗口的全局快捷键，null 表示关闭
+    pub global_shortcut: Option<String>,
+    /// 主题偏好
+    pub theme: crate::theme::ThemePreference,
+    /// 界面语言（首次运行时检测系统语言，DAWEI_LOCALE 覆盖）
+    pub locale: Option<crate::i18n::Locale>,
+    /// 命名的后端启动配置
+    pub backend_profiles: BTreeMap<String, crate::backend_profile::BackendProfile>,
+    /// start_backend 使用的配置，None 表示默认行为
+    pub active_profile: Option<String>,
+    /// 收藏的目录，可作为文件对话框的初始位置
+    pub bookmarks: Vec<crate::bookmarks::Bookmark>,
+    /// 规范化路径时解析还是保留符号链接
+    pub symlink_mode: crate::symlink_policy::SymlinkMode,
+    /// 应用更新通道
+    pub update_channel: crate::updater::UpdateChannel,
+    /// 后台检查应用和后端更新的频率
+    pub update_check_interval: crate::update_schedule::UpdateCheckInterval,
+    /// 固定的后端版本（例如 `0.3.2`），设置后安装和升级只装该版本，None 表示不固定
+    pub backend_version_pin: Option<String>,
+    /// 后端等其他组件保存的字段，原样保留
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            open_devtools: false,
+            devtools_toggle: false,
+            locked_down: false,
+            hang_timeout_secs: 10,
+            crash_upload: false,
+            telemetry: false,
+            telemetry_endpoint: None,
+            log_level: None,
+            always_on_top: false,
+            close_to_tray: false,
+            custom_titlebar: false,
+            zoom: 1.0,
+            global_shortcut: Some(crate::hotkey::DEFAULT_SHORTCUT.to_string()),
+            theme: Default::default(),
+            locale: None,
+            backend_profiles: BTreeMap::new(),
+            active_profile: None,
+            bookmarks: Vec::new(),
+            symlink_mode: Default::default(),
+            update_channel: Default::default(),
+            update_check_interval: Default::default(),
+            backend_version_pin: None,
+            extra: Map::new(),
+        }
+    }
+}
+
+/// 单个设置项的变化
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub old: Value,
+    pub new: Value,
+}
+
+/// 设置变更事件内容
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingsChange {
+    /// 变化的设置项，嵌套字段使用 `a.b` 形式的路径
+    pub changed: BTreeMap<String, FieldChange>,
+    /// 修改后的完整设置
+    pub settings: Settings,
+}
+
+/// 工作区的实际配置
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveSettings {
+    /// 全局设置叠加工作区设置后的结果
+    pub settings: Value,
+    /// 被工作区覆盖的设置项（`a.b` 形式的路径）
+    pub overridden: Vec<String>,
+    /// 工作区设置文件（不存在时为 None）
+    pub workspace_file: Option<PathBuf>,
+}
+
+/// 设置文件路径
+pub fn settings_file() -> PathBuf {
+    crate::get_dawei_home().join("settings.json")
+}
+
+/// v0（没有 schema_version 的最初版本）字段与 v1 相同，只补充版本号
+fn migrate_v0_to_v1(_settings: &mut Map<String, Value>) {}
+
+/// 将设置升级到当前版本，返回原版本
+fn migrate(settings: &mut Map<String, Value>) -> u32 {
+    let from = settings
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .map(|v| v as u32)
+        .unwrap_or(0);
+
+    for migration in MIGRATIONS.iter().skip(from as usize) {
+        migration(settings);
+    }
+    if from < SCHEMA_VERSION {
+        settings.insert("schema_version".to_string(), Value::from(SCHEMA_VERSION));
+    }
+    from
+}
+
+/// 从文件读取设置，文件不存在或无法解析时使用默认值
+///
+/// 旧版本的文件会先备份再迁移，并把迁移结果写回。
+fn load_from(path: &Path) -> Settings {
+    let Ok(content) = fs::read_to_string(path) else {
+        return Settings::default();
+    };
+    let mut value = match serde_json::from_str::<Value>(&content) {
+        Ok(Value::Object(map)) => map,
+        Ok(_) | Err(_) => {
+            tracing::warn!("Failed to parse {:?}, using default settings", path);
+            return Settings::default();
+        }
+    };
+
+    let from = migrate(&mut value);
+    if from > SCHEMA_VERSION {
+        tracing::warn!("Settings schema version {} is newer than supported version {}", from, SCHEMA_VERSION);
+    }
+
+    let settings: Settings = match serde_json::from_value(Value::Object(value)) {
+        Ok(settings) => settings,
+        Err(e) => {
+            tracing::warn!("Failed to parse {:?}, using default settings: {}", path, e);
+            return Settings::default();
+        }
+    };
+
+    if from < SCHEMA_VERSION {
+        let backup = path.with_extension(format!("json.v{}.bak", from));
+        let migrated = fs::copy(path, &backup).and_then(|_| write_json_atomic(path, &settings));
+        match migrated {
+            Ok(()) => tracing::info!("Settings migrated from v{} to v{} (backup: {:?})", from, SCHEMA_VERSION, backup),
+            Err(e) => tracing::warn!("Failed to save migrated settings: {}", e),
+        }
+    }
+    settings
+}
+
+/// 严格读取设置（重新加载和修改设置时使用）：文件不存在时使用默认值，无法解析时返回错误（不迁移、不写回）
+fn read_strict(path: &Path) -> Result<Settings, String> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Settings::default()),
+        Err(e) => return Err(e.to_string()),
+    };
+    let mut value = match serde_json::from_str::<Value>(&content).map_err(|e| e.to_string())? {
+        Value::Object(map) => map,
+        _ => return Err("设置文件必须是 JSON 对象".to_string()),
+    };
+    migrate(&mut value);
+    serde_json::from_value(Value::Object(value)).map_err(|e| e.to_string())
+}
+
+/// 将 JSON 补丁合并到设置中（对象递归合并，其他值直接替换）
+fn merge(target: &mut Value, patch: Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch {
+                merge(target.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (target, patch) => *target = patch,
+    }
+}
+
+/// 应用补丁，返回新设置；补丁中的字段类型不对时返回错误
+fn apply_patch(current: &Settings, patch: Value) -> Result<Settings, String> {
+    let mut value = serde_json::to_value(current).map_err(|e| e.to_string())?;
+    merge(&mut value, patch);
+    serde_json::from_value(value).map_err(|e| format!("设置格式错误: {}", e))
+}
+
+/// 处于锁定模式且锁定来自设置时，保持修改后的设置仍为锁定
+fn keep_lockdown(locked_down: bool, previous: &Settings, mut updated: Settings) -> Settings {
+    if locked_down && previous.locked_down {
+        updated.locked_down = true;
+    }
+    updated
+}
+
+/// 以文件的最新内容为基础应用补丁，文件无法解析时返回错误
+fn patched(path: &Path, patch: Value) -> Result<Settings, String> {
+    let on_disk = read_strict(path).map_err(|e| format!("设置文件 {:?} 无法解析，修改未保存: {}", path, e))?;
+    apply_patch(&on_disk, patch)
+}
+
+/// 比较两份设置，返回变化的字段（对象逐层比较）
+fn diff(old: &Value, new: &Value) -> BTreeMap<String, FieldChange> {
+    fn walk(path: &str, old: &Value, new: &Value, changes: &mut BTreeMap<String, FieldChange>) {
+        if let (Value::Object(old_map), Value::Object(new_map)) = (old, new) {
+            let mut keys: Vec<&String> = old_map.keys().chain(new_map.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let child = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                walk(
+                    &child,
+                    old_map.get(key).unwrap_or(&Value::Null),
+                    new_map.get(key).unwrap_or(&Value::Null),
+                    changes,
+                );
+            }
+        } else if old != new {
+            changes.insert(path.to_string(), FieldChange { old: old.clone(), new: new.clone() });
+        }
+    }
+
+    let mut changes = BTreeMap::new();
+    walk("", old, new, &mut changes);
+    changes
+}
+
+/// 工作区设置文件路径
+pub fn workspace_settings_file(workspace: &Path) -> PathBuf {
+    workspace.join(".dawei").join("settings.json")
+}
+
+/// 将工作区设置叠加到全局设置上
+fn overlay(global: &Settings, workspace: Option<Value>) -> Result<EffectiveSettings, String> {
+    let base = serde_json::to_value(global).map_err(|e| e.to_string())?;
+    let mut merged = base.clone();
+    if let Some(workspace) = workspace {
+        merge(&mut merged, workspace);
+    }
+
+    // 确认叠加后的设置仍然有效
+    serde_json::from_value::<Settings>(merged.clone()).map_err(|e| format!("工作区设置格式错误: {}", e))?;
+
+    Ok(EffectiveSettings {
+        overridden: diff(&base, &merged).into_keys().collect(),
+        settings: merged,
+        workspace_file: None,
+    })
+}
+
+/// 指定工作区的实际配置
+pub fn effective_for_workspace(workspace: &Path) -> Result<EffectiveSettings, String> {
+    let file = workspace_settings_file(workspace);
+    let workspace_value = match fs::read_to_string(&file) {
+        Ok(content) => Some(
+            serde_json::from_str::<Value>(&content)
+                .map_err(|e| format!("无法解析工作区设置 {:?}: {}", file, e))?,
+        ),
+        Err(_) => None,
+    };
+
+    let exists = workspace_value.is_some();
+    let mut effective = overlay(&current(), workspace_value)?;
+    effective.workspace_file = exists.then_some(file);
+    Ok(effective)
+}
+
+/// 当前设置
+pub fn current() -> Settings {
+    if let Some(settings) = SETTINGS.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        return settings.clone();
+    }
+
+    let mut guard = SETTINGS.write().unwrap_or_else(|e| e.into_inner());
+    guard.get_or_insert_with(|| load_from(&settings_file())).clone()
+}
+
+/// 确认设置文件已迁移到当前版本（加载时迁移失败只记录日志，这里返回错误）
+pub fn ensure_migrated() -> Result<(), String> {
+    current();
+    let content = match fs::read_to_string(settings_file()) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(format!("无法读取设置文件: {}", e)),
+    };
+    let value: Value = serde_json::from_str(&content).map_err(|e| format!("设置文件无法解析: {}", e))?;
+    let version = value.get("schema_version").and_then(Value::as_u64).unwrap_or(0);
+    if version < SCHEMA_VERSION as u64 {
+        return Err(format!("设置文件仍是 v{}，迁移到 v{} 后未能保存", version, SCHEMA_VERSION));
+    }
+    Ok(())
+}
+
+/// 修改设置：合并补丁、写入文件并通知所有窗口
+pub fn update(app: &AppHandle, patch: Value) -> Result<Settings, String> {
+    // 读取锁定状态需要读锁，在加写锁之前读取
+    let locked_down = crate::policy::is_locked_down();
+    let (previous, updated) = {
+        let mut guard = SETTINGS.write().unwrap_or_else(|e| e.into_inner());
+        let current = guard.get_or_insert_with(|| load_from(&settings_file()));
+        // 以文件的最新内容为基础，保留后端写入的修改
+        let updated = keep_lockdown(locked_down, current, patched(&settings_file(), patch)?);
+        if updated == *current {
+            return Ok(updated);
+        }
+
+        write_json_atomic(&settings_file(), &updated).map_err(|e| format!("保存设置失败: {}", e))?;
+        (std::mem::replace(current, updated.clone()), updated)
+    };
+
+    notify(app, &previous, &updated, "updated");
+    Ok(updated)
+}
+
+/// 记录并通知所有窗口设置变化
+fn notify(app: &AppHandle, previous: &Settings, updated: &Settings, action: &str) {
+    let change = SettingsChange {
+        changed: diff(
+            &serde_json::to_value(previous).unwrap_or_default(),
+            &serde_json::to_value(updated).unwrap_or_default(),
+        ),
+        settings: updated.clone(),
+    };
+    let fields: Vec<&str> = change.changed.keys().map(String::as_str).collect();
+    crate::breadcrumbs::record("settings", format!("Settings {}: {}", action, fields.join(", ")));
+
+    // emit 会发送给所有窗口（主窗口、崩溃报告窗口等）
+    if let Err(e) = app.emit(SETTINGS_CHANGED_EVENT, &change) {
+        tracing::warn!("Failed to emit settings change: {}", e);
+    }
+}
+
+/// 恢复默认设置：原文件（包括后端保存的 LLM 配置）备份为 `settings.json.reset.bak` 后删除
+pub fn reset(app: &AppHandle) -> Result<(), String> {
+    let locked_down = crate::policy::is_locked_down();
+    let (previous, defaults) = {
+        let mut guard = SETTINGS.write().unwrap_or_else(|e| e.into_inner());
+        let current = guard.get_or_insert_with(|| load_from(&settings_file()));
+        let defaults = keep_lockdown(locked_down, current, Settings::default());
+        let path = settings_file();
+        if path.exists() {
+            fs::copy(&path, path.with_extension("json.reset.bak"))
+                .and_then(|_| fs::remove_file(&path))
+                .map_err(|e| format!("重置设置失败: {}", e))?;
+        }
+        // 锁定模式保留在重置后的设置文件中
+        if defaults != Settings::default() {
+            write_json_atomic(&path, &defaults).map_err(|e| format!("重置设置失败: {}", e))?;
+        }
+        (std::mem::replace(current, defaults.clone()), defaults)
+    };
+    notify(app, &previous, &defaults, "reset");
+    Ok(())
+}
+
+/// 从文件重新加载设置，有变化时通知所有窗口（应用自己写入的修改不会重复通知）
+fn reload(app: &AppHandle) {
+    // 持锁读取文件，避免读到 update 写入前的旧内容后又覆盖内存中的新设置
+    let (previous, reloaded) = {
+        let mut guard = SETTINGS.write().unwrap_or_else(|e| e.into_inner());
+        let reloaded = match read_strict(&settings_file()) {
+            Ok(settings) => settings,
+            Err(e) => {
+                tracing::warn!("Ignoring external settings change that cannot be parsed: {}", e);
+                return;
+            }
+        };
+        let current = guard.get_or_insert_with(|| reloaded.clone());
+        if *current == reloaded {
+            return;
+        }
+        (std::mem::replace(current, reloaded.clone()), reloaded)
+    };
+    tracing::info!("Settings reloaded after external change");
+    notify(app, &previous, &reloaded, "reloaded");
+}
+
+/// 监听设置文件的外部修改
+///
+/// 监听的是 DAWEI_HOME 目录而不是文件本身：原子写入（包括本模块和很多编辑器）
+/// 通过重命名替换文件，直接监听文件会在第一次替换后失效。
+pub fn watch(app: &AppHandle) {
+    use notify::Watcher;
+
+    let file = settings_file();
+    let Some(dir) = file.parent().map(Path::to_path_buf) else {
+        return;
+    };
+    if let Err(e) = fs::create_dir_all(&dir) {
+        tracing::warn!("Cannot watch settings: {}", e);
+        return;
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = match notify::recommended_watcher(tx) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            tracing::warn!("Cannot watch settings: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = watcher.watch(&dir, notify::RecursiveMode::NonRecursive) {
+        tracing::warn!("Cannot watch settings: {}", e);
+        return;
+    }
+
+    let app = app.clone();
+    let spawned = std::thread::Builder::new().name("settings-watcher".to_string()).spawn(move || {
+        // 线程持有 watcher，应用退出前一直监听
+        let _watcher = watcher;
+        let touches_settings = |event: &notify::Event| event.paths.iter().any(|p| p.file_name() == file.file_name());
+        while let Ok(result) = rx.recv() {
+            if !result.is_ok_and(|event| touches_settings(&event)) {
+                continue;
+            }
+            // 合并短时间内的连续事件
+            while rx.recv_timeout(RELOAD_DEBOUNCE).is_ok() {}
+            reload(&app);
+        }
+    });
+    if let Err(e) = spawned {
+        tracing::warn!("Cannot start settings watcher: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_apply_patch_keeps_other_fields() {
+        let current = Settings { crash_upload: true, ..Settings::default() };
+
+        let updated = apply_patch(&current, json!({ "hang_timeout_secs": 30 })).unwrap();
+        assert_eq!(updated.hang_timeout_secs, 30);
+        assert!(updated.crash_upload);
+
+        assert!(apply_patch(&current, json!({ "hang_timeout_secs": "soon" })).is_err());
+    }
+
+    #[test]
+    fn test_read_strict() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        assert_eq!(read_strict(&path).unwrap(), Settings::default());
+
+        fs::write(&path, r#"{"hang_timeout_secs": 30, "providerProfiles": {}}"#).unwrap();
+        let settings = read_strict(&path).unwrap();
+        assert_eq!(settings.hang_timeout_secs, 30);
+        assert_eq!(settings.schema_version, SCHEMA_VERSION);
+
+        // 编辑到一半的文件不会覆盖当前设置
+        fs::write(&path, r#"{"hang_timeout_secs": "#).unwrap();
+        assert!(read_strict(&path).is_err());
+    }
+
+    #[test]
+    fn test_backend_fields_are_preserved() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        let content = json!({
+            "schema_version": SCHEMA_VERSION,
+            "providerProfiles": { "currentApiConfigName": "glm" }
+        });
+        fs::write(&path, content.to_string()).unwrap();
+
+        let updated = patched(&path, json!({ "crash_upload": true })).unwrap();
+        write_json_atomic(&path, &updated).unwrap();
+
+        let saved: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["providerProfiles"]["currentApiConfigName"], "glm");
+        assert_eq!(saved["crash_upload"], true);
+
+        // 字段类型不对或后端写到一半时不能用默认值覆盖
+        for broken in [r#"{ "zoom": "1.2", "providerProfiles": {} }"#, r#"{ "providerProfiles": { "#] {
+            fs::write(&path, broken).unwrap();
+            assert!(patched(&path, json!({ "crash_upload": false })).is_err());
+        }
+    }
+
+    #[test]
+    fn test_lockdown_cannot_be_cleared() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        let locked = Settings { locked_down: true, ..Default::default() };
+        write_json_atomic(&path, &locked).unwrap();
+
+        // update_settings 和覆盖导入都通过补丁修改
+        let updated = keep_lockdown(true, &locked, patched(&path, json!({ "locked_down": false, "crash_upload": true })).unwrap());
+        assert!(updated.locked_down && updated.crash_upload);
+        // 恢复出厂设置
+        assert!(keep_lockdown(true, &locked, Settings::default()).locked_down);
+
+        // 锁定来自策略文件或环境变量时设置中的开关可以修改；没有锁定时可以关闭
+        let unlocked = Settings::default();
+        assert!(!keep_lockdown(true, &unlocked, patched(&path, json!({ "locked_down": false })).unwrap()).locked_down);
+        assert!(!keep_lockdown(false, &locked, Settings::default()).locked_down);
+    }
+
+    #[test]
+    fn test_workspace_overlay() {
+        let mut global = Settings::default();
+        global.extra.insert("providerProfiles".to_string(), json!({ "currentApiConfigName": "glm", "apiConfigs": {} }));
+
+        let effective = overlay(
+            &global,
+            Some(json!({ "hang_timeout_secs": 60, "providerProfiles": { "currentApiConfigName": "deepseek" } })),
+        )
+        .unwrap();
+        assert_eq!(effective.settings["hang_timeout_secs"], 60);
+        assert_eq!(effective.settings["providerProfiles"]["currentApiConfigName"], "deepseek");
+        assert_eq!(effective.settings["providerProfiles"]["apiConfigs"], json!({}));
+        assert_eq!(effective.overridden, ["hang_timeout_secs", "providerProfiles.currentApiConfigName"]);
+
+        assert!(overlay(&global, Some(json!({ "crash_upload": "yes" }))).is_err());
+    }
+
+    #[test]
+    fn test_diff_reports_changed_paths() {
+        let old = json!({ "crash_upload": false, "hang_timeout_secs": 10, "proxy": { "url": "a", "enabled": true } });
+        let new = json!({ "crash_upload": true, "hang_timeout_secs": 10, "proxy": { "url": "b", "enabled": true } });
+
+        let changes = diff(&old, &new);
+        assert_eq!(changes.keys().collect::<Vec<_>>(), ["crash_upload", "proxy.url"]);
+        assert_eq!(changes["proxy.url"], FieldChange { old: json!("a"), new: json!("b") });
+    }
+
+    #[test]
+    fn test_migrate_from_v0_with_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        let v0 = r#"{ "open_devtools": true, "hang_timeout_secs": 0, "crash_upload": true }"#;
+        fs::write(&path, v0).unwrap();
+
+        let settings = load_from(&path);
+        assert_eq!(settings.schema_version, SCHEMA_VERSION);
+        assert!(settings.open_devtools);
+        assert_eq!(settings.hang_timeout_secs, 0);
+        assert!(settings.crash_upload);
+
+        assert_eq!(fs::read_to_string(dir.path().join("settings.json.v0.bak")).unwrap(), v0);
+        let saved: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["schema_version"], SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_current_and_newer_versions_are_not_migrated() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+
+        for version in [SCHEMA_VERSION, SCHEMA_VERSION + 1] {
+            let content = format!(r#"{{ "schema_version": {}, "crash_upload": true }}"#, version);
+            fs::write(&path, &content).unwrap();
+
+            let settings = load_from(&path);
+            assert_eq!(settings.schema_version, version);
+            assert!(settings.crash_upload);
+            assert_eq!(fs::read_to_string(&path).unwrap(), content);
+        }
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_write_atomic_roundtrip_and_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+
+        // 缺失和损坏的文件都使用默认值
+        assert_eq!(load_from(&path), Settings::default());
+        fs::write(&path, "{ not json").unwrap();
+        assert_eq!(load_from(&path), Settings::default());
+
+        let settings = Settings { open_devtools: true, ..Settings::default() };
+        write_json_atomic(&path, &settings).unwrap();
+        assert_eq!(load_from(&path), settings);
+        assert!(!path.with_extension("json.tmp").exists());
+
+        // 缺少的字段使用默认值
+        fs::write(&path, format!(r#"{{ "schema_version": {}, "crash_upload": true }}"#, SCHEMA_VERSION)).unwrap();
+        assert_eq!(load_from(&path).hang_timeout_secs, 10);
+    }
+}
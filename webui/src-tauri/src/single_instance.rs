@@ -0,0 +1,36 @@
+//! 单实例
+//!
+//! 第二次启动应用时不再创建新窗口和后端，而是聚焦已有的主窗口，
+//! 并把第二个进程的命令行参数（包括深度链接）通过 `second-instance` 事件转发给前端。
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// 第二次启动事件，内容为 [`SecondInstance`]
+pub const SECOND_INSTANCE_EVENT: &str = "second-instance";
+
+/// 第二次启动的参数
+#[derive(Debug, Clone, Serialize)]
+pub struct SecondInstance {
+    /// 命令行参数（不含可执行文件路径）
+    pub args: Vec<String>,
+    /// 第二个进程的工作目录
+    pub cwd: String,
+}
+
+/// 插件回调：聚焦已有窗口并转发参数
+pub fn on_second_instance(app: &AppHandle, args: Vec<String>, cwd: String) {
+    crate::breadcrumbs::record("window", format!("Second instance launched with {} args", args.len().saturating_sub(1)));
+
+    if let Some(window) = app.get_webview_window("main") {
+        let focused = window.unminimize().and_then(|_| window.show()).and_then(|_| window.set_focus());
+        if let Err(e) = focused {
+            tracing::warn!("Failed to focus main window: {}", e);
+        }
+    }
+
+    let payload = SecondInstance { args: args.into_iter().skip(1).collect(), cwd };
+    if let Err(e) = app.emit(SECOND_INSTANCE_EVENT, &payload) {
+        tracing::warn!("Failed to forward second instance args: {}", e);
+    }
+}
@@ -0,0 +1,245 @@
+//! 慢命令检测
+//!
+//! 每个命令都在一个 `slow_command` span 中执行。异步命令的 future 由 tauri 的 tracing
+//! 功能放在它的子 span（`ipc::request::run`）中，因此 span 在命令完成后才关闭；关闭时
+//! 耗时超过阈值就记录警告并发送 `slow-command` 事件，便于尽早发现在命令中阻塞 IO 等问题。
+//! 这两个 span 只对本模块的 layer 启用，不会出现在日志中。
+//! 等待用户操作（文件对话框）或本来就要访问网络、启动进程的命令不检测。
+
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tauri::ipc::InvokeBody;
+use tauri::{AppHandle, Emitter};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::Subscriber;
+use tracing_subscriber::filter::{filter_fn, FilterFn, Filtered};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// 慢命令事件
+pub const SLOW_COMMAND_EVENT: &str = "slow-command";
+
+/// 计时 span 的 target
+const TARGET: &str = "slow_command";
+
+/// tauri 执行命令的 span（启用 tauri 的 tracing 功能后由 `#[tauri::command]` 生成）
+const RUN_SPAN: &str = "ipc::request::run";
+
+/// 超过该耗时的命令视为慢命令
+const THRESHOLD: Duration = Duration::from_secs(1);
+
+/// 不检测的命令：等待用户在对话框中操作，或需要访问网络、启动/停止进程
+const EXPECTED_SLOW: [&str; 37] = [
+    "select_directory",
+    "select_directories",
+    "select_file",
+    "select_files",
+    "save_file_dialog",
+    "create_workspace_from_template",
+    "trash_paths",
+    "search_in_workspace",
+    "get_workspace_stats",
+    "export_workspace_zip",
+    "import_workspace_zip",
+    "export_crash_report",
+    "export_view_pdf",
+    "export_diagnostics",
+    "get_about_info",
+    "check_connectivity",
+    "run_self_test",
+    "relocate_dawei_home",
+    "start_backend",
+    "stop_backend",
+    "restart_backend",
+    "kill_port_owner",
+    "terminate_stale_processes",
+    "upload_dropped_files",
+    "stage_attachments",
+    "check_for_updates",
+    "download_update",
+    "check_backend_updates",
+    "start_coordinated_upgrade",
+    "retry_upgrade",
+    "rollback_backend",
+    "get_changelog",
+    "cleanup_app_data",
+    "repair_installation",
+    "start_download",
+    "resume_download",
+    "install_pinned_backend",
+];
+
+/// 不记录参数的命令：参数是环境变量的名称和值，可能是任意密钥
+const HIDDEN_ARGS: [&str; 3] = ["get_env_var", "set_env_var", "unset_env_var"];
+
+/// 参数摘要中每个值的最大长度
+const MAX_VALUE_LEN: usize = 60;
+
+static APP: OnceLock<AppHandle> = OnceLock::new();
+
+/// `slow-command` 事件内容
+#[derive(Debug, Clone, Serialize)]
+pub struct SlowCommand {
+    pub command: String,
+    pub duration_ms: u64,
+    /// 参数摘要，例如 `filename="crash_1.json", limit=20`
+    pub args: String,
+}
+
+/// 参数摘要：只展开顶层参数，长字符串截断，名称像密钥的参数不显示值
+///
+/// 键值对形式的参数（`key`/`name` 加 `value`）按 `key`/`name` 的值判断 `value` 是否为密钥。
+fn summarize_args(command: &str, body: &InvokeBody) -> String {
+    if HIDDEN_ARGS.contains(&command) {
+        return "<hidden>".to_string();
+    }
+    let object = match body {
+        InvokeBody::Json(Value::Object(object)) => object,
+        InvokeBody::Json(Value::Null) => return String::new(),
+        InvokeBody::Json(value) => return summarize_value(value),
+        InvokeBody::Raw(bytes) => return format!("<{} bytes>", bytes.len()),
+    };
+    let secret_pair = ["key", "name"]
+        .iter()
+        .filter_map(|field| object.get(*field).and_then(Value::as_str))
+        .any(crate::env_file::is_secret);
+    object
+        .iter()
+        .map(|(key, value)| {
+            let secret = crate::env_file::is_secret(key) || (secret_pair && key == "value");
+            let value = if secret { "***".to_string() } else { summarize_value(value) };
+            format!("{}={}", key, value)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn summarize_value(value: &Value) -> String {
+    match value {
+        Value::String(text) if text.chars().count() > MAX_VALUE_LEN => {
+            format!("{:?}…", text.chars().take(MAX_VALUE_LEN).collect::<String>())
+        }
+        Value::Array(items) => format!("[{} items]", items.len()),
+        Value::Object(object) => format!("{{{} keys}}", object.len()),
+        value => value.to_string(),
+    }
+}
+
+/// 为命令创建计时 span（不检测的命令返回 None），需要在调用命令处理函数期间进入
+pub fn span(command: &str, body: &InvokeBody) -> Option<tracing::Span> {
+    if EXPECTED_SLOW.contains(&command) {
+        return None;
+    }
+    Some(tracing::trace_span!(target: TARGET, "slow_command", command, args = %summarize_args(command, body)))
+}
+
+/// 记录 AppHandle，之后的慢命令会发送事件
+pub fn init(app: &AppHandle) {
+    let _ = APP.set(app.clone());
+}
+
+fn report(slow: SlowCommand) {
+    tracing::warn!("Slow command {} took {} ms ({})", slow.command, slow.duration_ms, slow.args);
+    if let Some(app) = APP.get() {
+        if let Err(e) = app.emit(SLOW_COMMAND_EVENT, &slow) {
+            tracing::warn!("Failed to emit slow-command: {}", e);
+        }
+    }
+}
+
+/// 计时 span 的字段和开始时间
+struct Timing {
+    start: Instant,
+    command: String,
+    args: String,
+}
+
+#[derive(Default)]
+struct TimingVisitor {
+    command: String,
+    args: String,
+}
+
+impl Visit for TimingVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "command" {
+            self.command = value.to_string();
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        match field.name() {
+            "command" => self.command = format!("{:?}", value),
+            "args" => self.args = format!("{:?}", value),
+            _ => {}
+        }
+    }
+}
+
+/// 计时 layer，只接收计时 span 和 tauri 执行命令的 span
+pub struct SlowCommandLayer;
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for SlowCommandLayer {
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if attrs.metadata().target() != TARGET {
+            return;
+        }
+        let mut visitor = TimingVisitor::default();
+        attrs.record(&mut visitor);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(Timing { start: Instant::now(), command: visitor.command, args: visitor.args });
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(timing) = span.extensions_mut().remove::<Timing>() else {
+            return;
+        };
+        let elapsed = timing.start.elapsed();
+        if elapsed >= THRESHOLD {
+            report(SlowCommand { command: timing.command, duration_ms: elapsed.as_millis() as u64, args: timing.args });
+        }
+    }
+}
+
+/// 带过滤器的计时 layer（不受日志级别影响）
+pub fn layer<S: Subscriber + for<'a> LookupSpan<'a>>() -> Filtered<SlowCommandLayer, FilterFn, S> {
+    SlowCommandLayer.with_filter(filter_fn(|metadata| {
+        metadata.is_span() && (metadata.target() == TARGET || metadata.name() == RUN_SPAN)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_summarize_args() {
+        let body = InvokeBody::Json(json!({
+            "filename": "crash_1.json",
+            "limit": 20,
+            "apiKey": "sk-1234567890",
+            "paths": ["/a", "/b"],
+            "content": "x".repeat(100),
+        }));
+        assert_eq!(
+            summarize_args("get_crash_report", &body),
+            format!("apiKey=***, content={:?}…, filename=\"crash_1.json\", limit=20, paths=[2 items]", "x".repeat(60))
+        );
+        assert_eq!(summarize_args("get_zoom", &InvokeBody::Json(Value::Null)), "");
+        assert_eq!(summarize_args("upload_dropped_files", &InvokeBody::Raw(vec![0; 16])), "<16 bytes>");
+
+        // 键值对参数按键名判断值是否为密钥
+        let pair = |key: &str| InvokeBody::Json(json!({ "key": key, "value": "sk-1234567890" }));
+        assert_eq!(summarize_args("update_entry", &pair("OPENAI_API_KEY")), "key=***, value=***");
+        assert_eq!(summarize_args("update_entry", &pair("HTTP_PROXY")), "key=***, value=\"sk-1234567890\"");
+        assert_eq!(summarize_args("set_env_var", &pair("HTTP_PROXY")), "<hidden>");
+    }
+}
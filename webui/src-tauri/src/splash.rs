@@ -0,0 +1,127 @@
+//! 启动画面
+//!
+//! 主窗口先隐藏创建，启动期间显示一个小的启动窗口，按阶段显示进度
+//! （检测运行环境、连接后端、启动后端）。主窗口页面加载完成、并且后端可以访问
+//! （或等待超时）后关闭启动窗口并显示主窗口，避免用户看到空白页面。
+
+use crate::i18n::{Locale, Text};
+use crate::task_progress::{self, TaskState};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+
+/// 启动窗口标签
+pub const WINDOW_LABEL: &str = "splash";
+
+/// 进度事件，只发送给启动窗口，内容为当前阶段的文字
+pub const PROGRESS_EVENT: &str = "splash-progress";
+
+/// 启动页面
+const SPLASH_PAGE: &str = "splash.html";
+
+/// 主窗口加载完成后最多等待后端的时间（后端可能需要用户在欢迎页手动启动）
+const BACKEND_WAIT: Duration = Duration::from_secs(5);
+
+/// 任务栏进度中的启动任务
+const STARTUP_TASK: &str = "startup";
+
+/// 无论如何都关闭启动窗口的时间
+const MAX_SPLASH: Duration = Duration::from_secs(20);
+
+/// 最近的进度文字（启动页面加载前发送的事件会丢失，页面加载后读取）
+static PROGRESS: Mutex<Option<String>> = Mutex::new(None);
+
+static MAIN_LOADED: AtomicBool = AtomicBool::new(false);
+static FINISHED: AtomicBool = AtomicBool::new(false);
+
+/// 显示启动窗口
+pub fn show(app: &AppHandle) -> tauri::Result<()> {
+    crate::portable::webview(WebviewWindowBuilder::new(app, WINDOW_LABEL, WebviewUrl::App(SPLASH_PAGE.into())))
+        .title("大微")
+        .inner_size(420.0, 240.0)
+        .resizable(false)
+        .decorations(false)
+        .center()
+        .build()?;
+
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move { run(handle).await });
+    Ok(())
+}
+
+/// 更新进度（启动窗口关闭后忽略）
+pub fn progress(app: &AppHandle, text: Text) {
+    if FINISHED.load(Ordering::SeqCst) {
+        return;
+    }
+    let message = text.in_locale(Locale::current()).to_string();
+    *PROGRESS.lock().unwrap_or_else(|e| e.into_inner()) = Some(message.clone());
+    if let Err(e) = app.emit_to(WINDOW_LABEL, PROGRESS_EVENT, message) {
+        tracing::warn!("Failed to emit splash progress: {}", e);
+    }
+}
+
+/// 当前进度文字
+pub fn current_progress() -> Option<String> {
+    PROGRESS.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// 主窗口页面加载完成时调用
+pub fn on_main_loaded() {
+    MAIN_LOADED.store(true, Ordering::SeqCst);
+}
+
+async fn run(app: AppHandle) {
+    let started = Instant::now();
+    task_progress::update(&app, STARTUP_TASK, TaskState::Running(None));
+
+    progress(&app, Text::SplashDetecting);
+    let uv_path = tauri::async_runtime::spawn_blocking(crate::get_uv_path).await;
+    if let Ok(uv_path) = uv_path {
+        tracing::info!("Startup: uv at {:?}", uv_path);
+    }
+    crate::startup_metrics::record(&app, crate::startup_metrics::Phase::PathsResolved);
+
+    progress(&app, Text::SplashCheckingBackend);
+    let client = reqwest::Client::builder().timeout(Duration::from_secs(1)).build().unwrap_or_default();
+    let mut loaded_at = None;
+    loop {
+        if MAIN_LOADED.load(Ordering::SeqCst) {
+            let loaded = *loaded_at.get_or_insert_with(Instant::now);
+            if crate::backend_process::healthy(&client).await {
+                crate::startup_metrics::record(&app, crate::startup_metrics::Phase::BackendReady);
+                progress(&app, Text::SplashReady);
+                break;
+            }
+            if loaded.elapsed() >= BACKEND_WAIT {
+                progress(&app, Text::SplashBackendOffline);
+                break;
+            }
+        }
+        if started.elapsed() >= MAX_SPLASH {
+            tracing::warn!("Main window not ready after {:?}, closing splash", MAX_SPLASH);
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+
+    finish(&app);
+}
+
+/// 关闭启动窗口并显示主窗口（只执行一次）
+pub fn finish(app: &AppHandle) {
+    if FINISHED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    task_progress::update(app, STARTUP_TASK, TaskState::Done);
+    if let Some(main) = app.get_webview_window("main") {
+        match main.show().and_then(|_| main.set_focus()) {
+            Ok(()) => crate::startup_metrics::record(app, crate::startup_metrics::Phase::WindowShown),
+            Err(e) => tracing::warn!("Failed to show main window: {}", e),
+        }
+    }
+    if let Some(splash) = app.get_webview_window(WINDOW_LABEL) {
+        let _ = splash.close();
+    }
+}
@@ -0,0 +1,110 @@
+//! 启动耗时
+//!
+//! 记录从进程启动到各个阶段的耗时（主窗口显示、运行环境路径解析完成、后端进程启动、
+//! 后端可以访问），每个阶段只记录第一次（之后重启后端不计入）。每记录一个阶段都写入日志
+//! 并向前端发送 `startup-metrics` 事件（内容为目前的全部耗时），便于对比不同版本的启动性能。
+
+use serde::{Deserialize, Serialize};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+use tauri::{AppHandle, Emitter};
+
+/// 启动耗时事件
+pub const STARTUP_METRICS_EVENT: &str = "startup-metrics";
+
+/// 进程启动时间
+static LAUNCHED: OnceLock<Instant> = OnceLock::new();
+
+static METRICS: Mutex<StartupMetrics> = Mutex::new(StartupMetrics {
+    window_shown_ms: None,
+    paths_resolved_ms: None,
+    backend_spawned_ms: None,
+    backend_ready_ms: None,
+});
+
+/// 启动阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// 关闭启动画面、显示主窗口
+    WindowShown,
+    /// uv / Python 等运行环境路径解析完成
+    PathsResolved,
+    /// 后端进程已启动
+    BackendSpawned,
+    /// 后端健康检查通过
+    BackendReady,
+}
+
+/// 各阶段距进程启动的毫秒数，未到达的阶段为 None
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StartupMetrics {
+    pub window_shown_ms: Option<u64>,
+    pub paths_resolved_ms: Option<u64>,
+    pub backend_spawned_ms: Option<u64>,
+    pub backend_ready_ms: Option<u64>,
+}
+
+impl StartupMetrics {
+    fn slot(&mut self, phase: Phase) -> &mut Option<u64> {
+        match phase {
+            Phase::WindowShown => &mut self.window_shown_ms,
+            Phase::PathsResolved => &mut self.paths_resolved_ms,
+            Phase::BackendSpawned => &mut self.backend_spawned_ms,
+            Phase::BackendReady => &mut self.backend_ready_ms,
+        }
+    }
+
+    /// 记录阶段耗时，已记录过时返回 false
+    fn set(&mut self, phase: Phase, elapsed_ms: u64) -> bool {
+        let slot = self.slot(phase);
+        if slot.is_some() {
+            return false;
+        }
+        *slot = Some(elapsed_ms);
+        true
+    }
+}
+
+/// 在 main() 开头调用，作为计时起点
+pub fn mark_launch() {
+    LAUNCHED.get_or_init(Instant::now);
+}
+
+/// 记录到达某个阶段（只记录第一次）
+pub fn record(app: &AppHandle, phase: Phase) {
+    let elapsed_ms = LAUNCHED.get_or_init(Instant::now).elapsed().as_millis() as u64;
+    let metrics = {
+        let mut metrics = METRICS.lock().unwrap_or_else(|e| e.into_inner());
+        if !metrics.set(phase, elapsed_ms) {
+            return;
+        }
+        metrics.clone()
+    };
+    tracing::info!("Startup: {:?} after {} ms", phase, elapsed_ms);
+    if let Err(e) = app.emit(STARTUP_METRICS_EVENT, &metrics) {
+        tracing::warn!("Failed to emit startup metrics: {}", e);
+    }
+}
+
+/// 目前记录的启动耗时（前端页面加载前发送的事件会丢失，加载后读取）
+pub fn current() -> StartupMetrics {
+    METRICS.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phase_recorded_once() {
+        let mut metrics = StartupMetrics::default();
+        assert!(metrics.set(Phase::BackendSpawned, 1200));
+        assert!(metrics.set(Phase::BackendReady, 3400));
+        // 之后重启后端不覆盖启动时的耗时
+        assert!(!metrics.set(Phase::BackendSpawned, 60_000));
+        assert_eq!(
+            metrics,
+            StartupMetrics { backend_spawned_ms: Some(1200), backend_ready_ms: Some(3400), ..Default::default() }
+        );
+    }
+}
@@ -0,0 +1,83 @@
+//! 路径规范化时的符号链接处理
+//!
+//! `Path::canonicalize` 会解析符号链接：工作区是指向其他位置的符号链接时（例如用 stow
+//! 管理的 dotfiles 目录），窗口标题、最近的工作区和传给后端的路径都会变成链接的目标。
+//! 设置中的 `symlink_mode` 决定应用内统一使用的规范化方式：
+//! - `resolve`（默认）：解析符号链接，等同 `canonicalize`；
+//! - `preserve`：保留符号链接，只转为绝对路径并按字面去掉 `.` 和 `..`。
+//!
+//! 两种模式都要求路径存在。判断路径是否位于工作区内的沙箱检查始终比较解析后的真实路径
+//! （见 [`crate::workspace_files::resolve`]），保留符号链接不会放宽检查。
+
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// 符号链接处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymlinkMode {
+    /// 解析符号链接
+    #[default]
+    Resolve,
+    /// 保留符号链接
+    Preserve,
+}
+
+/// 按字面去掉 `.` 和 `..`（不访问磁盘）
+fn lexical_normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                // 根目录的上级仍是根目录
+                if !normalized.pop() && !normalized.has_root() {
+                    normalized.push(component);
+                }
+            }
+            _ => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+fn canonicalize_with(path: &Path, mode: SymlinkMode) -> io::Result<PathBuf> {
+    match mode {
+        SymlinkMode::Resolve => path.canonicalize(),
+        SymlinkMode::Preserve => {
+            let path = lexical_normalize(&std::path::absolute(path)?);
+            std::fs::metadata(&path)?;
+            Ok(path)
+        }
+    }
+}
+
+/// 按设置规范化路径，替代 `Path::canonicalize`
+pub fn canonicalize(path: &Path) -> io::Result<PathBuf> {
+    canonicalize_with(path, crate::settings::current().symlink_mode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preserve_symlinks() {
+        assert_eq!(lexical_normalize(Path::new("/a/./b/../c")), PathBuf::from("/a/c"));
+        assert_eq!(lexical_normalize(Path::new("/a/b/../../..")), PathBuf::from("/"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let real = dir.path().canonicalize().unwrap().join("real");
+        std::fs::create_dir(&real).unwrap();
+        assert!(canonicalize_with(&real.join("missing"), SymlinkMode::Preserve).is_err());
+
+        #[cfg(unix)]
+        {
+            let link = real.parent().unwrap().join("link");
+            std::os::unix::fs::symlink(&real, &link).unwrap();
+            assert_eq!(canonicalize_with(&link, SymlinkMode::Resolve).unwrap(), real);
+            assert_eq!(canonicalize_with(&link.join("."), SymlinkMode::Preserve).unwrap(), link);
+        }
+    }
+}
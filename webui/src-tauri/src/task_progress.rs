@@ -0,0 +1,118 @@
+//! 任务栏/程序坞进度
+//!
+//! 依赖同步、下载、后端启动等耗时操作登记为任务，汇总后显示在 Windows 任务栏按钮和
+//! macOS 程序坞图标上（macOS 另外显示百分比角标），窗口不在前台时也能看到进度。
+//! 有任务没有具体进度时显示为不确定进度；任务失败时短暂显示错误状态。
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::window::{ProgressBarState, ProgressBarStatus};
+use tauri::{AppHandle, Manager};
+
+/// 进行中的任务：任务名 -> 进度（0-100，None 表示不确定）
+static TASKS: Mutex<BTreeMap<String, Option<u64>>> = Mutex::new(BTreeMap::new());
+
+/// 任务失败后错误状态的显示时间
+const ERROR_DISPLAY: Duration = Duration::from_secs(3);
+
+/// 任务状态，例如 `{ "state": "running", "progress": 42 }`
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "state", content = "progress", rename_all = "snake_case")]
+pub enum TaskState {
+    /// 进行中，进度为 0-100，省略时表示不确定
+    Running(Option<u64>),
+    /// 已完成
+    Done,
+    /// 已失败
+    Failed,
+}
+
+/// 汇总所有任务：没有任务时为 None；任一任务进度不确定时为 Some(None)；否则为平均进度
+fn aggregate(tasks: &BTreeMap<String, Option<u64>>) -> Option<Option<u64>> {
+    if tasks.is_empty() {
+        return None;
+    }
+    let progress: Option<Vec<u64>> = tasks.values().copied().collect();
+    Some(progress.map(|values| values.iter().sum::<u64>() / values.len() as u64))
+}
+
+/// 把进度应用到所有窗口（macOS/Linux 上进度条是应用级的）
+fn apply(app: &AppHandle, status: ProgressBarStatus, progress: Option<u64>) {
+    for window in app.webview_windows().values() {
+        let state = ProgressBarState { status: Some(status), progress };
+        if let Err(e) = window.set_progress_bar(state) {
+            tracing::warn!("Failed to set taskbar progress: {}", e);
+        }
+        #[cfg(target_os = "macos")]
+        {
+            let label = match status {
+                ProgressBarStatus::None => None,
+                _ => Some(progress.map(|p| format!("{}%", p)).unwrap_or_else(|| "…".to_string())),
+            };
+            let _ = window.set_badge_label(label);
+        }
+    }
+}
+
+/// 按当前任务刷新进度显示
+fn refresh(app: &AppHandle) {
+    let summary = aggregate(&TASKS.lock().unwrap_or_else(|e| e.into_inner()));
+    match summary {
+        None => apply(app, ProgressBarStatus::None, None),
+        Some(None) => apply(app, ProgressBarStatus::Indeterminate, None),
+        Some(Some(progress)) => apply(app, ProgressBarStatus::Normal, Some(progress)),
+    }
+}
+
+/// 更新任务状态
+pub fn update(app: &AppHandle, task: &str, state: TaskState) {
+    {
+        let mut tasks = TASKS.lock().unwrap_or_else(|e| e.into_inner());
+        match state {
+            TaskState::Running(progress) => {
+                tasks.insert(task.to_string(), progress.map(|p| p.min(100)));
+            }
+            TaskState::Done | TaskState::Failed => {
+                tasks.remove(task);
+            }
+        }
+    }
+
+    if state != TaskState::Failed {
+        refresh(app);
+        return;
+    }
+
+    // 失败时先显示错误状态，稍后恢复为其余任务的进度
+    apply(app, ProgressBarStatus::Error, Some(100));
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(ERROR_DISPLAY).await;
+        refresh(&handle);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggregate_and_parse() {
+        let mut tasks = BTreeMap::new();
+        assert_eq!(aggregate(&tasks), None);
+        tasks.insert("download".to_string(), Some(40));
+        tasks.insert("sync".to_string(), Some(80));
+        assert_eq!(aggregate(&tasks), Some(Some(60)));
+        tasks.insert("backend".to_string(), None);
+        assert_eq!(aggregate(&tasks), Some(None));
+
+        let state: TaskState = serde_json::from_str(r#"{"state":"running","progress":42}"#).unwrap();
+        assert_eq!(state, TaskState::Running(Some(42)));
+        let state: TaskState = serde_json::from_str(r#"{"state":"running"}"#).unwrap();
+        assert_eq!(state, TaskState::Running(None));
+        let state: TaskState = serde_json::from_str(r#"{"state":"done"}"#).unwrap();
+        assert_eq!(state, TaskState::Done);
+    }
+}
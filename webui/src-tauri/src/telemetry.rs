@@ -0,0 +1,327 @@
+//! 匿名使用统计
+//!
+//! 默认关闭，只有在设置中开启 telemetry（或设置 DAWEI_TELEMETRY=1）后才收集。收集的内容
+//! 只有计数和耗时，见 [`TelemetryPayload`]：
+//! - 各命令的调用次数（只有命令名，不含参数）
+//! - 每次启动的各阶段耗时（见 [`crate::startup_metrics`]）
+//! - 各类崩溃的次数（不含错误信息和堆栈）
+//! - 随机生成的安装 ID、应用版本、操作系统和架构
+//!
+//! 统计先在内存中累计，定期合并到 DAWEI_HOME/telemetry/pending.json，再整批发送到配置的地址
+//! （DAWEI_TELEMETRY_ENDPOINT 优先于设置中的 telemetry_endpoint，都未设置时使用编译时的同名
+//! 环境变量）；没有地址或发送失败时保留在本地，之后重试。`preview_telemetry` 返回下次发送的
+//! 完整内容，`purge_telemetry` 清除所有待发送的数据；关闭统计时也会清除。
+
+use crate::settings::SETTINGS_CHANGED_EVENT;
+use crate::startup_metrics::StartupMetrics;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Listener};
+
+/// 启动后第一次发送前等待的时间（发送之前运行积压的数据）
+const FIRST_SEND_DELAY: Duration = Duration::from_secs(5 * 60);
+
+/// 发送间隔
+const SEND_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// 发送请求的超时
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 最多保留的启动耗时记录数，超出时丢弃最旧的
+const MAX_STARTUP_SAMPLES: usize = 20;
+
+/// 待发送数据文件
+const PENDING_FILE: &str = "pending.json";
+
+/// 安装 ID 文件
+const INSTALL_ID_FILE: &str = "install_id";
+
+/// 是否收集（启动时从设置读取，设置变化时更新）
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// 本次运行尚未写入文件的统计
+static COLLECTED: Mutex<TelemetryPayload> = Mutex::new(TelemetryPayload {
+    install_id: String::new(),
+    app_version: String::new(),
+    os: String::new(),
+    arch: String::new(),
+    command_usage: BTreeMap::new(),
+    startup: Vec::new(),
+    crashes: BTreeMap::new(),
+});
+
+/// 本次运行的启动耗时是否已记录
+static STARTUP_RECORDED: AtomicBool = AtomicBool::new(false);
+
+/// 同一时间只允许一个任务读写待发送数据文件
+static PENDING_LOCK: Mutex<()> = Mutex::new(());
+
+/// 发送的数据（即 `preview_telemetry` 返回的内容）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelemetryPayload {
+    /// 开启统计时随机生成，清除数据后重新生成，与用户和设备信息无关
+    pub install_id: String,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    /// 命令名 → 调用次数
+    pub command_usage: BTreeMap<String, u64>,
+    /// 每次启动的各阶段耗时（最近 20 次）
+    pub startup: Vec<StartupMetrics>,
+    /// 崩溃类型（panic、native、exception、hang）→ 次数
+    pub crashes: BTreeMap<String, u64>,
+}
+
+impl TelemetryPayload {
+    fn is_empty(&self) -> bool {
+        self.command_usage.is_empty() && self.startup.is_empty() && self.crashes.is_empty()
+    }
+
+    /// 累加另一份统计
+    fn merge(&mut self, other: TelemetryPayload) {
+        for (command, count) in other.command_usage {
+            *self.command_usage.entry(command).or_default() += count;
+        }
+        for (kind, count) in other.crashes {
+            *self.crashes.entry(kind).or_default() += count;
+        }
+        self.startup.extend(other.startup);
+        let excess = self.startup.len().saturating_sub(MAX_STARTUP_SAMPLES);
+        self.startup.drain(..excess);
+    }
+}
+
+/// 是否在运行时开启了统计（DAWEI_TELEMETRY 优先于设置）
+fn enabled_in_settings() -> bool {
+    match std::env::var("DAWEI_TELEMETRY").as_deref().map(str::trim) {
+        Ok("1") | Ok("true") => true,
+        Ok("0") | Ok("false") => false,
+        _ => crate::settings::current().telemetry,
+    }
+}
+
+/// 发送地址
+fn endpoint() -> Option<String> {
+    std::env::var("DAWEI_TELEMETRY_ENDPOINT")
+        .ok()
+        .or_else(|| crate::settings::current().telemetry_endpoint)
+        .or_else(|| option_env!("DAWEI_TELEMETRY_ENDPOINT").map(str::to_string))
+        .filter(|url| !url.trim().is_empty())
+}
+
+fn telemetry_dir() -> PathBuf {
+    crate::get_dawei_home().join("telemetry")
+}
+
+/// 安装 ID，不存在时生成
+fn install_id(dir: &Path) -> String {
+    let path = dir.join(INSTALL_ID_FILE);
+    if let Some(id) = fs::read_to_string(&path).ok().map(|id| id.trim().to_string()).filter(|id| !id.is_empty()) {
+        return id;
+    }
+    let id = uuid::Uuid::new_v4().to_string();
+    if let Err(e) = fs::create_dir_all(dir).and_then(|_| fs::write(&path, &id)) {
+        tracing::warn!("Failed to save telemetry install id: {}", e);
+    }
+    id
+}
+
+fn read_pending(dir: &Path) -> TelemetryPayload {
+    fs::read_to_string(dir.join(PENDING_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// 待发送的数据：文件中的数据加上内存中的统计，带上标识信息
+fn pending_with(dir: &Path, collected: TelemetryPayload) -> TelemetryPayload {
+    let mut payload = read_pending(dir);
+    payload.merge(collected);
+    if payload.is_empty() {
+        return payload;
+    }
+    payload.install_id = install_id(dir);
+    payload.app_version = env!("CARGO_PKG_VERSION").to_string();
+    payload.os = std::env::consts::OS.to_string();
+    payload.arch = std::env::consts::ARCH.to_string();
+    payload
+}
+
+/// 本次启动的耗时（后端就绪后才记录，只记录一次）
+fn startup_sample() -> Option<StartupMetrics> {
+    let startup = crate::startup_metrics::current();
+    (startup.backend_ready_ms.is_some() && !STARTUP_RECORDED.load(Ordering::SeqCst)).then_some(startup)
+}
+
+/// 取出内存中的统计
+fn take_collected() -> TelemetryPayload {
+    let mut collected = std::mem::take(&mut *COLLECTED.lock().unwrap_or_else(|e| e.into_inner()));
+    if let Some(startup) = startup_sample() {
+        STARTUP_RECORDED.store(true, Ordering::SeqCst);
+        collected.startup.push(startup);
+    }
+    collected
+}
+
+/// 把内存中的统计合并到待发送数据文件
+fn persist_to(dir: &Path) -> Option<TelemetryPayload> {
+    let _lock = PENDING_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let payload = pending_with(dir, take_collected());
+    if payload.is_empty() {
+        return None;
+    }
+    let result = fs::create_dir_all(dir)
+        .and_then(|_| fs::write(dir.join(PENDING_FILE), serde_json::to_string_pretty(&payload).unwrap_or_default()));
+    if let Err(e) = result {
+        tracing::warn!("Failed to save telemetry: {}", e);
+    }
+    Some(payload)
+}
+
+/// 记录一次命令调用（未开启时不做任何事）
+pub fn record_command(command: &str) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let mut collected = COLLECTED.lock().unwrap_or_else(|e| e.into_inner());
+    match collected.command_usage.get_mut(command) {
+        Some(count) => *count += 1,
+        None => {
+            collected.command_usage.insert(command.to_string(), 1);
+        }
+    }
+}
+
+/// 记录一次崩溃并立即写入文件（进程可能即将退出）；在崩溃监控进程中也可以调用
+pub fn record_crash(kind: crate::crash_handler::CrashKind) {
+    if !enabled_in_settings() {
+        return;
+    }
+    let kind = format!("{:?}", kind).to_lowercase();
+    *COLLECTED.lock().unwrap_or_else(|e| e.into_inner()).crashes.entry(kind).or_default() += 1;
+    persist_to(&telemetry_dir());
+}
+
+/// 下次发送的完整内容，没有待发送的数据时返回 None
+pub fn preview() -> Option<TelemetryPayload> {
+    let mut collected = COLLECTED.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    collected.startup.extend(startup_sample());
+    let dir = telemetry_dir();
+    let _lock = PENDING_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let payload = pending_with(&dir, collected);
+    (!payload.is_empty()).then_some(payload)
+}
+
+/// 清除所有待发送的数据和安装 ID
+pub fn purge() -> Result<(), String> {
+    let _lock = PENDING_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    *COLLECTED.lock().unwrap_or_else(|e| e.into_inner()) = TelemetryPayload::default();
+    match fs::remove_dir_all(telemetry_dir()) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(format!("清除统计数据失败: {}", e)),
+    }
+    tracing::info!("Telemetry data purged");
+    Ok(())
+}
+
+/// 保存统计并发送待发送的数据，成功后删除本地数据
+async fn flush() {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let Some(payload) = tauri::async_runtime::spawn_blocking(|| persist_to(&telemetry_dir())).await.ok().flatten() else {
+        return;
+    };
+    let Some(url) = endpoint() else {
+        tracing::debug!("Telemetry endpoint not configured, keeping data locally");
+        return;
+    };
+    let client = reqwest::Client::builder().timeout(SEND_TIMEOUT).build().unwrap_or_default();
+    match client.post(&url).json(&payload).send().await {
+        Ok(response) if response.status().is_success() => {
+            // 发送期间新增的统计还在内存中，只删除已发送的文件
+            let _lock = PENDING_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            let _ = fs::remove_file(telemetry_dir().join(PENDING_FILE));
+            tracing::info!("Telemetry sent");
+        }
+        Ok(response) => tracing::warn!("Telemetry endpoint returned {}", response.status()),
+        Err(e) => tracing::warn!("Failed to send telemetry: {}", e),
+    }
+}
+
+/// 退出前保存本次运行的统计
+pub fn save() {
+    if ENABLED.load(Ordering::Relaxed) {
+        persist_to(&telemetry_dir());
+    }
+}
+
+/// 读取设置，设置变化时开启或关闭（关闭时清除数据），并启动后台发送任务
+pub fn init(app: &AppHandle) {
+    ENABLED.store(enabled_in_settings(), Ordering::Relaxed);
+
+    app.listen_any(SETTINGS_CHANGED_EVENT, |event| {
+        let changed = serde_json::from_str::<serde_json::Value>(event.payload())
+            .is_ok_and(|change| change["changed"].get("telemetry").is_some());
+        if !changed {
+            return;
+        }
+        let enabled = enabled_in_settings();
+        if ENABLED.swap(enabled, Ordering::Relaxed) && !enabled {
+            if let Err(e) = purge() {
+                tracing::warn!("{}", e);
+            }
+        }
+        tracing::info!("Telemetry {}", if enabled { "enabled" } else { "disabled" });
+    });
+
+    tauri::async_runtime::spawn(async {
+        tokio::time::sleep(FIRST_SEND_DELAY).await;
+        loop {
+            flush().await;
+            tokio::time::sleep(SEND_INTERVAL).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_merges_counts() {
+        let dir = tempfile::tempdir().unwrap();
+        let collected = |command: &str, startup_ms: Option<u64>| TelemetryPayload {
+            command_usage: BTreeMap::from([(command.to_string(), 2)]),
+            startup: startup_ms
+                .map(|ms| StartupMetrics { backend_ready_ms: Some(ms), ..Default::default() })
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        };
+
+        let first = pending_with(dir.path(), collected("get_settings", Some(1500)));
+        fs::write(dir.path().join(PENDING_FILE), serde_json::to_string(&first).unwrap()).unwrap();
+        let second = pending_with(dir.path(), collected("get_settings", None));
+        assert_eq!(second.install_id, first.install_id);
+        assert_eq!(second.command_usage["get_settings"], 4);
+        assert_eq!(second.startup.len(), 1);
+        assert_eq!(second.app_version, env!("CARGO_PKG_VERSION"));
+
+        let mut payload = TelemetryPayload::default();
+        assert!(payload.is_empty());
+        for ms in 0..25 {
+            payload.merge(collected("start_backend", Some(ms)));
+        }
+        assert_eq!(payload.startup.len(), MAX_STARTUP_SAMPLES);
+        assert_eq!(payload.startup[0].backend_ready_ms, Some(5));
+        assert_eq!(payload.command_usage["start_backend"], 50);
+    }
+}
@@ -0,0 +1,108 @@
+//! 主题模块
+//!
+//! 主题偏好（light / dark / system）保存在设置中，并应用到所有窗口。
+//! 偏好变化或在 system 模式下系统主题切换时，向所有窗口发送 `theme-changed` 事件。
+
+use crate::settings::{self, SETTINGS_CHANGED_EVENT};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Listener, Manager, Theme};
+
+/// 主题变化事件，内容为 [`ThemeInfo`]
+pub const THEME_CHANGED_EVENT: &str = "theme-changed";
+
+/// 最近一次通知前端的实际主题，避免多个窗口重复通知
+static LAST_EFFECTIVE: Mutex<Option<Theme>> = Mutex::new(None);
+
+/// 主题偏好
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemePreference {
+    Light,
+    Dark,
+    /// 跟随系统
+    #[default]
+    System,
+}
+
+impl ThemePreference {
+    fn to_tauri(self) -> Option<Theme> {
+        match self {
+            ThemePreference::Light => Some(Theme::Light),
+            ThemePreference::Dark => Some(Theme::Dark),
+            ThemePreference::System => None,
+        }
+    }
+}
+
+/// 主题信息
+#[derive(Debug, Clone, Serialize)]
+pub struct ThemeInfo {
+    /// 用户选择的偏好
+    pub preference: ThemePreference,
+    /// 实际使用的主题：`light` 或 `dark`
+    pub effective: Theme,
+}
+
+/// 当前实际主题（跟随系统时读取主窗口的主题）
+fn effective_theme(app: &AppHandle, preference: ThemePreference) -> Theme {
+    preference
+        .to_tauri()
+        .or_else(|| app.webview_windows().values().next().and_then(|w| w.theme().ok()))
+        .unwrap_or(Theme::Light)
+}
+
+/// 当前主题信息
+pub fn current(app: &AppHandle) -> ThemeInfo {
+    let preference = settings::current().theme;
+    ThemeInfo { preference, effective: effective_theme(app, preference) }
+}
+
+/// 应用设置中的主题并通知前端
+fn apply(app: &AppHandle) {
+    app.set_theme(settings::current().theme.to_tauri());
+    notify(app, current(app));
+}
+
+fn notify(app: &AppHandle, info: ThemeInfo) {
+    *LAST_EFFECTIVE.lock().unwrap_or_else(|e| e.into_inner()) = Some(info.effective);
+    if let Err(e) = app.emit(THEME_CHANGED_EVENT, &info) {
+        tracing::warn!("Failed to emit theme change: {}", e);
+    }
+}
+
+/// 修改主题偏好
+pub fn set_theme(app: &AppHandle, preference: ThemePreference) -> Result<ThemeInfo, String> {
+    // 设置变更事件会触发 apply
+    settings::update(app, serde_json::json!({ "theme": preference }))?;
+    Ok(current(app))
+}
+
+/// 启动时应用主题，并在设置中的主题变化时重新应用
+pub fn init(app: &AppHandle) {
+    app.set_theme(settings::current().theme.to_tauri());
+
+    let handle = app.clone();
+    app.listen_any(SETTINGS_CHANGED_EVENT, move |event| {
+        let theme_changed = serde_json::from_str::<serde_json::Value>(event.payload())
+            .map(|change| change["changed"].get("theme").is_some())
+            .unwrap_or(false);
+        if theme_changed {
+            apply(&handle);
+        }
+    });
+}
+
+/// 窗口收到系统主题变化时调用（只在跟随系统时通知前端）
+pub fn on_system_theme_changed(app: &AppHandle, theme: Theme) {
+    let preference = settings::current().theme;
+    if preference != ThemePreference::System {
+        return;
+    }
+
+    let changed = *LAST_EFFECTIVE.lock().unwrap_or_else(|e| e.into_inner()) != Some(theme);
+    if changed {
+        crate::breadcrumbs::record("window", format!("System theme changed to {}", theme));
+        notify(app, ThemeInfo { preference, effective: theme });
+    }
+}
@@ -0,0 +1,84 @@
+//! 自定义标题栏
+//!
+//! 开启 `custom_titlebar` 设置后，主窗口和工作区窗口去掉系统标题栏，由前端绘制标题栏并
+//! 通过窗口命令实现最小化、最大化、关闭和拖动。macOS 上保留系统的红绿灯按钮，
+//! 标题栏改为覆盖在页面上方，前端需要按 `TitlebarInfo` 留出按钮的位置。
+
+use crate::settings::{self, SETTINGS_CHANGED_EVENT};
+use serde::Serialize;
+use tauri::{AppHandle, Listener, Manager, WebviewWindow};
+
+/// macOS 红绿灯按钮占用的宽度和标题栏高度（逻辑像素）
+#[cfg(target_os = "macos")]
+const TRAFFIC_LIGHTS_INSET: (f64, f64) = (78.0, 28.0);
+
+/// 前端绘制标题栏需要的信息
+#[derive(Debug, Clone, Serialize)]
+pub struct TitlebarInfo {
+    /// 是否使用自定义标题栏
+    pub custom: bool,
+    /// 窗口控制按钮由系统绘制（macOS 红绿灯），前端不需要绘制
+    pub native_controls: bool,
+    /// 标题栏左侧需要留出的宽度（逻辑像素）
+    pub inset_left: f64,
+    /// 系统标题栏覆盖的高度（逻辑像素）
+    pub inset_top: f64,
+}
+
+/// 当前标题栏信息
+pub fn info() -> TitlebarInfo {
+    let custom = settings::current().custom_titlebar;
+    #[cfg(target_os = "macos")]
+    let (inset_left, inset_top) = if custom { TRAFFIC_LIGHTS_INSET } else { (0.0, 0.0) };
+    #[cfg(not(target_os = "macos"))]
+    let (inset_left, inset_top) = (0.0, 0.0);
+    TitlebarInfo { custom, native_controls: cfg!(target_os = "macos"), inset_left, inset_top }
+}
+
+/// 按设置应用标题栏样式
+pub fn apply(window: &WebviewWindow) {
+    let custom = settings::current().custom_titlebar;
+    // macOS 去掉系统标题栏会同时去掉红绿灯按钮，改用覆盖式标题栏
+    #[cfg(target_os = "macos")]
+    let result = window.set_decorations(true).and_then(|_| {
+        window.set_title_bar_style(if custom { tauri::TitleBarStyle::Overlay } else { tauri::TitleBarStyle::Visible })
+    });
+    #[cfg(not(target_os = "macos"))]
+    let result = window.set_decorations(!custom);
+    if let Err(e) = result {
+        tracing::warn!("Failed to set title bar for {}: {}", window.label(), e);
+    }
+}
+
+fn apply_all(app: &AppHandle) {
+    for (label, window) in app.webview_windows() {
+        if label == "main" || crate::workspace_window::get(&label).is_some() {
+            apply(&window);
+        }
+    }
+}
+
+/// 启动时应用设置，并在设置变化时重新应用
+pub fn init(app: &AppHandle) {
+    // 系统标题栏是默认样式，只有开启自定义标题栏时需要修改
+    if settings::current().custom_titlebar {
+        apply_all(app);
+    }
+
+    let handle = app.clone();
+    app.listen_any(SETTINGS_CHANGED_EVENT, move |event| {
+        let changed = serde_json::from_str::<serde_json::Value>(event.payload())
+            .map(|change| change["changed"].get("custom_titlebar").is_some())
+            .unwrap_or(false);
+        if changed && !crate::kiosk::is_enabled() {
+            apply_all(&handle);
+        }
+    });
+}
+
+/// 开启或关闭自定义标题栏
+pub fn set(app: &AppHandle, custom: bool) -> Result<TitlebarInfo, String> {
+    // 设置变更事件会触发 apply
+    settings::update(app, serde_json::json!({ "custom_titlebar": custom }))?;
+    Ok(info())
+}
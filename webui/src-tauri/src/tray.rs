@@ -0,0 +1,126 @@
+//! 系统托盘
+//!
+//! 托盘图标左键单击显示/隐藏主窗口，右键菜单提供常用操作。
+//! 开启 `close_to_tray` 设置后，关闭主窗口只是隐藏到托盘（后端继续运行），
+//! 需要从托盘菜单退出。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use tauri::menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Manager, Window, WindowEvent, Wry};
+
+/// 托盘图标 ID
+const TRAY_ID: &str = "main";
+
+// 菜单项 ID
+const MENU_TOGGLE_WINDOW: &str = "tray-toggle-window";
+const MENU_ALWAYS_ON_TOP: &str = "tray-always-on-top";
+const MENU_QUIT: &str = "tray-quit";
+
+/// 置顶菜单项（设置变化时更新勾选状态）
+static ALWAYS_ON_TOP_ITEM: OnceLock<CheckMenuItem<Wry>> = OnceLock::new();
+
+/// 已从托盘菜单退出，不再拦截关闭
+static QUITTING: AtomicBool = AtomicBool::new(false);
+
+/// 显示或隐藏主窗口，返回主窗口是否变为可见
+pub fn toggle_main_window(app: &AppHandle) -> bool {
+    let Some(window) = app.get_webview_window("main") else {
+        return false;
+    };
+    let visible = window.is_visible().unwrap_or(false) && !window.is_minimized().unwrap_or(false);
+    let result = if visible {
+        window.hide()
+    } else {
+        window.unminimize().and_then(|_| window.show()).and_then(|_| window.set_focus())
+    };
+    if let Err(e) = result {
+        tracing::warn!("Failed to toggle main window: {}", e);
+    }
+    !visible
+}
+
+/// 更新置顶菜单项的勾选状态
+pub fn set_always_on_top_checked(checked: bool) {
+    if let Some(item) = ALWAYS_ON_TOP_ITEM.get() {
+        let _ = item.set_checked(checked);
+    }
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+    match event.id().as_ref() {
+        MENU_TOGGLE_WINDOW => {
+            toggle_main_window(app);
+        }
+        MENU_ALWAYS_ON_TOP => {
+            let enabled = !crate::settings::current().always_on_top;
+            if let Err(e) = crate::always_on_top::set(app, enabled) {
+                tracing::warn!("{}", e);
+            }
+        }
+        MENU_QUIT => {
+            QUITTING.store(true, Ordering::SeqCst);
+            app.exit(0);
+        }
+        _ => {}
+    }
+}
+
+/// 开启 `close_to_tray` 时，把关闭主窗口改为隐藏到托盘，返回是否已隐藏
+pub fn on_window_event(window: &Window, event: &WindowEvent) -> bool {
+    let WindowEvent::CloseRequested { api, .. } = event else {
+        return false;
+    };
+    if window.label() != "main" || QUITTING.load(Ordering::SeqCst) || !crate::settings::current().close_to_tray {
+        return false;
+    }
+    // 没有托盘图标时无法再显示窗口，正常关闭
+    if window.app_handle().tray_by_id(TRAY_ID).is_none() {
+        return false;
+    }
+    api.prevent_close();
+    if let Err(e) = window.hide() {
+        tracing::warn!("Failed to hide main window: {}", e);
+    }
+    true
+}
+
+/// 创建托盘图标
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    let always_on_top = CheckMenuItem::with_id(
+        app,
+        MENU_ALWAYS_ON_TOP,
+        "窗口置顶",
+        true,
+        crate::settings::current().always_on_top,
+        None::<&str>,
+    )?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &MenuItem::with_id(app, MENU_TOGGLE_WINDOW, "显示/隐藏主窗口", true, None::<&str>)?,
+            &PredefinedMenuItem::separator(app)?,
+            &always_on_top,
+            &PredefinedMenuItem::separator(app)?,
+            &MenuItem::with_id(app, MENU_QUIT, "退出", true, None::<&str>)?,
+        ],
+    )?;
+    let _ = ALWAYS_ON_TOP_ITEM.set(always_on_top);
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("大微")
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(on_menu_event)
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click { button: MouseButton::Left, button_state: MouseButtonState::Up, .. } = event {
+                toggle_main_window(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+    Ok(())
+}
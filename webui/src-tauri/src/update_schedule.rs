@@ -0,0 +1,212 @@
+//! 定期检查更新
+//!
+//! 按设置中的 `update_check_interval`（daily / weekly / never）在后台检查应用更新和后端更新，
+//! 上次检查的时间和结果保存在 DAWEI_HOME/update-check.json，重启应用不会提前重复检查。
+//! 发现新版本时只发送 `update-available` 事件（内容为 [`UpdateAvailable`]），由前端显示不打扰的提示；
+//! 同一版本只通知一次。后台每小时看一次是否到期，修改设置后无需重启。
+
+use crate::atomic_file::write_json_atomic;
+use crate::backend_update::BackendUpdateInfo;
+use crate::updater::UpdateInfo;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// 发现新版本事件，内容为 [`UpdateAvailable`]
+pub const UPDATE_AVAILABLE_EVENT: &str = "update-available";
+
+/// 记录文件名
+const RECORD_FILE: &str = "update-check.json";
+
+/// 启动后首次检查前的等待时间，避免和启动争抢网络
+const FIRST_CHECK_DELAY: Duration = Duration::from_secs(60);
+
+/// 判断是否到期的间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// 检查频率
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateCheckInterval {
+    #[default]
+    Daily,
+    Weekly,
+    Never,
+}
+
+impl UpdateCheckInterval {
+    fn period(self) -> Option<chrono::Duration> {
+        match self {
+            UpdateCheckInterval::Daily => Some(chrono::Duration::days(1)),
+            UpdateCheckInterval::Weekly => Some(chrono::Duration::weeks(1)),
+            UpdateCheckInterval::Never => None,
+        }
+    }
+}
+
+/// 新版本通知
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateAvailable {
+    /// 应用的新版本，没有时为 None
+    pub app: Option<UpdateInfo>,
+    /// 后端的新版本，没有时为 None
+    pub backend: Option<BackendUpdateInfo>,
+}
+
+/// 检查记录
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UpdateCheckRecord {
+    /// 上次检查的时间（RFC 3339）
+    pub last_checked: Option<String>,
+    /// 上次检查到的应用新版本
+    pub app_version: Option<String>,
+    /// 上次检查到的后端新版本
+    pub backend_version: Option<String>,
+    /// 上次检查失败的原因
+    pub errors: Vec<String>,
+    /// 已通知过的应用和后端版本
+    pub notified_app: Option<String>,
+    pub notified_backend: Option<String>,
+}
+
+fn record_file() -> PathBuf {
+    crate::get_dawei_home().join(RECORD_FILE)
+}
+
+fn read_record(path: &Path) -> UpdateCheckRecord {
+    let Ok(content) = fs::read_to_string(path) else {
+        return UpdateCheckRecord::default();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        tracing::warn!("Invalid {}: {}", RECORD_FILE, e);
+        UpdateCheckRecord::default()
+    })
+}
+
+fn write_record(path: &Path, record: &UpdateCheckRecord) -> Result<(), String> {
+    write_json_atomic(path, record).map_err(|e| format!("保存更新检查记录失败: {}", e))
+}
+
+/// 是否到了检查时间（从未检查过或记录无法解析时立即检查）
+fn is_due(record: &UpdateCheckRecord, interval: UpdateCheckInterval, now: chrono::DateTime<chrono::FixedOffset>) -> bool {
+    let Some(period) = interval.period() else {
+        return false;
+    };
+    match record.last_checked.as_deref().and_then(|time| chrono::DateTime::parse_from_rfc3339(time).ok()) {
+        Some(last) => now - last >= period || last > now,
+        None => true,
+    }
+}
+
+/// 记录检查结果，返回需要通知的新版本（已通知过的版本不再通知）
+fn record_result(record: &mut UpdateCheckRecord, app: Option<UpdateInfo>, backend: Option<BackendUpdateInfo>, errors: Vec<String>) -> Option<UpdateAvailable> {
+    record.last_checked = Some(chrono::Local::now().to_rfc3339());
+    record.app_version = app.as_ref().map(|info| info.version.clone());
+    record.backend_version = backend.as_ref().map(|info| info.latest.clone());
+    record.errors = errors;
+
+    let app = app.filter(|info| record.notified_app.as_deref() != Some(info.version.as_str()));
+    let backend = backend.filter(|info| record.notified_backend.as_deref() != Some(info.latest.as_str()));
+    if app.is_none() && backend.is_none() {
+        return None;
+    }
+    if let Some(info) = &app {
+        record.notified_app = Some(info.version.clone());
+    }
+    if let Some(info) = &backend {
+        record.notified_backend = Some(info.latest.clone());
+    }
+    Some(UpdateAvailable { app, backend })
+}
+
+/// 检查应用和后端更新，记录结果并在发现新版本时发送事件
+async fn check_now(app: &AppHandle) {
+    let mut errors = Vec::new();
+    let app_update = crate::updater::check(app).await.unwrap_or_else(|e| {
+        errors.push(e);
+        None
+    });
+    let backend_update = match crate::backend_update::check().await {
+        Ok(info) => Some(info).filter(|info| info.update_available),
+        Err(e) => {
+            errors.push(e);
+            None
+        }
+    };
+    for error in &errors {
+        tracing::info!("Scheduled update check: {}", error);
+    }
+
+    let path = record_file();
+    let mut record = read_record(&path);
+    let available = record_result(&mut record, app_update, backend_update, errors);
+    if let Err(e) = write_record(&path, &record) {
+        tracing::warn!("{}", e);
+    }
+    if let Some(available) = available {
+        tracing::info!("Update available: app {:?}, backend {:?}", record.app_version, record.backend_version);
+        if let Err(e) = app.emit(UPDATE_AVAILABLE_EVENT, &available) {
+            tracing::warn!("Failed to emit update-available: {}", e);
+        }
+    }
+}
+
+/// 启动后台检查
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(FIRST_CHECK_DELAY).await;
+        loop {
+            let interval = crate::settings::current().update_check_interval;
+            if is_due(&read_record(&record_file()), interval, chrono::Local::now().fixed_offset()) {
+                check_now(&app).await;
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+}
+
+/// 上次检查的记录
+pub fn status() -> UpdateCheckRecord {
+    read_record(&record_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_due_and_notify_once() {
+        let now = chrono::DateTime::parse_from_rfc3339("2026-10-16T12:00:00+08:00").unwrap();
+        let checked = |time: &str| UpdateCheckRecord { last_checked: Some(time.to_string()), ..Default::default() };
+        assert!(is_due(&UpdateCheckRecord::default(), UpdateCheckInterval::Daily, now));
+        assert!(!is_due(&UpdateCheckRecord::default(), UpdateCheckInterval::Never, now));
+        assert!(!is_due(&checked("2026-10-16T00:00:00+08:00"), UpdateCheckInterval::Daily, now));
+        assert!(is_due(&checked("2026-10-15T11:00:00+08:00"), UpdateCheckInterval::Daily, now));
+        assert!(!is_due(&checked("2026-10-12T11:00:00+08:00"), UpdateCheckInterval::Weekly, now));
+        // 系统时间被调回过
+        assert!(is_due(&checked("2027-01-01T00:00:00+08:00"), UpdateCheckInterval::Weekly, now));
+
+        let backend = || BackendUpdateInfo {
+            current: Some("0.1.0".to_string()),
+            latest: "0.2.0".to_string(),
+            update_available: true,
+            changelog_url: String::new(),
+            index: "https://pypi.org/simple".to_string(),
+            pin: None,
+        };
+        let mut record = UpdateCheckRecord::default();
+        let available = record_result(&mut record, None, Some(backend()), Vec::new()).unwrap();
+        assert_eq!(available.backend.unwrap().latest, "0.2.0");
+        assert!(record_result(&mut record, None, Some(backend()), Vec::new()).is_none());
+        assert_eq!(record.backend_version.as_deref(), Some("0.2.0"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(RECORD_FILE);
+        write_record(&path, &record).unwrap();
+        assert_eq!(read_record(&path), record);
+    }
+}
@@ -0,0 +1,250 @@
+//! 应用自动更新
+//!
+//! 基于 tauri-plugin-updater，分三步由前端驱动：
+//! 1. `check_for_updates`：按设置中的更新通道（`update_channel`：stable / beta）请求对应的
+//!    更新清单（GitHub Release 中的 latest.json，`DAWEI_UPDATE_ENDPOINT` 覆盖），返回新版本信息；
+//! 2. `download_update`：下载并校验签名，期间发送 `update-download-progress` 事件并在任务栏显示进度。
+//!    下载可以暂停（`pause_download`，ID 为 [`DOWNLOAD_ID`]），再次调用时从断点继续（见 [`crate::download`]）；
+//! 3. `install_and_restart`：安装下载好的更新后重启（退出时照常停止后端，重启不需要确认）。
+//!
+//! 签名公钥在构建时通过环境变量 `DAWEI_UPDATER_PUBKEY` 注入，未注入的构建不能更新。
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+/// 下载进度事件，内容为 [`DownloadProgress`]
+pub const UPDATE_DOWNLOAD_PROGRESS_EVENT: &str = "update-download-progress";
+
+/// 任务栏进度中的任务名
+const DOWNLOAD_TASK: &str = "update-download";
+
+/// 更新包在 [`crate::download`] 中的下载 ID
+pub const DOWNLOAD_ID: &str = "app-update";
+
+/// 更新签名公钥（构建时注入）
+pub const PUBKEY: Option<&str> = option_env!("DAWEI_UPDATER_PUBKEY");
+
+/// 稳定版的更新清单
+const STABLE_ENDPOINT: &str = "https://github.com/geluzhiwei1/davybot/releases/latest/download/latest.json";
+
+/// 测试版的更新清单（固定标签的 Release，每次发布测试版时更新）
+const BETA_ENDPOINT: &str = "https://github.com/geluzhiwei1/davybot/releases/download/updater-beta/latest.json";
+
+/// 最近一次检查到的更新
+static AVAILABLE: Mutex<Option<Update>> = Mutex::new(None);
+
+/// 已下载、等待安装的更新
+static DOWNLOADED: Mutex<Option<(Update, Vec<u8>)>> = Mutex::new(None);
+
+/// 更新通道
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    Beta,
+}
+
+impl UpdateChannel {
+    /// 通道的更新清单地址
+    fn endpoint(self) -> &'static str {
+        match self {
+            UpdateChannel::Stable => STABLE_ENDPOINT,
+            UpdateChannel::Beta => BETA_ENDPOINT,
+        }
+    }
+}
+
+/// 新版本信息
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateInfo {
+    pub current_version: String,
+    pub version: String,
+    /// 发布时间（更新清单中的 pub_date，RFC 3339）
+    pub date: Option<String>,
+    /// 更新说明
+    pub notes: Option<String>,
+    pub channel: UpdateChannel,
+    /// 新版本要求的最低后端版本（更新清单中的 min_backend_version）
+    pub min_backend_version: Option<String>,
+}
+
+/// 下载进度
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadProgress {
+    pub version: String,
+    pub downloaded: u64,
+    /// 总大小，服务器未提供时为 None
+    pub total: Option<u64>,
+}
+
+fn info(update: &Update, channel: UpdateChannel) -> UpdateInfo {
+    UpdateInfo {
+        current_version: update.current_version.clone(),
+        version: update.version.clone(),
+        date: update.raw_json.get("pub_date").and_then(|date| date.as_str()).map(str::to_string),
+        notes: update.body.clone(),
+        channel,
+        min_backend_version: update.raw_json.get("min_backend_version").and_then(|v| v.as_str()).map(str::to_string),
+    }
+}
+
+/// 更新清单地址：`DAWEI_UPDATE_ENDPOINT` 优先，否则按通道
+fn endpoint(channel: UpdateChannel) -> Result<tauri::Url, String> {
+    let endpoint = std::env::var("DAWEI_UPDATE_ENDPOINT").ok().filter(|url| !url.trim().is_empty());
+    let endpoint = endpoint.as_deref().unwrap_or(channel.endpoint());
+    tauri::Url::parse(endpoint).map_err(|e| format!("无效的更新地址 {}: {}", endpoint, e))
+}
+
+/// 检查更新，没有新版本时返回 None
+pub async fn check(app: &AppHandle) -> Result<Option<UpdateInfo>, String> {
+    if PUBKEY.is_none() {
+        return Err("此版本未配置更新签名公钥，不支持自动更新".to_string());
+    }
+    let channel = crate::settings::current().update_channel;
+    let updater = app
+        .updater_builder()
+        .endpoints(vec![endpoint(channel)?])
+        .and_then(|builder| builder.build())
+        .map_err(|e| format!("无法初始化更新: {}", e))?;
+    let update = updater.check().await.map_err(|e| format!("检查更新失败: {}", e))?;
+    let result = update.as_ref().map(|update| info(update, channel));
+    match &result {
+        Some(info) => tracing::info!("Update available: {} -> {} ({:?})", info.current_version, info.version, channel),
+        None => tracing::info!("No update available ({:?})", channel),
+    }
+    *AVAILABLE.lock().unwrap_or_else(|e| e.into_inner()) = update;
+    Ok(result)
+}
+
+/// 校验 minisign 签名（公钥和签名都是 base64 编码的文本）
+fn verify_signature(data: &[u8], signature: &str, pubkey: &str) -> Result<(), String> {
+    use base64::Engine;
+    let decode = |value: &str| base64::engine::general_purpose::STANDARD.decode(value).ok().and_then(|bytes| String::from_utf8(bytes).ok());
+    let public_key = decode(pubkey)
+        .and_then(|key| minisign_verify::PublicKey::decode(&key).ok())
+        .ok_or_else(|| "无效的更新签名公钥".to_string())?;
+    let signature = decode(signature)
+        .and_then(|signature| minisign_verify::Signature::decode(&signature).ok())
+        .ok_or_else(|| "无效的更新签名".to_string())?;
+    public_key.verify(data, &signature, true).map_err(|e| format!("更新包签名校验失败: {}", e))
+}
+
+/// 下载最近一次检查到的更新并校验签名，已下载的部分继续下载
+pub async fn download(app: &AppHandle) -> Result<UpdateInfo, String> {
+    let update = AVAILABLE.lock().unwrap_or_else(|e| e.into_inner()).clone().ok_or_else(|| "没有可下载的更新，请先检查更新".to_string())?;
+    let pubkey = PUBKEY.ok_or_else(|| "此版本未配置更新签名公钥，不支持自动更新".to_string())?;
+    let channel = crate::settings::current().update_channel;
+    let version = update.version.clone();
+    let file_name = update
+        .download_url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|name| !name.is_empty())
+        .map_or_else(|| format!("davybot-{}.update", version), str::to_string);
+
+    crate::task_progress::update(app, DOWNLOAD_TASK, crate::task_progress::TaskState::Running(Some(0)));
+    let result = crate::download::run(app, DOWNLOAD_ID, update.download_url.as_str(), &file_name, update.headers.clone(), |downloaded, total| {
+        let progress = DownloadProgress { version: version.clone(), downloaded, total };
+        let _ = app.emit(UPDATE_DOWNLOAD_PROGRESS_EVENT, &progress);
+        let percent = total.filter(|total| *total > 0).map(|total| downloaded * 100 / total);
+        crate::task_progress::update(app, DOWNLOAD_TASK, crate::task_progress::TaskState::Running(percent));
+    })
+    .await;
+
+    let state = match result {
+        Ok(state) if state.status == crate::download::DownloadStatus::Paused => {
+            crate::task_progress::update(app, DOWNLOAD_TASK, crate::task_progress::TaskState::Done);
+            return Err("下载已暂停".to_string());
+        }
+        Ok(state) => state,
+        Err(e) => {
+            crate::task_progress::update(app, DOWNLOAD_TASK, crate::task_progress::TaskState::Failed);
+            return Err(format!("下载更新失败: {}", e));
+        }
+    };
+    let bytes = std::fs::read(crate::download::downloads_dir().join(&state.file_name)).map_err(|e| format!("无法读取下载的更新: {}", e));
+    let verified = bytes.and_then(|bytes| verify_signature(&bytes, &update.signature, pubkey).map(|_| bytes));
+    match verified {
+        Ok(bytes) => {
+            crate::task_progress::update(app, DOWNLOAD_TASK, crate::task_progress::TaskState::Done);
+            tracing::info!("Downloaded update {} ({} bytes)", update.version, bytes.len());
+            let info = info(&update, channel);
+            *DOWNLOADED.lock().unwrap_or_else(|e| e.into_inner()) = Some((update, bytes));
+            Ok(info)
+        }
+        Err(e) => {
+            crate::task_progress::update(app, DOWNLOAD_TASK, crate::task_progress::TaskState::Failed);
+            // 签名不对的文件不能留着续传
+            if let Err(e) = crate::download::discard(DOWNLOAD_ID) {
+                tracing::warn!("{}", e);
+            }
+            Err(e)
+        }
+    }
+}
+
+/// 重新下载并安装当前通道的最新版本（版本相同也安装），用于修复损坏的安装，成功后重启
+pub async fn reinstall(app: &AppHandle) -> Result<(), String> {
+    if PUBKEY.is_none() {
+        return Err("此版本未配置更新签名公钥，请从发布页面重新下载安装程序".to_string());
+    }
+    let channel = crate::settings::current().update_channel;
+    let updater = app
+        .updater_builder()
+        .endpoints(vec![endpoint(channel)?])
+        .map(|builder| builder.version_comparator(|current, remote| remote.version >= current))
+        .and_then(|builder| builder.build())
+        .map_err(|e| format!("无法初始化更新: {}", e))?;
+    let update = updater.check().await.map_err(|e| format!("检查更新失败: {}", e))?.ok_or_else(|| "更新服务器上没有可用的安装包".to_string())?;
+    crate::breadcrumbs::record("app", format!("Reinstalling {}", update.version));
+    crate::task_progress::update(app, DOWNLOAD_TASK, crate::task_progress::TaskState::Running(None));
+    let result = update.download_and_install(|_, _| {}, || {}).await;
+    let state = if result.is_ok() { crate::task_progress::TaskState::Done } else { crate::task_progress::TaskState::Failed };
+    crate::task_progress::update(app, DOWNLOAD_TASK, state);
+    result.map_err(|e| format!("重新安装失败: {}", e))?;
+    tracing::info!("Reinstalled {}, restarting", update.version);
+    app.restart()
+}
+
+/// 已下载、等待安装的更新
+pub fn downloaded() -> Option<UpdateInfo> {
+    let downloaded = DOWNLOADED.lock().unwrap_or_else(|e| e.into_inner());
+    downloaded.as_ref().map(|(update, _)| info(update, crate::settings::current().update_channel))
+}
+
+/// 安装已下载的更新（Windows 上安装程序接管后应用直接退出）
+pub fn install() -> Result<(), String> {
+    let (update, bytes) =
+        DOWNLOADED.lock().unwrap_or_else(|e| e.into_inner()).take().ok_or_else(|| "没有已下载的更新".to_string())?;
+    crate::breadcrumbs::record("app", format!("Installing update {}", update.version));
+    // 安装包已在内存中，Windows 上安装程序接管后应用不再有机会删除下载的文件
+    if let Err(e) = crate::download::discard(DOWNLOAD_ID) {
+        tracing::warn!("{}", e);
+    }
+    update.install(&bytes).map_err(|e| format!("安装更新失败: {}", e))?;
+    tracing::info!("Installed update {}", update.version);
+    Ok(())
+}
+
+/// 安装已下载的更新并重启应用
+pub fn install_and_restart(app: &AppHandle) -> Result<(), String> {
+    install()?;
+    app.restart()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_endpoint() {
+        assert_ne!(UpdateChannel::Stable.endpoint(), UpdateChannel::Beta.endpoint());
+        assert!(tauri::Url::parse(UpdateChannel::Beta.endpoint()).is_ok());
+        let channel: UpdateChannel = serde_json::from_str("\"beta\"").unwrap();
+        assert_eq!(channel, UpdateChannel::Beta);
+        assert!(verify_signature(b"update", "invalid", "invalid").is_err());
+    }
+}
@@ -0,0 +1,436 @@
+//! 应用和后端的协同升级
+//!
+//! 应用的新版本要求更新的后端时（更新清单中的 `min_backend_version` 高于已安装的后端），
+//! `start_coordinated_upgrade` 按以下阶段升级，每进入一个阶段都先写入 DAWEI_HOME/upgrade.json：
+//!
+//! 1. `stop_backend`：停止后端，避免安装期间占用后端环境中的文件；
+//! 2. `install_app`：安装已下载的应用更新并重启（Windows 上由安装程序重启）；
+//! 3. `sync_backend`：新版本启动后升级后端依赖（源码目录中 `uv sync --upgrade`，内置环境中
+//!    `uv pip install --upgrade davybot`）。设置了 `backend_version_pin` 时内置环境只装固定版本，
+//!    源码目录按锁文件安装不升级；固定版本低于新应用的要求时在安装应用前拒绝升级；
+//! 4. `migrate`：迁移应用数据（见 [`crate::migrations`]）；
+//! 5. `start_backend`：启动新后端并等待健康检查通过。
+//!
+//! 应用在任意阶段退出后，下次启动从记录的阶段继续；停在前两个阶段说明应用更新没有装上，
+//! 此时放弃本次升级（旧版本应用和旧后端仍然匹配）。阶段失败时记录错误，下次启动重试，
+//! 连续失败 [`MAX_ATTEMPTS`] 次后停止自动重试，由用户选择重试或放弃。
+//! 每次状态变化发送 `upgrade-status` 事件，内容为 [`UpgradeState`]。
+
+use crate::atomic_file::write_json_atomic;
+use crate::backend_update::BackendEnv;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// 升级状态事件，内容为 [`UpgradeState`]
+pub const UPGRADE_STATUS_EVENT: &str = "upgrade-status";
+
+/// 状态文件名
+const STATE_FILE: &str = "upgrade.json";
+
+/// 任务栏进度中的任务名
+const UPGRADE_TASK: &str = "backend-upgrade";
+
+/// 同一阶段自动重试的次数
+pub const MAX_ATTEMPTS: u32 = 3;
+
+/// 新后端启动后等待健康检查的时间（首次启动可能要编译字节码、迁移数据库）
+const START_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// 是否正在执行升级阶段
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// 升级阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpgradeStage {
+    StopBackend,
+    InstallApp,
+    SyncBackend,
+    Migrate,
+    StartBackend,
+    /// 升级完成（不写入状态文件，只出现在事件中）
+    Done,
+}
+
+impl UpgradeStage {
+    fn next(self) -> UpgradeStage {
+        match self {
+            UpgradeStage::StopBackend => UpgradeStage::InstallApp,
+            UpgradeStage::InstallApp => UpgradeStage::SyncBackend,
+            UpgradeStage::SyncBackend => UpgradeStage::Migrate,
+            UpgradeStage::Migrate => UpgradeStage::StartBackend,
+            UpgradeStage::StartBackend | UpgradeStage::Done => UpgradeStage::Done,
+        }
+    }
+}
+
+/// 升级状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UpgradeState {
+    pub stage: UpgradeStage,
+    /// 升级前的应用版本
+    pub from_version: String,
+    /// 升级后的应用版本
+    pub target_version: String,
+    /// 新版本要求的最低后端版本
+    pub min_backend_version: Option<String>,
+    /// 当前阶段失败的次数
+    pub attempts: u32,
+    /// 当前阶段最近一次失败的原因
+    pub error: Option<String>,
+    pub started_at: String,
+    pub updated_at: String,
+}
+
+impl UpgradeState {
+    /// 是否已停止自动重试，等待用户处理
+    pub fn needs_attention(&self) -> bool {
+        self.error.is_some() && self.attempts >= MAX_ATTEMPTS
+    }
+}
+
+fn state_file() -> PathBuf {
+    crate::get_dawei_home().join(STATE_FILE)
+}
+
+fn read_state(path: &Path) -> Option<UpgradeState> {
+    let content = fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(state) => Some(state),
+        Err(e) => {
+            tracing::warn!("Invalid {}: {}", STATE_FILE, e);
+            None
+        }
+    }
+}
+
+fn write_state(path: &Path, state: &mut UpgradeState) -> Result<(), String> {
+    state.updated_at = chrono::Local::now().to_rfc3339();
+    write_json_atomic(path, state).map_err(|e| format!("保存升级状态失败: {}", e))
+}
+
+fn remove_state(path: &Path) {
+    if let Err(e) = fs::remove_file(path) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            tracing::warn!("Failed to remove {}: {}", STATE_FILE, e);
+        }
+    }
+}
+
+/// 进入下一阶段
+fn advance(path: &Path, state: &mut UpgradeState) -> Result<(), String> {
+    state.stage = state.stage.next();
+    state.attempts = 0;
+    state.error = None;
+    if state.stage == UpgradeStage::Done {
+        remove_state(path);
+        return Ok(());
+    }
+    write_state(path, state)
+}
+
+/// 启动时检查上次留下的状态：应用更新没有装上时放弃升级，否则返回要继续的状态
+fn on_launch(path: &Path, app_version: &str) -> Option<UpgradeState> {
+    let mut state = read_state(path)?;
+    if matches!(state.stage, UpgradeStage::StopBackend | UpgradeStage::InstallApp) {
+        if app_version != state.target_version {
+            tracing::warn!("App update {} was not installed, abandoning upgrade", state.target_version);
+            remove_state(path);
+            return None;
+        }
+        state.stage = UpgradeStage::InstallApp;
+        if let Err(e) = advance(path, &mut state) {
+            tracing::warn!("{}", e);
+        }
+    }
+    Some(state)
+}
+
+fn emit(app: &AppHandle, state: &UpgradeState) {
+    if let Err(e) = app.emit(UPGRADE_STATUS_EVENT, state) {
+        tracing::warn!("Failed to emit upgrade status: {}", e);
+    }
+}
+
+/// 当前的升级状态，没有进行中的升级时为 None
+pub fn status() -> Option<UpgradeState> {
+    read_state(&state_file())
+}
+
+/// 是否正在升级后端（此时不能由前端启动后端）
+pub fn is_upgrading_backend() -> bool {
+    RUNNING.load(Ordering::SeqCst)
+        && status().is_some_and(|state| matches!(state.stage, UpgradeStage::SyncBackend | UpgradeStage::Migrate))
+}
+
+/// 要求的后端版本是否高于已安装的版本
+async fn backend_outdated(min_version: &str) -> bool {
+    let installed = tauri::async_runtime::spawn_blocking(crate::backend_update::installed_version).await.ok().flatten();
+    let installed = match installed {
+        Some(version) => Some(version),
+        None => crate::about::backend_version().await,
+    };
+    // 无法确定已安装的版本时按需要升级处理
+    installed.is_none_or(|installed| crate::backend_update::compare_versions(min_version, &installed).is_gt())
+}
+
+/// 安装已下载的应用更新；需要更新后端时按协同升级的流程执行，否则直接安装并重启
+pub async fn start(app: &AppHandle) -> Result<(), String> {
+    let update = crate::updater::downloaded().ok_or_else(|| "没有已下载的更新".to_string())?;
+    let min_backend = match update.min_backend_version.clone() {
+        Some(version) if backend_outdated(&version).await => Some(version),
+        _ => None,
+    };
+    // 固定的后端版本不满足新版本时不安装，避免装上应用后后端无法升级
+    if let Some(min) = &update.min_backend_version {
+        crate::backend_update::requirement(crate::backend_update::version_pin()?.as_deref(), Some(min))?;
+    }
+    if min_backend.is_none() {
+        return crate::updater::install_and_restart(app);
+    }
+
+    let path = state_file();
+    let now = chrono::Local::now().to_rfc3339();
+    let mut state = UpgradeState {
+        stage: UpgradeStage::StopBackend,
+        from_version: update.current_version,
+        target_version: update.version,
+        min_backend_version: min_backend,
+        attempts: 0,
+        error: None,
+        started_at: now.clone(),
+        updated_at: now,
+    };
+    write_state(&path, &mut state)?;
+    crate::breadcrumbs::record("app", format!("Coordinated upgrade to {} started", state.target_version));
+    emit(app, &state);
+
+    let stopped = tauri::async_runtime::spawn_blocking(crate::backend_process::stop).await.map_err(|e| e.to_string());
+    let result = match stopped.and_then(|stopped| stopped) {
+        Ok(_) => {
+            advance(&path, &mut state)?;
+            emit(app, &state);
+            crate::updater::install()
+        }
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        // 应用还是旧版本，放弃升级
+        remove_state(&path);
+        return Err(e);
+    }
+    app.restart()
+}
+
+/// 升级后端依赖，固定了后端版本时安装固定的版本（阻塞调用）
+fn sync_backend(min_version: Option<&str>) -> Result<(), String> {
+    let pin = crate::backend_update::version_pin()?;
+    let requirement = crate::backend_update::requirement(pin.as_deref(), min_version)?;
+    let mut command = Command::new(crate::get_uv_path());
+    match crate::backend_update::backend_env()? {
+        BackendEnv::Source(agent_dir) => {
+            crate::disk_space::ensure_free(&agent_dir, crate::disk_space::INSTALL_MIN_FREE, "升级后端依赖")?;
+            // 源码目录的版本由检出的代码决定，固定版本时不升级锁文件中的依赖
+            let mode = if pin.is_some() { "--frozen" } else { "--upgrade" };
+            command.args(["sync", mode, "--directory"]).arg(&agent_dir).current_dir(&agent_dir);
+            command.args(crate::offline_wheels::index_args(None));
+        }
+        BackendEnv::Bundled { dir, python } => {
+            crate::disk_space::ensure_free(&dir, crate::disk_space::INSTALL_MIN_FREE, "升级后端依赖")?;
+            command
+                .args(["pip", "install", "--upgrade", "--python"])
+                .arg(python)
+                .args(crate::offline_wheels::index_args(Some(crate::connectivity::pypi_index())))
+                .arg(requirement);
+        }
+    }
+    crate::backend_update::run_uv(&mut command, "升级后端依赖").map(|_| ())
+}
+
+/// 迁移应用数据（后端在启动时自行迁移数据库）
+fn migrate(app: &AppHandle) -> Result<(), String> {
+    let failed = crate::migrations::run_pending(app);
+    if failed.is_empty() {
+        return Ok(());
+    }
+    let errors: Vec<String> = failed.iter().map(|(id, failure)| format!("{}: {}", id, failure.error)).collect();
+    Err(format!("数据迁移失败: {}", errors.join("; ")))
+}
+
+async fn run_stage(app: &AppHandle, state: &UpgradeState) -> Result<(), String> {
+    match state.stage {
+        UpgradeStage::SyncBackend => {
+            let min_version = state.min_backend_version.clone();
+            tauri::async_runtime::spawn_blocking(move || sync_backend(min_version.as_deref()))
+                .await
+                .map_err(|e| e.to_string())?
+        }
+        UpgradeStage::Migrate => {
+            let app = app.clone();
+            tauri::async_runtime::spawn_blocking(move || migrate(&app)).await.map_err(|e| e.to_string())?
+        }
+        UpgradeStage::StartBackend => {
+            crate::start_backend(app.clone()).await?;
+            if crate::backend_process::wait_until_healthy(START_TIMEOUT).await {
+                Ok(())
+            } else {
+                Err("新后端未能通过健康检查".to_string())
+            }
+        }
+        // 前两个阶段由旧版本执行，启动时已处理
+        UpgradeStage::StopBackend | UpgradeStage::InstallApp | UpgradeStage::Done => Ok(()),
+    }
+}
+
+/// 从当前阶段继续执行到完成或失败
+async fn run(app: &AppHandle, path: &Path, state: UpgradeState) {
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    run_stages(app, path, state).await;
+    RUNNING.store(false, Ordering::SeqCst);
+}
+
+async fn run_stages(app: &AppHandle, path: &Path, mut state: UpgradeState) {
+    crate::task_progress::update(app, UPGRADE_TASK, crate::task_progress::TaskState::Running(None));
+    while state.stage != UpgradeStage::Done {
+        emit(app, &state);
+        tracing::info!("Upgrade to {}: {:?}", state.target_version, state.stage);
+        let result = match run_stage(app, &state).await {
+            Ok(()) => advance(path, &mut state),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            tracing::error!("Upgrade stage {:?} failed: {}", state.stage, e);
+            crate::breadcrumbs::record("app", format!("Upgrade stage {:?} failed: {}", state.stage, e));
+            state.attempts += 1;
+            state.error = Some(e);
+            if let Err(e) = write_state(path, &mut state) {
+                tracing::warn!("{}", e);
+            }
+            emit(app, &state);
+            crate::task_progress::update(app, UPGRADE_TASK, crate::task_progress::TaskState::Failed);
+            return;
+        }
+    }
+    tracing::info!("Upgrade to {} completed", state.target_version);
+    crate::breadcrumbs::record("app", format!("Coordinated upgrade to {} completed", state.target_version));
+    emit(app, &state);
+    crate::task_progress::update(app, UPGRADE_TASK, crate::task_progress::TaskState::Done);
+}
+
+/// 启动时继续未完成的升级（失败次数达到上限时只通知前端）
+pub fn resume(app: &AppHandle) {
+    let path = state_file();
+    let Some(state) = on_launch(&path, env!("CARGO_PKG_VERSION")) else {
+        return;
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if state.needs_attention() {
+            emit(&app, &state);
+            return;
+        }
+        run(&app, &path, state).await;
+    });
+}
+
+/// 用户要求重试失败的阶段
+pub async fn retry(app: &AppHandle) -> Result<(), String> {
+    if RUNNING.load(Ordering::SeqCst) {
+        return Err("升级正在进行".to_string());
+    }
+    let path = state_file();
+    let mut state = read_state(&path).ok_or_else(|| "没有进行中的升级".to_string())?;
+    state.attempts = 0;
+    write_state(&path, &mut state)?;
+    run(app, &path, state).await;
+    match read_state(&path) {
+        Some(state) => Err(state.error.unwrap_or_default()),
+        None => Ok(()),
+    }
+}
+
+/// 把后端安装为设置中固定的版本并重新启动后端，返回固定的版本
+pub async fn install_pinned(app: &AppHandle) -> Result<String, String> {
+    let pin = crate::backend_update::version_pin()?.ok_or_else(|| "没有设置固定的后端版本".to_string())?;
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err("升级正在进行".to_string());
+    }
+    crate::breadcrumbs::record("backend", format!("Installing pinned backend {}", pin));
+    crate::task_progress::update(app, UPGRADE_TASK, crate::task_progress::TaskState::Running(None));
+    let result = tauri::async_runtime::spawn_blocking(|| {
+        crate::backend_process::stop()?;
+        sync_backend(None)
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|result| result);
+    RUNNING.store(false, Ordering::SeqCst);
+    let state = if result.is_ok() { crate::task_progress::TaskState::Done } else { crate::task_progress::TaskState::Failed };
+    crate::task_progress::update(app, UPGRADE_TASK, state);
+    result?;
+    tracing::info!("Installed pinned backend {}", pin);
+
+    crate::start_backend(app.clone()).await?;
+    Ok(pin)
+}
+
+/// 放弃升级（后端保持当前状态，由用户自行处理）
+pub fn abandon() -> Result<(), String> {
+    if RUNNING.load(Ordering::SeqCst) {
+        return Err("升级正在进行".to_string());
+    }
+    let path = state_file();
+    let state = read_state(&path).ok_or_else(|| "没有进行中的升级".to_string())?;
+    crate::breadcrumbs::record("app", format!("Upgrade to {} abandoned at {:?}", state.target_version, state.stage));
+    remove_state(&path);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resume_on_launch() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(STATE_FILE);
+        let mut state = UpgradeState {
+            stage: UpgradeStage::InstallApp,
+            from_version: "1.0.0".to_string(),
+            target_version: "1.1.0".to_string(),
+            min_backend_version: Some("0.5.0".to_string()),
+            attempts: 0,
+            error: None,
+            started_at: String::new(),
+            updated_at: String::new(),
+        };
+
+        // 应用仍是旧版本：放弃升级
+        write_state(&path, &mut state).unwrap();
+        assert_eq!(on_launch(&path, "1.0.0"), None);
+        assert!(!path.exists());
+
+        // 新版本启动：从升级后端继续
+        write_state(&path, &mut state).unwrap();
+        let resumed = on_launch(&path, "1.1.0").unwrap();
+        assert_eq!(resumed.stage, UpgradeStage::SyncBackend);
+        assert_eq!(read_state(&path).unwrap().stage, UpgradeStage::SyncBackend);
+
+        // 失败的阶段保持不变，直到达到重试上限
+        let mut failed = UpgradeState { attempts: MAX_ATTEMPTS, error: Some("offline".to_string()), ..resumed };
+        write_state(&path, &mut failed).unwrap();
+        assert!(on_launch(&path, "1.1.0").unwrap().needs_attention());
+
+        let mut last = UpgradeState { stage: UpgradeStage::StartBackend, ..failed };
+        advance(&path, &mut last).unwrap();
+        assert_eq!(last.stage, UpgradeStage::Done);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,192 @@
+//! 主窗口位置和大小
+//!
+//! 窗口移动、缩放时在内存中记录位置、大小、是否最大化和所在显示器，
+//! 关闭或退出时写入 DAWEI_HOME/window_state.json，下次启动时恢复。
+//! 原显示器已断开或窗口不在任何显示器的可见区域内时，缩放并移动到可见区域中。
+//! 最大化时保留最大化之前的位置和大小，取消最大化后回到原位置。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{PhysicalPosition, PhysicalSize, WebviewWindow, Window, WindowEvent};
+
+/// 记录状态的窗口
+const WINDOW_LABEL: &str = "main";
+
+/// 状态文件名
+const STATE_FILE: &str = "window_state.json";
+
+/// 当前状态（窗口事件中更新，关闭时写入文件）
+static STATE: Mutex<Option<WindowState>> = Mutex::new(None);
+
+/// 矩形区域（物理像素）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    fn contains_center_of(&self, other: &Rect) -> bool {
+        let cx = other.x as i64 + other.width as i64 / 2;
+        let cy = other.y as i64 + other.height as i64 / 2;
+        cx >= self.x as i64
+            && cx < self.x as i64 + self.width as i64
+            && cy >= self.y as i64
+            && cy < self.y as i64 + self.height as i64
+    }
+
+    /// 缩小到不超过 `area`，并移动到 `area` 内
+    fn clamp_into(&self, area: &Rect) -> Rect {
+        let width = self.width.min(area.width);
+        let height = self.height.min(area.height);
+        let max_x = area.x as i64 + (area.width - width) as i64;
+        let max_y = area.y as i64 + (area.height - height) as i64;
+        Rect {
+            x: (self.x as i64).clamp(area.x as i64, max_x) as i32,
+            y: (self.y as i64).clamp(area.y as i64, max_y) as i32,
+            width,
+            height,
+        }
+    }
+}
+
+/// 窗口状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowState {
+    /// 未最大化时的位置和大小
+    pub bounds: Rect,
+    pub maximized: bool,
+    /// 所在显示器名称
+    pub monitor: Option<String>,
+}
+
+fn state_file() -> PathBuf {
+    crate::get_dawei_home().join(STATE_FILE)
+}
+
+/// 选择恢复到的显示器可见区域：优先原显示器，其次包含窗口中心的显示器，最后是第一个显示器
+fn target_area(state: &WindowState, monitors: &[(Option<String>, Rect)]) -> Option<Rect> {
+    let by_name = state
+        .monitor
+        .as_ref()
+        .and_then(|name| monitors.iter().find(|(n, _)| n.as_ref() == Some(name)));
+    by_name
+        .or_else(|| monitors.iter().find(|(_, area)| area.contains_center_of(&state.bounds)))
+        .or_else(|| monitors.first())
+        .map(|(_, area)| *area)
+}
+
+/// 启动时恢复主窗口状态
+pub fn restore(window: &WebviewWindow) {
+    let Some(state) = fs::read_to_string(state_file())
+        .ok()
+        .and_then(|content| serde_json::from_str::<WindowState>(&content).ok())
+    else {
+        return;
+    };
+
+    let monitors: Vec<(Option<String>, Rect)> = window
+        .available_monitors()
+        .unwrap_or_default()
+        .iter()
+        .map(|m| {
+            let area = m.work_area();
+            let rect = Rect { x: area.position.x, y: area.position.y, width: area.size.width, height: area.size.height };
+            (m.name().cloned(), rect)
+        })
+        .collect();
+    let bounds = match target_area(&state, &monitors) {
+        Some(area) => state.bounds.clamp_into(&area),
+        None => state.bounds,
+    };
+
+    let restored = window
+        .set_size(PhysicalSize::new(bounds.width, bounds.height))
+        .and_then(|_| window.set_position(PhysicalPosition::new(bounds.x, bounds.y)));
+    if let Err(e) = restored {
+        tracing::warn!("Failed to restore window state: {}", e);
+        return;
+    }
+    if state.maximized {
+        let _ = window.maximize();
+    }
+    *STATE.lock().unwrap_or_else(|e| e.into_inner()) = Some(WindowState { bounds, ..state });
+}
+
+/// 读取窗口当前状态，最小化时不记录
+fn capture(window: &Window, previous: Option<&WindowState>) -> Option<WindowState> {
+    // 最小化和全屏（演示模式）时不记录
+    if window.is_minimized().unwrap_or(false) || window.is_fullscreen().unwrap_or(false) {
+        return None;
+    }
+    let maximized = window.is_maximized().unwrap_or(false);
+    let bounds = match (maximized, previous) {
+        // 最大化时保留之前的位置和大小
+        (true, Some(previous)) => previous.bounds,
+        _ => {
+            let position = window.outer_position().ok()?;
+            let size = window.inner_size().ok()?;
+            Rect { x: position.x, y: position.y, width: size.width, height: size.height }
+        }
+    };
+    let monitor = window.current_monitor().ok().flatten().and_then(|m| m.name().cloned());
+    Some(WindowState { bounds, maximized, monitor })
+}
+
+/// 写入状态文件
+pub fn save() {
+    let Some(state) = STATE.lock().unwrap_or_else(|e| e.into_inner()).clone() else {
+        return;
+    };
+    let result = serde_json::to_string_pretty(&state)
+        .map_err(std::io::Error::other)
+        .and_then(|json| fs::write(state_file(), json));
+    if let Err(e) = result {
+        tracing::warn!("Failed to save window state: {}", e);
+    }
+}
+
+/// 主窗口事件：移动、缩放时更新状态，关闭时写入文件
+pub fn on_window_event(window: &Window, event: &WindowEvent) {
+    if window.label() != WINDOW_LABEL {
+        return;
+    }
+    match event {
+        WindowEvent::Moved(_) | WindowEvent::Resized(_) => {
+            let mut guard = STATE.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(state) = capture(window, guard.as_ref()) {
+                *guard = Some(state);
+            }
+        }
+        WindowEvent::CloseRequested { .. } => save(),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore_to_visible_area() {
+        let primary = Rect { x: 0, y: 0, width: 1920, height: 1040 };
+        let monitors = [(Some("primary".to_string()), primary)];
+
+        // 原显示器（位于右侧）已断开，窗口移回主显示器
+        let state = WindowState {
+            bounds: Rect { x: 2100, y: 100, width: 1400, height: 900 },
+            maximized: false,
+            monitor: Some("external".to_string()),
+        };
+        let area = target_area(&state, &monitors).unwrap();
+        assert_eq!(state.bounds.clamp_into(&area), Rect { x: 520, y: 100, width: 1400, height: 900 });
+
+        // 比可见区域大的窗口缩小到可见区域
+        let large = Rect { x: -50, y: 0, width: 2560, height: 1440 };
+        assert_eq!(large.clamp_into(&primary), primary);
+    }
+}
@@ -0,0 +1,324 @@
+//! 工作区 zip 导出/导入
+//!
+//! 导出时按 .gitignore 和额外的 gitignore 写法的排除规则遍历工作区，逐个文件流式压缩写入
+//! 临时文件，完成后再改名为目标文件；.git、node_modules、虚拟环境、缓存目录和工作区锁文件
+//! 始终排除。导入时先检查所有条目的路径（不能解压到目标目录之外）和所需磁盘空间，
+//! 再解压到不存在或为空的目录，失败时删除已解压的内容，完成后记入最近的工作区。
+//! 两个方向都通过 `workspace-archive-progress` 事件和任务栏报告进度。
+
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// 进度事件，内容为 [`ArchiveProgress`]
+pub const WORKSPACE_ARCHIVE_PROGRESS_EVENT: &str = "workspace-archive-progress";
+
+/// 任务栏进度中的任务名
+const ARCHIVE_TASK: &str = "workspace-archive";
+
+/// 进度事件间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 始终排除的目录和文件（gitignore 写法）
+const DEFAULT_EXCLUDES: [&str; 10] = [
+    ".git/",
+    "node_modules/",
+    ".venv/",
+    "venv/",
+    "__pycache__/",
+    ".mypy_cache/",
+    ".pytest_cache/",
+    ".ruff_cache/",
+    ".dawei/lock",
+    ".DS_Store",
+];
+
+/// 进度
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchiveProgress {
+    /// `export` 或 `import`
+    pub operation: &'static str,
+    pub archive: PathBuf,
+    pub processed_bytes: u64,
+    pub total_bytes: u64,
+}
+
+/// 导出/导入结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchiveResult {
+    pub archive: PathBuf,
+    pub workspace: PathBuf,
+    pub file_count: u64,
+    pub total_bytes: u64,
+}
+
+/// 按间隔回调进度
+struct Reporter<F: FnMut(u64, u64)> {
+    total: u64,
+    processed: u64,
+    last: Instant,
+    callback: F,
+}
+
+impl<F: FnMut(u64, u64)> Reporter<F> {
+    fn new(total: u64, callback: F) -> Self {
+        Self { total, processed: 0, last: Instant::now(), callback }
+    }
+
+    fn advance(&mut self, bytes: u64) {
+        self.processed += bytes;
+        if self.last.elapsed() >= PROGRESS_INTERVAL || self.processed == self.total {
+            (self.callback)(self.processed, self.total);
+            self.last = Instant::now();
+        }
+    }
+}
+
+/// 要导出的条目：(相对路径, 是否目录, 大小)
+fn collect_entries(root: &Path, exclude: &[String]) -> Result<Vec<(PathBuf, bool, u64)>, String> {
+    // 覆盖规则中以 ! 开头的表示排除
+    let mut overrides = OverrideBuilder::new(root);
+    for pattern in DEFAULT_EXCLUDES.iter().copied().chain(exclude.iter().map(|p| p.trim())).filter(|p| !p.is_empty()) {
+        overrides.add(&format!("!{}", pattern)).map_err(|e| format!("无效的排除规则 {}: {}", pattern, e))?;
+    }
+    let overrides = overrides.build().map_err(|e| e.to_string())?;
+    let walker = WalkBuilder::new(root).hidden(false).require_git(false).overrides(overrides).build();
+
+    let mut entries = Vec::new();
+    for entry in walker {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let Ok(relative) = entry.path().strip_prefix(root) else {
+            continue;
+        };
+        if relative.as_os_str().is_empty() {
+            continue;
+        }
+        match entry.file_type() {
+            Some(kind) if kind.is_dir() => entries.push((relative.to_path_buf(), true, 0)),
+            Some(kind) if kind.is_file() => {
+                let size = entry.metadata().map(|m| m.len()).unwrap_or_default();
+                entries.push((relative.to_path_buf(), false, size));
+            }
+            // 不导出符号链接
+            _ => {}
+        }
+    }
+    Ok(entries)
+}
+
+/// zip 中的条目名（使用 / 分隔）
+fn entry_name(relative: &Path) -> String {
+    relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+}
+
+fn write_zip(root: &Path, tmp: &Path, entries: &[(PathBuf, bool, u64)], progress: impl FnMut(u64, u64)) -> Result<(), String> {
+    let file = File::create(tmp).map_err(|e| format!("无法创建 {}: {}", tmp.display(), e))?;
+    let mut zip = ZipWriter::new(BufWriter::new(file));
+    let mut reporter = Reporter::new(entries.iter().map(|(_, _, size)| size).sum(), progress);
+    for (relative, is_dir, size) in entries {
+        let name = entry_name(relative);
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .large_file(*size >= u32::MAX as u64);
+        if *is_dir {
+            zip.add_directory(name, options).map_err(|e| e.to_string())?;
+            continue;
+        }
+        let path = root.join(relative);
+        let mut source = File::open(&path).map_err(|e| format!("无法读取 {}: {}", path.display(), e))?;
+        zip.start_file(name, options).map_err(|e| e.to_string())?;
+        std::io::copy(&mut source, &mut zip).map_err(|e| format!("无法读取 {}: {}", path.display(), e))?;
+        reporter.advance(*size);
+    }
+    zip.finish().map_err(|e| e.to_string())?.flush().map_err(|e| e.to_string())
+}
+
+/// 导出工作区到 zip（阻塞调用），`exclude` 为额外的 gitignore 写法的排除规则
+fn export_to(root: &Path, dest: &Path, exclude: &[String], progress: impl FnMut(u64, u64)) -> Result<ArchiveResult, String> {
+    let root = crate::symlink_policy::canonicalize(root).map_err(|e| format!("工作区目录不存在: {}", e))?;
+    let dest = std::path::absolute(dest).map_err(|e| e.to_string())?;
+    if dest.starts_with(&root) {
+        return Err("不能导出到工作区内".to_string());
+    }
+    let entries = collect_entries(&root, exclude)?;
+    let total_bytes = entries.iter().map(|(_, _, size)| size).sum();
+    crate::disk_space::ensure_free(&dest, total_bytes, "导出工作区")?;
+
+    let tmp = dest.with_extension("zip.tmp");
+    let result = write_zip(&root, &tmp, &entries, progress).and_then(|_| fs::rename(&tmp, &dest).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        let _ = fs::remove_file(&tmp);
+        return Err(format!("导出工作区失败: {}", e));
+    }
+    let file_count = entries.iter().filter(|(_, is_dir, _)| !is_dir).count() as u64;
+    Ok(ArchiveResult { archive: dest, workspace: root, file_count, total_bytes })
+}
+
+fn extract(archive: &mut ZipArchive<BufReader<File>>, dest: &Path, progress: impl FnMut(u64, u64)) -> Result<(u64, u64), String> {
+    // 先检查全部条目，避免解压到一半才发现问题
+    let mut total = 0u64;
+    for index in 0..archive.len() {
+        let entry = archive.by_index(index).map_err(|e| e.to_string())?;
+        if entry.enclosed_name().is_none() {
+            return Err(format!("压缩包中的路径无效: {}", entry.name()));
+        }
+        total += entry.size();
+    }
+    crate::disk_space::ensure_free(dest, total, "导入工作区")?;
+
+    let mut reporter = Reporter::new(total, progress);
+    let mut files = 0;
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(|e| e.to_string())?;
+        let Some(relative) = entry.enclosed_name() else {
+            continue;
+        };
+        let path = dest.join(relative);
+        if entry.is_dir() {
+            fs::create_dir_all(&path).map_err(|e| format!("无法创建 {}: {}", path.display(), e))?;
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("无法创建 {}: {}", parent.display(), e))?;
+        }
+        let mut target = BufWriter::new(File::create(&path).map_err(|e| format!("无法创建 {}: {}", path.display(), e))?);
+        let mut buffer = [0u8; 64 * 1024];
+        loop {
+            let read = entry.read(&mut buffer).map_err(|e| format!("无法解压 {}: {}", entry.name(), e))?;
+            if read == 0 {
+                break;
+            }
+            target.write_all(&buffer[..read]).map_err(|e| format!("无法写入 {}: {}", path.display(), e))?;
+            reporter.advance(read as u64);
+        }
+        target.flush().map_err(|e| format!("无法写入 {}: {}", path.display(), e))?;
+        files += 1;
+    }
+    Ok((files, total))
+}
+
+/// 从 zip 导入工作区到 `dest`（阻塞调用）
+fn import_from(archive_path: &Path, dest: &Path, progress: impl FnMut(u64, u64)) -> Result<ArchiveResult, String> {
+    let existed = dest.exists();
+    if existed && fs::read_dir(dest).map_err(|e| e.to_string())?.next().is_some() {
+        return Err(format!("目标目录不为空: {}", dest.display()));
+    }
+    let file = File::open(archive_path).map_err(|e| format!("无法打开 {}: {}", archive_path.display(), e))?;
+    let mut archive = ZipArchive::new(BufReader::new(file)).map_err(|e| format!("不是有效的 zip 文件: {}", e))?;
+
+    fs::create_dir_all(dest).map_err(|e| format!("无法创建 {}: {}", dest.display(), e))?;
+    match extract(&mut archive, dest, progress) {
+        Ok((file_count, total_bytes)) => {
+            let workspace = crate::symlink_policy::canonicalize(dest).map_err(|e| e.to_string())?;
+            Ok(ArchiveResult { archive: archive_path.to_path_buf(), workspace, file_count, total_bytes })
+        }
+        Err(e) => {
+            // 原本就存在的空目录保留
+            let _ = fs::remove_dir_all(dest);
+            if existed {
+                let _ = fs::create_dir(dest);
+            }
+            Err(format!("导入工作区失败: {}", e))
+        }
+    }
+}
+
+/// 在后台线程中执行，报告进度
+async fn run_with_progress(
+    app: &AppHandle,
+    operation: &'static str,
+    archive: PathBuf,
+    task: impl FnOnce(&mut dyn FnMut(u64, u64)) -> Result<ArchiveResult, String> + Send + 'static,
+) -> Result<ArchiveResult, String> {
+    use crate::task_progress::{update, TaskState};
+
+    update(app, ARCHIVE_TASK, TaskState::Running(Some(0)));
+    let handle = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let mut progress = |processed_bytes: u64, total_bytes: u64| {
+            let event = ArchiveProgress { operation, archive: archive.clone(), processed_bytes, total_bytes };
+            let _ = handle.emit(WORKSPACE_ARCHIVE_PROGRESS_EVENT, &event);
+            let percent = (processed_bytes * 100).checked_div(total_bytes).unwrap_or(100);
+            update(&handle, ARCHIVE_TASK, TaskState::Running(Some(percent)));
+        };
+        task(&mut progress)
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|result| result);
+
+    update(app, ARCHIVE_TASK, if result.is_ok() { TaskState::Done } else { TaskState::Failed });
+    match &result {
+        Ok(result) => tracing::info!(
+            "Workspace {} {}: {} files, {} bytes",
+            operation,
+            result.archive.display(),
+            result.file_count,
+            result.total_bytes
+        ),
+        Err(e) => tracing::warn!("Workspace {} failed: {}", operation, e),
+    }
+    result
+}
+
+/// 导出工作区到 zip
+pub async fn export(app: &AppHandle, path: PathBuf, dest: PathBuf, exclude: Vec<String>) -> Result<ArchiveResult, String> {
+    run_with_progress(app, "export", dest.clone(), move |progress| export_to(&path, &dest, &exclude, progress)).await
+}
+
+/// 从 zip 导入工作区并记入最近的工作区
+pub async fn import(app: &AppHandle, archive: PathBuf, dest: PathBuf) -> Result<ArchiveResult, String> {
+    let result = run_with_progress(app, "import", archive.clone(), move |progress| import_from(&archive, &dest, progress)).await?;
+    crate::recent_workspaces::record(&result.workspace);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_and_import() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("ws");
+        for file in ["notes.md", "src/main.py", "out/big.log", ".git/HEAD", "node_modules/m.js", ".dawei/lock", ".dawei/config.json"] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, file).unwrap();
+        }
+        fs::create_dir_all(root.join("empty")).unwrap();
+
+        let archive = dir.path().join("ws.zip");
+        let mut last = (0, 0);
+        let exported = export_to(&root, &archive, &["out/".to_string()], |p, t| last = (p, t)).unwrap();
+        assert_eq!(exported.file_count, 3);
+        assert_eq!(last, (exported.total_bytes, exported.total_bytes));
+        assert!(!dir.path().join("ws.zip.tmp").exists());
+        assert!(export_to(&root, &root.join("self.zip"), &[], |_, _| {}).is_err());
+
+        let dest = dir.path().join("imported");
+        let imported = import_from(&archive, &dest, |_, _| {}).unwrap();
+        assert_eq!(imported.file_count, 3);
+        assert_eq!(fs::read_to_string(dest.join("src/main.py")).unwrap(), "src/main.py");
+        assert!(dest.join(".dawei/config.json").is_file() && dest.join("empty").is_dir());
+        assert!(!dest.join(".dawei/lock").exists() && !dest.join(".git").exists() && !dest.join("out").exists());
+        // 目标目录不为空
+        assert!(import_from(&archive, &dest, |_, _| {}).is_err());
+
+        // 路径跳出目标目录的条目
+        let evil = dir.path().join("evil.zip");
+        let mut zip = ZipWriter::new(File::create(&evil).unwrap());
+        zip.start_file("../escape.txt", SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"x").unwrap();
+        zip.finish().unwrap();
+        assert!(import_from(&evil, &dir.path().join("evil"), |_, _| {}).is_err());
+        assert!(!dir.path().join("escape.txt").exists() && !dir.path().join("evil").exists());
+    }
+}
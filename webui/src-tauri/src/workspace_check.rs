@@ -0,0 +1,170 @@
+//! 工作区目录检查
+//!
+//! 用户选择工作区目录后、正式使用前检查目录是否合适，返回结构化的问题列表供前端提示：
+//! - `not_found`：目录不存在或不是目录
+//! - `not_writable`：无法在目录中创建文件
+//! - `system_directory`：系统目录、磁盘根目录或整个用户主目录
+//! - `inside_app_bundle`：位于应用安装目录中（升级时会被覆盖）
+//! - `low_disk_space`：所在磁盘可用空间不足
+//! - `locked`：已被其他正在运行的应用实例打开（见 [`crate::workspace_lock`]）
+//!
+//! `blocking` 的问题不能继续使用该目录，其余只作提醒，由用户决定是否继续。
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// 工作区所在磁盘低于该可用空间时提醒
+const WORKSPACE_MIN_FREE: u64 = 1024 * 1024 * 1024;
+
+/// 问题类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    NotFound,
+    NotWritable,
+    SystemDirectory,
+    InsideAppBundle,
+    LowDiskSpace,
+    Locked,
+}
+
+/// 检查发现的问题
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceIssue {
+    pub kind: IssueKind,
+    pub message: String,
+    /// 为 true 时不能使用该目录
+    pub blocking: bool,
+}
+
+/// 检查结果
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceValidation {
+    pub path: PathBuf,
+    /// 没有 `blocking` 的问题
+    pub usable: bool,
+    pub issues: Vec<WorkspaceIssue>,
+}
+
+fn issue(kind: IssueKind, blocking: bool, message: String) -> WorkspaceIssue {
+    WorkspaceIssue { kind, message, blocking }
+}
+
+/// 不能作为工作区的系统目录（目录本身及其子目录）
+fn system_directories() -> Vec<PathBuf> {
+    #[cfg(windows)]
+    {
+        ["SystemRoot", "ProgramFiles", "ProgramFiles(x86)", "ProgramData"]
+            .iter()
+            .filter_map(|var| std::env::var_os(var).map(PathBuf::from))
+            .collect()
+    }
+    #[cfg(not(windows))]
+    {
+        let mut dirs = vec!["/bin", "/boot", "/dev", "/etc", "/lib", "/lib64", "/proc", "/sbin", "/sys", "/usr"];
+        if cfg!(target_os = "macos") {
+            dirs.extend(["/System", "/Library", "/Applications", "/private"]);
+        }
+        dirs.into_iter().map(PathBuf::from).collect()
+    }
+}
+
+/// 应用安装目录（macOS 上为 .app 包，其他平台为可执行文件所在目录）
+fn app_bundle() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?.canonicalize().ok()?;
+    let dir = exe.parent()?;
+    if cfg!(target_os = "macos") {
+        if let Some(bundle) = dir.ancestors().find(|dir| dir.extension().is_some_and(|ext| ext == "app")) {
+            return Some(bundle.to_path_buf());
+        }
+    }
+    Some(dir.to_path_buf())
+}
+
+/// 系统目录、磁盘根目录或用户主目录的说明，其他目录返回 None
+fn system_directory(path: &Path, system: &[PathBuf], home: Option<&Path>) -> Option<String> {
+    if path.parent().is_none() {
+        return Some(format!("{} 是磁盘根目录", path.display()));
+    }
+    if let Some(dir) = system.iter().find(|dir| path.starts_with(dir)) {
+        return Some(format!("{} 位于系统目录 {} 中", path.display(), dir.display()));
+    }
+    if home == Some(path) {
+        return Some(format!("{} 是整个用户主目录", path.display()));
+    }
+    None
+}
+
+/// 在目录中创建并删除临时文件
+fn check_writable(path: &Path) -> Result<(), String> {
+    let probe = path.join(format!(".dawei-write-test-{}", uuid::Uuid::new_v4()));
+    std::fs::write(&probe, b"").map_err(|e| format!("无法在 {} 中创建文件: {}", path.display(), e))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+/// 检查工作区目录（阻塞调用，需要访问磁盘）
+pub fn validate(path: &Path) -> WorkspaceValidation {
+    let mut issues = Vec::new();
+    let path = match crate::symlink_policy::canonicalize(path) {
+        Ok(path) if path.is_dir() => path,
+        Ok(path) => {
+            issues.push(issue(IssueKind::NotFound, true, format!("{} 不是目录", path.display())));
+            return WorkspaceValidation { path, usable: false, issues };
+        }
+        Err(e) => {
+            issues.push(issue(IssueKind::NotFound, true, format!("目录不存在: {}", e)));
+            return WorkspaceValidation { path: path.to_path_buf(), usable: false, issues };
+        }
+    };
+
+    let home = dirs::home_dir().and_then(|home| home.canonicalize().ok());
+    if let Some(message) = system_directory(&path, &system_directories(), home.as_deref()) {
+        issues.push(issue(IssueKind::SystemDirectory, true, message));
+    }
+    if let Some(bundle) = app_bundle().filter(|bundle| path.starts_with(bundle)) {
+        issues.push(issue(
+            IssueKind::InsideAppBundle,
+            true,
+            format!("{} 位于应用安装目录 {} 中，升级时会被覆盖", path.display(), bundle.display()),
+        ));
+    }
+    if let Err(message) = check_writable(&path) {
+        issues.push(issue(IssueKind::NotWritable, true, message));
+    }
+    if let Err(message) = crate::disk_space::ensure_free(&path, WORKSPACE_MIN_FREE, "工作区") {
+        issues.push(issue(IssueKind::LowDiskSpace, false, message));
+    }
+    if let Some(lock) = crate::workspace_lock::holder(&path) {
+        issues.push(issue(IssueKind::Locked, true, format!("该工作区已被另一个正在运行的大微打开（PID: {}）", lock.pid)));
+    }
+
+    let usable = !issues.iter().any(|issue| issue.blocking);
+    if !usable {
+        let kinds: Vec<IssueKind> = issues.iter().filter(|issue| issue.blocking).map(|issue| issue.kind).collect();
+        tracing::info!("Workspace {} rejected: {:?}", path.display(), kinds);
+    }
+    WorkspaceValidation { path, usable, issues }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_system_directory_and_writable() {
+        let system = [PathBuf::from("/usr"), PathBuf::from("/etc")];
+        let home = Path::new("/home/user");
+        assert!(system_directory(Path::new("/"), &system, Some(home)).is_some());
+        assert!(system_directory(Path::new("/usr/local/src"), &system, Some(home)).is_some());
+        assert!(system_directory(home, &system, Some(home)).is_some());
+        assert!(system_directory(&home.join("projects"), &system, Some(home)).is_none());
+        assert!(system_directory(Path::new("/usrdata"), &system, Some(home)).is_none());
+
+        let dir = tempfile::tempdir().unwrap();
+        check_writable(dir.path()).unwrap();
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+        assert!(check_writable(&dir.path().join("missing")).is_err());
+        assert_eq!(validate(&dir.path().join("missing")).issues[0].kind, IssueKind::NotFound);
+    }
+}
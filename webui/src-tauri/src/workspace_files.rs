@@ -0,0 +1,165 @@
+//! 工作区内的文件读写
+//!
+//! 页面只能通过相对路径读写当前工作区中的文件：工作区窗口使用窗口对应的工作区，
+//! 其他窗口需要指定本次运行中用户通过目录选择对话框选择或在工作区窗口中打开过的工作区
+//! （见 [`trust`]）。workspaces.json 和最近的工作区都可以被页面修改，不作为依据。
+//! 相对路径不能是绝对路径、不能包含 `..`，路径中每一级符号链接解析后仍必须位于工作区内，
+//! 指向不存在目标的符号链接直接拒绝（写入时会在链接目标处创建文件），
+//! 避免页面借此读写磁盘上的任意位置。
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+/// 单个文件最大读取大小
+const MAX_READ: u64 = 10 * 1024 * 1024;
+
+/// 本次运行中用户选择或打开过的工作区（规范化后的路径）
+static TRUSTED: Mutex<Option<HashSet<PathBuf>>> = Mutex::new(None);
+
+/// 记录用户通过对话框选择或在窗口中打开的目录，之后可以作为工作区访问
+pub fn trust(path: &Path) {
+    if let Ok(path) = crate::symlink_policy::canonicalize(path) {
+        TRUSTED.lock().unwrap_or_else(|e| e.into_inner()).get_or_insert_with(HashSet::new).insert(path);
+    }
+}
+
+fn is_trusted(path: &Path) -> bool {
+    TRUSTED.lock().unwrap_or_else(|e| e.into_inner()).as_ref().is_some_and(|trusted| trusted.contains(path))
+}
+
+/// 确定要访问的工作区（规范化后的路径）
+pub fn workspace_root(window_label: &str, workspace: Option<&str>) -> Result<PathBuf, String> {
+    if let Some(window) = crate::workspace_window::get(window_label) {
+        return Ok(window.path);
+    }
+    let workspace = workspace.ok_or_else(|| "未指定工作区".to_string())?;
+    let path = crate::symlink_policy::canonicalize(Path::new(workspace)).map_err(|e| format!("工作区目录不存在: {}", e))?;
+    if !is_trusted(&path) {
+        return Err(format!("不是已打开过的工作区: {}", path.display()));
+    }
+    Ok(path)
+}
+
+/// 把相对路径解析为工作区内的路径，文件不存在时检查最近的已存在的上级目录
+pub fn resolve(root: &Path, relative: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(relative);
+    let valid = relative.components().all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if !valid || relative.as_os_str().is_empty() {
+        return Err(format!("无效的相对路径: {}", relative.display()));
+    }
+    // 符号链接可能指向工作区外；工作区本身可能是保留的符号链接（见 crate::symlink_policy），都按真实路径比较
+    let real_root = root.canonicalize().map_err(|e| format!("无法访问工作区: {}", e))?;
+    let mut path = root.to_path_buf();
+    for component in relative.components() {
+        path.push(component);
+        // 用 symlink_metadata 逐级检查，exists() 会跟随链接而把悬空的链接当作不存在
+        let Ok(metadata) = fs::symlink_metadata(&path) else {
+            break;
+        };
+        if metadata.file_type().is_symlink() {
+            let target = path.canonicalize().map_err(|_| format!("符号链接的目标不存在: {}", relative.display()))?;
+            if !target.starts_with(&real_root) {
+                return Err(format!("路径不在工作区内: {}", relative.display()));
+            }
+        }
+    }
+    let path = root.join(relative);
+    let existing = path.ancestors().find(|dir| dir.exists()).unwrap_or(root);
+    let resolved = existing.canonicalize().map_err(|e| format!("无法访问 {}: {}", existing.display(), e))?;
+    if !resolved.starts_with(&real_root) {
+        return Err(format!("路径不在工作区内: {}", relative.display()));
+    }
+    Ok(path)
+}
+
+/// 读取工作区中的文本文件
+pub fn read(root: &Path, relative: &str) -> Result<String, String> {
+    let path = resolve(root, relative)?;
+    let metadata = fs::metadata(&path).map_err(|e| format!("无法读取 {}: {}", relative, e))?;
+    if !metadata.is_file() {
+        return Err(format!("不是文件: {}", relative));
+    }
+    if metadata.len() > MAX_READ {
+        return Err(format!("文件过大（超过 {} MB）: {}", MAX_READ / 1024 / 1024, relative));
+    }
+    let bytes = fs::read(&path).map_err(|e| format!("无法读取 {}: {}", relative, e))?;
+    String::from_utf8(bytes).map_err(|_| format!("不是 UTF-8 文本文件: {}", relative))
+}
+
+/// 写入工作区中的文本文件，上级目录不存在时创建
+pub fn write(root: &Path, relative: &str, contents: &str) -> Result<(), String> {
+    let path = resolve(root, relative)?;
+    if path.is_dir() {
+        return Err(format!("是目录: {}", relative));
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("无法创建目录: {}", e))?;
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    // 检查之后文件被替换为符号链接时拒绝打开
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::custom_flags(&mut options, libc::O_NOFOLLOW);
+    options
+        .open(&path)
+        .and_then(|mut file| std::io::Write::write_all(&mut file, contents.as_bytes()))
+        .map_err(|e| format!("无法写入 {}: {}", relative, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_trusted_roots() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().join("anywhere");
+        fs::create_dir(&workspace).unwrap();
+        let workspace_arg = workspace.to_str().unwrap();
+
+        // 页面可以把任意目录记为最近打开并固定
+        let recent = dir.path().join("recent_workspaces.json");
+        crate::recent_workspaces::record_in(&recent, &workspace).unwrap();
+        crate::recent_workspaces::pin_in(&recent, &workspace, true).unwrap();
+        assert!(workspace_root("main", Some(workspace_arg)).is_err());
+
+        trust(&workspace);
+        assert_eq!(workspace_root("main", Some(workspace_arg)).unwrap(), workspace.canonicalize().unwrap());
+    }
+
+    #[test]
+    fn test_paths_stay_in_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("ws");
+        fs::create_dir(&root).unwrap();
+        let root = root.canonicalize().unwrap();
+        fs::write(dir.path().join("secret.txt"), "secret").unwrap();
+
+        write(&root, "notes/todo.md", "- a").unwrap();
+        assert_eq!(read(&root, "./notes/todo.md").unwrap(), "- a");
+        for relative in ["../secret.txt", "notes/../../secret.txt", "", "/etc/passwd"] {
+            assert!(read(&root, relative).is_err(), "{}", relative);
+        }
+        assert!(write(&root, "../escape.txt", "x").is_err());
+        assert!(!dir.path().join("escape.txt").exists());
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(dir.path(), root.join("link")).unwrap();
+            assert!(read(&root, "link/secret.txt").is_err());
+            assert!(write(&root, "link/new/file.txt", "x").is_err());
+
+            // 悬空的符号链接：目标在工作区外且不存在
+            std::os::unix::fs::symlink(dir.path().join("outside.txt"), root.join("dangling")).unwrap();
+            assert!(write(&root, "dangling", "x").is_err());
+            assert!(read(&root, "dangling").is_err());
+            assert!(!dir.path().join("outside.txt").exists());
+
+            // 指向工作区内的链接仍可使用
+            std::os::unix::fs::symlink(root.join("notes"), root.join("notes-link")).unwrap();
+            assert_eq!(read(&root, "notes-link/todo.md").unwrap(), "- a");
+        }
+    }
+}
@@ -0,0 +1,231 @@
+//! 工作区锁
+//!
+//! 打开工作区窗口时在 `<工作区>/.dawei/lock` 写入锁文件（PID + 会话 ID），窗口关闭或应用退出时删除。
+//! 锁文件属于另一个仍在运行的实例时拒绝打开，避免两个实例同时修改同一工作区的会话和索引；
+//! 持有锁的进程已退出（崩溃或被强制结束后留下的锁）时视为过期，直接接管。
+//! 锁文件先写入临时文件再硬链接到位，其他实例不会读到写了一半的锁；接管过期的锁时先把它
+//! 原子地重命名到一边，确认仍是检查过的那一个再删除，不会误删其他实例刚创建的锁。
+//! 同一实例内同一工作区只有一个窗口（见 [`crate::workspace_window`]）。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// 工作区锁文件（相对工作区）
+pub const LOCK_FILE: &str = ".dawei/lock";
+
+/// 本实例持有锁的工作区
+static HELD: Mutex<Option<HashSet<PathBuf>>> = Mutex::new(None);
+
+/// 锁文件内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockInfo {
+    pub pid: u32,
+    pub session_id: String,
+    #[serde(default)]
+    pub acquired_at: String,
+}
+
+/// 获取锁时最多尝试的次数（每次接管过期的锁后重试）
+const MAX_ATTEMPTS: usize = 3;
+
+fn read_lock(workspace: &Path) -> Option<LockInfo> {
+    let content = fs::read_to_string(workspace.join(LOCK_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// 同目录下本进程专用的临时文件
+fn side_path(lock_path: &Path, suffix: &str) -> PathBuf {
+    let mut name = lock_path.as_os_str().to_owned();
+    name.push(format!(".{}.{}", std::process::id(), suffix));
+    PathBuf::from(name)
+}
+
+fn process_alive(pid: u32) -> bool {
+    let pid = sysinfo::Pid::from_u32(pid);
+    let mut system = sysinfo::System::new();
+    system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[pid]), true);
+    system.process(pid).is_some()
+}
+
+/// 其他正在运行的实例持有的锁（本实例的锁和过期的锁返回 None）
+pub fn holder(workspace: &Path) -> Option<LockInfo> {
+    let lock = read_lock(workspace)?;
+    (lock.session_id != crate::session::session_id() && process_alive(lock.pid)).then_some(lock)
+}
+
+/// 新建锁文件，已存在时返回 AlreadyExists
+///
+/// 先写完临时文件再硬链接为锁文件：硬链接在目标已存在时失败，锁文件一出现就是完整的。
+fn create_lock(workspace: &Path) -> std::io::Result<()> {
+    let path = workspace.join(LOCK_FILE);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let lock = LockInfo {
+        pid: std::process::id(),
+        session_id: crate::session::session_id().to_string(),
+        acquired_at: chrono::Local::now().to_rfc3339(),
+    };
+    let content = serde_json::to_string_pretty(&lock)?;
+
+    let tmp = side_path(&path, "tmp");
+    fs::File::create(&tmp).and_then(|mut file| file.write_all(content.as_bytes()))?;
+    let linked = fs::hard_link(&tmp, &path);
+    let _ = fs::remove_file(&tmp);
+    match linked {
+        Err(e) if e.kind() != std::io::ErrorKind::AlreadyExists => {
+            // 不支持硬链接的文件系统（如 FAT、部分网络盘）
+            tracing::debug!("Hard link not supported for workspace lock ({}), creating it directly", e);
+            let mut file = fs::OpenOptions::new().write(true).create_new(true).open(&path)?;
+            file.write_all(content.as_bytes())
+        }
+        linked => linked,
+    }
+}
+
+/// 删除检查过的过期锁：先重命名到一边，确认内容仍是 `observed` 再删除，否则放回原处
+///
+/// 返回是否删除了锁文件。
+fn remove_stale(lock_path: &Path, observed: &str) -> bool {
+    let aside = side_path(lock_path, "stale");
+    if fs::rename(lock_path, &aside).is_err() {
+        // 已被其他实例删除或接管
+        return false;
+    }
+    if fs::read_to_string(&aside).is_ok_and(|content| content == observed) {
+        let _ = fs::remove_file(&aside);
+        return true;
+    }
+    // 检查之后锁文件已被替换为其他实例的新锁，放回原处（期间又出现新锁时以新锁为准）
+    let _ = fs::hard_link(&aside, lock_path);
+    let _ = fs::remove_file(&aside);
+    false
+}
+
+fn held_elsewhere(pid: impl std::fmt::Display) -> String {
+    format!("该工作区已被另一个正在运行的大微打开（PID: {}），请先在该窗口中关闭", pid)
+}
+
+/// 获取工作区锁（阻塞调用），被其他实例持有时返回错误
+pub fn acquire(workspace: &Path) -> Result<(), String> {
+    let lock_path = workspace.join(LOCK_FILE);
+    let mut acquired = false;
+    for _ in 0..MAX_ATTEMPTS {
+        match create_lock(workspace) {
+            Ok(()) => {
+                acquired = true;
+                break;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(format!("无法写入工作区锁文件: {}", e)),
+        }
+
+        let Ok(observed) = fs::read_to_string(&lock_path) else {
+            // 读取前已被删除，重试
+            continue;
+        };
+        match serde_json::from_str::<LockInfo>(&observed) {
+            // 本实例的锁
+            Ok(lock) if lock.session_id == crate::session::session_id() => {
+                acquired = true;
+                break;
+            }
+            Ok(lock) if process_alive(lock.pid) => return Err(held_elsewhere(lock.pid)),
+            // 过期的锁或无法解析的锁文件
+            stale => {
+                if remove_stale(&lock_path, &observed) {
+                    match stale {
+                        Ok(lock) => tracing::warn!("Reclaimed stale workspace lock of PID {} in {}", lock.pid, workspace.display()),
+                        Err(_) => tracing::warn!("Removed unreadable workspace lock in {}", workspace.display()),
+                    }
+                }
+            }
+        }
+    }
+    if !acquired {
+        let pid = read_lock(workspace).map(|lock| lock.pid.to_string()).unwrap_or_else(|| "未知".to_string());
+        return Err(held_elsewhere(pid));
+    }
+    HELD.lock().unwrap_or_else(|e| e.into_inner()).get_or_insert_with(HashSet::new).insert(workspace.to_path_buf());
+    Ok(())
+}
+
+/// 释放工作区锁，只删除本实例写入的锁文件
+pub fn release(workspace: &Path) {
+    HELD.lock().unwrap_or_else(|e| e.into_inner()).as_mut().map(|held| held.remove(workspace));
+    if read_lock(workspace).is_some_and(|lock| lock.session_id == crate::session::session_id()) {
+        if let Err(e) = fs::remove_file(workspace.join(LOCK_FILE)) {
+            tracing::warn!("Failed to remove workspace lock in {}: {}", workspace.display(), e);
+        }
+    }
+}
+
+/// 应用退出时释放所有锁
+pub fn release_all() {
+    let held = HELD.lock().unwrap_or_else(|e| e.into_inner()).take().unwrap_or_default();
+    for workspace in held {
+        release(&workspace);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_acquire_and_stale_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path();
+        let lock_path = workspace.join(LOCK_FILE);
+
+        acquire(workspace).unwrap();
+        let lock = read_lock(workspace).unwrap();
+        assert_eq!(lock.pid, std::process::id());
+        assert!(holder(workspace).is_none());
+        // 本实例重复获取
+        acquire(workspace).unwrap();
+
+        // 另一个仍在运行的实例（用本进程的 PID 模拟）
+        fs::write(&lock_path, format!(r#"{{"pid": {}, "session_id": "other"}}"#, std::process::id())).unwrap();
+        assert!(acquire(workspace).unwrap_err().contains("另一个正在运行的大微"));
+        release(workspace);
+        assert!(lock_path.exists());
+
+        // 持有锁的进程已退出
+        fs::write(&lock_path, r#"{"pid": 4294967000, "session_id": "other"}"#).unwrap();
+        assert!(holder(workspace).is_none());
+        acquire(workspace).unwrap();
+        assert_eq!(read_lock(workspace).unwrap().session_id, crate::session::session_id());
+        release(workspace);
+        assert!(!lock_path.exists());
+
+        // 无法解析的锁文件视为过期
+        fs::create_dir_all(lock_path.parent().unwrap()).unwrap();
+        fs::write(&lock_path, "{").unwrap();
+        acquire(workspace).unwrap();
+        release(workspace);
+        assert_eq!(fs::read_dir(lock_path.parent().unwrap()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_remove_stale_only_removes_observed_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock_path = dir.path().join("lock");
+        let stale = r#"{"pid": 4294967000, "session_id": "old"}"#;
+
+        // 检查之后另一个实例已接管并写入了新锁
+        fs::write(&lock_path, r#"{"pid": 1, "session_id": "new"}"#).unwrap();
+        assert!(!remove_stale(&lock_path, stale));
+        assert!(fs::read_to_string(&lock_path).unwrap().contains("new"));
+
+        fs::write(&lock_path, stale).unwrap();
+        assert!(remove_stale(&lock_path, stale));
+        assert!(!lock_path.exists());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+        assert!(!remove_stale(&lock_path, stale));
+    }
+}
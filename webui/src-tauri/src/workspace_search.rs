@@ -0,0 +1,277 @@
+//! 工作区内容搜索
+//!
+//! 在本地搜索工作区中的文件内容，不经过后端：按 .gitignore 规则遍历（与文件树相同，
+//! 见 [`crate::workspace_tree`]），跳过二进制文件和超过 10 MB 的文件。默认按字面搜索，
+//! 查询全部为小写时不区分大小写；`regex` 为 true 时按正则表达式搜索。
+//!
+//! 匹配结果每 100 毫秒整批通过 `workspace-search-matches` 事件发给发起搜索的窗口，
+//! 命令在搜索结束后返回汇总。同一窗口发起新的搜索时取消上一次搜索（边输入边搜索），
+//! `cancel_workspace_search` 取消当前窗口的搜索。
+
+use grep_regex::RegexMatcherBuilder;
+use grep_searcher::sinks::Lossy;
+use grep_searcher::{BinaryDetection, SearcherBuilder};
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// 匹配结果事件，内容为 [`SearchBatch`]
+pub const WORKSPACE_SEARCH_EVENT: &str = "workspace-search-matches";
+
+/// 发送匹配结果的间隔
+const BATCH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 最多返回的匹配行数
+const MAX_MATCHES: usize = 5000;
+
+/// 预览最多保留的字符数
+const MAX_PREVIEW: usize = 200;
+
+/// 跳过超过该大小的文件
+const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// 正在进行的搜索：窗口标签 -> 取消标志
+static RUNNING: Mutex<Option<HashMap<String, Arc<AtomicBool>>>> = Mutex::new(None);
+
+/// 搜索条件
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    pub query: String,
+    /// 只搜索匹配这些 glob 的文件（例如 `*.rs`），为空时搜索全部
+    pub globs: Vec<String>,
+    pub regex: bool,
+}
+
+/// 一行匹配
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchMatch {
+    /// 相对工作区的路径
+    pub path: PathBuf,
+    /// 行号（从 1 开始）
+    pub line: u64,
+    /// 去掉首尾空白的行内容，过长时截断
+    pub preview: String,
+}
+
+/// 一批匹配结果
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchBatch {
+    /// 本次搜索的查询，前端据此丢弃已过时的结果
+    pub query: String,
+    pub matches: Vec<SearchMatch>,
+}
+
+/// 搜索汇总
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SearchSummary {
+    pub query: String,
+    /// 有匹配的文件数
+    pub file_count: u64,
+    pub match_count: u64,
+    /// 搜索过的文件数
+    pub searched_files: u64,
+    /// 匹配超过上限，只返回了一部分
+    pub truncated: bool,
+    pub cancelled: bool,
+    pub duration_ms: u64,
+}
+
+fn preview(line: &str) -> String {
+    let line = line.trim();
+    match line.char_indices().nth(MAX_PREVIEW) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line.to_string(),
+    }
+}
+
+/// 在 `root` 中搜索，每批匹配结果交给 `on_batch`
+fn run(
+    root: &Path,
+    options: &SearchOptions,
+    cancel: &AtomicBool,
+    mut on_batch: impl FnMut(Vec<SearchMatch>),
+) -> Result<SearchSummary, String> {
+    let start = Instant::now();
+    if options.query.is_empty() {
+        return Err("搜索内容为空".to_string());
+    }
+    let matcher = RegexMatcherBuilder::new()
+        .case_smart(true)
+        .fixed_strings(!options.regex)
+        .build(&options.query)
+        .map_err(|e| format!("无效的正则表达式: {}", e))?;
+    let mut overrides = OverrideBuilder::new(root);
+    for glob in options.globs.iter().map(|g| g.trim()).filter(|g| !g.is_empty()) {
+        overrides.add(glob).map_err(|e| format!("无效的文件匹配规则 {}: {}", glob, e))?;
+    }
+    overrides.add("!.git/").map_err(|e| e.to_string())?;
+    let overrides = overrides.build().map_err(|e| e.to_string())?;
+
+    let walker = WalkBuilder::new(root)
+        .hidden(false)
+        .require_git(false)
+        .max_filesize(Some(MAX_FILE_SIZE))
+        .overrides(overrides)
+        .build();
+    let mut searcher = SearcherBuilder::new().binary_detection(BinaryDetection::quit(b'\x00')).line_number(true).build();
+
+    let mut summary = SearchSummary { query: options.query.clone(), ..Default::default() };
+    let mut batch = Vec::new();
+    let mut last_batch = Instant::now();
+    for entry in walker.filter_map(Result::ok) {
+        if cancel.load(Ordering::Relaxed) {
+            summary.cancelled = true;
+            break;
+        }
+        if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+            continue;
+        }
+        summary.searched_files += 1;
+        let relative = entry.path().strip_prefix(root).unwrap_or(entry.path()).to_path_buf();
+        let before = summary.match_count;
+        let result = searcher.search_path(
+            &matcher,
+            entry.path(),
+            Lossy(|line, text| {
+                if summary.match_count as usize >= MAX_MATCHES {
+                    summary.truncated = true;
+                    return Ok(false);
+                }
+                summary.match_count += 1;
+                batch.push(SearchMatch { path: relative.clone(), line, preview: preview(text) });
+                Ok(true)
+            }),
+        );
+        if let Err(e) = result {
+            tracing::debug!("Search skipped {}: {}", entry.path().display(), e);
+        }
+        if summary.match_count > before {
+            summary.file_count += 1;
+        }
+        if !batch.is_empty() && last_batch.elapsed() >= BATCH_INTERVAL {
+            on_batch(std::mem::take(&mut batch));
+            last_batch = Instant::now();
+        }
+        if summary.truncated {
+            break;
+        }
+    }
+    if !batch.is_empty() && !summary.cancelled {
+        on_batch(batch);
+    }
+    summary.duration_ms = start.elapsed().as_millis() as u64;
+    Ok(summary)
+}
+
+/// 在工作区中搜索（取消同一窗口正在进行的搜索），匹配结果发给 `window_label` 对应的窗口
+pub async fn search(app: &AppHandle, window_label: &str, root: &Path, options: SearchOptions) -> Result<SearchSummary, String> {
+    let cancel = Arc::new(AtomicBool::new(false));
+    {
+        let mut running = RUNNING.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(previous) = running.get_or_insert_with(HashMap::new).insert(window_label.to_string(), cancel.clone()) {
+            previous.store(true, Ordering::Relaxed);
+        }
+    }
+
+    let (handle, label, dir, flag) = (app.clone(), window_label.to_string(), root.to_path_buf(), cancel.clone());
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let query = options.query.clone();
+        run(&dir, &options, &flag, |matches| {
+            // 被取消后不再发送
+            if flag.load(Ordering::Relaxed) {
+                return;
+            }
+            let batch = SearchBatch { query: query.clone(), matches };
+            if let Err(e) = handle.emit_to(label.as_str(), WORKSPACE_SEARCH_EVENT, &batch) {
+                tracing::warn!("Failed to emit search results: {}", e);
+            }
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|result| result);
+
+    // 只移除自己的标志（新的搜索可能已经替换了它）
+    let mut running = RUNNING.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(running) = running.as_mut() {
+        if running.get(window_label).is_some_and(|flag| Arc::ptr_eq(flag, &cancel)) {
+            running.remove(window_label);
+        }
+    }
+    if let Ok(summary) = &result {
+        tracing::debug!(
+            "Workspace search in {}: {} matches in {} files ({} searched) in {} ms",
+            root.display(),
+            summary.match_count,
+            summary.file_count,
+            summary.searched_files,
+            summary.duration_ms
+        );
+    }
+    result
+}
+
+/// 取消窗口正在进行的搜索，没有在搜索时返回 false
+pub fn cancel(window_label: &str) -> bool {
+    let running = RUNNING.lock().unwrap_or_else(|e| e.into_inner());
+    match running.as_ref().and_then(|running| running.get(window_label)) {
+        Some(flag) => {
+            flag.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_search() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {\n    let Total = 1;\n    println!(\"{}\", total);\n}\n").unwrap();
+        fs::write(root.join("notes.md"), "total: 3\n").unwrap();
+        fs::write(root.join("data.bin"), b"total\x00\x01").unwrap();
+        fs::write(root.join(".gitignore"), "build/\n").unwrap();
+        fs::create_dir(root.join("build")).unwrap();
+        fs::write(root.join("build/out.txt"), "total\n").unwrap();
+
+        let find = |query: &str, globs: &[&str], regex: bool| {
+            let options = SearchOptions {
+                query: query.to_string(),
+                globs: globs.iter().map(|g| g.to_string()).collect(),
+                regex,
+            };
+            let mut matches = Vec::new();
+            let summary = run(root, &options, &AtomicBool::new(false), |batch| matches.extend(batch)).unwrap();
+            let mut found: Vec<(String, u64)> =
+                matches.iter().map(|m| (m.path.to_string_lossy().replace('\\', "/"), m.line)).collect();
+            found.sort();
+            (summary, found)
+        };
+
+        // 小写查询不区分大小写；二进制文件和被忽略的目录不搜索
+        let (summary, found) = find("total", &[], false);
+        assert_eq!(found, [("notes.md".to_string(), 1), ("src/main.rs".to_string(), 2), ("src/main.rs".to_string(), 3)]);
+        assert_eq!((summary.file_count, summary.match_count), (2, 3));
+        assert_eq!(find("Total", &[], false).1, [("src/main.rs".to_string(), 2)]);
+        assert_eq!(find("*.rs", &[], false).1.len(), 0);
+        assert_eq!(find(r"let \w+ =", &["*.rs"], true).1, [("src/main.rs".to_string(), 2)]);
+        assert_eq!(find("total", &["*.md"], false).1, [("notes.md".to_string(), 1)]);
+        assert!(run(root, &SearchOptions { query: "(".to_string(), regex: true, ..Default::default() }, &AtomicBool::new(false), |_| {}).is_err());
+
+        let cancelled = run(root, &SearchOptions { query: "total".to_string(), ..Default::default() }, &AtomicBool::new(true), |_| {}).unwrap();
+        assert!(cancelled.cancelled);
+        assert_eq!(preview(&format!("  {}  ", "x".repeat(300))).chars().count(), MAX_PREVIEW + 1);
+    }
+}
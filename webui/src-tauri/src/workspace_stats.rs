@@ -0,0 +1,202 @@
+//! 工作区磁盘占用统计
+//!
+//! 在后台线程中遍历工作区（包括 .git、node_modules 等通常被忽略的目录，不跟随符号链接），
+//! 统计总大小、文件数和占用最大的目录，供用户在建立索引前了解工作区的内容。
+//! 遍历期间每 500 毫秒发送一次 `workspace-stats-progress` 事件，并在任务栏显示不确定进度；
+//! `cancel_workspace_stats` 取消正在进行的统计。
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// 统计进度事件，内容为 [`StatsProgress`]
+pub const WORKSPACE_STATS_PROGRESS_EVENT: &str = "workspace-stats-progress";
+
+/// 任务栏进度中的任务名
+const STATS_TASK: &str = "workspace-stats";
+
+/// 进度事件间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 参与"最大目录"排名的目录的最大深度（相对工作区）
+const MAX_DIR_DEPTH: usize = 3;
+
+/// 返回的最大目录数
+const LARGEST_DIRS: usize = 10;
+
+/// 正在进行的统计：工作区 -> 取消标志
+static RUNNING: Mutex<Option<HashMap<PathBuf, Arc<AtomicBool>>>> = Mutex::new(None);
+
+/// 目录的占用
+#[derive(Debug, Clone, Serialize)]
+pub struct DirStats {
+    /// 相对工作区的路径
+    pub path: PathBuf,
+    pub size: u64,
+    pub file_count: u64,
+}
+
+/// 统计结果
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceStats {
+    pub path: PathBuf,
+    /// 所有文件的大小之和（字节）
+    pub total_size: u64,
+    pub file_count: u64,
+    pub dir_count: u64,
+    /// 占用最大的目录（深度不超过 3 层），从大到小
+    pub largest_dirs: Vec<DirStats>,
+    pub duration_ms: u64,
+}
+
+/// 统计进度
+#[derive(Debug, Clone, Serialize)]
+pub struct StatsProgress {
+    pub path: PathBuf,
+    pub total_size: u64,
+    pub file_count: u64,
+    pub dir_count: u64,
+}
+
+/// 遍历统计，`cancel` 被设置时返回错误
+fn collect(root: &Path, cancel: &AtomicBool, mut on_progress: impl FnMut(&StatsProgress)) -> Result<WorkspaceStats, String> {
+    let start = Instant::now();
+    let mut progress = StatsProgress { path: root.to_path_buf(), total_size: 0, file_count: 0, dir_count: 0 };
+    let mut dirs: HashMap<PathBuf, (u64, u64)> = HashMap::new();
+    let mut last_progress = Instant::now();
+
+    let walker = ignore::WalkBuilder::new(root).standard_filters(false).follow_links(false).build();
+    for entry in walker {
+        if cancel.load(Ordering::Relaxed) {
+            return Err("已取消".to_string());
+        }
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if entry.depth() == 0 {
+            continue;
+        }
+        match entry.file_type() {
+            Some(kind) if kind.is_dir() => progress.dir_count += 1,
+            Some(kind) if kind.is_file() => {
+                let size = entry.metadata().map(|m| m.len()).unwrap_or_default();
+                progress.file_count += 1;
+                progress.total_size += size;
+                let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+                // 计入各级上级目录（不含工作区本身）
+                for dir in relative.ancestors().skip(1).filter(|dir| !dir.as_os_str().is_empty()) {
+                    if dir.components().count() <= MAX_DIR_DEPTH {
+                        let stats = dirs.entry(dir.to_path_buf()).or_default();
+                        stats.0 += size;
+                        stats.1 += 1;
+                    }
+                }
+            }
+            _ => {}
+        }
+        if last_progress.elapsed() >= PROGRESS_INTERVAL {
+            on_progress(&progress);
+            last_progress = Instant::now();
+        }
+    }
+
+    let mut largest_dirs: Vec<DirStats> =
+        dirs.into_iter().map(|(path, (size, file_count))| DirStats { path, size, file_count }).collect();
+    largest_dirs.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    largest_dirs.truncate(LARGEST_DIRS);
+    Ok(WorkspaceStats {
+        path: progress.path,
+        total_size: progress.total_size,
+        file_count: progress.file_count,
+        dir_count: progress.dir_count,
+        largest_dirs,
+        duration_ms: start.elapsed().as_millis() as u64,
+    })
+}
+
+/// 统计工作区（同一工作区已在统计时返回错误）
+pub async fn stats(app: &AppHandle, path: &Path) -> Result<WorkspaceStats, String> {
+    let root = crate::symlink_policy::canonicalize(path).map_err(|e| format!("工作区目录不存在: {}", e))?;
+    if !root.is_dir() {
+        return Err(format!("不是目录: {}", root.display()));
+    }
+    let cancel = Arc::new(AtomicBool::new(false));
+    {
+        let mut running = RUNNING.lock().unwrap_or_else(|e| e.into_inner());
+        let running = running.get_or_insert_with(HashMap::new);
+        if running.contains_key(&root) {
+            return Err(format!("正在统计 {}", root.display()));
+        }
+        running.insert(root.clone(), cancel.clone());
+    }
+
+    crate::task_progress::update(app, STATS_TASK, crate::task_progress::TaskState::Running(None));
+    let (handle, dir) = (app.clone(), root.clone());
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        collect(&dir, &cancel, |progress| {
+            let _ = handle.emit(WORKSPACE_STATS_PROGRESS_EVENT, progress);
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|result| result);
+
+    RUNNING.lock().unwrap_or_else(|e| e.into_inner()).as_mut().map(|running| running.remove(&root));
+    crate::task_progress::update(app, STATS_TASK, crate::task_progress::TaskState::Done);
+    if let Ok(stats) = &result {
+        tracing::info!(
+            "Workspace stats for {}: {} files, {} bytes in {} ms",
+            root.display(),
+            stats.file_count,
+            stats.total_size,
+            stats.duration_ms
+        );
+    }
+    result
+}
+
+/// 取消统计，没有在统计时返回 false
+pub fn cancel(path: &Path) -> bool {
+    let root = crate::symlink_policy::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let running = RUNNING.lock().unwrap_or_else(|e| e.into_inner());
+    match running.as_ref().and_then(|running| running.get(&root)) {
+        Some(flag) => {
+            flag.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_collect() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for (file, size) in [("a.txt", 10), ("src/lib.rs", 100), ("src/deep/x/y/z.bin", 1000), ("node_modules/m.js", 50)] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, vec![0u8; size]).unwrap();
+        }
+
+        let stats = collect(root, &AtomicBool::new(false), |_| {}).unwrap();
+        assert_eq!((stats.file_count, stats.total_size, stats.dir_count), (4, 1160, 5));
+        let largest: Vec<(String, u64)> = stats
+            .largest_dirs
+            .iter()
+            .map(|d| (d.path.to_string_lossy().replace('\\', "/"), d.size))
+            .collect();
+        // 深度超过 3 层的 src/deep/x/y 不单独列出
+        assert_eq!(largest, [("src".to_string(), 1100), ("src/deep".to_string(), 1000), ("src/deep/x".to_string(), 1000), ("node_modules".to_string(), 50)]);
+
+        assert!(collect(root, &AtomicBool::new(true), |_| {}).is_err());
+    }
+}
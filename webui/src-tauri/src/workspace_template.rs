@@ -0,0 +1,196 @@
+//! 从模板创建工作区
+//!
+//! 模板是一个目录：用户模板在 DAWEI_HOME/templates/<名称>，随应用发布的模板在
+//! 安装目录的 resources/templates/<名称>（同名时用户模板优先），也可以直接指定模板目录的绝对路径。
+//! 复制时替换文件名和文本文件内容中的 `{{project_name}}`、`{{author}}`，
+//! 创建完成后记入最近的工作区。复制失败时删除已创建的目标目录。
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+/// 超过该大小的文件不做变量替换
+const MAX_SUBSTITUTE_SIZE: u64 = 1024 * 1024;
+
+/// 模板中不复制的目录
+const SKIPPED_DIRS: [&str; 1] = [".git"];
+
+/// 可用的模板
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateInfo {
+    pub name: String,
+    pub path: PathBuf,
+    /// 随应用发布的模板
+    pub bundled: bool,
+}
+
+/// 模板中替换的变量
+#[derive(Debug, Clone)]
+pub struct Variables {
+    pub project_name: String,
+    pub author: String,
+}
+
+impl Variables {
+    fn apply(&self, text: &str) -> String {
+        text.replace("{{project_name}}", &self.project_name).replace("{{author}}", &self.author)
+    }
+}
+
+fn user_templates_dir() -> PathBuf {
+    crate::get_dawei_home().join("templates")
+}
+
+fn bundled_templates_dir() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    Some(exe.parent()?.join("resources/templates"))
+}
+
+/// 可用的模板，用户模板在前
+pub fn list() -> Vec<TemplateInfo> {
+    let dirs = [(Some(user_templates_dir()), false), (bundled_templates_dir(), true)];
+    let mut templates: Vec<TemplateInfo> = Vec::new();
+    for (dir, bundled) in dirs.into_iter().filter_map(|(dir, bundled)| Some((dir?, bundled))) {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut found: Vec<TemplateInfo> = entries
+            .filter_map(Result::ok)
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| TemplateInfo { name: entry.file_name().to_string_lossy().to_string(), path: entry.path(), bundled })
+            .filter(|template| !templates.iter().any(|existing| existing.name == template.name))
+            .collect();
+        found.sort_by(|a, b| a.name.cmp(&b.name));
+        templates.extend(found);
+    }
+    templates
+}
+
+/// 模板名称或模板目录的绝对路径
+fn resolve_template(template: &str) -> Result<PathBuf, String> {
+    let path = Path::new(template);
+    if path.is_absolute() {
+        return path.is_dir().then(|| path.to_path_buf()).ok_or_else(|| format!("模板目录不存在: {}", template));
+    }
+    if !matches!(path.components().collect::<Vec<_>>()[..], [Component::Normal(_)]) {
+        return Err(format!("无效的模板名称: {}", template));
+    }
+    list()
+        .into_iter()
+        .find(|info| info.name == template)
+        .map(|info| info.path)
+        .ok_or_else(|| format!("模板不存在: {}", template))
+}
+
+/// 默认作者：git 的 user.name，没有时为系统用户名
+pub fn default_author() -> String {
+    let mut command = Command::new("git");
+    command.args(["config", "--get", "user.name"]);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    command
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|name| !name.is_empty())
+        .or_else(|| std::env::var("USER").or_else(|_| std::env::var("USERNAME")).ok())
+        .unwrap_or_default()
+}
+
+/// 复制目录，替换文件名和文本文件内容中的变量
+fn copy_dir(from: &Path, to: &Path, variables: &Variables) -> std::io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let file_type = entry.file_type()?;
+        let target = to.join(variables.apply(&name));
+        if file_type.is_dir() {
+            if !SKIPPED_DIRS.contains(&name.as_str()) {
+                copy_dir(&entry.path(), &target, variables)?;
+            }
+            continue;
+        }
+        let text = (entry.metadata()?.len() <= MAX_SUBSTITUTE_SIZE)
+            .then(|| fs::read(entry.path()).ok().and_then(|bytes| String::from_utf8(bytes).ok()))
+            .flatten();
+        match text {
+            Some(text) => fs::write(&target, variables.apply(&text))?,
+            None => {
+                fs::copy(entry.path(), &target)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 把模板复制到 `dest`（不存在或为空目录），返回规范化后的目标路径
+fn create_in(template: &Path, dest: &Path, variables: &Variables) -> Result<PathBuf, String> {
+    let existed = dest.exists();
+    if existed && fs::read_dir(dest).map_err(|e| e.to_string())?.next().is_some() {
+        return Err(format!("目标目录不为空: {}", dest.display()));
+    }
+    let template = template.canonicalize().map_err(|e| e.to_string())?;
+    if std::path::absolute(dest).is_ok_and(|dest| dest.starts_with(&template)) {
+        return Err("目标目录不能位于模板目录中".to_string());
+    }
+    if let Err(e) = copy_dir(&template, dest, variables) {
+        // 原本就存在的空目录保留
+        let _ = fs::remove_dir_all(dest);
+        if existed {
+            let _ = fs::create_dir(dest);
+        }
+        return Err(format!("复制模板失败: {}", e));
+    }
+    dest.canonicalize().map_err(|e| e.to_string())
+}
+
+/// 从模板创建工作区并记入最近的工作区（阻塞调用）
+pub fn create(template: &str, dest: &Path, author: Option<String>) -> Result<PathBuf, String> {
+    let template_dir = resolve_template(template)?;
+    let project_name = std::path::absolute(dest)
+        .ok()
+        .and_then(|dest| dest.file_name().map(|name| name.to_string_lossy().to_string()))
+        .ok_or_else(|| format!("无效的目标目录: {}", dest.display()))?;
+    let variables = Variables { project_name, author: author.unwrap_or_else(default_author) };
+    let created = create_in(&template_dir, dest, &variables)?;
+    tracing::info!("Workspace created from template {}: {}", template, created.display());
+    crate::breadcrumbs::record("workspace", format!("Workspace created from template {}", template));
+    crate::recent_workspaces::record(&created);
+    Ok(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_with_substitution() {
+        let dir = tempfile::tempdir().unwrap();
+        let template = dir.path().join("template");
+        fs::create_dir_all(template.join("src")).unwrap();
+        fs::create_dir_all(template.join(".git")).unwrap();
+        fs::write(template.join("README.md"), "# {{project_name}}\nby {{author}}\n").unwrap();
+        fs::write(template.join("src/{{project_name}}.py"), "").unwrap();
+        fs::write(template.join("logo.bin"), [0xff, 0xfe, 0x00]).unwrap();
+
+        let variables = Variables { project_name: "demo".to_string(), author: "Li".to_string() };
+        let dest = dir.path().join("demo");
+        create_in(&template, &dest, &variables).unwrap();
+        assert_eq!(fs::read_to_string(dest.join("README.md")).unwrap(), "# demo\nby Li\n");
+        assert!(dest.join("src/demo.py").is_file());
+        assert_eq!(fs::read(dest.join("logo.bin")).unwrap(), [0xff, 0xfe, 0x00]);
+        assert!(!dest.join(".git").exists());
+
+        // 目标目录不为空或位于模板中时拒绝
+        assert!(create_in(&template, &dest, &variables).is_err());
+        assert!(create_in(&template, &template.join("nested"), &variables).is_err());
+        assert!(resolve_template("../etc").is_err());
+    }
+}
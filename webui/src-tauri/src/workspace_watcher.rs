@@ -0,0 +1,232 @@
+//! 工作区文件监听
+//!
+//! `watch_workspace` 递归监听工作区目录，外部程序（编辑器、git 等）创建、修改、删除文件时
+//! 发送 `workspace-files-changed` 事件，前端据此刷新文件列表或通知后端。
+//! 短时间内的连续变化合并为一次事件（同一文件先创建后删除则不报告），
+//! .git、node_modules、虚拟环境等目录中的变化不报告。每个工作区只有一个监听，
+//! `unwatch_workspace` 停止监听。
+
+use notify::event::{ModifyKind, RenameMode};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{mpsc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// 文件变化事件，内容为 [`WorkspaceChanges`]
+pub const WORKSPACE_FILES_CHANGED_EVENT: &str = "workspace-files-changed";
+
+/// 没有新变化超过该时间后发送事件
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// 持续有变化时最多等待的时间
+const MAX_DELAY: Duration = Duration::from_secs(2);
+
+/// 一次事件最多报告的文件数
+const MAX_CHANGES: usize = 1000;
+
+/// 不报告其中变化的目录
+const IGNORED_DIRS: [&str; 9] =
+    [".git", "node_modules", ".venv", "venv", "__pycache__", ".mypy_cache", ".pytest_cache", ".ruff_cache", ".dawei"];
+
+/// 正在监听的工作区，移除时 watcher 被释放，监听线程随之退出
+static WATCHERS: Mutex<Option<HashMap<PathBuf, RecommendedWatcher>>> = Mutex::new(None);
+
+/// 变化类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Created,
+    Modified,
+    Deleted,
+}
+
+/// 单个文件的变化
+#[derive(Debug, Clone, Serialize)]
+pub struct FileChange {
+    /// 相对工作区的路径
+    pub path: PathBuf,
+    pub kind: ChangeKind,
+}
+
+/// 一次事件报告的变化
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceChanges {
+    pub workspace: PathBuf,
+    pub changes: Vec<FileChange>,
+    /// 变化的文件超过上限，只报告了一部分（前端应重新读取整个目录）
+    pub truncated: bool,
+}
+
+/// 相对工作区的路径是否位于忽略的目录中
+fn is_ignored(relative: &Path) -> bool {
+    relative.components().any(|component| match component {
+        Component::Normal(name) => IGNORED_DIRS.iter().any(|dir| name == *dir),
+        _ => false,
+    })
+}
+
+/// 把一次变化合并到待发送的变化中
+fn merge(changes: &mut BTreeMap<PathBuf, ChangeKind>, path: PathBuf, kind: ChangeKind) {
+    use ChangeKind::*;
+    match (changes.get(&path).copied(), kind) {
+        // 期间创建又删除的临时文件不报告
+        (Some(Created), Deleted) => {
+            changes.remove(&path);
+        }
+        (Some(Created), Modified) => {}
+        (Some(Deleted), Created) => {
+            changes.insert(path, Modified);
+        }
+        _ => {
+            changes.insert(path, kind);
+        }
+    }
+}
+
+/// 把 notify 事件转换为 (路径, 变化类型)
+fn classify(event: notify::Event) -> Vec<(PathBuf, ChangeKind)> {
+    let kind = match event.kind {
+        EventKind::Create(_) => ChangeKind::Created,
+        EventKind::Remove(_) => ChangeKind::Deleted,
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => ChangeKind::Deleted,
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => ChangeKind::Created,
+        // 重命名：第一个路径为原名，第二个为新名
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+            let mut paths = event.paths.into_iter();
+            return paths
+                .next()
+                .map(|from| (from, ChangeKind::Deleted))
+                .into_iter()
+                .chain(paths.next().map(|to| (to, ChangeKind::Created)))
+                .collect();
+        }
+        EventKind::Modify(_) => ChangeKind::Modified,
+        _ => return Vec::new(),
+    };
+    event.paths.into_iter().map(|path| (path, kind)).collect()
+}
+
+/// 待发送的变化
+#[derive(Default)]
+struct Pending {
+    changes: BTreeMap<PathBuf, ChangeKind>,
+    truncated: bool,
+}
+
+impl Pending {
+    /// `real` 为工作区解析符号链接后的路径（部分平台的事件使用真实路径）
+    fn add(&mut self, workspace: &Path, real: &Path, result: notify::Result<notify::Event>) {
+        let event = match result {
+            Ok(event) => event,
+            Err(e) => {
+                tracing::debug!("Workspace watcher error: {}", e);
+                return;
+            }
+        };
+        for (path, kind) in classify(event) {
+            let Ok(relative) = path.strip_prefix(workspace).or_else(|_| path.strip_prefix(real)).map(Path::to_path_buf) else {
+                continue;
+            };
+            if relative.as_os_str().is_empty() || is_ignored(&relative) {
+                continue;
+            }
+            if self.changes.len() >= MAX_CHANGES && !self.changes.contains_key(&relative) {
+                self.truncated = true;
+                continue;
+            }
+            merge(&mut self.changes, relative, kind);
+        }
+    }
+}
+
+/// 接收变化并合并后发送，watcher 释放后退出
+fn run(app: AppHandle, workspace: PathBuf, rx: mpsc::Receiver<notify::Result<notify::Event>>) {
+    let real = workspace.canonicalize().unwrap_or_else(|_| workspace.clone());
+    while let Ok(result) = rx.recv() {
+        let mut pending = Pending::default();
+        pending.add(&workspace, &real, result);
+        let start = Instant::now();
+        while start.elapsed() < MAX_DELAY {
+            match rx.recv_timeout(DEBOUNCE) {
+                Ok(result) => pending.add(&workspace, &real, result),
+                Err(mpsc::RecvTimeoutError::Timeout) => break,
+                Err(mpsc::RecvTimeoutError::Disconnected) => return,
+            }
+        }
+        if pending.changes.is_empty() {
+            continue;
+        }
+        let changes = WorkspaceChanges {
+            workspace: workspace.clone(),
+            changes: pending.changes.into_iter().map(|(path, kind)| FileChange { path, kind }).collect(),
+            truncated: pending.truncated,
+        };
+        if let Err(e) = app.emit(WORKSPACE_FILES_CHANGED_EVENT, &changes) {
+            tracing::warn!("Failed to emit workspace changes: {}", e);
+        }
+    }
+}
+
+/// 开始监听工作区，已在监听时直接返回，返回规范化后的工作区路径
+pub fn watch(app: &AppHandle, path: &Path) -> Result<PathBuf, String> {
+    let workspace = crate::symlink_policy::canonicalize(path).map_err(|e| format!("工作区目录不存在: {}", e))?;
+    if !workspace.is_dir() {
+        return Err(format!("不是目录: {}", workspace.display()));
+    }
+    let mut watchers = WATCHERS.lock().unwrap_or_else(|e| e.into_inner());
+    let watchers = watchers.get_or_insert_with(HashMap::new);
+    if watchers.contains_key(&workspace) {
+        return Ok(workspace);
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(|e| format!("无法监听工作区: {}", e))?;
+    watcher.watch(&workspace, RecursiveMode::Recursive).map_err(|e| format!("无法监听工作区: {}", e))?;
+    let (app, dir) = (app.clone(), workspace.clone());
+    std::thread::Builder::new()
+        .name("workspace-watcher".to_string())
+        .spawn(move || run(app, dir, rx))
+        .map_err(|e| format!("无法监听工作区: {}", e))?;
+
+    watchers.insert(workspace.clone(), watcher);
+    tracing::info!("Watching workspace {}", workspace.display());
+    Ok(workspace)
+}
+
+/// 停止监听工作区，没有在监听时返回 false
+pub fn unwatch(path: &Path) -> bool {
+    let workspace = crate::symlink_policy::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let removed = WATCHERS.lock().unwrap_or_else(|e| e.into_inner()).as_mut().and_then(|w| w.remove(&workspace));
+    if removed.is_some() {
+        tracing::info!("Stopped watching workspace {}", workspace.display());
+    }
+    removed.is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_and_ignore() {
+        let mut changes = BTreeMap::new();
+        merge(&mut changes, PathBuf::from("a.txt"), ChangeKind::Created);
+        merge(&mut changes, PathBuf::from("a.txt"), ChangeKind::Modified);
+        merge(&mut changes, PathBuf::from("tmp.swp"), ChangeKind::Created);
+        merge(&mut changes, PathBuf::from("tmp.swp"), ChangeKind::Deleted);
+        // 编辑器通过删除再创建保存文件
+        merge(&mut changes, PathBuf::from("b.txt"), ChangeKind::Deleted);
+        merge(&mut changes, PathBuf::from("b.txt"), ChangeKind::Created);
+        assert_eq!(
+            changes.into_iter().collect::<Vec<_>>(),
+            [(PathBuf::from("a.txt"), ChangeKind::Created), (PathBuf::from("b.txt"), ChangeKind::Modified)]
+        );
+
+        assert!(is_ignored(Path::new(".git/index")));
+        assert!(is_ignored(Path::new("web/node_modules/react/index.js")));
+        assert!(!is_ignored(Path::new("src/venv.py")));
+    }
+}
@@ -0,0 +1,147 @@
+//! 工作区窗口
+//!
+//! 每个工作区可以在独立的窗口中打开。窗口标签由工作区路径生成（`workspace-<哈希>`），
+//! 同一工作区重复打开时聚焦已有窗口。窗口创建时通过初始化脚本注入
+//! `window.__DAWEI_WORKSPACE__`，前端据此只处理本窗口的工作区；发给单个工作区窗口的
+//! 事件使用 `emit_to(label, ...)`，前端用当前窗口的 `listen` 接收。
+//! 打开时获取工作区锁（见 [`crate::workspace_lock`]），已被其他实例打开时拒绝；
+//! 窗口关闭后释放锁并清除该窗口的登记信息。打开的工作区记入最近的工作区（见 [`crate::recent_workspaces`]）。
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder, Window, WindowEvent};
+
+/// 工作区窗口标签前缀
+const LABEL_PREFIX: &str = "workspace-";
+
+/// 已打开的工作区窗口：标签 -> 工作区
+static WINDOWS: Mutex<Option<HashMap<String, WorkspaceWindow>>> = Mutex::new(None);
+
+/// 工作区窗口信息
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceWindow {
+    pub label: String,
+    pub path: PathBuf,
+    /// workspaces.json 中的工作区 ID（未登记的目录为 None）
+    pub id: Option<String>,
+}
+
+/// 由工作区路径生成窗口标签
+fn label_for(path: &Path) -> String {
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    format!("{}{:016x}", LABEL_PREFIX, hasher.finish())
+}
+
+/// 在工作区索引中查找路径对应的工作区 ID
+fn find_workspace_id(index: &Value, path: &Path) -> Option<String> {
+    index["workspaces"].as_array()?.iter().find_map(|ws| {
+        let ws_path = ws.get("path").and_then(Value::as_str)?;
+        let same = Path::new(ws_path) == path
+            || crate::symlink_policy::canonicalize(Path::new(ws_path)).is_ok_and(|p| p == path);
+        same.then(|| ws.get("id").and_then(Value::as_str).map(str::to_string)).flatten()
+    })
+}
+
+/// 工作区在 workspaces.json 中的 ID（未登记的目录为 None）
+pub fn workspace_id(path: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(crate::get_dawei_home().join("workspaces.json")).ok()?;
+    find_workspace_id(&serde_json::from_str(&content).ok()?, path)
+}
+
+/// 在新窗口中打开工作区，已打开时聚焦该窗口
+pub fn open(app: &AppHandle, path: &Path) -> Result<WorkspaceWindow, String> {
+    let path = crate::symlink_policy::canonicalize(path).map_err(|e| format!("工作区目录不存在: {}", e))?;
+    if !path.is_dir() {
+        return Err(format!("不是目录: {}", path.display()));
+    }
+
+    let label = label_for(&path);
+    if let Some(window) = app.get_webview_window(&label) {
+        window.unminimize().and_then(|_| window.show()).and_then(|_| window.set_focus()).map_err(|e| e.to_string())?;
+        if let Some(info) = get(&label) {
+            return Ok(info);
+        }
+    }
+
+    crate::workspace_lock::acquire(&path)?;
+    let info = WorkspaceWindow { label: label.clone(), id: workspace_id(&path), path };
+    let route = match &info.id {
+        Some(id) => format!("index.html#/dawei/{}", id),
+        None => "index.html#/workspaces".to_string(),
+    };
+    let name = info.path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let script = format!(
+        "window.__DAWEI_WORKSPACE__ = {};",
+        serde_json::to_string(&info).map_err(|e| e.to_string())?
+    );
+
+    let window = match crate::portable::webview(WebviewWindowBuilder::new(app, &label, WebviewUrl::App(route.into())))
+        .title(format!("大微 - {}", name))
+        .inner_size(1200.0, 800.0)
+        .min_inner_size(1024.0, 768.0)
+        .devtools(!crate::policy::is_locked_down())
+        .initialization_script(script)
+        .initialization_script(crate::log_timeline::CONSOLE_FORWARD_SCRIPT)
+        .build()
+    {
+        Ok(window) => window,
+        Err(e) => {
+            crate::workspace_lock::release(&info.path);
+            return Err(format!("无法打开工作区窗口: {}", e));
+        }
+    };
+    crate::menu::attach(&window);
+    crate::zoom::apply_to(&window);
+    crate::titlebar::apply(&window);
+
+    WINDOWS.lock().unwrap_or_else(|e| e.into_inner()).get_or_insert_with(HashMap::new).insert(label, info.clone());
+    crate::breadcrumbs::record("window", format!("Workspace window opened: {}", info.path.display()));
+    crate::recent_workspaces::record(&info.path);
+    crate::workspace_files::trust(&info.path);
+    Ok(info)
+}
+
+/// 窗口对应的工作区（主窗口等非工作区窗口返回 None）
+pub fn get(label: &str) -> Option<WorkspaceWindow> {
+    WINDOWS.lock().unwrap_or_else(|e| e.into_inner()).as_ref()?.get(label).cloned()
+}
+
+/// 窗口销毁时释放工作区锁并清除登记信息
+pub fn on_window_event(window: &Window, event: &WindowEvent) {
+    if !window.label().starts_with(LABEL_PREFIX) || !matches!(event, WindowEvent::Destroyed) {
+        return;
+    }
+    let removed = WINDOWS.lock().unwrap_or_else(|e| e.into_inner()).as_mut().and_then(|w| w.remove(window.label()));
+    if let Some(info) = removed {
+        crate::workspace_lock::release(&info.path);
+        crate::breadcrumbs::record("window", format!("Workspace window closed: {}", info.path.display()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_label_and_workspace_lookup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().canonicalize().unwrap();
+        assert_eq!(label_for(&path), label_for(&path));
+        assert_ne!(label_for(&path), label_for(&path.join("other")));
+        assert!(label_for(&path).starts_with(LABEL_PREFIX));
+
+        let index = json!({ "workspaces": [
+            { "id": "a", "path": "/nonexistent/a" },
+            { "id": "b", "path": path.to_string_lossy() },
+        ]});
+        assert_eq!(find_workspace_id(&index, &path), Some("b".to_string()));
+        assert_eq!(find_workspace_id(&index, Path::new("/nonexistent/c")), None);
+    }
+}
@@ -0,0 +1,98 @@
+//! 页面缩放
+//!
+//! 缩放比例保存在设置中，启动时应用到主窗口和工作区窗口，设置变化时重新应用，
+//! 并向前端发送 `zoom-change` 事件。Ctrl+= / Ctrl+- / Ctrl+0（macOS 为 Cmd）
+//! 由菜单栏的快捷键处理，各平台行为一致。
+
+use crate::settings::{self, SETTINGS_CHANGED_EVENT};
+use tauri::{AppHandle, Emitter, Listener, Manager, WebviewWindow};
+
+/// 缩放比例变化时发送的事件，内容为新的缩放比例
+pub const ZOOM_CHANGE_EVENT: &str = "zoom-change";
+
+/// 缩放范围
+const MIN_ZOOM: f64 = 0.5;
+const MAX_ZOOM: f64 = 3.0;
+
+/// 每次放大/缩小的步长
+const ZOOM_STEP: f64 = 0.1;
+
+/// 限制范围并保留两位小数（避免 0.1 累加产生 1.2000000000000002）
+fn normalize(level: f64) -> f64 {
+    let level = if level.is_finite() { level } else { 1.0 };
+    (level.clamp(MIN_ZOOM, MAX_ZOOM) * 100.0).round() / 100.0
+}
+
+/// 当前缩放比例
+pub fn current() -> f64 {
+    normalize(settings::current().zoom)
+}
+
+/// 把当前缩放比例应用到窗口（主窗口和工作区窗口）
+pub fn apply_to(window: &WebviewWindow) {
+    if let Err(e) = window.set_zoom(current()) {
+        tracing::warn!("Failed to set zoom for {}: {}", window.label(), e);
+    }
+}
+
+fn apply(app: &AppHandle) {
+    for (label, window) in app.webview_windows() {
+        if label == "main" || crate::workspace_window::get(&label).is_some() {
+            apply_to(&window);
+        }
+    }
+    if let Err(e) = app.emit(ZOOM_CHANGE_EVENT, current()) {
+        tracing::warn!("Failed to emit zoom change: {}", e);
+    }
+}
+
+/// 启动时应用设置，并在设置变化时重新应用
+pub fn init(app: &AppHandle) {
+    apply(app);
+
+    let handle = app.clone();
+    app.listen_any(SETTINGS_CHANGED_EVENT, move |event| {
+        let changed = serde_json::from_str::<serde_json::Value>(event.payload())
+            .map(|change| change["changed"].get("zoom").is_some())
+            .unwrap_or(false);
+        if changed {
+            apply(&handle);
+        }
+    });
+}
+
+/// 设置缩放比例，返回实际应用的比例
+pub fn set(app: &AppHandle, level: f64) -> Result<f64, String> {
+    let level = normalize(level);
+    // 设置变更事件会触发 apply
+    settings::update(app, serde_json::json!({ "zoom": level }))?;
+    Ok(level)
+}
+
+/// 放大一级
+pub fn zoom_in(app: &AppHandle) -> Result<f64, String> {
+    set(app, current() + ZOOM_STEP)
+}
+
+/// 缩小一级
+pub fn zoom_out(app: &AppHandle) -> Result<f64, String> {
+    set(app, current() - ZOOM_STEP)
+}
+
+/// 恢复为 100%
+pub fn reset(app: &AppHandle) -> Result<f64, String> {
+    set(app, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize(1.1 + 0.1), 1.2);
+        assert_eq!(normalize(0.1), MIN_ZOOM);
+        assert_eq!(normalize(10.0), MAX_ZOOM);
+        assert_eq!(normalize(f64::NAN), 1.0);
+    }
+}